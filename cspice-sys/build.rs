@@ -4,7 +4,14 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{env, fs};
 
+#[cfg(feature = "downloadcspice")]
+#[path = "src/lzw.rs"]
+mod lzw;
+
 const CSPICE_DIR: &str = "CSPICE_DIR";
+const CSPICE_INCLUDE_DIR: &str = "CSPICE_INCLUDE_DIR";
+const CSPICE_LIB_DIR: &str = "CSPICE_LIB_DIR";
+const CSPICE_LINK_KIND: &str = "CSPICE_LINK_KIND";
 const CSPICE_CLANG_TARGET: &str = "CSPICE_CLANG_TARGET";
 const CSPICE_CLANG_ROOT: &str = "CSPICE_CLANG_ROOT";
 
@@ -15,35 +22,22 @@ fn main() {
     }
 
     println!("cargo:rerun-if-env-changed={}", CSPICE_DIR);
+    println!("cargo:rerun-if-env-changed={}", CSPICE_INCLUDE_DIR);
+    println!("cargo:rerun-if-env-changed={}", CSPICE_LIB_DIR);
+    println!("cargo:rerun-if-env-changed={}", CSPICE_LINK_KIND);
     println!("cargo:rerun-if-env-changed={}", CSPICE_CLANG_TARGET);
     println!("cargo:rerun-if-env-changed={}", CSPICE_CLANG_ROOT);
 
-    let cspice_dir = env::var(CSPICE_DIR)
-        .ok()
-        .map(PathBuf::from)
-        .or_else(locate_cspice);
-
-    #[cfg(feature = "downloadcspice")]
-    let cspice_dir = cspice_dir.or_else(|| {
-        let downloaded = out_path.join("cspice");
-        if !downloaded.exists() {
-            download_cspice(&out_path);
-        }
-        Some(downloaded)
-    });
+    let link_kind = link_kind();
+    let (include_dir, lib_dir) = locate_dirs(&out_path, link_kind);
 
-    let cspice_dir =
-		cspice_dir.expect("Cannot build: CSPICE_DIR environment variable was not provided, no CSPICE install was found, and feature \"downloadcspice\" is disabled.");
-
-    if !cspice_dir.is_dir() {
+    if !include_dir.is_dir() {
         panic!(
-            "Provided {CSPICE_DIR} ({}) is not a directory",
-            cspice_dir.display()
+            "CSPICE include directory ({}) is not a directory",
+            include_dir.display()
         )
     }
 
-    let include_dir = cspice_dir.join("include");
-
     let mut clang_args = vec![];
     if let Ok(target) = env::var(CSPICE_CLANG_TARGET) {
         if !target.is_empty() {
@@ -68,21 +62,106 @@ fn main() {
         .write_to_file(out_path.join("bindgen.rs"))
         .expect("Couldn't write bindings!");
 
-    println!(
-        "cargo:rustc-link-search=native={}",
-        cspice_dir.join("lib").display()
-    );
-    println!("cargo:rustc-link-lib=static=cspice");
+    println!("cargo:rustc-link-search=native={}", lib_dir.display());
+    println!("cargo:rustc-link-lib={}=cspice", link_kind.as_str());
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LinkKind {
+    Static,
+    Dynamic,
+}
+
+impl LinkKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::Static => "static",
+            LinkKind::Dynamic => "dylib",
+        }
+    }
 }
 
-// Check for CSPICE installation in system library folders
-fn locate_cspice() -> Option<PathBuf> {
-    match env::consts::OS {
-        "linux" | "macos" if Path::new("/usr/lib/libcspice.a").exists() => {
-            Some(PathBuf::from("/usr"))
+// Static linking is the default, the `dynamic` feature switches to dynamic linking, and the
+// `CSPICE_LINK_KIND` environment variable takes precedence over both
+fn link_kind() -> LinkKind {
+    match env::var(CSPICE_LINK_KIND) {
+        Ok(kind) if !kind.is_empty() => match kind.to_lowercase().as_str() {
+            "static" => LinkKind::Static,
+            "dylib" | "dynamic" => LinkKind::Dynamic,
+            _ => panic!("Invalid {CSPICE_LINK_KIND} ({kind}), expected \"static\" or \"dylib\""),
+        },
+        _ if cfg!(feature = "dynamic") => LinkKind::Dynamic,
+        _ => LinkKind::Static,
+    }
+}
+
+// Resolve the include and lib directories.
+// `CSPICE_INCLUDE_DIR` and `CSPICE_LIB_DIR` take precedence over the `include` and `lib`
+// subdirectories of `CSPICE_DIR` (or of a located / downloaded installation).
+#[cfg_attr(not(feature = "downloadcspice"), allow(unused_variables))]
+fn locate_dirs(out_path: &Path, link_kind: LinkKind) -> (PathBuf, PathBuf) {
+    let include_dir = env::var(CSPICE_INCLUDE_DIR).ok().map(PathBuf::from);
+    let lib_dir = env::var(CSPICE_LIB_DIR).ok().map(PathBuf::from);
+    if let (Some(include_dir), Some(lib_dir)) = (&include_dir, &lib_dir) {
+        return (include_dir.clone(), lib_dir.clone());
+    }
+
+    let cspice_dirs = env::var(CSPICE_DIR)
+        .ok()
+        .map(|dir| {
+            let dir = PathBuf::from(dir);
+            if !dir.is_dir() {
+                panic!(
+                    "Provided {CSPICE_DIR} ({}) is not a directory",
+                    dir.display()
+                )
+            }
+            (dir.join("include"), dir.join("lib"))
+        })
+        .or_else(|| locate_cspice(link_kind));
+
+    #[cfg(feature = "downloadcspice")]
+    let cspice_dirs = cspice_dirs.or_else(|| {
+        let downloaded = out_path.join("cspice");
+        if !downloaded.exists() {
+            download_cspice(out_path);
         }
-        _ => None,
+        Some((downloaded.join("include"), downloaded.join("lib")))
+    });
+
+    let (default_include_dir, default_lib_dir) = cspice_dirs.expect("Cannot build: CSPICE_DIR (or CSPICE_INCLUDE_DIR and CSPICE_LIB_DIR) environment variables were not provided, no CSPICE install was found, and feature \"downloadcspice\" is disabled.");
+    (
+        include_dir.unwrap_or(default_include_dir),
+        lib_dir.unwrap_or(default_lib_dir),
+    )
+}
+
+// Check for CSPICE installation in system library folders.
+// Distribution packages typically install the headers into an `include/cspice` subdirectory.
+fn locate_cspice(link_kind: LinkKind) -> Option<(PathBuf, PathBuf)> {
+    if !matches!(env::consts::OS, "linux" | "macos") {
+        return None;
     }
+    let lib_name = match (link_kind, env::consts::OS) {
+        (LinkKind::Static, _) => "libcspice.a",
+        (LinkKind::Dynamic, "macos") => "libcspice.dylib",
+        (LinkKind::Dynamic, _) => "libcspice.so",
+    };
+    ["/usr", "/usr/local"]
+        .iter()
+        .map(Path::new)
+        .flat_map(|prefix| {
+            ["lib", "lib64"]
+                .iter()
+                .map(move |lib| (prefix, prefix.join(lib)))
+        })
+        .find(|(_, lib_dir)| lib_dir.join(lib_name).exists())
+        .and_then(|(prefix, lib_dir)| {
+            [prefix.join("include"), prefix.join("include/cspice")]
+                .into_iter()
+                .find(|include_dir| include_dir.join("SpiceUsr.h").exists())
+                .map(|include_dir| (include_dir, lib_dir))
+        })
 }
 
 // Fetch CSPICE source from NAIF servers and extract to `<out_dir>/cspice`
@@ -110,27 +189,18 @@ fn download_cspice(out_dir: &Path) {
         platform, extension
     );
 
-    let download_target = out_dir.join(format!("cspice.{}", extension));
-
     let body = reqwest::blocking::get(url)
         .expect("Failed to download CSPICE")
         .bytes()
         .unwrap();
-    std::fs::write(download_target, body).expect("Failed to write archive file");
 
     // Extract package based on platform
     match (env::consts::OS, extension) {
         ("linux" | "macos", "tar.Z") => {
-            Command::new("gzip")
-                .current_dir(out_dir)
-                .args(["-d", "cspice.tar.Z"])
-                .status()
-                .expect("Failed to extract with gzip");
-            Command::new("tar")
-                .current_dir(out_dir)
-                .args(["xf", "cspice.tar"])
-                .status()
-                .expect("Failed to extract with tar");
+            let tar = lzw::uncompress(&body).expect("Failed to decompress archive");
+            tar::Archive::new(tar.as_slice())
+                .unpack(out_dir)
+                .expect("Failed to extract archive");
 
             fs::rename(
                 out_dir.join("cspice/lib/cspice.a"),
@@ -139,11 +209,10 @@ fn download_cspice(out_dir: &Path) {
             .unwrap();
         }
         ("windows", "zip") => {
-            Command::new("tar")
-                .current_dir(out_dir)
-                .args(["xf", "cspice.zip"])
-                .status()
-                .expect("Failed to extract with tar");
+            zip::ZipArchive::new(std::io::Cursor::new(body))
+                .expect("Failed to read archive")
+                .extract(out_dir)
+                .expect("Failed to extract archive");
         }
         _ => unreachable!(),
     }
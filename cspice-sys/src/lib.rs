@@ -12,6 +12,9 @@
 
 include!(concat!(env!("OUT_DIR"), "/bindgen.rs"));
 
+#[cfg(test)]
+mod lzw;
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -0,0 +1,247 @@
+//! Decompression of the Unix `compress` (.Z) format that the CSPICE packages are distributed in,
+//! so they can be extracted without external tools. Based on the algorithm used by `ncompress` /
+//! gzip's `unlzw.c`.
+//!
+//! This is included by the build script with `#[path]`, and by the library only for its tests.
+
+/// Decompress data in the Unix `compress` (.Z) format, which is an LZW variant.
+pub fn uncompress(data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    const INIT_BITS: u32 = 9;
+    const CLEAR: usize = 256;
+
+    if data.len() < 3 || data[0..2] != [0x1f, 0x9d] {
+        return Err("not in compress format");
+    }
+    let max_bits = (data[2] & 0x1f) as u32;
+    let block_mode = data[2] & 0x80 != 0;
+    if !(INIT_BITS..=16).contains(&max_bits) {
+        return Err("unsupported number of bits");
+    }
+    let max_max_code = 1usize << max_bits;
+    let data = &data[3..];
+    let total_bits = data.len() * 8;
+
+    let mut prefix = vec![0usize; max_max_code];
+    let mut suffix: Vec<u8> = (0..max_max_code).map(|i| i as u8).collect();
+    let mut output = Vec::with_capacity(data.len() * 3);
+    let mut stack = Vec::new();
+
+    let mut n_bits = INIT_BITS;
+    let mut max_code = (1usize << n_bits) - 1;
+    let mut free_ent = if block_mode { CLEAR + 1 } else { CLEAR };
+    let mut old_code: Option<usize> = None;
+    let mut fin_char = 0u8;
+    // Codes are written in groups of `n_bits` bytes (8 codes). Whenever the code width changes
+    // the remainder of the current group is skipped, so we track where the group started
+    let mut group_start = 0;
+    let mut pos = 0;
+
+    let align = |pos: usize, group_start: usize, n_bits: u32| {
+        let group_bits = n_bits as usize * 8;
+        group_start + (pos - group_start).div_ceil(group_bits) * group_bits
+    };
+
+    loop {
+        if free_ent > max_code {
+            pos = align(pos, group_start, n_bits);
+            group_start = pos;
+            n_bits += 1;
+            max_code = if n_bits == max_bits {
+                max_max_code
+            } else {
+                (1 << n_bits) - 1
+            };
+        }
+        if pos + n_bits as usize > total_bits {
+            break;
+        }
+
+        // Read the next code (least significant bit first)
+        let mut code = 0usize;
+        for i in 0..n_bits as usize {
+            let bit = pos + i;
+            code |= (((data[bit / 8] >> (bit % 8)) & 1) as usize) << i;
+        }
+        pos += n_bits as usize;
+
+        let prev = match old_code {
+            None => {
+                if code >= CLEAR {
+                    return Err("corrupt input");
+                }
+                fin_char = code as u8;
+                old_code = Some(code);
+                output.push(fin_char);
+                continue;
+            }
+            Some(prev) => prev,
+        };
+
+        if code == CLEAR && block_mode {
+            prefix.iter_mut().for_each(|p| *p = 0);
+            free_ent = CLEAR;
+            pos = align(pos, group_start, n_bits);
+            group_start = pos;
+            n_bits = INIT_BITS;
+            max_code = (1 << n_bits) - 1;
+            continue;
+        }
+
+        let in_code = code;
+        if code >= free_ent {
+            // Special case for the KwKwK string
+            if code > free_ent {
+                return Err("corrupt input");
+            }
+            stack.push(fin_char);
+            code = prev;
+        }
+        while code >= 256 {
+            stack.push(suffix[code]);
+            code = prefix[code];
+        }
+        fin_char = suffix[code];
+        stack.push(fin_char);
+        output.extend(stack.drain(..).rev());
+
+        if free_ent < max_max_code {
+            prefix[free_ent] = prev;
+            suffix[free_ent] = fin_char;
+            free_ent += 1;
+        }
+        old_code = Some(in_code);
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// A minimal `compress` encoder, in block mode without ever emitting a clear code.
+    fn compress(input: &[u8], max_bits: u32) -> Vec<u8> {
+        let mut output = vec![0x1f, 0x9d, 0x80 | max_bits as u8];
+        let max_max_code = 1usize << max_bits;
+        let mut dictionary: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut next_code = 257;
+        let mut codes = Vec::new();
+        let mut current: Vec<u8> = Vec::new();
+        for &byte in input {
+            let mut extended = current.clone();
+            extended.push(byte);
+            if current.is_empty() || dictionary.contains_key(&extended) {
+                current = extended;
+                continue;
+            }
+            codes.push(code_of(&dictionary, &current));
+            if next_code < max_max_code {
+                dictionary.insert(extended, next_code);
+                next_code += 1;
+            }
+            current = vec![byte];
+        }
+        if !current.is_empty() {
+            codes.push(code_of(&dictionary, &current));
+        }
+
+        // Write the codes with the same widths and group alignment the decoder expects
+        let mut bits: Vec<bool> = Vec::new();
+        let mut n_bits = 9;
+        let mut max_code = (1 << n_bits) - 1;
+        let mut group_start = 0;
+        let mut free_ent = 257;
+        for (i, code) in codes.into_iter().enumerate() {
+            if free_ent > max_code {
+                let group_bits = n_bits as usize * 8;
+                let aligned =
+                    group_start + (bits.len() - group_start).div_ceil(group_bits) * group_bits;
+                bits.resize(aligned, false);
+                group_start = aligned;
+                n_bits += 1;
+                max_code = if n_bits == max_bits {
+                    max_max_code
+                } else {
+                    (1 << n_bits) - 1
+                };
+            }
+            bits.extend((0..n_bits).map(|b| code >> b & 1 == 1));
+            if i > 0 && free_ent < max_max_code {
+                free_ent += 1;
+            }
+        }
+        for chunk in bits.chunks(8) {
+            output.push(
+                chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &bit)| (bit as u8) << i)
+                    .sum(),
+            );
+        }
+        output
+    }
+
+    fn code_of(dictionary: &HashMap<Vec<u8>, usize>, string: &[u8]) -> usize {
+        match string {
+            [byte] => *byte as usize,
+            _ => dictionary[string],
+        }
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        // Repetitive text with some pseudo-random noise, so codes of every width are used
+        let mut state = 12345u32;
+        (0..len)
+            .map(|i| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                if i % 7 == 0 {
+                    (state >> 16) as u8
+                } else {
+                    b"TOBEORNOTTOBEORTOBEORNOT"[i % 24]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in [
+            Vec::new(),
+            b"A".to_vec(),
+            b"TOBEORNOTTOBEORTOBEORNOT".to_vec(),
+            vec![b'a'; 1000],
+            sample(100_000),
+        ] {
+            for max_bits in [9, 12, 16] {
+                assert_eq!(uncompress(&compress(&input, max_bits)).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(uncompress(b"PK\x03\x04"), Err("not in compress format"));
+        assert_eq!(
+            uncompress(&[0x1f, 0x9d, 0x80 | 17]),
+            Err("unsupported number of bits")
+        );
+        // The first code must be a literal byte
+        assert_eq!(
+            uncompress(&[0x1f, 0x9d, 0x90, 0xff, 0xff]),
+            Err("corrupt input")
+        );
+    }
+
+    #[test]
+    fn test_truncated_input() {
+        let input = sample(10_000);
+        let compressed = compress(&input, 16);
+        assert_eq!(uncompress(&compressed[..2]), Err("not in compress format"));
+        // The format has no length or checksum, so a truncated stream decodes to a prefix
+        let truncated = uncompress(&compressed[..compressed.len() / 2]).unwrap();
+        assert!(!truncated.is_empty() && truncated.len() < input.len());
+        assert!(input.starts_with(&truncated));
+    }
+}
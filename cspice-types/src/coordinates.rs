@@ -0,0 +1,58 @@
+//! Coordinate systems, angles are in radians and distances in kilometers.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Rectangular coordinates
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rectangular {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// Range, azimuth, and elevation
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AzEl {
+    pub range: f64,
+    pub az: f64,
+    pub el: f64,
+}
+
+/// Range, right ascension, and declination.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RaDec {
+    pub range: f64,
+    pub ra: f64,
+    pub dec: f64,
+}
+
+/// Latitudinal coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Latitudinal {
+    pub radius: f64,
+    pub longitude: f64,
+    pub latitude: f64,
+}
+
+/// Planetographic coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Planetographic {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub altitude: f64,
+}
+
+/// Geodetic coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Geodetic {
+    pub longitude: f64,
+    pub latitude: f64,
+    pub altitude: f64,
+}
@@ -0,0 +1,16 @@
+//! Plain data types used by the `cspice` crate, without any dependency on the CSPICE library.
+//!
+//! The crate is `no_std` so the types can be shared with embedded consumers, and it compiles for
+//! `wasm32-unknown-unknown` so browser visualisations can share code with native tools. Besides
+//! the types it contains the pure Rust subset of the math, and [table::StateTable] for
+//! interpolating states sampled elsewhere with SPICE. The `cspice` crate provides `From`
+//! conversions between these types and its own, which additionally implement the SPICE
+//! computations.
+#![no_std]
+
+pub mod coordinates;
+pub mod matrix;
+pub mod spk;
+pub mod table;
+pub mod time;
+pub mod vector;
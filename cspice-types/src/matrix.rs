@@ -0,0 +1,60 @@
+//! Rotation matrices and quaternions.
+use crate::vector::Vector3D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A 3x3 matrix, in row major order.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Matrix3x3(pub [[f64; 3]; 3]);
+
+impl Matrix3x3 {
+    /// The identity matrix.
+    pub fn identity() -> Self {
+        Self(core::array::from_fn(|i| {
+            core::array::from_fn(|j| if i == j { 1.0 } else { 0.0 })
+        }))
+    }
+
+    /// The transpose of this matrix, which for a rotation matrix is its inverse.
+    pub fn transpose(&self) -> Self {
+        Self(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[j][i])
+        }))
+    }
+
+    /// Multiply this matrix by a 3-dimensional vector.
+    pub fn mul_vec(&self, v: &Vector3D) -> Vector3D {
+        Vector3D(self.0.map(|row| Vector3D(row).dot(v)))
+    }
+
+    /// Multiply this matrix by another, returning `self * other`.
+    pub fn mul_mat(&self, other: &Matrix3x3) -> Matrix3x3 {
+        let other = other.transpose();
+        Self(
+            self.0
+                .map(|row| other.0.map(|col| Vector3D(row).dot(&Vector3D(col)))),
+        )
+    }
+}
+
+/// A SPICE-style quaternion, with the scalar component first.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quaternion(pub [f64; 4]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_math() {
+        // 90 degrees about Z
+        let m = Matrix3x3([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(
+            m.mul_vec(&Vector3D([1.0, 0.0, 0.0])),
+            Vector3D([0.0, -1.0, 0.0])
+        );
+        assert_eq!(m.mul_mat(&m.transpose()), Matrix3x3::identity());
+    }
+}
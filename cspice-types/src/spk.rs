@@ -0,0 +1,31 @@
+//! Ephemeris states.
+use crate::coordinates::Rectangular;
+use crate::vector::Vector3D;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A Cartesian state vector representing the position and velocity of the target body
+/// relative to the specified observer
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct State {
+    pub position: Rectangular,
+    pub velocity: Vector3D,
+}
+
+impl State {
+    /// The position as a vector.
+    pub fn position_vector(&self) -> Vector3D {
+        let p = self.position;
+        Vector3D([p.x, p.y, p.z])
+    }
+
+    /// Create a state from position and velocity vectors.
+    pub fn from_vectors(position: Vector3D, velocity: Vector3D) -> Self {
+        let [x, y, z] = position.0;
+        Self {
+            position: Rectangular { x, y, z },
+            velocity,
+        }
+    }
+}
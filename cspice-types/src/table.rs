@@ -0,0 +1,116 @@
+//! Interpolation of tabulated states, e.g. sampled from SPICE on a server and sent to a client.
+use crate::spk::State;
+use crate::time::Et;
+
+/// Error returned when creating a [StateTable].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StateTableError {
+    /// At least two samples are required.
+    TooFewSamples,
+    /// The sample times must be strictly increasing, the index of the first sample out of order.
+    NotIncreasing(usize),
+}
+
+impl core::fmt::Display for StateTableError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            StateTableError::TooFewSamples => f.write_str("At least two samples are required"),
+            StateTableError::NotIncreasing(i) => {
+                write!(f, "Sample {i} is not after the previous sample")
+            }
+        }
+    }
+}
+
+/// A table of states interpolated with cubic Hermite polynomials, using the position and
+/// velocity of the samples either side of the requested time.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StateTable<'a> {
+    samples: &'a [(Et, State)],
+}
+
+impl<'a> StateTable<'a> {
+    /// Create a table from samples in order of strictly increasing time.
+    pub fn new(samples: &'a [(Et, State)]) -> Result<Self, StateTableError> {
+        if samples.len() < 2 {
+            return Err(StateTableError::TooFewSamples);
+        }
+        if let Some(i) = (1..samples.len()).find(|&i| samples[i].0 .0 <= samples[i - 1].0 .0) {
+            return Err(StateTableError::NotIncreasing(i));
+        }
+        Ok(Self { samples })
+    }
+
+    /// The first and last sample times.
+    pub fn coverage(&self) -> (Et, Et) {
+        (self.samples[0].0, self.samples[self.samples.len() - 1].0)
+    }
+
+    /// Interpolate the state at `et`, or `None` if `et` is outside of the table's coverage.
+    pub fn interpolate(&self, et: Et) -> Option<State> {
+        let (start, end) = self.coverage();
+        if !(start.0..=end.0).contains(&et.0) {
+            return None;
+        }
+        let i = self
+            .samples
+            .partition_point(|(t, _)| t.0 <= et.0)
+            .clamp(1, self.samples.len() - 1);
+        let ((t0, s0), (t1, s1)) = (self.samples[i - 1], self.samples[i]);
+        let h = t1.0 - t0.0;
+        let s = (et.0 - t0.0) / h;
+        let (s2, s3) = (s * s, s * s * s);
+        let (p0, p1) = (s0.position_vector(), s1.position_vector());
+        let (v0, v1) = (s0.velocity, s1.velocity);
+        let position = p0 * (2.0 * s3 - 3.0 * s2 + 1.0)
+            + v0 * ((s3 - 2.0 * s2 + s) * h)
+            + p1 * (-2.0 * s3 + 3.0 * s2)
+            + v1 * ((s3 - s2) * h);
+        let velocity = (p1 - p0) * ((6.0 * s - 6.0 * s2) / h)
+            + v0 * (3.0 * s2 - 4.0 * s + 1.0)
+            + v1 * (3.0 * s2 - 2.0 * s);
+        Some(State::from_vectors(position, velocity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::Vector3D;
+
+    fn sample(t: f64) -> (Et, State) {
+        // Quadratic motion, which cubic Hermite interpolation reproduces exactly
+        let position = Vector3D([t * t, 2.0 * t, 1.0]);
+        let velocity = Vector3D([2.0 * t, 2.0, 0.0]);
+        (Et(t), State::from_vectors(position, velocity))
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let samples = [sample(0.0), sample(10.0), sample(30.0)];
+        let table = StateTable::new(&samples).unwrap();
+        assert_eq!(table.coverage(), (Et(0.0), Et(30.0)));
+        for t in [0.0, 2.5, 10.0, 17.0, 30.0] {
+            let state = table.interpolate(Et(t)).unwrap();
+            let expected = sample(t).1;
+            let error = state.position_vector() - expected.position_vector();
+            assert!(error.dot(&error) < 1e-18);
+            let error = state.velocity - expected.velocity;
+            assert!(error.dot(&error) < 1e-18);
+        }
+        assert_eq!(table.interpolate(Et(-1.0)), None);
+        assert_eq!(table.interpolate(Et(31.0)), None);
+    }
+
+    #[test]
+    fn test_invalid_table() {
+        assert_eq!(
+            StateTable::new(&[sample(0.0)]),
+            Err(StateTableError::TooFewSamples)
+        );
+        assert_eq!(
+            StateTable::new(&[sample(0.0), sample(1.0), sample(1.0)]),
+            Err(StateTableError::NotIncreasing(2))
+        );
+    }
+}
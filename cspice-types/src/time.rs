@@ -0,0 +1,52 @@
+//! Time representations.
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Ephemeris Time (time in seconds past the ephemeris epoch J2000) (TDB).
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Et(pub f64);
+
+/// A calendar supported by SPICE.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CalendarKind {
+    Mixed,
+    Gregorian,
+    Julian,
+}
+
+/// A time system supported by SPICE.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TimeSystem {
+    Tdt,
+    Tdb,
+    /// UTC, with an offset for the time zone.
+    Utc {
+        zone_hours: i8,
+        zone_minutes: u8,
+    },
+}
+
+/// A Julian Date in a time system. Julian Dates in UTC ignore the time zone.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JulianDate {
+    pub value: f64,
+    pub system: TimeSystem,
+}
+
+/// A date and time of day. The year uses astronomical year numbering, where year 0 is 1 BCE.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DateTime {
+    pub year: i16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: f32,
+    pub calendar: CalendarKind,
+    pub system: TimeSystem,
+}
@@ -0,0 +1,69 @@
+//! 3D vectors.
+use core::ops::{Add, Mul, Neg, Sub};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A 3D vector
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vector3D(pub [f64; 3]);
+
+impl Vector3D {
+    /// The dot product of two vectors.
+    pub fn dot(&self, other: &Vector3D) -> f64 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum()
+    }
+
+    /// The cross product of two vectors.
+    pub fn cross(&self, other: &Vector3D) -> Vector3D {
+        let ([a1, a2, a3], [b1, b2, b3]) = (self.0, other.0);
+        Vector3D([a2 * b3 - a3 * b2, a3 * b1 - a1 * b3, a1 * b2 - a2 * b1])
+    }
+}
+
+impl Add for Vector3D {
+    type Output = Vector3D;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vector3D(core::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl Sub for Vector3D {
+    type Output = Vector3D;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector3D(core::array::from_fn(|i| self.0[i] - rhs.0[i]))
+    }
+}
+
+impl Neg for Vector3D {
+    type Output = Vector3D;
+
+    fn neg(self) -> Self::Output {
+        Vector3D(self.0.map(|x| -x))
+    }
+}
+
+impl Mul<f64> for Vector3D {
+    type Output = Vector3D;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Vector3D(self.0.map(|x| x * rhs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vector_math() {
+        let x = Vector3D([1.0, 0.0, 0.0]);
+        let y = Vector3D([0.0, 1.0, 0.0]);
+        assert_eq!(x.cross(&y), Vector3D([0.0, 0.0, 1.0]));
+        assert_eq!(x.dot(&y), 0.0);
+        assert_eq!((x + y) * 2.0 - y, Vector3D([2.0, 1.0, 0.0]));
+        assert_eq!(-x, Vector3D([-1.0, 0.0, 0.0]));
+    }
+}
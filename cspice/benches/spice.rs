@@ -0,0 +1,83 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use cspice::common::AberrationCorrection;
+use cspice::data::furnish;
+use cspice::spk;
+use cspice::string::SpiceString;
+use cspice::time::Et;
+use std::path::PathBuf;
+
+fn load_test_data() {
+    let data_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test_data");
+    furnish(data_dir.join("testkernel.txt").to_string_lossy()).unwrap();
+}
+
+fn time(c: &mut Criterion) {
+    c.bench_function("Et::from_string", |b| {
+        b.iter(|| Et::from_string(black_box("2022-06-01T12:00:00")).unwrap())
+    });
+    let utc = SpiceString::from("2022-06-01T12:00:00");
+    c.bench_function("Et::from_string (SpiceString)", |b| {
+        b.iter(|| Et::from_string(black_box(&utc)).unwrap())
+    });
+}
+
+fn ephemeris(c: &mut Criterion) {
+    let et = Et::from_string("2022-06-01T12:00:00").unwrap();
+    c.bench_function("spk::easier_reader", |b| {
+        b.iter(|| {
+            spk::easier_reader(
+                "MOON",
+                black_box(et),
+                "J2000",
+                AberrationCorrection::LT_S,
+                "EARTH",
+            )
+            .unwrap()
+        })
+    });
+    let (moon, j2000, earth) = (
+        SpiceString::from("MOON"),
+        SpiceString::from("J2000"),
+        SpiceString::from("EARTH"),
+    );
+    c.bench_function("spk::easier_reader (SpiceString)", |b| {
+        b.iter(|| {
+            spk::easier_reader(
+                &moon,
+                black_box(et),
+                &j2000,
+                AberrationCorrection::LT_S,
+                &earth,
+            )
+            .unwrap()
+        })
+    });
+}
+
+fn report_stats(_: &mut Criterion) {
+    #[cfg(feature = "perf-counters")]
+    {
+        let stats = cspice::spice_stats();
+        println!("SPICE lock acquisitions: {}", stats.lock_acquisitions);
+        println!(
+            "Unattributed string allocations: {}",
+            stats.unattributed_string_allocations
+        );
+        for (site, s) in &stats.call_sites {
+            println!(
+                "{site}: {} calls, {} string allocations",
+                s.calls, s.string_allocations
+            );
+        }
+    }
+}
+
+fn benches(c: &mut Criterion) {
+    load_test_data();
+    time(c);
+    ephemeris(c);
+    report_stats(c);
+}
+
+criterion_group!(spice, benches);
+criterion_main!(spice);
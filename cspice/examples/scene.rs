@@ -0,0 +1,42 @@
+//! Build model transforms for a renderer from a [SolarSystemSnapshot], using `glam`.
+//!
+//! Run with a meta-kernel that loads an SPK and PCK covering the bodies, e.g.
+//! `cargo run --example scene -- my_kernels.tm`.
+use cspice::body::BodyRef;
+use cspice::data::furnish;
+use cspice::naif_ids::{EARTH, MOON, SUN};
+use cspice::snapshot::SolarSystemSnapshot;
+use cspice::time::Et;
+use glam::{DAffine3, DMat3, DVec3};
+
+/// Scene units per kilometer.
+const SCALE: f64 = 1e-6;
+
+fn main() {
+    let kernel = std::env::args()
+        .nth(1)
+        .expect("usage: scene <meta-kernel> [utc]");
+    let utc = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| String::from("2022-06-01T12:00:00"));
+    furnish(kernel).unwrap();
+    let et = Et::from_string(utc).unwrap();
+
+    let bodies = [SUN, EARTH, MOON].map(BodyRef::from);
+    let snapshot = SolarSystemSnapshot::capture(et, "ECLIPJ2000", &bodies).unwrap();
+    // Center the scene on the Earth to keep the coordinates small
+    let origin = snapshot.body(EARTH).unwrap().position;
+    for body in &snapshot.bodies {
+        let p = body.position;
+        let translation = DVec3::new(p.x - origin.x, p.y - origin.y, p.z - origin.z) * SCALE;
+        // The orientation rotates scene vectors into the body-fixed frame, a model matrix
+        // needs the inverse. SPICE matrices are row major, glam's are column major, so the
+        // transpose comes for free.
+        let rotation = body
+            .orientation
+            .map(|m| DMat3::from_cols_array_2d(&m.0))
+            .unwrap_or(DMat3::IDENTITY);
+        let model = DAffine3::from_mat3_translation(rotation, translation);
+        println!("{}: {model:?}", body.id);
+    }
+}
@@ -0,0 +1,158 @@
+//! Export of sampled ephemerides and windows as [Arrow](https://arrow.apache.org/)
+//! [RecordBatch]es, enabled by the `arrow` feature.
+//!
+//! The batches can be passed directly to dataframe libraries such as Polars or DataFusion, or
+//! written out with a Parquet writer. All columns are non-nullable `Float64`.
+use crate::cell::Window;
+use crate::common::AberrationCorrection;
+use crate::coordinates::Rectangular;
+use crate::spk::{easier_reader, State};
+use crate::string::StringParam;
+use crate::time::Et;
+use crate::{with_spice_lock_or_panic, Error};
+use arrow_array::{ArrayRef, Float64Array, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use cspice_sys::SpiceDouble;
+use std::sync::Arc;
+
+/// Error returned when exporting to Arrow.
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Spice(#[from] Error),
+    #[error(transparent)]
+    Arrow(#[from] ArrowError),
+}
+
+/// Build a record batch from named columns of equal length.
+fn record_batch(columns: Vec<(&str, Vec<SpiceDouble>)>) -> Result<RecordBatch, ArrowError> {
+    let fields = columns
+        .iter()
+        .map(|(name, _)| Field::new(*name, DataType::Float64, false))
+        .collect::<Vec<_>>();
+    let arrays = columns
+        .into_iter()
+        .map(|(_, values)| Arc::new(Float64Array::from(values)) as ArrayRef)
+        .collect();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+}
+
+/// Convert sampled positions, e.g. from [sample_adaptive()](crate::spk::sample_adaptive), to a
+/// record batch with the columns `et, x, y, z`.
+pub fn positions_record_batch(samples: &[(Et, Rectangular)]) -> Result<RecordBatch, ArrowError> {
+    record_batch(vec![
+        ("et", samples.iter().map(|(et, _)| et.0).collect()),
+        ("x", samples.iter().map(|(_, p)| p.x).collect()),
+        ("y", samples.iter().map(|(_, p)| p.y).collect()),
+        ("z", samples.iter().map(|(_, p)| p.z).collect()),
+    ])
+}
+
+/// Convert sampled states to a record batch with the columns `et, x, y, z, vx, vy, vz`.
+pub fn states_record_batch(samples: &[(Et, State)]) -> Result<RecordBatch, ArrowError> {
+    record_batch(vec![
+        ("et", samples.iter().map(|(et, _)| et.0).collect()),
+        ("x", samples.iter().map(|(_, s)| s.position.x).collect()),
+        ("y", samples.iter().map(|(_, s)| s.position.y).collect()),
+        ("z", samples.iter().map(|(_, s)| s.position.z).collect()),
+        ("vx", samples.iter().map(|(_, s)| s.velocity[0]).collect()),
+        ("vy", samples.iter().map(|(_, s)| s.velocity[1]).collect()),
+        ("vz", samples.iter().map(|(_, s)| s.velocity[2]).collect()),
+    ])
+}
+
+/// Sample the state of a target body relative to an observing body at each of `ets`, returning
+/// a record batch as described by [states_record_batch()].
+///
+/// See [easier_reader()].
+pub fn sample_states<'t, 'r, 'o, T, R, O>(
+    target: T,
+    ets: &[Et],
+    reference_frame: R,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<RecordBatch, ExportError>
+where
+    T: Into<StringParam<'t>>,
+    R: Into<StringParam<'r>>,
+    O: Into<StringParam<'o>>,
+{
+    let (target, reference_frame, observing_body) =
+        (target.into(), reference_frame.into(), observing_body.into());
+    let samples = with_spice_lock_or_panic(|| {
+        ets.iter()
+            .map(|et| {
+                let (state, _) = easier_reader(
+                    &*target,
+                    *et,
+                    &*reference_frame,
+                    aberration_correction,
+                    &*observing_body,
+                )?;
+                Ok((*et, state))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+    })?;
+    Ok(states_record_batch(&samples)?)
+}
+
+/// Convert the intervals of a window, e.g. the result of a geometry finder search, to a record
+/// batch with the columns `start, stop`.
+pub fn window_record_batch(window: &Window) -> Result<RecordBatch, ExportError> {
+    let intervals = (0..window.window_cardinality()? as usize)
+        .map(|i| window.window_interval(i))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(record_batch(vec![
+        ("start", intervals.iter().map(|(start, _)| *start).collect()),
+        ("stop", intervals.iter().map(|(_, stop)| *stop).collect()),
+    ])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+
+    fn column(batch: &RecordBatch, name: &str) -> Vec<SpiceDouble> {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_sample_states() {
+        load_test_data();
+        let ets = [Et(0.0), Et(3600.0)];
+        let batch =
+            sample_states("MOON", &ets, "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+        let names = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["et", "x", "y", "z", "vx", "vy", "vz"]);
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(column(&batch, "et"), [0.0, 3600.0]);
+        let (state, _) =
+            easier_reader("MOON", ets[1], "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+        assert_eq!(column(&batch, "vz")[1], state.velocity[2]);
+
+        let positions = positions_record_batch(&[(ets[1], state.position)]).unwrap();
+        assert_eq!(positions.num_columns(), 4);
+        assert_eq!(column(&positions, "x"), [state.position.x]);
+    }
+
+    #[test]
+    fn test_window_record_batch() {
+        let window = Window::from_intervals(&[(1.0, 2.0), (5.0, 8.0)]).unwrap();
+        let batch = window_record_batch(&window).unwrap();
+        assert_eq!(column(&batch, "start"), [1.0, 5.0]);
+        assert_eq!(column(&batch, "stop"), [2.0, 8.0]);
+    }
+}
@@ -0,0 +1,143 @@
+//! Types for referring to bodies, and functions for querying body constants in the kernel pool.
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::string::{SpiceString, StringParam};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{bodfnd_c, bods2c_c, SpiceBoolean, SpiceInt, SPICETRUE};
+use std::fmt::{Display, Formatter};
+
+/// A reference to a body (or barycenter, spacecraft, station etc.), either by name or by NAIF
+/// ID code.
+///
+/// Can be created from a Rust string, a [SpiceString], or an integer ID code such as the
+/// constants in [naif_ids](crate::naif_ids).
+pub enum BodyRef<'a> {
+    Name(StringParam<'a>),
+    Id(SpiceInt),
+}
+
+impl BodyRef<'_> {
+    /// Get the body as a string that can be passed to SPICE. ID codes are converted to their
+    /// string representation, which SPICE accepts wherever it accepts a body name.
+    pub(crate) fn to_string_param(&self) -> StringParam<'_> {
+        match self {
+            BodyRef::Name(name) => StringParam::Ref(name),
+            BodyRef::Id(id) => StringParam::Owned(SpiceString::from(id.to_string())),
+        }
+    }
+
+    /// Get the NAIF ID code of the body, signalling `SPICE(IDCODENOTFOUND)` if a name is not
+    /// recognised.
+    ///
+    /// See [bods2c_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/bods2c_c.html).
+    pub(crate) fn to_id(&self) -> Result<SpiceInt, Error> {
+        let name = match self {
+            BodyRef::Name(name) => name,
+            BodyRef::Id(id) => return Ok(*id),
+        };
+        with_spice_lock_or_panic(|| {
+            let mut id: SpiceInt = 0;
+            let mut found: SpiceBoolean = 0;
+            unsafe { bods2c_c(name.as_mut_ptr()?, &mut id, &mut found) };
+            get_last_error()?;
+            if found != SPICETRUE as SpiceBoolean {
+                set_error_message(format!(
+                    "The body {} could not be translated to an ID code",
+                    &**name
+                ));
+                signal_error("SPICE(IDCODENOTFOUND)");
+                get_last_error()?;
+            }
+            Ok(id)
+        })
+    }
+}
+
+/// Check whether a constant, such as `"RADII"` or `"GM"`, is present in the kernel pool for a body.
+///
+/// See [bodfnd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/bodfnd_c.html).
+pub fn has_constant<'b, 'i, B, I>(body: B, item: I) -> Result<bool, Error>
+where
+    B: Into<BodyRef<'b>>,
+    I: Into<StringParam<'i>>,
+{
+    let id = body.into().to_id()?;
+    with_spice_lock_or_panic(|| {
+        let found = unsafe { bodfnd_c(id, item.into().as_mut_ptr()?) };
+        get_last_error()?;
+        Ok(found == SPICETRUE as SpiceBoolean)
+    })
+}
+
+impl From<SpiceInt> for BodyRef<'_> {
+    fn from(id: SpiceInt) -> Self {
+        BodyRef::Id(id)
+    }
+}
+
+impl<'a> From<&'a str> for BodyRef<'a> {
+    fn from(name: &'a str) -> Self {
+        BodyRef::Name(name.into())
+    }
+}
+
+impl From<String> for BodyRef<'_> {
+    fn from(name: String) -> Self {
+        BodyRef::Name(name.into())
+    }
+}
+
+impl<'a> From<&'a String> for BodyRef<'a> {
+    fn from(name: &'a String) -> Self {
+        BodyRef::Name(name.into())
+    }
+}
+
+impl<'a> From<&'a SpiceString> for BodyRef<'a> {
+    fn from(name: &'a SpiceString) -> Self {
+        BodyRef::Name(name.into())
+    }
+}
+
+impl From<SpiceString> for BodyRef<'_> {
+    fn from(name: SpiceString) -> Self {
+        BodyRef::Name(name.into())
+    }
+}
+
+impl<'a> From<StringParam<'a>> for BodyRef<'a> {
+    fn from(name: StringParam<'a>) -> Self {
+        BodyRef::Name(name)
+    }
+}
+
+impl Display for BodyRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyRef::Name(name) => Display::fmt(&**name, f),
+            BodyRef::Id(id) => Display::fmt(id, f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naif_ids::{EARTH, MOON};
+    use crate::tests::load_moon_constants;
+
+    #[test]
+    fn test_to_string_param() {
+        assert_eq!(BodyRef::from(EARTH).to_string_param().as_str(), "399");
+        assert_eq!(BodyRef::from("MOON").to_string_param().as_str(), "MOON");
+    }
+
+    #[test]
+    fn test_has_constant() {
+        load_moon_constants();
+        assert!(has_constant(MOON, "RADII").unwrap());
+        assert!(has_constant("MOON", "RADII").unwrap());
+        assert!(!has_constant(MOON, "GM").unwrap());
+        let err = has_constant("NOT A BODY", "RADII").unwrap_err();
+        assert_eq!(err.short_message, "SPICE(IDCODENOTFOUND)");
+    }
+}
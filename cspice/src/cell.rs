@@ -1,16 +1,19 @@
 //! Functions for working with SPICE Cells.
 use crate::common::{ComparisonOperator, Side};
-use crate::error::get_last_error;
+use crate::error::{get_last_error, set_error_message, signal_error};
 use crate::string::StringParam;
+use crate::time::Et;
 use crate::{with_spice_lock_or_panic, Error};
 use cspice_sys::{
     _SpiceDataType_SPICE_CHR, _SpiceDataType_SPICE_DP, _SpiceDataType_SPICE_INT, appndc_c,
-    appndd_c, appndi_c, card_c, copy_c, scard_c, wncard_c, wncomd_c, wncond_c, wndifd_c, wnelmd_c,
-    wnexpd_c, wnextd_c, wnfetd_c, wnfild_c, wnfltd_c, wnincd_c, wninsd_c, wnintd_c, wnreld_c,
-    wnsumd_c, wnunid_c, wnvald_c, SpiceBoolean, SpiceChar, SpiceDouble, SpiceInt, SPICEFALSE,
-    SPICETRUE, SPICE_CELL_CTRLSZ,
+    appndd_c, appndi_c, card_c, copy_c, scard_c, size_c, wncard_c, wncomd_c, wncond_c, wndifd_c,
+    wnelmd_c, wnexpd_c, wnextd_c, wnfetd_c, wnfild_c, wnfltd_c, wnincd_c, wninsd_c, wnintd_c,
+    wnreld_c, wnsumd_c, wnunid_c, wnvald_c, SpiceBoolean, SpiceChar, SpiceDouble, SpiceInt,
+    SPICEFALSE, SPICETRUE, SPICE_CELL_CTRLSZ,
 };
+use std::cell::UnsafeCell;
 use std::ffi::c_void;
+use std::ops::{BitAnd, BitOr, Sub};
 
 /// A type that can be used in a SPICE Cell.
 pub trait CellType {}
@@ -21,15 +24,27 @@ impl CellType for SpiceChar {}
 
 /// A Rust wrapper around a SpiceCell and its data.
 pub struct Cell<T: CellType> {
-    cell: cspice_sys::SpiceCell,
-    #[allow(dead_code)]
+    // SPICE may update the header of cells it only reads from (e.g. to initialise them on first
+    // use), so the cell is wrapped in an UnsafeCell to allow passing it from a shared reference
+    cell: UnsafeCell<cspice_sys::SpiceCell>,
     data: Vec<T>,
 }
 
 impl<T: CellType> Cell<T> {
     /// Access the internal CSPICE Cell structure.
     pub fn as_mut_cell(&mut self) -> *mut cspice_sys::SpiceCell {
-        &mut self.cell
+        self.cell.get_mut()
+    }
+
+    /// Access the internal CSPICE Cell structure, for passing to SPICE functions that don't
+    /// modify the contents of the cell.
+    pub(crate) fn as_const_cell(&self) -> *mut cspice_sys::SpiceCell {
+        self.cell.get()
+    }
+
+    fn header(&self) -> &cspice_sys::SpiceCell {
+        // Safety: the header is only modified by SPICE while the cell is borrowed for a call
+        unsafe { &*self.cell.get() }
     }
 
     /// Set the cardinality of a cell.
@@ -47,12 +62,23 @@ impl<T: CellType> Cell<T> {
     /// See [size_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/size_c.html)
     pub fn get_size(&mut self) -> Result<usize, Error> {
         with_spice_lock_or_panic(|| {
-            let out = unsafe { card_c(self.as_mut_cell()) };
+            let out = unsafe { size_c(self.as_mut_cell()) };
             get_last_error()?;
             Ok(out as usize)
         })
     }
 
+    /// The size (maximum cardinality) the cell was created with.
+    pub fn size(&self) -> usize {
+        self.header().size as usize
+    }
+
+    /// The length of each element in the cell, including the nul terminator. This is only
+    /// non-zero for character cells.
+    pub fn length(&self) -> usize {
+        self.header().length as usize
+    }
+
     /// Return the cardinality (current number of elements) in a cell.
     ///
     /// See [card_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/card_c.html).
@@ -92,7 +118,10 @@ impl Cell<SpiceDouble> {
             base: data.as_mut_ptr() as *mut c_void,
             data: data[SPICE_CELL_CTRLSZ as usize..].as_mut_ptr() as *mut c_void,
         };
-        Self { cell, data }
+        Self {
+            cell: UnsafeCell::new(cell),
+            data,
+        }
     }
 
     /// Append an item to a double precision cell
@@ -123,7 +152,10 @@ impl Cell<SpiceInt> {
             base: data.as_mut_ptr() as *mut c_void,
             data: data[SPICE_CELL_CTRLSZ as usize..].as_mut_ptr() as *mut c_void,
         };
-        Self { cell, data }
+        Self {
+            cell: UnsafeCell::new(cell),
+            data,
+        }
     }
 
     /// Append an item to an integer cell
@@ -135,6 +167,12 @@ impl Cell<SpiceInt> {
             get_last_error()
         })
     }
+
+    /// The elements of an integer cell.
+    pub fn elements(&self) -> &[SpiceInt] {
+        let start = SPICE_CELL_CTRLSZ as usize;
+        &self.data[start..start + self.header().card as usize]
+    }
 }
 
 impl Cell<SpiceChar> {
@@ -156,18 +194,55 @@ impl Cell<SpiceChar> {
             base: data.as_mut_ptr() as *mut c_void,
             data: data[start_index..].as_mut_ptr() as *mut c_void,
         };
-        Self { cell, data }
+        Self {
+            cell: UnsafeCell::new(cell),
+            data,
+        }
+    }
+
+    /// The maximum length of a string that can be stored in the cell without being truncated
+    /// (excluding the nul terminator).
+    pub fn element_length(&self) -> usize {
+        self.length().saturating_sub(1)
     }
 
     /// Append an item to a character cell
     ///
+    /// Strings longer than [Cell::element_length()] are silently truncated by SPICE, see
+    /// [Cell::append_checked()] to return an error instead.
+    ///
     /// See [appndc_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/appndc_c.html)
     pub fn append<'s, S: Into<StringParam<'s>>>(&mut self, item: S) -> Result<(), Error> {
         with_spice_lock_or_panic(|| {
-            unsafe { appndc_c(item.into().as_mut_ptr(), self.as_mut_cell()) };
+            unsafe { appndc_c(item.into().as_mut_ptr()?, self.as_mut_cell()) };
             get_last_error()
         })
     }
+
+    /// Append an item to a character cell, returning an error if it would be truncated.
+    ///
+    /// See [appndc_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/appndc_c.html)
+    pub fn append_checked<'s, S: Into<StringParam<'s>>>(
+        &mut self,
+        item: S,
+    ) -> Result<(), AppendError> {
+        let item = item.into();
+        let length = item.0.as_bytes().len();
+        let max_length = self.element_length();
+        if length > max_length {
+            return Err(AppendError::Truncated { length, max_length });
+        }
+        Ok(self.append(item)?)
+    }
+}
+
+/// Error returned from [Cell::append_checked()].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum AppendError {
+    #[error("String of length {length} exceeds the cell's maximum element length of {max_length}")]
+    Truncated { length: usize, max_length: usize },
+    #[error(transparent)]
+    Spice(#[from] Error),
 }
 
 /// Summary of a double precision window.
@@ -186,12 +261,57 @@ pub type Window = Cell<SpiceDouble>;
 
 /// Window specific functions
 impl Cell<SpiceDouble> {
+    /// Create a window containing the given intervals, sized to hold exactly that many intervals.
+    ///
+    /// The intervals do not need to be sorted, overlapping intervals will be merged.
+    /// Returns an error if any interval has its left endpoint greater than its right endpoint.
+    ///
+    /// See [wnvald_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/wnvald_c.html).
+    pub fn from_intervals(intervals: &[(SpiceDouble, SpiceDouble)]) -> Result<Window, Error> {
+        let size = intervals.len() * 2;
+        let mut window = Window::new_double(size);
+        for &(left, right) in intervals {
+            window.append(left)?;
+            window.append(right)?;
+        }
+        window.window_validate(size, size)?;
+        Ok(window)
+    }
+
+    /// Create a window containing the given intervals of Ephemeris Time.
+    ///
+    /// See [Cell::from_intervals()].
+    pub fn from_et_intervals(intervals: &[(Et, Et)]) -> Result<Window, Error> {
+        let intervals: Vec<_> = intervals.iter().map(|(l, r)| (l.0, r.0)).collect();
+        Window::from_intervals(&intervals)
+    }
+
+    /// The number of intervals in the window, equivalent to [Cell::window_cardinality()] without
+    /// calling SPICE.
+    pub fn len(&self) -> usize {
+        self.header().card as usize / 2
+    }
+
+    /// Returns true if the window contains no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the interval at index `n`, or `None` if `n` is out of range.
+    pub fn get_interval(&self, n: usize) -> Option<(SpiceDouble, SpiceDouble)> {
+        if n >= self.len() {
+            return None;
+        }
+        let i = SPICE_CELL_CTRLSZ as usize + 2 * n;
+        Some((self.data[i], self.data[i + 1]))
+    }
+
     /// Return the cardinality (number of intervals) of a double precision window.
     ///
     /// See [wncard_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/wncard_c.html).
-    pub fn window_cardinality(&mut self) -> Result<SpiceInt, Error> {
+    pub fn window_cardinality(&self) -> Result<SpiceInt, Error> {
         with_spice_lock_or_panic(|| {
-            let out = unsafe { wncard_c(self.as_mut_cell()) };
+            let out = unsafe { wncard_c(self.as_const_cell()) };
             get_last_error()?;
             Ok(out)
         })
@@ -275,12 +395,23 @@ impl Cell<SpiceDouble> {
 
     /// Fetch a particular interval from a double precision window.
     ///
+    /// Returns a `SPICE(NOINTERVAL)` error if `n` is out of range, see also
+    /// [Cell::get_interval()].
+    ///
     /// See [wnfetd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/wnfetd_c.html).
-    pub fn window_interval(&mut self, n: usize) -> Result<(SpiceDouble, SpiceDouble), Error> {
+    pub fn window_interval(&self, n: usize) -> Result<(SpiceDouble, SpiceDouble), Error> {
         with_spice_lock_or_panic(|| {
+            if n >= self.len() {
+                set_error_message(format!(
+                    "Interval index {n} is out of range, the window has {} intervals",
+                    self.len()
+                ));
+                signal_error("SPICE(NOINTERVAL)");
+                get_last_error()?;
+            }
             let (mut left, mut right) = (0.0, 0.0);
             unsafe {
-                wnfetd_c(self.as_mut_cell(), n as SpiceInt, &mut left, &mut right);
+                wnfetd_c(self.as_const_cell(), n as SpiceInt, &mut left, &mut right);
             };
             get_last_error()?;
             Ok((left, right))
@@ -433,3 +564,123 @@ impl Cell<SpiceDouble> {
         })
     }
 }
+
+impl Window {
+    /// Create an output window large enough for any set operation on `a` and `b`.
+    fn new_for_operands(a: &mut Window, b: &mut Window) -> Result<Window, Error> {
+        let intervals = (a.window_cardinality()? + b.window_cardinality()?) as usize;
+        Ok(Window::new_double(2 * intervals.max(1)))
+    }
+}
+
+macro_rules! impl_window_op {
+    ($op:ident, $method:ident, $window_fn:ident) => {
+        impl $op<&mut Window> for &mut Window {
+            type Output = Result<Window, Error>;
+
+            fn $method(self, rhs: &mut Window) -> Self::Output {
+                let mut output = Window::new_for_operands(self, rhs)?;
+                self.$window_fn(rhs, &mut output)?;
+                Ok(output)
+            }
+        }
+
+        impl $op<Window> for Window {
+            type Output = Result<Window, Error>;
+
+            fn $method(mut self, mut rhs: Window) -> Self::Output {
+                (&mut self).$method(&mut rhs)
+            }
+        }
+    };
+}
+
+// Set algebra on windows, the output is sized automatically.
+impl_window_op!(BitAnd, bitand, window_intersect);
+impl_window_op!(BitOr, bitor, window_union);
+impl_window_op!(Sub, sub, window_difference);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        let mut cell = Cell::new_int(10);
+        assert_eq!(cell.size(), 10);
+        assert_eq!(cell.get_size().unwrap(), 10);
+        assert_eq!(cell.length(), 0);
+        cell.append(1).unwrap();
+        assert_eq!(cell.get_cardinality().unwrap(), 1);
+        cell.append(5).unwrap();
+        assert_eq!(cell.elements(), [1, 5]);
+
+        let cell = Cell::new_char(5, 8);
+        assert_eq!(cell.size(), 5);
+        assert_eq!(cell.length(), 8);
+        assert_eq!(cell.element_length(), 7);
+    }
+
+    #[test]
+    fn test_window_from_intervals() {
+        let window = Window::from_intervals(&[(5.0, 6.0), (1.0, 3.0), (2.0, 4.0)]).unwrap();
+        assert_eq!(window.size(), 6);
+        assert_eq!(window.window_cardinality().unwrap(), 2);
+        assert_eq!(window.window_interval(0).unwrap(), (1.0, 4.0));
+        assert_eq!(window.window_interval(1).unwrap(), (5.0, 6.0));
+        assert_eq!(window.len(), 2);
+        assert!(!window.is_empty());
+        assert_eq!(window.get_interval(1), Some((5.0, 6.0)));
+        assert_eq!(window.get_interval(2), None);
+        let error = window.window_interval(2).unwrap_err();
+        assert_eq!(error.short_message, "SPICE(NOINTERVAL)");
+        assert!(Window::new_double(4).is_empty());
+
+        let window = Window::from_et_intervals(&[(Et(0.0), Et(10.0))]).unwrap();
+        assert_eq!(window.window_interval(0).unwrap(), (0.0, 10.0));
+
+        let error = Window::from_intervals(&[(2.0, 1.0)]).err().unwrap();
+        assert_eq!(error.short_message, "SPICE(BADENDPOINTS)");
+    }
+
+    #[test]
+    fn test_append_checked() {
+        let mut cell = Cell::new_char(5, 4);
+        cell.append_checked("abc").unwrap();
+        match cell.append_checked("abcd") {
+            Err(AppendError::Truncated { length, max_length }) => {
+                assert_eq!(length, 4);
+                assert_eq!(max_length, 3);
+            }
+            _ => panic!("Expected a truncation error"),
+        }
+        assert_eq!(cell.get_cardinality().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_window_operators() {
+        let window = |intervals: &[(f64, f64)]| Window::from_intervals(intervals).unwrap();
+        let intervals = |w: Window| {
+            (0..w.window_cardinality().unwrap() as usize)
+                .map(|i| w.window_interval(i).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let mut a = window(&[(0.0, 10.0), (20.0, 30.0)]);
+        let mut b = window(&[(5.0, 25.0)]);
+
+        let and = (&mut a & &mut b).unwrap();
+        assert_eq!(intervals(and), vec![(5.0, 10.0), (20.0, 25.0)]);
+        let or = (&mut a | &mut b).unwrap();
+        assert_eq!(intervals(or), vec![(0.0, 30.0)]);
+        let diff = (&mut a - &mut b).unwrap();
+        assert_eq!(intervals(diff), vec![(0.0, 5.0), (25.0, 30.0)]);
+
+        let empty = (window(&[]) & window(&[])).unwrap();
+        assert_eq!(intervals(empty), vec![]);
+        let chained = ((a - b).unwrap() | window(&[(40.0, 50.0)])).unwrap();
+        assert_eq!(
+            intervals(chained),
+            vec![(0.0, 5.0), (25.0, 30.0), (40.0, 50.0)]
+        );
+    }
+}
@@ -0,0 +1,674 @@
+//! Functions relating to the C-kernel (CK) subsystem of SPICE, for spacecraft and instrument
+//! orientation.
+//!
+//! Times in this module are encoded spacecraft clock (SCLK) ticks, see
+//! [SclkTime::encoded](crate::time::SclkTime::encoded).
+use crate::cell::{Cell, Window};
+use crate::error::get_last_error;
+use crate::frame::{FrameClass, FrameId};
+use crate::matrix::Matrix3x3;
+use crate::quaternion::Quaternion;
+use crate::string::{static_spice_str, StaticSpiceStr, StringParam};
+use crate::time::{Et, OutputFormat, SclkTime};
+use crate::units::Seconds;
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    ckcov_c, ckfrot_c, ckgp_c, ckgpav_c, ckmeta_c, ckobj_c, SpiceBoolean, SpiceDouble, SpiceInt,
+    SPICEFALSE, SPICETRUE,
+};
+
+/// Size of the cells used by [coverage_report()] to hold the instruments and coverage of a CK.
+pub const COVERAGE_WINDOW_SIZE: usize = 10000;
+
+/// The orientation of a structure at a given time.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Pointing {
+    /// Rotation from the reference frame to the instrument frame.
+    pub quaternion: Quaternion,
+    /// Angular velocity of the instrument frame relative to the reference frame, expressed in
+    /// the reference frame (radians/s), if requested.
+    pub angular_velocity: Option<Vector3D>,
+    /// The encoded SCLK time of the pointing.
+    pub clock: SpiceDouble,
+}
+
+/// Error returned from [interpolate_pointing()].
+#[derive(Debug, thiserror::Error)]
+pub enum PointingError {
+    /// There is no pointing on one or both sides of the requested time within the maximum gap.
+    #[error(
+        "No pointing available to interpolate at {clock} (before: {before:?}, after: {after:?})"
+    )]
+    Gap {
+        clock: SpiceDouble,
+        before: Option<SpiceDouble>,
+        after: Option<SpiceDouble>,
+    },
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+/// Get pointing (attitude) for a specified spacecraft clock time, returning `None` if no
+/// pointing is available within the tolerance.
+///
+/// See [ckgp_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckgp_c.html).
+pub fn get_pointing<'r, R: Into<StringParam<'r>>>(
+    instrument: SpiceInt,
+    clock: SpiceDouble,
+    tolerance: SpiceDouble,
+    reference_frame: R,
+) -> Result<Option<Pointing>, Error> {
+    lookup(instrument, clock, tolerance, &reference_frame.into(), false)
+}
+
+/// Get pointing (attitude) and angular velocity for a specified spacecraft clock time, returning
+/// `None` if no pointing with angular velocity is available within the tolerance.
+///
+/// See [ckgpav_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckgpav_c.html).
+pub fn get_pointing_and_av<'r, R: Into<StringParam<'r>>>(
+    instrument: SpiceInt,
+    clock: SpiceDouble,
+    tolerance: SpiceDouble,
+    reference_frame: R,
+) -> Result<Option<Pointing>, Error> {
+    lookup(instrument, clock, tolerance, &reference_frame.into(), true)
+}
+
+fn lookup(
+    instrument: SpiceInt,
+    clock: SpiceDouble,
+    tolerance: SpiceDouble,
+    reference_frame: &StringParam,
+    angular_velocity: bool,
+) -> Result<Option<Pointing>, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut cmat = Matrix3x3::default();
+        let mut av = Vector3D::default();
+        let mut clkout = 0.0;
+        let mut found: SpiceBoolean = 0;
+        unsafe {
+            if angular_velocity {
+                ckgpav_c(
+                    instrument,
+                    clock,
+                    tolerance,
+                    reference_frame.as_mut_ptr()?,
+                    cmat.as_mut_ptr(),
+                    av.as_mut_ptr(),
+                    &mut clkout,
+                    &mut found,
+                )
+            } else {
+                ckgp_c(
+                    instrument,
+                    clock,
+                    tolerance,
+                    reference_frame.as_mut_ptr()?,
+                    cmat.as_mut_ptr(),
+                    &mut clkout,
+                    &mut found,
+                )
+            }
+        };
+        get_last_error()?;
+        if found != SPICETRUE as SpiceBoolean {
+            return Ok(None);
+        }
+        Ok(Some(Pointing {
+            quaternion: Quaternion::from_matrix(&cmat)?,
+            angular_velocity: angular_velocity.then_some(av),
+            clock: clkout,
+        }))
+    })
+}
+
+/// Get pointing at a specified spacecraft clock time, interpolating between the nearest CK
+/// records using [Quaternion::slerp()] when no pointing is available at that exact time.
+///
+/// Records are only interpolated if they are no more than `max_gap` ticks apart, otherwise
+/// [PointingError::Gap] is returned. Angular velocity is linearly interpolated when requested.
+pub fn interpolate_pointing<'r, R: Into<StringParam<'r>>>(
+    instrument: SpiceInt,
+    clock: SpiceDouble,
+    max_gap: SpiceDouble,
+    reference_frame: R,
+    angular_velocity: bool,
+) -> Result<Pointing, PointingError> {
+    let reference_frame = reference_frame.into();
+    let find = |center: SpiceDouble, tolerance: SpiceDouble| {
+        lookup(
+            instrument,
+            center,
+            tolerance,
+            &reference_frame,
+            angular_velocity,
+        )
+    };
+
+    if let Some(exact) = find(clock, 0.0)? {
+        return Ok(exact);
+    }
+    let nearest = match find(clock, max_gap)? {
+        Some(p) => p,
+        None => {
+            return Err(PointingError::Gap {
+                clock,
+                before: None,
+                after: None,
+            })
+        }
+    };
+
+    // Search for the closest record on the other side of the requested time, that is within
+    // max_gap of the nearest record, by repeatedly narrowing the search interval.
+    let (mut lo, mut hi) = if nearest.clock < clock {
+        (clock, nearest.clock + max_gap)
+    } else {
+        (nearest.clock - max_gap, clock)
+    };
+    let mut other: Option<Pointing> = None;
+    loop {
+        let found = match find((lo + hi) / 2.0, (hi - lo) / 2.0)? {
+            Some(p) if p.clock != clock && Some(p.clock) != other.map(|o| o.clock) => p,
+            _ => break,
+        };
+        if nearest.clock < clock {
+            hi = found.clock;
+        } else {
+            lo = found.clock;
+        }
+        other = Some(found);
+    }
+
+    let other = match other {
+        Some(p) => p,
+        None => {
+            let (before, after) = if nearest.clock < clock {
+                (Some(nearest.clock), None)
+            } else {
+                (None, Some(nearest.clock))
+            };
+            return Err(PointingError::Gap {
+                clock,
+                before,
+                after,
+            });
+        }
+    };
+    let (before, after) = if nearest.clock < clock {
+        (nearest, other)
+    } else {
+        (other, nearest)
+    };
+    let t = (clock - before.clock) / (after.clock - before.clock);
+    let angular_velocity = match (before.angular_velocity, after.angular_velocity) {
+        (Some(a), Some(b)) => {
+            let mut av = Vector3D::default();
+            for i in 0..3 {
+                av[i] = a[i] + (b[i] - a[i]) * t;
+            }
+            Some(av)
+        }
+        _ => None,
+    };
+    Ok(Pointing {
+        quaternion: before.quaternion.slerp(&after.quaternion, t),
+        angular_velocity,
+        clock,
+    })
+}
+
+/// An interval of time in both spacecraft clock and UTC, see [InstrumentCoverage].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageInterval {
+    pub start: SclkTime,
+    pub end: SclkTime,
+    /// The start in ISO UTC format, e.g. `2000-01-01T11:58:55.816`.
+    pub start_utc: String,
+    /// The end in ISO UTC format.
+    pub end_utc: String,
+    /// The duration in seconds of Ephemeris Time.
+    pub duration: Seconds,
+}
+
+impl CoverageInterval {
+    fn new(spacecraft: SpiceInt, start: SpiceDouble, end: SpiceDouble) -> Result<Self, Error> {
+        let (start, end) = (
+            SclkTime::from_encoded(spacecraft, start),
+            SclkTime::from_encoded(spacecraft, end),
+        );
+        let (start_et, end_et) = (start.to_et()?, end.to_et()?);
+        let format = OutputFormat::IsoCalendar { precision: 3 };
+        Ok(Self {
+            start,
+            end,
+            start_utc: start_et.format(format.clone())?,
+            end_utc: end_et.format(format)?,
+            duration: Seconds(end_et.0 - start_et.0),
+        })
+    }
+}
+
+/// The coverage of one instrument (or structure) in a CK, see [coverage_report()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstrumentCoverage {
+    /// The CK ID code of the instrument.
+    pub instrument: SpiceInt,
+    /// The NAIF ID code of the spacecraft clock used by the instrument.
+    pub clock: SpiceInt,
+    /// The intervals with pointing, in ascending order.
+    pub intervals: Vec<CoverageInterval>,
+    /// The gaps between intervals that are longer than the threshold, in ascending order.
+    pub gaps: Vec<CoverageInterval>,
+}
+
+/// Summarise the coverage of every instrument in a CK file, reporting the gaps in coverage
+/// that are longer than `gap_threshold`.
+///
+/// The SCLK kernel of each spacecraft clock and a leapseconds kernel must be loaded.
+///
+/// See [ckobj_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckobj_c.html) and
+/// [ckcov_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckcov_c.html).
+pub fn coverage_report<'f, F: Into<StringParam<'f>>>(
+    file: F,
+    gap_threshold: Seconds,
+) -> Result<Vec<InstrumentCoverage>, Error> {
+    let file = file.into();
+    with_spice_lock_or_panic(|| {
+        let mut instruments = Cell::new_int(COVERAGE_WINDOW_SIZE);
+        unsafe { ckobj_c(file.as_mut_ptr()?, instruments.as_mut_cell()) };
+        get_last_error()?;
+        instruments
+            .elements()
+            .iter()
+            .map(|&instrument| {
+                let clock = clock_id(instrument)?;
+                let mut cover = Window::new_double(COVERAGE_WINDOW_SIZE);
+                unsafe {
+                    ckcov_c(
+                        file.as_mut_ptr()?,
+                        instrument,
+                        SPICEFALSE as SpiceBoolean,
+                        static_spice_str!("INTERVAL").as_mut_ptr(),
+                        0.0,
+                        static_spice_str!("SCLK").as_mut_ptr(),
+                        cover.as_mut_cell(),
+                    )
+                };
+                get_last_error()?;
+                let intervals = (0..cover.window_cardinality()? as usize)
+                    .map(|i| {
+                        let (start, end) = cover.window_interval(i)?;
+                        CoverageInterval::new(clock, start, end)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let mut gaps = Vec::new();
+                for pair in intervals.windows(2) {
+                    let (end, start) = (pair[0].end.encoded(), pair[1].start.encoded());
+                    let gap = CoverageInterval::new(clock, end, start)?;
+                    if gap.duration.0 > gap_threshold.0 {
+                        gaps.push(gap);
+                    }
+                }
+                Ok(InstrumentCoverage {
+                    instrument,
+                    clock,
+                    intervals,
+                    gaps,
+                })
+            })
+            .collect()
+    })
+}
+
+/// Find the rotation from the frame of a CK instrument (or structure) to its base frame at
+/// `et`, i.e. the reference frame of the CK segment providing the orientation. Returns `None` if
+/// no loaded CK has pointing for the instrument at `et`.
+///
+/// See [ckfrot_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckfrot_c.html).
+pub fn frame_rotation(instrument: SpiceInt, et: Et) -> Result<Option<(Matrix3x3, FrameId)>, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut rotation = Matrix3x3::default();
+        let (mut base, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+        unsafe {
+            ckfrot_c(
+                instrument,
+                et.0,
+                rotation.as_mut_ptr(),
+                &mut base,
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok((found == SPICETRUE as SpiceBoolean).then_some((rotation, FrameId(base))))
+    })
+}
+
+/// The NAIF ID code of the spacecraft clock used by a CK instrument (or structure).
+///
+/// See [ckmeta_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckmeta_c.html).
+pub fn clock_id(instrument: SpiceInt) -> Result<SpiceInt, Error> {
+    metadata(instrument, static_spice_str!("SCLK"))
+}
+
+/// The NAIF ID code of the spacecraft a CK instrument (or structure) is on.
+///
+/// See [ckmeta_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ckmeta_c.html).
+pub fn spacecraft_id(instrument: SpiceInt) -> Result<SpiceInt, Error> {
+    metadata(instrument, static_spice_str!("SPK"))
+}
+
+fn metadata(instrument: SpiceInt, meta: StaticSpiceStr) -> Result<SpiceInt, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut id = 0;
+        unsafe { ckmeta_c(instrument, meta.as_mut_ptr(), &mut id) };
+        get_last_error()?;
+        Ok(id)
+    })
+}
+
+/// The CK ID code of the instrument (or structure) providing the orientation of a CK frame, or
+/// `None` if the frame is not recognised or is not a CK frame.
+pub fn instrument_of_frame(frame: FrameId) -> Result<Option<SpiceInt>, Error> {
+    Ok(frame
+        .info()?
+        .filter(|info| info.class == FrameClass::Ck)
+        .map(|info| info.class_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::furnish;
+    use crate::string::SpiceString;
+    use crate::tests::load_test_data;
+    use std::path::{Path, PathBuf};
+    use std::sync::Once;
+
+    const INSTRUMENT: SpiceInt = -998000;
+    const FRAME: SpiceInt = -998001;
+    /// An instrument rotating about +Z, see `test_interpolate_rotating_pointing`.
+    const ROTATING_INSTRUMENT: SpiceInt = -998002;
+
+    /// Write and load an SCLK kernel with one second per 256 ticks starting at J2000 TDB, and a
+    /// frame kernel defining CSPICE_RS_CK_FRAME. Then write (but don't load) a CK with pointing
+    /// from 0 to 10 s, 20 to 30 s and 32 to 40 s, rotated 90 degrees about +Z from J2000.
+    fn load_test_ck() -> PathBuf {
+        static LOAD: Once = Once::new();
+        let ck = std::env::temp_dir().join("cspice_rs_ck_test.bc");
+        LOAD.call_once(|| {
+            load_test_data();
+            let sclk = std::env::temp_dir().join("cspice_rs_ck_test.tsc");
+            std::fs::write(
+                &sclk,
+                "\\begindata
+SCLK_KERNEL_ID           = ( @2000-01-01/00:00:00 )
+SCLK_DATA_TYPE_998       = ( 1 )
+SCLK01_TIME_SYSTEM_998   = ( 1 )
+SCLK01_N_FIELDS_998      = ( 2 )
+SCLK01_MODULI_998        = ( 4294967296 256 )
+SCLK01_OFFSETS_998       = ( 0 0 )
+SCLK01_OUTPUT_DELIM_998  = ( 2 )
+SCLK_PARTITION_START_998 = ( 0 )
+SCLK_PARTITION_END_998   = ( 1E9 )
+SCLK01_COEFFICIENTS_998  = ( 0 0 1 )
+FRAME_CSPICE_RS_CK_FRAME = -998001
+FRAME_-998001_NAME       = 'CSPICE_RS_CK_FRAME'
+FRAME_-998001_CLASS      = 3
+FRAME_-998001_CLASS_ID   = -998000
+FRAME_-998001_CENTER     = -998
+CK_-998000_SCLK          = -998
+CK_-998000_SPK           = -998
+CK_-998002_SCLK          = -998
+CK_-998002_SPK           = -998
+\\begintext
+",
+            )
+            .unwrap();
+            furnish(sclk.to_string_lossy()).unwrap();
+
+            let half = std::f64::consts::FRAC_1_SQRT_2;
+            write_ck(
+                &ck,
+                INSTRUMENT,
+                [0.0, 20.0, 32.0],
+                [10.0, 30.0, 40.0],
+                [[half, 0.0, 0.0, half]; 3],
+                [[0.0; 3]; 3],
+            );
+        });
+        ck
+    }
+
+    /// Write a type 2 CK with one record per interval, with the start and stop times in seconds.
+    fn write_ck<const N: usize>(
+        path: &Path,
+        instrument: SpiceInt,
+        starts: [SpiceDouble; N],
+        stops: [SpiceDouble; N],
+        mut quats: [[SpiceDouble; 4]; N],
+        mut avvs: [[SpiceDouble; 3]; N],
+    ) {
+        let _ = std::fs::remove_file(path);
+        let mut starts = starts.map(|s| s * 256.0);
+        let mut stops = stops.map(|s| s * 256.0);
+        let mut rates = [1.0 / 256.0; N];
+        with_spice_lock_or_panic(|| unsafe {
+            let mut handle = 0;
+            cspice_sys::ckopn_c(
+                SpiceString::from(path.to_string_lossy())
+                    .as_mut_ptr()
+                    .unwrap(),
+                static_spice_str!("CK TEST").as_mut_ptr(),
+                0,
+                &mut handle,
+            );
+            cspice_sys::ckw02_c(
+                handle,
+                starts[0],
+                stops[N - 1],
+                instrument,
+                static_spice_str!("J2000").as_mut_ptr(),
+                static_spice_str!("CSPICE_RS_CK").as_mut_ptr(),
+                N as SpiceInt,
+                starts.as_mut_ptr(),
+                stops.as_mut_ptr(),
+                quats.as_mut_ptr(),
+                avvs.as_mut_ptr(),
+                rates.as_mut_ptr(),
+            );
+            cspice_sys::ckcls_c(handle);
+            get_last_error().unwrap();
+        });
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let ck = load_test_ck();
+        let report = coverage_report(ck.to_string_lossy(), Seconds(5.0)).unwrap();
+        assert_eq!(report.len(), 1);
+        let coverage = &report[0];
+        assert_eq!(coverage.instrument, INSTRUMENT);
+        assert_eq!(coverage.clock, -998);
+        assert_eq!(coverage.intervals.len(), 3);
+        assert_eq!(coverage.intervals[1].start.encoded(), 20.0 * 256.0);
+        assert_eq!(coverage.intervals[1].duration, Seconds(10.0));
+        assert_eq!(
+            coverage.intervals[0].start_utc,
+            Et(0.0)
+                .format(OutputFormat::IsoCalendar { precision: 3 })
+                .unwrap()
+        );
+        // The 2 s gap is below the threshold
+        assert_eq!(coverage.gaps.len(), 1);
+        assert_eq!(coverage.gaps[0].start, coverage.intervals[0].end);
+        assert_eq!(coverage.gaps[0].end, coverage.intervals[1].start);
+        assert_eq!(coverage.gaps[0].duration, Seconds(10.0));
+
+        let report = coverage_report(ck.to_string_lossy(), Seconds(1.0)).unwrap();
+        assert_eq!(report[0].gaps.len(), 2);
+    }
+
+    fn assert_rotated_about_z(pointing: &Pointing) {
+        let half = std::f64::consts::FRAC_1_SQRT_2;
+        let expected = [half, 0.0, 0.0, half];
+        for (a, b) in pointing.quaternion.0.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-12, "{:?}", pointing.quaternion);
+        }
+    }
+
+    #[test]
+    fn test_get_pointing() {
+        let ck = load_test_ck();
+        furnish(ck.to_string_lossy()).unwrap();
+
+        let pointing = get_pointing(INSTRUMENT, 5.0 * 256.0, 0.0, "J2000")
+            .unwrap()
+            .unwrap();
+        assert_rotated_about_z(&pointing);
+        assert_eq!(pointing.clock, 5.0 * 256.0);
+        assert_eq!(pointing.angular_velocity, None);
+
+        let pointing = get_pointing_and_av(INSTRUMENT, 5.0 * 256.0, 0.0, "J2000")
+            .unwrap()
+            .unwrap();
+        assert_rotated_about_z(&pointing);
+        assert_eq!(pointing.angular_velocity, Some(Vector3D::default()));
+
+        // In the gap between the first two intervals
+        assert_eq!(
+            get_pointing(INSTRUMENT, 12.0 * 256.0, 0.0, "J2000").unwrap(),
+            None
+        );
+        let pointing = get_pointing(INSTRUMENT, 12.0 * 256.0, 3.0 * 256.0, "J2000")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pointing.clock, 10.0 * 256.0);
+        // After the end of coverage
+        assert_eq!(
+            get_pointing_and_av(INSTRUMENT, 50.0 * 256.0, 0.0, "J2000").unwrap(),
+            None
+        );
+        crate::data::unload(ck.to_string_lossy()).unwrap();
+    }
+
+    #[test]
+    fn test_interpolate_pointing() {
+        let ck = load_test_ck();
+        furnish(ck.to_string_lossy()).unwrap();
+
+        let exact = interpolate_pointing(INSTRUMENT, 5.0 * 256.0, 0.0, "J2000", false).unwrap();
+        assert_rotated_about_z(&exact);
+        assert_eq!(exact.clock, 5.0 * 256.0);
+
+        // The 10 s gap between the first two intervals
+        let pointing =
+            interpolate_pointing(INSTRUMENT, 15.0 * 256.0, 12.0 * 256.0, "J2000", true).unwrap();
+        assert_rotated_about_z(&pointing);
+        assert_eq!(pointing.clock, 15.0 * 256.0);
+        assert_eq!(pointing.angular_velocity, Some(Vector3D::default()));
+
+        match interpolate_pointing(INSTRUMENT, 15.0 * 256.0, 2.0 * 256.0, "J2000", false) {
+            Err(PointingError::Gap {
+                before: None,
+                after: None,
+                ..
+            }) => {}
+            other => panic!("Expected a gap, got {other:?}"),
+        }
+        // After the end of coverage there is only pointing before
+        match interpolate_pointing(INSTRUMENT, 45.0 * 256.0, 10.0 * 256.0, "J2000", false) {
+            Err(PointingError::Gap {
+                before: Some(before),
+                after: None,
+                ..
+            }) => assert_eq!(before, 40.0 * 256.0),
+            other => panic!("Expected a gap, got {other:?}"),
+        }
+        crate::data::unload(ck.to_string_lossy()).unwrap();
+    }
+
+    #[test]
+    fn test_interpolate_rotating_pointing() {
+        load_test_ck();
+        let ck = std::env::temp_dir().join("cspice_rs_ck_rotating_test.bc");
+        // Rotating about +Z at 0.01 rad/s from 0 to 10 s, then at 0.03 rad/s from 20 to 30 s
+        // starting from 0.5 rad
+        let (sin, cos) = 0.25f64.sin_cos();
+        write_ck(
+            &ck,
+            ROTATING_INSTRUMENT,
+            [0.0, 20.0],
+            [10.0, 30.0],
+            [[1.0, 0.0, 0.0, 0.0], [cos, 0.0, 0.0, sin]],
+            [[0.0, 0.0, 0.01], [0.0, 0.0, 0.03]],
+        );
+        furnish(ck.to_string_lossy()).unwrap();
+
+        let record = |seconds: SpiceDouble| {
+            get_pointing_and_av(ROTATING_INSTRUMENT, seconds * 256.0, 0.0, "J2000")
+                .unwrap()
+                .unwrap()
+        };
+        let (before, after) = (record(10.0), record(20.0));
+        let midpoint = interpolate_pointing(
+            ROTATING_INSTRUMENT,
+            15.0 * 256.0,
+            12.0 * 256.0,
+            "J2000",
+            true,
+        )
+        .unwrap();
+        assert_eq!(midpoint.clock, 15.0 * 256.0);
+
+        // Halfway along the shortest arc is the normalised sum of the end quaternions
+        let (q0, q1) = (before.quaternion.0, after.quaternion.0);
+        assert!(q0.iter().zip(q1.iter()).map(|(a, b)| a * b).sum::<f64>() > 0.0);
+        let sum: Vec<f64> = q0.iter().zip(q1.iter()).map(|(a, b)| a + b).collect();
+        let norm = sum.iter().map(|x| x * x).sum::<f64>().sqrt();
+        for (a, b) in midpoint.quaternion.0.iter().zip(sum.iter()) {
+            assert!((a - b / norm).abs() < 1e-12, "{:?}", midpoint.quaternion);
+        }
+        for end in [q0, q1] {
+            assert!((midpoint.quaternion.0[3] - end[3]).abs() > 1e-3);
+        }
+
+        let av = midpoint.angular_velocity.unwrap();
+        let (av0, av1) = (
+            before.angular_velocity.unwrap(),
+            after.angular_velocity.unwrap(),
+        );
+        assert!((av0[2].abs() - 0.01).abs() < 1e-12, "{av0:?}");
+        assert!((av1[2].abs() - 0.03).abs() < 1e-12, "{av1:?}");
+        for i in 0..3 {
+            assert!((av[i] - (av0[i] + av1[i]) / 2.0).abs() < 1e-12, "{av:?}");
+        }
+        assert!((av[2].abs() - 0.02).abs() < 1e-12, "{av:?}");
+        crate::data::unload(ck.to_string_lossy()).unwrap();
+    }
+
+    #[test]
+    fn test_frame_level() {
+        let ck = load_test_ck();
+        assert_eq!(clock_id(INSTRUMENT).unwrap(), -998);
+        assert_eq!(spacecraft_id(INSTRUMENT).unwrap(), -998);
+        assert_eq!(
+            instrument_of_frame(FrameId(FRAME)).unwrap(),
+            Some(INSTRUMENT)
+        );
+        assert_eq!(instrument_of_frame(FrameId::J2000).unwrap(), None);
+
+        assert_eq!(frame_rotation(INSTRUMENT, Et(5.0)).unwrap(), None);
+        furnish(ck.to_string_lossy()).unwrap();
+        let (rotation, base) = frame_rotation(INSTRUMENT, Et(5.0)).unwrap().unwrap();
+        assert_eq!(base, FrameId::J2000);
+        let expected = Matrix3x3::from_frames("CSPICE_RS_CK_FRAME", "J2000", Et(5.0)).unwrap();
+        for (a, b) in rotation.0.iter().flatten().zip(expected.0.iter().flatten()) {
+            assert!((a - b).abs() < 1e-12, "{a} != {b}");
+        }
+        assert!(rotation.0[0][0].abs() < 1e-12);
+        assert_eq!(frame_rotation(INSTRUMENT, Et(15.0)).unwrap(), None);
+        crate::data::unload(ck.to_string_lossy()).unwrap();
+    }
+}
@@ -5,6 +5,8 @@ use cspice_sys::SpiceChar;
 pub(crate) static SET: StaticSpiceStr = static_spice_str!("SET");
 pub(crate) static GET: StaticSpiceStr = static_spice_str!("GET");
 pub(crate) static CALENDAR: StaticSpiceStr = static_spice_str!("CALENDAR");
+pub(crate) static SYSTEM: StaticSpiceStr = static_spice_str!("SYSTEM");
+pub(crate) static ZONE: StaticSpiceStr = static_spice_str!("ZONE");
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ComparisonOperator {
@@ -45,6 +47,11 @@ impl Side {
     }
 }
 
+/// Aberration correction for either reception or transmission.
+///
+/// Functions that only accept corrections in one direction take a [ReceptionCorrection] instead,
+/// e.g. [terminator_crossings()](crate::events::terminator_crossings). Both it and
+/// [TransmissionCorrection] convert into this type.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum AberrationCorrection {
@@ -60,7 +67,7 @@ pub enum AberrationCorrection {
 }
 
 impl AberrationCorrection {
-    pub(crate) unsafe fn as_spice_char(&self) -> *mut SpiceChar {
+    fn spice_str(&self) -> StaticSpiceStr {
         match &self {
             AberrationCorrection::NONE => static_spice_str!("NONE"),
             AberrationCorrection::LT => static_spice_str!("LT"),
@@ -72,6 +79,146 @@ impl AberrationCorrection {
             AberrationCorrection::XCN => static_spice_str!("XCN"),
             AberrationCorrection::XCN_S => static_spice_str!("XCN+S"),
         }
-        .as_mut_ptr()
+    }
+
+    pub(crate) unsafe fn as_spice_char(&self) -> *mut SpiceChar {
+        self.spice_str().as_mut_ptr()
+    }
+
+    /// The SPICE name of the correction, e.g. `"LT+S"`.
+    pub fn as_str(&self) -> &'static str {
+        self.spice_str().0.trim_end_matches('\0')
+    }
+
+    /// Whether this is a reception correction, [AberrationCorrection::NONE] is considered to be
+    /// both a reception and transmission correction.
+    pub fn is_reception(&self) -> bool {
+        ReceptionCorrection::try_from(*self).is_ok()
+    }
+
+    /// Whether this is a transmission correction, [AberrationCorrection::NONE] is considered to be
+    /// both a reception and transmission correction.
+    pub fn is_transmission(&self) -> bool {
+        TransmissionCorrection::try_from(*self).is_ok()
+    }
+}
+
+/// Aberration correction where photons are received by the observer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum ReceptionCorrection {
+    NONE,
+    LT,
+    LT_S,
+    CN,
+    CN_S,
+}
+
+/// Aberration correction where photons are transmitted by the observer.
+///
+/// No SPICE function is restricted to transmission corrections, this can be used to validate
+/// corrections for calculations in one direction, e.g. with [AberrationCorrection::is_transmission].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[allow(non_camel_case_types)]
+pub enum TransmissionCorrection {
+    NONE,
+    XLT,
+    XLT_S,
+    XCN,
+    XCN_S,
+}
+
+impl From<ReceptionCorrection> for AberrationCorrection {
+    fn from(c: ReceptionCorrection) -> Self {
+        match c {
+            ReceptionCorrection::NONE => AberrationCorrection::NONE,
+            ReceptionCorrection::LT => AberrationCorrection::LT,
+            ReceptionCorrection::LT_S => AberrationCorrection::LT_S,
+            ReceptionCorrection::CN => AberrationCorrection::CN,
+            ReceptionCorrection::CN_S => AberrationCorrection::CN_S,
+        }
+    }
+}
+
+impl From<TransmissionCorrection> for AberrationCorrection {
+    fn from(c: TransmissionCorrection) -> Self {
+        match c {
+            TransmissionCorrection::NONE => AberrationCorrection::NONE,
+            TransmissionCorrection::XLT => AberrationCorrection::XLT,
+            TransmissionCorrection::XLT_S => AberrationCorrection::XLT_S,
+            TransmissionCorrection::XCN => AberrationCorrection::XCN,
+            TransmissionCorrection::XCN_S => AberrationCorrection::XCN_S,
+        }
+    }
+}
+
+/// Error returned when converting an [AberrationCorrection] in the wrong direction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0:?} is not a valid correction in this direction")]
+pub struct WrongCorrectionDirection(pub AberrationCorrection);
+
+impl TryFrom<AberrationCorrection> for ReceptionCorrection {
+    type Error = WrongCorrectionDirection;
+
+    fn try_from(c: AberrationCorrection) -> Result<Self, Self::Error> {
+        Ok(match c {
+            AberrationCorrection::NONE => ReceptionCorrection::NONE,
+            AberrationCorrection::LT => ReceptionCorrection::LT,
+            AberrationCorrection::LT_S => ReceptionCorrection::LT_S,
+            AberrationCorrection::CN => ReceptionCorrection::CN,
+            AberrationCorrection::CN_S => ReceptionCorrection::CN_S,
+            c => return Err(WrongCorrectionDirection(c)),
+        })
+    }
+}
+
+impl TryFrom<AberrationCorrection> for TransmissionCorrection {
+    type Error = WrongCorrectionDirection;
+
+    fn try_from(c: AberrationCorrection) -> Result<Self, Self::Error> {
+        Ok(match c {
+            AberrationCorrection::NONE => TransmissionCorrection::NONE,
+            AberrationCorrection::XLT => TransmissionCorrection::XLT,
+            AberrationCorrection::XLT_S => TransmissionCorrection::XLT_S,
+            AberrationCorrection::XCN => TransmissionCorrection::XCN,
+            AberrationCorrection::XCN_S => TransmissionCorrection::XCN_S,
+            c => return Err(WrongCorrectionDirection(c)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correction_direction() {
+        assert_eq!(
+            AberrationCorrection::from(ReceptionCorrection::CN_S),
+            AberrationCorrection::CN_S
+        );
+        assert_eq!(
+            AberrationCorrection::from(TransmissionCorrection::XLT),
+            AberrationCorrection::XLT
+        );
+        assert_eq!(
+            ReceptionCorrection::try_from(AberrationCorrection::LT),
+            Ok(ReceptionCorrection::LT)
+        );
+        assert_eq!(
+            ReceptionCorrection::try_from(AberrationCorrection::XLT),
+            Err(WrongCorrectionDirection(AberrationCorrection::XLT))
+        );
+        assert!(TransmissionCorrection::try_from(AberrationCorrection::CN).is_err());
+        assert!(AberrationCorrection::NONE.is_reception());
+        assert!(AberrationCorrection::NONE.is_transmission());
+        assert!(!AberrationCorrection::XCN_S.is_reception());
+    }
+
+    #[test]
+    fn test_correction_names() {
+        assert_eq!(AberrationCorrection::NONE.as_str(), "NONE");
+        assert_eq!(AberrationCorrection::LT_S.as_str(), "LT+S");
+        assert_eq!(AberrationCorrection::XCN_S.as_str(), "XCN+S");
     }
 }
@@ -0,0 +1,208 @@
+//! Comparison of ephemerides between two sets of kernels, e.g. to validate a kernel update.
+use crate::body::BodyRef;
+use crate::cell::Window;
+use crate::common::AberrationCorrection;
+use crate::data::{furnish, unload, KernelError};
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::naif_ids::SOLAR_SYSTEM_BARYCENTER;
+use crate::spk::{easy_reader, State};
+use crate::time::Et;
+use crate::units::{Kilometers, Seconds};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{SpiceDouble, SpiceInt};
+
+/// Statistics of the differences between the states of a body computed from two sets of
+/// kernels, see [states()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StateDifferences {
+    /// The number of times the states were compared at.
+    pub samples: usize,
+    /// The largest difference in position.
+    pub max_position: Kilometers,
+    /// The time of the largest difference in position.
+    pub max_position_et: Et,
+    /// The root mean square difference in position.
+    pub rms_position: Kilometers,
+    /// The largest difference in velocity in km/s.
+    pub max_velocity: SpiceDouble,
+    /// The time of the largest difference in velocity.
+    pub max_velocity_et: Et,
+    /// The root mean square difference in velocity in km/s.
+    pub rms_velocity: SpiceDouble,
+}
+
+/// Compare the state of `body` relative to the solar system barycenter, in the `J2000` frame,
+/// computed with `kernels_a` loaded against `kernels_b` loaded. The states are sampled every
+/// `step` seconds over each interval of `window`, and at the end of each interval.
+///
+/// Each set is loaded on top of the kernels that are already loaded, and unloaded afterwards,
+/// so an empty set compares against the kernels that are already loaded. Kernels that are
+/// already loaded with [furnish()] can't be part of either set.
+pub fn states<'b, A, B, T>(
+    kernels_a: &[A],
+    kernels_b: &[B],
+    body: T,
+    window: &Window,
+    step: Seconds,
+) -> Result<StateDifferences, KernelError>
+where
+    A: AsRef<str>,
+    B: AsRef<str>,
+    T: Into<BodyRef<'b>>,
+{
+    let body = body.into();
+    with_spice_lock_or_panic(|| {
+        if step.0.is_nan() || step.0 <= 0.0 {
+            set_error_message(format!("The step {} must be positive", step.0));
+            signal_error("SPICE(INVALIDSTEP)");
+            get_last_error()?;
+        }
+        let id = body.to_id()?;
+        let mut times = Vec::new();
+        for i in 0..window.window_cardinality()? as usize {
+            let (start, stop) = window.window_interval(i)?;
+            let mut et = start;
+            while et < stop {
+                times.push(Et(et));
+                et += step.0;
+            }
+            times.push(Et(stop));
+        }
+        let a = with_kernels(kernels_a, || sample(id, &times))?;
+        let b = with_kernels(kernels_b, || sample(id, &times))?;
+
+        let mut differences = StateDifferences {
+            samples: times.len(),
+            max_position: Kilometers(0.0),
+            max_position_et: Et(0.0),
+            rms_position: Kilometers(0.0),
+            max_velocity: 0.0,
+            max_velocity_et: Et(0.0),
+            rms_velocity: 0.0,
+        };
+        let (mut position_squares, mut velocity_squares) = (0.0, 0.0);
+        for ((a, b), et) in a.iter().zip(b.iter()).zip(times.iter()) {
+            let difference = *a - *b;
+            let position = difference.range();
+            let velocity = difference
+                .velocity
+                .iter()
+                .map(|v| v * v)
+                .sum::<SpiceDouble>();
+            position_squares += position * position;
+            velocity_squares += velocity;
+            if position > differences.max_position.0 {
+                differences.max_position = Kilometers(position);
+                differences.max_position_et = *et;
+            }
+            if velocity.sqrt() > differences.max_velocity {
+                differences.max_velocity = velocity.sqrt();
+                differences.max_velocity_et = *et;
+            }
+        }
+        if !times.is_empty() {
+            let n = times.len() as SpiceDouble;
+            differences.rms_position = Kilometers((position_squares / n).sqrt());
+            differences.rms_velocity = (velocity_squares / n).sqrt();
+        }
+        Ok(differences)
+    })
+}
+
+/// Load `kernels`, call `f`, then unload them again, even if `f` fails.
+fn with_kernels<K, F, R>(kernels: &[K], f: F) -> Result<R, KernelError>
+where
+    K: AsRef<str>,
+    F: FnOnce() -> Result<R, Error>,
+{
+    let mut loaded = Vec::new();
+    let mut result = kernels
+        .iter()
+        .try_for_each(|k| {
+            furnish(k.as_ref())?;
+            loaded.push(k);
+            Ok(())
+        })
+        .and_then(|_| Ok(f()?));
+    for k in loaded.into_iter().rev() {
+        let unloaded = unload(k.as_ref());
+        if result.is_ok() {
+            result = unloaded.and(result);
+        }
+    }
+    result
+}
+
+fn sample(body: SpiceInt, times: &[Et]) -> Result<Vec<State>, Error> {
+    times
+        .iter()
+        .map(|et| {
+            let (state, _) = easy_reader(
+                body,
+                *et,
+                "J2000",
+                AberrationCorrection::NONE,
+                SOLAR_SYSTEM_BARYCENTER,
+            )?;
+            Ok(state)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naif_ids::MOON;
+    use crate::spk::{SegmentHeader, SpkWriter};
+    use crate::tests::load_test_data;
+
+    #[test]
+    fn test_compare_states() {
+        load_test_data();
+        let window = Window::from_intervals(&[(0.0, 1000.0)]).unwrap();
+        let none: [&str; 0] = [];
+
+        // An SPK for the Moon offset by 1 km along the x axis
+        let path = std::env::temp_dir().join("cspice_rs_compare_test.bsp");
+        let _ = std::fs::remove_file(&path);
+        let times = (0..=10).map(|i| Et(i as f64 * 100.0)).collect::<Vec<_>>();
+        let offset = State::from([1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let offset_states = sample(MOON, &times)
+            .unwrap()
+            .into_iter()
+            .map(|s| s + offset)
+            .collect::<Vec<_>>();
+        let header = SegmentHeader {
+            body: MOON,
+            center: SOLAR_SYSTEM_BARYCENTER,
+            frame: "J2000",
+            start: Et(0.0),
+            end: Et(1000.0),
+            id: "CSPICE_RS_COMPARE",
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "COMPARE TEST").unwrap();
+        writer
+            .write_type_08(&header, 1, &offset_states, Et(0.0), Seconds(100.0))
+            .unwrap();
+        writer.close().unwrap();
+        let offset_kernels = [path.to_string_lossy().to_string()];
+
+        let same = states(&none, &none, MOON, &window, Seconds(100.0)).unwrap();
+        assert_eq!(same.samples, 11);
+        assert_eq!(same.max_position, Kilometers(0.0));
+
+        let differences = states(&none, &offset_kernels, MOON, &window, Seconds(100.0)).unwrap();
+        assert!((differences.max_position.0 - 1.0).abs() < 1e-6);
+        assert!((differences.rms_position.0 - 1.0).abs() < 1e-6);
+        assert!(differences.max_velocity < 1e-9);
+        // The kernels are unloaded afterwards
+        assert_eq!(
+            sample(MOON, &times[..1]).unwrap()[0] + offset,
+            offset_states[0]
+        );
+
+        let err = states(&none, &none, MOON, &window, Seconds(0.0)).unwrap_err();
+        assert!(matches!(err, KernelError::Spice(e) if e.short_message == "SPICE(INVALIDSTEP)"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
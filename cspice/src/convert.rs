@@ -0,0 +1,95 @@
+//! Conversions to single precision for graphics and visualisation.
+//!
+//! SPICE works in double precision kilometers, while rendering engines typically use `f32` and
+//! their own scene units. Scaling is applied in double precision before the conversion, so that
+//! choosing a suitable scale (and origin) preserves as much precision as possible.
+use crate::coordinates::Rectangular;
+use crate::spk::State;
+use crate::vector::Vector3D;
+use cspice_sys::SpiceDouble;
+
+/// Error returned when a value is too large to be represented as a finite `f32`.
+#[derive(Debug, Copy, Clone, PartialEq, thiserror::Error)]
+#[error("Value {0} is out of range for f32")]
+pub struct F32Overflow(pub SpiceDouble);
+
+/// Conversion of a double precision type to single precision.
+pub trait AsF32 {
+    type Output;
+
+    /// Convert each component to `f32` after multiplying by `scale` (scene units per km).
+    ///
+    /// Returns [F32Overflow] if any component would overflow to infinity.
+    fn try_as_f32_scaled(&self, scale: SpiceDouble) -> Result<Self::Output, F32Overflow>;
+
+    /// Convert each component to `f32` after multiplying by `scale` (scene units per km).
+    ///
+    /// Components that are out of range become infinite.
+    fn as_f32_scaled(&self, scale: SpiceDouble) -> Self::Output;
+
+    /// Convert each component to `f32`.
+    fn as_f32(&self) -> Self::Output {
+        self.as_f32_scaled(1.0)
+    }
+}
+
+fn scale_array<const N: usize>(a: [SpiceDouble; N], scale: SpiceDouble) -> [f32; N] {
+    a.map(|x| (x * scale) as f32)
+}
+
+fn try_scale_array<const N: usize>(
+    a: [SpiceDouble; N],
+    scale: SpiceDouble,
+) -> Result<[f32; N], F32Overflow> {
+    let mut out = [0.0; N];
+    for (o, x) in out.iter_mut().zip(a.iter()) {
+        let scaled = x * scale;
+        *o = scaled as f32;
+        if scaled.is_finite() && !o.is_finite() {
+            return Err(F32Overflow(scaled));
+        }
+    }
+    Ok(out)
+}
+
+macro_rules! impl_as_f32 {
+    ($t:ty, $n:literal) => {
+        impl AsF32 for $t {
+            type Output = [f32; $n];
+
+            fn try_as_f32_scaled(&self, scale: SpiceDouble) -> Result<Self::Output, F32Overflow> {
+                let a: [SpiceDouble; $n] = (*self).into();
+                try_scale_array(a, scale)
+            }
+
+            fn as_f32_scaled(&self, scale: SpiceDouble) -> Self::Output {
+                let a: [SpiceDouble; $n] = (*self).into();
+                scale_array(a, scale)
+            }
+        }
+    };
+}
+
+impl_as_f32!(Rectangular, 3);
+impl_as_f32!(Vector3D, 3);
+impl_as_f32!(State, 6);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_f32() {
+        let rect = Rectangular::from([1.5, -2.0, 1e6]);
+        assert_eq!(rect.as_f32(), [1.5, -2.0, 1e6]);
+        assert_eq!(rect.as_f32_scaled(1e-3), [1.5e-3, -2.0e-3, 1e3]);
+
+        let state = State::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(state.as_f32_scaled(2.0), [2.0, 4.0, 6.0, 8.0, 10.0, 12.0]);
+
+        let big = Vector3D([1e40, 0.0, 0.0]);
+        assert_eq!(big.try_as_f32_scaled(1.0), Err(F32Overflow(1e40)));
+        assert!(big.try_as_f32_scaled(1e-10).is_ok());
+        assert!(big.as_f32()[0].is_infinite());
+    }
+}
@@ -1,7 +1,21 @@
 //! Functions for converting between different types of coordinates.
-use crate::with_spice_lock_or_panic;
-use cspice_sys::{azlrec_c, recazl_c, reclat_c, recrad_c, SpiceBoolean, SpiceDouble};
+//!
+//! [Rectangular] and [Vector3D](crate::vector::Vector3D) have the same layout and convert freely
+//! into each other: [Rectangular] is used for positions, and [Vector3D] for directions and
+//! velocities and for vector arithmetic. The other coordinate types can be converted to and from
+//! either of them.
+use crate::body::BodyRef;
+use crate::error::get_last_error;
+use crate::string::{static_spice_str, StaticSpiceStr};
+use crate::units::Radians;
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    azlrec_c, bodvrd_c, georec_c, latrec_c, pgrrec_c, radrec_c, recazl_c, reclat_c, recpgr_c,
+    recrad_c, SpiceBoolean, SpiceDouble, SpiceInt,
+};
 use derive_more::Into;
+use std::array::TryFromSliceError;
 
 /// Rectangular coordinates
 #[repr(C)]
@@ -28,6 +42,27 @@ impl From<Rectangular> for [SpiceDouble; 3] {
     }
 }
 
+impl From<Vector3D> for Rectangular {
+    fn from(v: Vector3D) -> Self {
+        v.0.into()
+    }
+}
+
+impl From<(SpiceDouble, SpiceDouble, SpiceDouble)> for Rectangular {
+    fn from((x, y, z): (SpiceDouble, SpiceDouble, SpiceDouble)) -> Self {
+        Rectangular { x, y, z }
+    }
+}
+
+/// Fails unless the slice has exactly 3 elements.
+impl TryFrom<&[SpiceDouble]> for Rectangular {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[SpiceDouble]) -> Result<Self, Self::Error> {
+        <[SpiceDouble; 3]>::try_from(slice).map(Self::from)
+    }
+}
+
 /// Range, azimuth, and elevation
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct AzEl {
@@ -54,9 +89,46 @@ impl AzEl {
             az_el
         })
     }
+
+    /// The unit vector pointing along the azimuth and elevation, ignoring the range.
+    ///
+    /// See [Rectangular::from_azel()].
+    pub fn direction(&self, azccw: bool, elplsz: bool) -> Vector3D {
+        let unit = AzEl {
+            range: 1.0,
+            ..*self
+        };
+        Rectangular::from_azel(unit, azccw, elplsz).into()
+    }
+
+    /// Create from the azimuth and elevation of `direction`, which need not be a unit vector,
+    /// and the given range.
+    ///
+    /// See [AzEl::from_rect()].
+    pub fn from_direction(
+        direction: Vector3D,
+        range: SpiceDouble,
+        azccw: bool,
+        elplsz: bool,
+    ) -> Self {
+        AzEl {
+            range,
+            ..AzEl::from_rect(direction.into(), azccw, elplsz)
+        }
+    }
 }
 
 impl Rectangular {
+    /// Create rectangular coordinates from their components.
+    pub const fn new(x: SpiceDouble, y: SpiceDouble, z: SpiceDouble) -> Self {
+        Rectangular { x, y, z }
+    }
+
+    /// Copy the coordinates into an array in `[x, y, z]` order.
+    pub const fn to_array(&self) -> [SpiceDouble; 3] {
+        [self.x, self.y, self.z]
+    }
+
     /// See [azlrec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/azlrec_c.html)
     pub fn from_azel(azel: AzEl, azccw: bool, elplsz: bool) -> Self {
         with_spice_lock_or_panic(|| {
@@ -102,6 +174,56 @@ impl From<Rectangular> for RaDec {
     }
 }
 
+impl RaDec {
+    /// The unit vector pointing along the right ascension and declination, ignoring the range.
+    pub fn direction(&self) -> Vector3D {
+        RaDec {
+            range: 1.0,
+            ..*self
+        }
+        .into()
+    }
+
+    /// Create from the right ascension and declination of `direction`, which need not be a unit
+    /// vector, and the given range.
+    pub fn from_direction(direction: Vector3D, range: SpiceDouble) -> Self {
+        RaDec {
+            range,
+            ..RaDec::from(direction)
+        }
+    }
+
+    /// The angular separation between two directions on the sky, ignoring their ranges.
+    ///
+    /// See [Vector3D::separation_angle()].
+    pub fn separation(&self, other: &RaDec) -> Radians {
+        Radians(self.direction().separation_angle(&other.direction()))
+    }
+}
+
+impl From<RaDec> for Rectangular {
+    /// See [radrec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/radrec_c.html).
+    fn from(ra_dec: RaDec) -> Self {
+        with_spice_lock_or_panic(|| {
+            let mut rect = [0.0f64; 3];
+            unsafe { radrec_c(ra_dec.range, ra_dec.ra, ra_dec.dec, rect.as_mut_ptr()) };
+            rect.into()
+        })
+    }
+}
+
+impl From<Vector3D> for RaDec {
+    fn from(v: Vector3D) -> Self {
+        Rectangular::from(v).into()
+    }
+}
+
+impl From<RaDec> for Vector3D {
+    fn from(ra_dec: RaDec) -> Self {
+        Rectangular::from(ra_dec).into()
+    }
+}
+
 /// Latitudinal coordinates.
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct Latitudinal {
@@ -128,12 +250,254 @@ impl From<Rectangular> for Latitudinal {
     }
 }
 
+impl From<Latitudinal> for Rectangular {
+    /// See [latrec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/latrec_c.html).
+    fn from(lat: Latitudinal) -> Self {
+        with_spice_lock_or_panic(|| {
+            let mut rect = [0.0f64; 3];
+            unsafe { latrec_c(lat.radius, lat.longitude, lat.latitude, rect.as_mut_ptr()) };
+            rect.into()
+        })
+    }
+}
+
+impl From<Vector3D> for Latitudinal {
+    fn from(v: Vector3D) -> Self {
+        Rectangular::from(v).into()
+    }
+}
+
+impl From<Latitudinal> for Vector3D {
+    fn from(lat: Latitudinal) -> Self {
+        Rectangular::from(lat).into()
+    }
+}
+
+/// Planetographic coordinates.
+///
+/// The sense of planetographic longitude depends on the body, for most bodies it is positive
+/// west, but for the Earth, Moon and Sun it is positive east.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Planetographic {
+    pub longitude: SpiceDouble,
+    pub latitude: SpiceDouble,
+    pub altitude: SpiceDouble,
+}
+
+impl Planetographic {
+    /// Convert rectangular coordinates to planetographic coordinates, using a reference spheroid
+    /// with equatorial radius `re` and flattening coefficient `f`.
+    ///
+    /// See [recpgr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/recpgr_c.html).
+    pub fn from_rect<'b, B: Into<BodyRef<'b>>>(
+        body: B,
+        mut rect: Rectangular,
+        re: SpiceDouble,
+        f: SpiceDouble,
+    ) -> Result<Self, Error> {
+        let body = body.into();
+        with_spice_lock_or_panic(|| {
+            let mut pgr = Planetographic::default();
+            unsafe {
+                recpgr_c(
+                    body.to_string_param().as_mut_ptr()?,
+                    &mut rect.x as *mut SpiceDouble,
+                    re,
+                    f,
+                    &mut pgr.longitude,
+                    &mut pgr.latitude,
+                    &mut pgr.altitude,
+                )
+            };
+            get_last_error()?;
+            Ok(pgr)
+        })
+    }
+
+    /// Convert rectangular coordinates to planetographic coordinates, using the radii of the body
+    /// from the kernel pool.
+    ///
+    /// The equatorial radius and flattening are derived from the `BODYnnn_RADII` kernel variable,
+    /// so a suitable PCK must be loaded.
+    pub fn of_point<'b, B: Into<BodyRef<'b>>>(body: B, rect: Rectangular) -> Result<Self, Error> {
+        let body = body.into();
+        let (re, f) = spheroid(&body)?;
+        Self::from_rect(body.to_string_param(), rect, re, f)
+    }
+
+    /// Convert planetographic coordinates to rectangular coordinates, using a reference spheroid
+    /// with equatorial radius `re` and flattening coefficient `f`.
+    ///
+    /// See [pgrrec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pgrrec_c.html).
+    pub fn to_rect<'b, B: Into<BodyRef<'b>>>(
+        &self,
+        body: B,
+        re: SpiceDouble,
+        f: SpiceDouble,
+    ) -> Result<Rectangular, Error> {
+        let body = body.into();
+        with_spice_lock_or_panic(|| {
+            let mut rect = [0.0f64; 3];
+            unsafe {
+                pgrrec_c(
+                    body.to_string_param().as_mut_ptr()?,
+                    self.longitude,
+                    self.latitude,
+                    self.altitude,
+                    re,
+                    f,
+                    rect.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(rect.into())
+        })
+    }
+
+    /// Convert planetographic coordinates to rectangular coordinates, using the radii of the body
+    /// from the kernel pool.
+    ///
+    /// See [Planetographic::of_point()].
+    pub fn to_rect_of<'b, B: Into<BodyRef<'b>>>(&self, body: B) -> Result<Rectangular, Error> {
+        let body = body.into();
+        let (re, f) = spheroid(&body)?;
+        self.to_rect(body.to_string_param(), re, f)
+    }
+}
+
+/// Geodetic coordinates.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Geodetic {
+    pub longitude: SpiceDouble,
+    pub latitude: SpiceDouble,
+    pub altitude: SpiceDouble,
+}
+
+impl Geodetic {
+    /// Convert geodetic coordinates to rectangular coordinates, using a reference spheroid with
+    /// equatorial radius `re` and flattening coefficient `f`.
+    ///
+    /// See [georec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/georec_c.html).
+    pub fn to_rect(&self, re: SpiceDouble, f: SpiceDouble) -> Result<Rectangular, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut rect = [0.0f64; 3];
+            unsafe {
+                georec_c(
+                    self.longitude,
+                    self.latitude,
+                    self.altitude,
+                    re,
+                    f,
+                    rect.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(rect.into())
+        })
+    }
+
+    /// Convert geodetic coordinates to rectangular coordinates, using the radii of the body
+    /// from the kernel pool.
+    ///
+    /// See [Planetographic::of_point()].
+    pub fn to_rect_of<'b, B: Into<BodyRef<'b>>>(&self, body: B) -> Result<Rectangular, Error> {
+        let (re, f) = spheroid(&body.into())?;
+        self.to_rect(re, f)
+    }
+}
+
+/// Get the equatorial radius and flattening coefficient of a body from the kernel pool.
+fn spheroid(body: &BodyRef) -> Result<(SpiceDouble, SpiceDouble), Error> {
+    with_spice_lock_or_panic(|| {
+        let mut radii = [0.0f64; 3];
+        let mut dim: SpiceInt = 0;
+        unsafe {
+            bodvrd_c(
+                body.to_string_param().as_mut_ptr()?,
+                static_spice_str!("RADII").as_mut_ptr(),
+                radii.len() as SpiceInt,
+                &mut dim,
+                radii.as_mut_ptr(),
+            )
+        };
+        get_last_error()?;
+        let (re, rp) = (radii[0], radii[2]);
+        Ok((re, (re - rp) / re))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     const EPSILON: f64 = 1e-3;
 
+    #[test]
+    fn test_rectangular_conversions() {
+        let rect = Rectangular::new(1.0, 2.0, 3.0);
+        assert_eq!(Rectangular::from((1.0, 2.0, 3.0)), rect);
+        assert_eq!(rect.to_array(), [1.0, 2.0, 3.0]);
+        assert_eq!(Rectangular::try_from(&[1.0, 2.0, 3.0][..]).unwrap(), rect);
+        assert!(Rectangular::try_from(&[1.0, 2.0][..]).is_err());
+    }
+
+    #[test]
+    fn test_spherical_round_trip() {
+        let v = Vector3D([1.0, -2.0, 3.0]);
+        let lat = Latitudinal::from(v);
+        assert!((lat.radius - 14f64.sqrt()).abs() < 1e-12);
+        let back = Vector3D::from(lat);
+        let ra_dec = RaDec::from(v);
+        assert!((ra_dec.ra - (2.0 * std::f64::consts::PI - 2f64.atan())).abs() < 1e-12);
+        let back_ra_dec = Vector3D::from(ra_dec);
+        for i in 0..3 {
+            assert!((back[i] - v[i]).abs() < 1e-12);
+            assert!((back_ra_dec[i] - v[i]).abs() < 1e-12);
+        }
+        assert_eq!(Rectangular::from(lat), Rectangular::from(back));
+    }
+
+    #[test]
+    fn test_directions() {
+        let direction = Vector3D([0.0, 3.0, 4.0]);
+        let ra_dec = RaDec::from_direction(direction, 10.0);
+        assert_eq!(ra_dec.range, 10.0);
+        let unit = ra_dec.direction();
+        let expected = [0.0, 0.6, 0.8];
+        for i in 0..3 {
+            assert!((unit[i] - expected[i]).abs() < 1e-12);
+        }
+
+        let az_el = AzEl::from_direction(direction, 10.0, false, true);
+        assert_eq!(az_el.range, 10.0);
+        let unit = az_el.direction(false, true);
+        for i in 0..3 {
+            assert!((unit[i] - expected[i]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_ra_dec_separation() {
+        let a = RaDec {
+            range: 1.0,
+            ra: 0.1,
+            dec: 0.2,
+        };
+        let b = RaDec {
+            range: 5.0,
+            ra: 0.1,
+            dec: 0.5,
+        };
+        assert!((a.separation(&b).0 - 0.3).abs() < 1e-12);
+        assert!(a.separation(&a).0.abs() < 1e-12);
+        let pole = RaDec {
+            range: 1.0,
+            ra: 2.0,
+            dec: std::f64::consts::FRAC_PI_2,
+        };
+        assert!((a.separation(&pole).0 - (std::f64::consts::FRAC_PI_2 - 0.2)).abs() < 1e-12);
+    }
+
     // Test data comes from NAIF website https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/recazl_c.html
     const TEST_DATA_F_F: [[SpiceDouble; 6]; 11] = [
         [0.000, 0.000, 0.000, 0.000, 0.000, 0.000],
@@ -216,4 +580,38 @@ mod tests {
             assert!((azel_.el - test[5].to_radians()).abs() < EPSILON);
         }
     }
+
+    #[test]
+    fn test_planetographic() {
+        let (re, rp) = (3396.19, 3376.20);
+        let f = (re - rp) / re;
+        with_spice_lock_or_panic(|| unsafe {
+            let mut radii = [re, re, rp];
+            cspice_sys::pdpool_c(
+                static_spice_str!("BODY499_RADII").as_mut_ptr(),
+                3,
+                radii.as_mut_ptr(),
+            );
+        });
+
+        // Planetographic longitude is positive west for Mars
+        let rect = Rectangular {
+            x: 0.0,
+            y: re + 100.0,
+            z: 0.0,
+        };
+        let pgr = Planetographic::of_point("MARS", rect).unwrap();
+        assert!((pgr.longitude - 270f64.to_radians()).abs() < EPSILON);
+        assert!(pgr.latitude.abs() < EPSILON);
+        assert!((pgr.altitude - 100.0).abs() < EPSILON);
+        assert_eq!(pgr, Planetographic::from_rect(499, rect, re, f).unwrap());
+
+        let rect_ = pgr.to_rect_of(499).unwrap();
+        assert!((rect_.x - rect.x).abs() < EPSILON);
+        assert!((rect_.y - rect.y).abs() < EPSILON);
+        assert!((rect_.z - rect.z).abs() < EPSILON);
+
+        let err = Planetographic::of_point("NOT_A_BODY", rect).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(NOTRANSLATION)");
+    }
 }
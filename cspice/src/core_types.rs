@@ -0,0 +1,234 @@
+//! Conversions between the crate's types and the plain data types of [cspice_types].
+use crate::coordinates::{AzEl, Geodetic, Latitudinal, Planetographic, RaDec, Rectangular};
+use crate::matrix::Matrix3x3;
+use crate::quaternion::Quaternion;
+use crate::spk::State;
+use crate::time::calendar::{Calendar, CalendarKind};
+use crate::time::system::{System, Tdb, Tdt, TimeSystem, Utc};
+use crate::time::{DateTime, DynDateTime, Et, JulianDate};
+use crate::vector::Vector3D;
+use cspice_types as types;
+
+macro_rules! newtype_conversions {
+    ($($module:ident::$ty:ident),*) => {
+        $(
+            impl From<$ty> for types::$module::$ty {
+                fn from(value: $ty) -> Self {
+                    Self(value.0)
+                }
+            }
+
+            impl From<types::$module::$ty> for $ty {
+                fn from(value: types::$module::$ty) -> Self {
+                    Self(value.0)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! struct_conversions {
+    ($($module:ident::$ty:ident { $($field:ident),* }),*) => {
+        $(
+            impl From<$ty> for types::$module::$ty {
+                fn from(value: $ty) -> Self {
+                    Self { $($field: value.$field),* }
+                }
+            }
+
+            impl From<types::$module::$ty> for $ty {
+                fn from(value: types::$module::$ty) -> Self {
+                    Self { $($field: value.$field),* }
+                }
+            }
+        )*
+    };
+}
+
+newtype_conversions!(
+    time::Et,
+    vector::Vector3D,
+    matrix::Matrix3x3,
+    matrix::Quaternion
+);
+
+struct_conversions!(
+    coordinates::Rectangular { x, y, z },
+    coordinates::AzEl { range, az, el },
+    coordinates::RaDec { range, ra, dec },
+    coordinates::Latitudinal {
+        radius,
+        longitude,
+        latitude
+    },
+    coordinates::Planetographic {
+        longitude,
+        latitude,
+        altitude
+    },
+    coordinates::Geodetic {
+        longitude,
+        latitude,
+        altitude
+    }
+);
+
+impl From<State> for types::spk::State {
+    fn from(value: State) -> Self {
+        Self {
+            position: value.position.into(),
+            velocity: value.velocity.into(),
+        }
+    }
+}
+
+impl From<types::spk::State> for State {
+    fn from(value: types::spk::State) -> Self {
+        Self {
+            position: value.position.into(),
+            velocity: value.velocity.into(),
+        }
+    }
+}
+
+impl From<CalendarKind> for types::time::CalendarKind {
+    fn from(value: CalendarKind) -> Self {
+        match value {
+            CalendarKind::Mixed => Self::Mixed,
+            CalendarKind::Gregorian => Self::Gregorian,
+            CalendarKind::Julian => Self::Julian,
+        }
+    }
+}
+
+impl From<types::time::CalendarKind> for CalendarKind {
+    fn from(value: types::time::CalendarKind) -> Self {
+        match value {
+            types::time::CalendarKind::Mixed => Self::Mixed,
+            types::time::CalendarKind::Gregorian => Self::Gregorian,
+            types::time::CalendarKind::Julian => Self::Julian,
+        }
+    }
+}
+
+impl From<TimeSystem> for types::time::TimeSystem {
+    fn from(value: TimeSystem) -> Self {
+        match value {
+            TimeSystem::Tdt => Self::Tdt,
+            TimeSystem::Tdb => Self::Tdb,
+            TimeSystem::Utc(utc) => Self::Utc {
+                zone_hours: utc.zone_hours,
+                zone_minutes: utc.zone_minutes,
+            },
+        }
+    }
+}
+
+impl From<types::time::TimeSystem> for TimeSystem {
+    fn from(value: types::time::TimeSystem) -> Self {
+        match value {
+            types::time::TimeSystem::Tdt => Self::Tdt,
+            types::time::TimeSystem::Tdb => Self::Tdb,
+            types::time::TimeSystem::Utc {
+                zone_hours,
+                zone_minutes,
+            } => Self::Utc(Utc {
+                zone_hours,
+                zone_minutes,
+            }),
+        }
+    }
+}
+
+impl<S: System> From<JulianDate<S>> for types::time::JulianDate {
+    fn from(value: JulianDate<S>) -> Self {
+        Self {
+            value: value.value,
+            system: S::default().time_system().into(),
+        }
+    }
+}
+
+/// Julian Dates in UTC ignore the time zone.
+impl From<types::time::JulianDate> for Et {
+    fn from(jd: types::time::JulianDate) -> Self {
+        match jd.system {
+            types::time::TimeSystem::Tdt => Et::from(JulianDate::<Tdt>::new(jd.value)),
+            types::time::TimeSystem::Tdb => Et::from(JulianDate::<Tdb>::new(jd.value)),
+            types::time::TimeSystem::Utc { .. } => Et::from(JulianDate::<Utc>::new(jd.value)),
+        }
+    }
+}
+
+impl From<DynDateTime> for types::time::DateTime {
+    fn from(value: DynDateTime) -> Self {
+        Self {
+            year: value.year,
+            month: value.month,
+            day: value.day,
+            hour: value.hour,
+            minute: value.minute,
+            second: value.second,
+            calendar: value.calendar.into(),
+            system: value.system.into(),
+        }
+    }
+}
+
+impl From<types::time::DateTime> for DynDateTime {
+    fn from(value: types::time::DateTime) -> Self {
+        DynDateTime::new(
+            value.year,
+            value.month,
+            value.day,
+            value.hour,
+            value.minute,
+            value.second,
+            value.calendar.into(),
+            value.system.into(),
+        )
+    }
+}
+
+impl<C: Calendar, S: System> From<DateTime<C, S>> for types::time::DateTime {
+    fn from(value: DateTime<C, S>) -> Self {
+        DynDateTime::from(value).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+    use crate::time::calendar::Gregorian;
+
+    #[test]
+    fn test_round_trip() {
+        load_test_data();
+        let rect = Rectangular::from([1.0, 2.0, 3.0]);
+        assert_eq!(
+            Rectangular::from(types::coordinates::Rectangular::from(rect)),
+            rect
+        );
+        let state = State::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(State::from(types::spk::State::from(state)), state);
+        let q = Quaternion([1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(Quaternion::from(types::matrix::Quaternion::from(q)), q);
+
+        let dt = DateTime::<Gregorian, Utc>::new(2000, 1, 2, 3, 4, 5.5, Utc::default());
+        let plain = types::time::DateTime::from(dt);
+        assert_eq!(plain.calendar, types::time::CalendarKind::Gregorian);
+        assert_eq!(
+            plain.system,
+            types::time::TimeSystem::Utc {
+                zone_hours: 0,
+                zone_minutes: 0
+            }
+        );
+        assert_eq!(DynDateTime::from(plain), DynDateTime::from(dt));
+
+        let jd = types::time::JulianDate::from(JulianDate::<Tdb>::new(2451545.0));
+        assert_eq!(jd.system, types::time::TimeSystem::Tdb);
+        assert_eq!(Et::from(jd), Et(0.0));
+    }
+}
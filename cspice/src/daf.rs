@@ -0,0 +1,314 @@
+//! Raw access to Double precision Array Files (DAF), the format underlying SPK, CK and binary
+//! PCK files.
+//!
+//! This is intended for reading segment types that the crate doesn't interpret; the layout of
+//! each segment type is documented in the required reading of the kernel type, e.g.
+//! [SPK required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/spk.html).
+//!
+//! See [DAF required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/daf.html).
+use crate::error::get_last_error;
+use crate::string::{SpiceStr, StringParam};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    dafbfs_c, dafcls_c, daffna_c, dafgda_c, dafgn_c, dafgs_c, dafgsr_c, dafhsf_c, dafopr_c,
+    dafps_c, dafus_c, SpiceBoolean, SpiceChar, SpiceDouble, SpiceInt, SPICETRUE,
+};
+
+/// The number of double precision numbers in a DAF record.
+pub const RECORD_LENGTH: usize = 128;
+
+/// A segment (array) of a DAF, described by its summary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DafSegment {
+    /// The name of the segment, for SPK and CK files the segment identifier.
+    pub name: String,
+    /// The double precision components of the summary, e.g. the start and end times.
+    pub doubles: Vec<SpiceDouble>,
+    /// The integer components of the summary. The last two are always the initial and final
+    /// addresses of the segment's data.
+    pub integers: Vec<SpiceInt>,
+}
+
+impl DafSegment {
+    /// The initial and final addresses of the segment's data, see [DafFile::read()]. Returns
+    /// `None` if there are fewer than two integer components, which is only possible if the
+    /// segment wasn't read from a DAF.
+    pub fn addresses(&self) -> Option<(SpiceInt, SpiceInt)> {
+        match self.integers[..] {
+            [.., begin, end] => Some((begin, end)),
+            _ => None,
+        }
+    }
+
+    /// Unpack a packed summary with `nd` double precision and `ni` integer components.
+    ///
+    /// See [dafus_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafus_c.html).
+    pub(crate) fn unpack(
+        summary: &[SpiceDouble],
+        nd: SpiceInt,
+        ni: SpiceInt,
+        name: String,
+    ) -> Result<Self, Error> {
+        let (mut doubles, mut integers) = (vec![0.0; nd as usize], vec![0; ni as usize]);
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                dafus_c(
+                    summary.as_ptr() as *mut SpiceDouble,
+                    nd,
+                    ni,
+                    doubles.as_mut_ptr(),
+                    integers.as_mut_ptr(),
+                )
+            };
+            get_last_error()
+        })?;
+        Ok(Self {
+            name,
+            doubles,
+            integers,
+        })
+    }
+
+    /// Pack the summary into the form stored in the file, e.g. for passing to `spksub_c`.
+    ///
+    /// See [dafps_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafps_c.html).
+    pub(crate) fn pack(&self) -> Result<Vec<SpiceDouble>, Error> {
+        let (nd, ni) = (self.doubles.len(), self.integers.len());
+        let mut summary = vec![0.0; summary_length(nd as SpiceInt, ni as SpiceInt)];
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                dafps_c(
+                    nd as SpiceInt,
+                    ni as SpiceInt,
+                    self.doubles.as_ptr() as *mut SpiceDouble,
+                    self.integers.as_ptr() as *mut SpiceInt,
+                    summary.as_mut_ptr(),
+                )
+            };
+            get_last_error()
+        })?;
+        Ok(summary)
+    }
+}
+
+/// The number of double precision numbers in a packed summary.
+fn summary_length(nd: SpiceInt, ni: SpiceInt) -> usize {
+    (nd + (ni + 1) / 2) as usize
+}
+
+/// A DAF opened for reading. The file is closed when dropped.
+#[derive(Debug)]
+pub struct DafFile {
+    handle: Option<SpiceInt>,
+}
+
+impl DafFile {
+    /// Open a DAF for reading.
+    ///
+    /// See [dafopr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafopr_c.html).
+    pub fn open<'p, P: Into<StringParam<'p>>>(path: P) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut handle = 0;
+            unsafe { dafopr_c(path.into().as_mut_ptr()?, &mut handle) };
+            get_last_error()?;
+            Ok(Self {
+                handle: Some(handle),
+            })
+        })
+    }
+
+    /// The DAF handle of the file.
+    pub fn handle(&self) -> SpiceInt {
+        // Only None after being closed, which consumes the DafFile
+        self.handle.unwrap()
+    }
+
+    /// The number of double precision and integer components in each segment summary, e.g.
+    /// `(2, 6)` for an SPK.
+    ///
+    /// See [dafhsf_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafhsf_c.html).
+    pub fn summary_format(&self) -> Result<(SpiceInt, SpiceInt), Error> {
+        summary_format(self.handle())
+    }
+
+    /// List the segments in the file, in the order they appear.
+    pub fn segments(&self) -> Result<Vec<DafSegment>, Error> {
+        segments(self.handle())
+    }
+
+    /// Read the double precision numbers from address `begin` to `end` inclusive. Addresses
+    /// start at 1.
+    ///
+    /// See [dafgda_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafgda_c.html).
+    pub fn read(&self, begin: SpiceInt, end: SpiceInt) -> Result<Vec<SpiceDouble>, Error> {
+        let mut data = vec![0.0; (end - begin + 1).max(0) as usize];
+        with_spice_lock_or_panic(|| {
+            unsafe { dafgda_c(self.handle(), begin, end, data.as_mut_ptr()) };
+            get_last_error()
+        })?;
+        Ok(data)
+    }
+
+    /// Read all of the data of a segment, returning `None` if the segment has no
+    /// [addresses](DafSegment::addresses).
+    pub fn read_segment(&self, segment: &DafSegment) -> Result<Option<Vec<SpiceDouble>>, Error> {
+        segment
+            .addresses()
+            .map(|(begin, end)| self.read(begin, end))
+            .transpose()
+    }
+
+    /// Read a whole record, numbered from 1, returning `None` if the record doesn't exist.
+    ///
+    /// See [dafgsr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafgsr_c.html).
+    pub fn read_record(
+        &self,
+        record: SpiceInt,
+    ) -> Result<Option<[SpiceDouble; RECORD_LENGTH]>, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut data = [0.0; RECORD_LENGTH];
+            let mut found: SpiceBoolean = 0;
+            unsafe {
+                dafgsr_c(
+                    self.handle(),
+                    record,
+                    1,
+                    RECORD_LENGTH as SpiceInt,
+                    data.as_mut_ptr(),
+                    &mut found,
+                )
+            };
+            get_last_error()?;
+            Ok((found == SPICETRUE as SpiceBoolean).then_some(data))
+        })
+    }
+
+    /// Close the file.
+    ///
+    /// See [dafcls_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dafcls_c.html).
+    pub fn close(mut self) -> Result<(), Error> {
+        let handle = self.handle.take().unwrap();
+        with_spice_lock_or_panic(|| {
+            unsafe { dafcls_c(handle) };
+            get_last_error()
+        })
+    }
+}
+
+/// The summary format of the DAF with `handle`, see [DafFile::summary_format()].
+pub(crate) fn summary_format(handle: SpiceInt) -> Result<(SpiceInt, SpiceInt), Error> {
+    with_spice_lock_or_panic(|| {
+        let (mut nd, mut ni) = (0, 0);
+        unsafe { dafhsf_c(handle, &mut nd, &mut ni) };
+        get_last_error()?;
+        Ok((nd, ni))
+    })
+}
+
+/// List the segments of the DAF with `handle`, which may have been opened by another subsystem,
+/// e.g. an SPK loaded with `spklef_c`.
+pub(crate) fn segments(handle: SpiceInt) -> Result<Vec<DafSegment>, Error> {
+    with_spice_lock_or_panic(|| {
+        let (nd, ni) = summary_format(handle)?;
+        let summary_length = summary_length(nd, ni);
+        let mut segments = Vec::new();
+        unsafe { dafbfs_c(handle) };
+        get_last_error()?;
+        loop {
+            let mut found: SpiceBoolean = 0;
+            let mut summary = vec![0.0; summary_length];
+            let mut name = vec![0 as SpiceChar; summary_length * 8 + 1];
+            unsafe {
+                daffna_c(&mut found);
+                if found != SPICETRUE as SpiceBoolean {
+                    break;
+                }
+                dafgs_c(summary.as_mut_ptr());
+                dafgn_c(name.len() as SpiceInt, name.as_mut_ptr());
+            }
+            get_last_error()?;
+            let name = SpiceStr::from_buffer(&name).as_str().into_owned();
+            segments.push(DafSegment::unpack(&summary, nd, ni, name)?);
+        }
+        get_last_error()?;
+        Ok(segments)
+    })
+}
+
+impl Drop for DafFile {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            with_spice_lock_or_panic(|| {
+                unsafe { dafcls_c(handle) };
+                let _ = get_last_error();
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spk::{SegmentHeader, SpkWriter, State};
+    use crate::time::Et;
+    use crate::units::Seconds;
+
+    #[test]
+    fn test_daf_file() {
+        let path = std::env::temp_dir().join("cspice_rs_daf_test.bsp");
+        let _ = std::fs::remove_file(&path);
+        let states = [
+            State::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            State::from([7.0, 8.0, 9.0, 10.0, 11.0, 12.0]),
+        ];
+        let header = SegmentHeader {
+            body: -999803,
+            center: 399,
+            frame: "J2000",
+            start: Et(0.0),
+            end: Et(100.0),
+            id: "CSPICE_RS_DAF",
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "DAF TEST").unwrap();
+        writer
+            .write_type_08(&header, 1, &states, Et(0.0), Seconds(100.0))
+            .unwrap();
+        writer.close().unwrap();
+
+        let daf = DafFile::open(path.to_string_lossy()).unwrap();
+        assert_eq!(daf.summary_format().unwrap(), (2, 6));
+        let segments = daf.segments().unwrap();
+        assert_eq!(segments.len(), 1);
+        let segment = &segments[0];
+        assert_eq!(segment.name, "CSPICE_RS_DAF");
+        assert_eq!(segment.doubles, vec![0.0, 100.0]);
+        assert_eq!(segment.integers[..4], [-999803, 399, 1, 8]);
+        let packed = segment.pack().unwrap();
+        assert_eq!(packed.len(), 5);
+        let unpacked = DafSegment::unpack(&packed, 2, 6, segment.name.clone()).unwrap();
+        assert_eq!(&unpacked, segment);
+
+        // Type 8 is the states followed by the start epoch, step, degree and number of states
+        let data = daf.read_segment(segment).unwrap().unwrap();
+        let mut expected = states.iter().flat_map(State::to_array).collect::<Vec<_>>();
+        expected.extend([0.0, 100.0, 1.0, 2.0]);
+        assert_eq!(data, expected);
+
+        let (begin, _) = segment.addresses().unwrap();
+        let record = (begin - 1) / RECORD_LENGTH as SpiceInt + 1;
+        let offset = (begin - 1) as usize % RECORD_LENGTH;
+        let raw = daf.read_record(record).unwrap().unwrap();
+        assert_eq!(raw[offset..offset + 6], states[0].to_array());
+        assert_eq!(daf.read_record(1000).unwrap(), None);
+
+        let no_addresses = DafSegment {
+            integers: vec![1],
+            ..segment.clone()
+        };
+        assert_eq!(no_addresses.addresses(), None);
+        assert_eq!(daf.read_segment(&no_addresses).unwrap(), None);
+
+        daf.close().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,30 +1,192 @@
 //! Functions for loading and unloading SPICE Kernels.
+//!
+//! Kernels loaded with [furnish()] are tracked in a registry, so that loading a kernel twice can
+//! be detected before calling SPICE. Kernels loaded indirectly, for example by a meta-kernel, are
+//! not tracked individually, but can still be unloaded with [unload()].
+//!
+//! Kernels are identified by their canonical path, so the same file loaded using a different
+//! relative path, or through a symbolic link, is detected as already loaded. Loading a kernel
+//! twice is an error rather than being reference counted because SPICE doesn't count loads:
+//! [furnsh_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/furnsh_c.html) reloads a
+//! kernel that is already loaded, so a matching number of unloads wouldn't restore the previous
+//! state of the kernel subsystem.
 use crate::error::get_last_error;
-use crate::string::StringParam;
+use crate::string::{SpiceString, StringParam};
 use crate::{with_spice_lock_or_panic, Error};
-use cspice_sys::{furnsh_c, unload_c};
+use cspice_sys::{
+    furnsh_c, kclear_c, kdata_c, kinfo_c, ktotal_c, unload_c, SpiceBoolean, SpiceInt, SPICETRUE,
+};
+use parking_lot::Mutex;
+use std::path::PathBuf;
+
+struct LoadedKernel {
+    /// The canonical path, or the name if it can't be canonicalized.
+    path: PathBuf,
+    /// The name the kernel was loaded with, which SPICE requires to unload it.
+    name: String,
+}
+
+// Only accessed while holding the SPICE lock, in load order
+static LOADED: Mutex<Vec<LoadedKernel>> = parking_lot::const_mutex(Vec::new());
+
+fn canonical_path(name: &str) -> PathBuf {
+    std::fs::canonicalize(name).unwrap_or_else(|_| PathBuf::from(name))
+}
+
+/// Error returned when loading or unloading kernels.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum KernelError {
+    #[error("Kernel is already loaded: {0}")]
+    AlreadyLoaded(String),
+    #[error("Kernel is not loaded: {0}")]
+    NotLoaded(String),
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
 
 /// Load one or more SPICE kernels into a program.
 ///
+/// Returns [KernelError::AlreadyLoaded] if the file has already been loaded with this function,
+/// by any path.
+///
 /// See [furnsh_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/furnsh_c.html).
-pub fn furnish<'f, F: Into<StringParam<'f>>>(file: F) -> Result<(), Error> {
+pub fn furnish<'f, F: Into<StringParam<'f>>>(file: F) -> Result<(), KernelError> {
+    let file = file.into();
+    let name = file.as_str().trim().to_string();
+    let path = canonical_path(&name);
     with_spice_lock_or_panic(|| {
+        let mut loaded = LOADED.lock();
+        if loaded.iter().any(|k| k.path == path) {
+            return Err(KernelError::AlreadyLoaded(name));
+        }
         unsafe {
-            furnsh_c(file.into().as_mut_ptr());
+            furnsh_c(file.as_mut_ptr()?);
         };
-        get_last_error()
+        get_last_error()?;
+        loaded.push(LoadedKernel { path, name });
+        Ok(())
     })
 }
 
-/// Unload a SPICE kernel.
+/// Unload a SPICE kernel, which may be referred to by a different path to the one it was loaded
+/// with if it was loaded with [furnish()].
+///
+/// Kernels not loaded with [furnish()], for example those loaded by a meta-kernel, are passed to
+/// SPICE as is. Returns [KernelError::NotLoaded] if SPICE doesn't have the file loaded either.
 ///
 /// See [unload_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/unload_c.html).
-pub fn unload<'f, F: Into<StringParam<'f>>>(file: F) -> Result<(), Error> {
+pub fn unload<'f, F: Into<StringParam<'f>>>(file: F) -> Result<(), KernelError> {
+    let file = file.into();
+    let name = file.as_str().trim().to_string();
+    let path = canonical_path(&name);
     with_spice_lock_or_panic(|| {
-        unsafe {
-            unload_c(file.into().as_mut_ptr());
-        };
-        get_last_error()
+        let mut loaded = LOADED.lock();
+        match loaded.iter().position(|k| k.path == path) {
+            Some(index) => {
+                unsafe {
+                    unload_c(SpiceString::from(&loaded[index].name).as_mut_ptr()?);
+                };
+                get_last_error()?;
+                loaded.remove(index);
+            }
+            None => {
+                if !spice_has_loaded(&file)? {
+                    return Err(KernelError::NotLoaded(name));
+                }
+                unsafe {
+                    unload_c(file.as_mut_ptr()?);
+                };
+                get_last_error()?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Whether SPICE has a file loaded under this name, by any means.
+///
+/// See [kinfo_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/kinfo_c.html).
+fn spice_has_loaded(file: &SpiceString) -> Result<bool, Error> {
+    let (mut file_type, mut source) = ([0; 33], [0; 256]);
+    let (mut handle, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+    unsafe {
+        kinfo_c(
+            file.as_mut_ptr()?,
+            file_type.len() as SpiceInt,
+            source.len() as SpiceInt,
+            file_type.as_mut_ptr(),
+            source.as_mut_ptr(),
+            &mut handle,
+            &mut found,
+        )
+    };
+    get_last_error()?;
+    Ok(found == SPICETRUE as SpiceBoolean)
+}
+
+/// The names of the kernels currently loaded with [furnish()], as they were passed to it, in load
+/// order.
+pub fn loaded_kernels() -> Vec<String> {
+    with_spice_lock_or_panic(|| LOADED.lock().iter().map(|k| k.name.clone()).collect())
+}
+
+/// Whether a kernel is currently loaded with [furnish()], by any path.
+pub fn is_loaded<S: AsRef<str>>(file: S) -> bool {
+    let path = canonical_path(file.as_ref().trim());
+    with_spice_lock_or_panic(|| LOADED.lock().iter().any(|k| k.path == path))
+}
+
+/// Unload all kernels and clear the kernel pool, returning the kernels that were loaded with
+/// [furnish()].
+///
+/// See [kclear_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/kclear_c.html).
+pub fn clear_all() -> Result<Vec<String>, Error> {
+    with_spice_lock_or_panic(|| {
+        unsafe { kclear_c() };
+        get_last_error()?;
+        let loaded = std::mem::take(&mut *LOADED.lock());
+        Ok(loaded.into_iter().map(|k| k.name).collect())
+    })
+}
+
+/// Get the files and handles of all loaded kernels of a kind, e.g. `"PCK"` or `"CK"`, including
+/// those loaded by meta-kernels, in load order.
+///
+/// See [kdata_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/kdata_c.html).
+pub(crate) fn loaded_files<'k, K: Into<StringParam<'k>>>(
+    kind: K,
+) -> Result<Vec<(String, SpiceInt)>, Error> {
+    let kind = kind.into();
+    with_spice_lock_or_panic(|| {
+        let mut count = 0;
+        unsafe { ktotal_c(kind.as_mut_ptr()?, &mut count) };
+        get_last_error()?;
+        let mut files = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let mut file = vec![0; 256];
+            let (mut file_type, mut source) = ([0; 33], [0; 256]);
+            let (mut handle, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+            unsafe {
+                kdata_c(
+                    i,
+                    kind.as_mut_ptr()?,
+                    file.len() as SpiceInt,
+                    file_type.len() as SpiceInt,
+                    source.len() as SpiceInt,
+                    file.as_mut_ptr(),
+                    file_type.as_mut_ptr(),
+                    source.as_mut_ptr(),
+                    &mut handle,
+                    &mut found,
+                )
+            };
+            get_last_error()?;
+            if found != SPICETRUE as SpiceBoolean {
+                break;
+            }
+            files.push((SpiceString::from_buffer(file).to_string(), handle));
+        }
+        Ok(files)
     })
 }
 
@@ -35,6 +197,86 @@ mod tests {
     #[test]
     fn test_furnish() {
         let error = furnish("NON_EXISTENT_FILE").err().unwrap();
-        assert_eq!(error.short_message, "SPICE(NOSUCHFILE)");
+        match error {
+            KernelError::Spice(e) => assert_eq!(e.short_message, "SPICE(NOSUCHFILE)"),
+            e => panic!("{e}"),
+        }
+        assert!(!is_loaded("NON_EXISTENT_FILE"));
+    }
+
+    #[test]
+    fn test_registry() {
+        let path = std::env::temp_dir().join("cspice_rs_registry_test.tk");
+        std::fs::write(&path, "\\begindata\nCSPICE_RS_TEST = 1\n\\begintext\n").unwrap();
+        let kernel = path.to_string_lossy().into_owned();
+
+        furnish(&kernel).unwrap();
+        assert!(is_loaded(&kernel));
+        assert!(loaded_kernels().contains(&kernel));
+        assert!(matches!(
+            furnish(&kernel),
+            Err(KernelError::AlreadyLoaded(_))
+        ));
+        unload(&kernel).unwrap();
+        assert!(!is_loaded(&kernel));
+        assert!(matches!(unload(&kernel), Err(KernelError::NotLoaded(_))));
+    }
+
+    #[test]
+    fn test_unload_from_meta_kernel() {
+        let dir = std::env::temp_dir();
+        let inner = dir.join("cspice_rs_meta_inner_test.tk");
+        std::fs::write(
+            &inner,
+            "\\begindata\nCSPICE_RS_META_INNER_TEST = 1\n\\begintext\n",
+        )
+        .unwrap();
+        let inner = inner.to_string_lossy().into_owned();
+        let meta = dir.join("cspice_rs_meta_test.tm");
+        std::fs::write(
+            &meta,
+            format!("\\begindata\nKERNELS_TO_LOAD = ( '{inner}' )\n\\begintext\n"),
+        )
+        .unwrap();
+        let meta = meta.to_string_lossy().into_owned();
+
+        furnish(&meta).unwrap();
+        assert!(!is_loaded(&inner));
+        unload(&inner).unwrap();
+        assert_eq!(
+            crate::kernel_pool::get_integer("CSPICE_RS_META_INNER_TEST").unwrap(),
+            None
+        );
+        assert!(matches!(unload(&inner), Err(KernelError::NotLoaded(_))));
+        unload(&meta).unwrap();
+    }
+
+    #[test]
+    fn test_registry_canonical_paths() {
+        let dir = std::env::temp_dir();
+        std::fs::write(
+            dir.join("cspice_rs_canonical_test.tk"),
+            "\\begindata\nCSPICE_RS_CANONICAL_TEST = 1\n\\begintext\n",
+        )
+        .unwrap();
+        let kernel = dir.join("cspice_rs_canonical_test.tk");
+        let kernel = kernel.to_string_lossy();
+        let other = dir.join(".").join("cspice_rs_canonical_test.tk");
+        let other = other.to_string_lossy();
+
+        furnish(&*kernel).unwrap();
+        assert!(is_loaded(&*other));
+        assert!(matches!(
+            furnish(&*other),
+            Err(KernelError::AlreadyLoaded(_))
+        ));
+        assert!(loaded_kernels().contains(&kernel.to_string()));
+        // SPICE is passed the name the kernel was loaded with
+        unload(&*other).unwrap();
+        assert!(!is_loaded(&*kernel));
+        assert_eq!(
+            crate::kernel_pool::get_integer("CSPICE_RS_CANONICAL_TEST").unwrap(),
+            None
+        );
     }
 }
@@ -0,0 +1,227 @@
+//! Pre-flight checks that the kernels an application needs are loaded, so that a missing kernel
+//! is reported up front (e.g. in CI for mission configurations) rather than as an error part way
+//! through a computation.
+use crate::body::has_constant;
+use crate::cell::Window;
+use crate::data::loaded_files;
+use crate::error::get_last_error;
+use crate::frame::FrameId;
+use crate::kernel_pool;
+use crate::string::SpiceString;
+use crate::time::Et;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{spkcov_c, wndifd_c, SpiceInt};
+use std::fmt::{Display, Formatter};
+
+/// Size of the window used to hold the SPK coverage of a body.
+pub const COVERAGE_WINDOW_SIZE: usize = 20000;
+
+/// Something an application needs to be loaded, see [check_requirements()].
+pub enum Requirement {
+    /// Ephemeris data for the body (relative to any center) over the whole window.
+    SpkCoverage { body: SpiceInt, window: Window },
+    /// A frame with the given name is defined.
+    FrameDefined(String),
+    /// A leapseconds kernel is loaded, needed for UTC conversions.
+    LeapSeconds,
+    /// A body constant, such as `"RADII"` or `"GM"`, is in the kernel pool.
+    BodyConstant { body: SpiceInt, item: String },
+}
+
+impl Display for Requirement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Requirement::SpkCoverage { body, .. } => write!(f, "SPK coverage of body {body}"),
+            Requirement::FrameDefined(name) => write!(f, "frame {name}"),
+            Requirement::LeapSeconds => f.write_str("leapseconds kernel"),
+            Requirement::BodyConstant { body, item } => write!(f, "constant BODY{body}_{item}"),
+        }
+    }
+}
+
+/// What is missing for a requirement that isn't met.
+#[derive(Debug)]
+pub enum Missing {
+    /// The intervals of the window that aren't covered by any loaded SPK.
+    SpkCoverage(Vec<(Et, Et)>),
+    Frame,
+    LeapSeconds,
+    BodyConstant,
+    /// SPICE signalled an error while checking the requirement.
+    Error(Error),
+}
+
+/// A requirement that isn't met, see [Report].
+pub struct Unmet<'r> {
+    pub requirement: &'r Requirement,
+    pub missing: Missing,
+}
+
+impl Display for Unmet<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Missing {}", self.requirement)?;
+        match &self.missing {
+            Missing::SpkCoverage(gaps) => {
+                let gaps: Vec<_> = gaps
+                    .iter()
+                    .map(|(start, stop)| format!("{} to {}", start.0, stop.0))
+                    .collect();
+                write!(f, " for ET {}", gaps.join(", "))
+            }
+            Missing::Error(e) => write!(f, " ({e:#})"),
+            Missing::Frame | Missing::LeapSeconds | Missing::BodyConstant => Ok(()),
+        }
+    }
+}
+
+/// The result of [check_requirements()].
+pub struct Report<'r> {
+    /// The requirements that aren't met, in the order they were given.
+    pub unmet: Vec<Unmet<'r>>,
+}
+
+impl Report<'_> {
+    /// Whether all the requirements are met.
+    pub fn is_satisfied(&self) -> bool {
+        self.unmet.is_empty()
+    }
+}
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_satisfied() {
+            return f.write_str("All requirements are met");
+        }
+        for (i, unmet) in self.unmet.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{unmet}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Check whether the loaded kernels meet all of the requirements, reporting what is missing.
+///
+/// Errors signalled by SPICE while checking are reported against the requirement being checked,
+/// so that the remaining requirements are still checked.
+pub fn check_requirements(requirements: &[Requirement]) -> Report<'_> {
+    let unmet = with_spice_lock_or_panic(|| {
+        requirements
+            .iter()
+            .filter_map(|requirement| {
+                let missing = match check(requirement) {
+                    Ok(missing) => missing?,
+                    Err(e) => Missing::Error(e),
+                };
+                Some(Unmet {
+                    requirement,
+                    missing,
+                })
+            })
+            .collect()
+    });
+    Report { unmet }
+}
+
+fn check(requirement: &Requirement) -> Result<Option<Missing>, Error> {
+    Ok(match requirement {
+        Requirement::SpkCoverage { body, window } => {
+            let gaps = spk_gaps(*body, window)?;
+            (!gaps.is_empty()).then_some(Missing::SpkCoverage(gaps))
+        }
+        Requirement::FrameDefined(name) => FrameId::from_name(name.as_str())?
+            .is_none()
+            .then_some(Missing::Frame),
+        Requirement::LeapSeconds => kernel_pool::get_integer("DELTET/DELTA_AT")?
+            .is_none()
+            .then_some(Missing::LeapSeconds),
+        Requirement::BodyConstant { body, item } => {
+            (!has_constant(*body, item.as_str())?).then_some(Missing::BodyConstant)
+        }
+    })
+}
+
+/// The intervals of `window` not covered by any loaded SPK.
+///
+/// See [spkcov_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkcov_c.html).
+fn spk_gaps(body: SpiceInt, window: &Window) -> Result<Vec<(Et, Et)>, Error> {
+    let mut cover = Window::new_double(COVERAGE_WINDOW_SIZE);
+    for (file, _) in loaded_files("SPK")? {
+        unsafe {
+            spkcov_c(
+                SpiceString::from(file).as_mut_ptr()?,
+                body,
+                cover.as_mut_cell(),
+            )
+        };
+        get_last_error()?;
+    }
+    let size = 2 * (window.window_cardinality()? + cover.window_cardinality()?) as usize + 2;
+    let mut gaps = Window::new_double(size);
+    unsafe {
+        wndifd_c(
+            window.as_const_cell(),
+            cover.as_mut_cell(),
+            gaps.as_mut_cell(),
+        )
+    };
+    get_last_error()?;
+    (0..gaps.window_cardinality()? as usize)
+        .map(|i| {
+            let (start, stop) = gaps.window_interval(i)?;
+            Ok((Et(start), Et(stop)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naif_ids::MOON;
+    use crate::tests::load_test_data;
+
+    #[test]
+    fn test_check_requirements() {
+        load_test_data();
+        let day = 86400.0;
+        let requirements = [
+            Requirement::LeapSeconds,
+            Requirement::FrameDefined("IAU_MARS".to_string()),
+            Requirement::SpkCoverage {
+                body: MOON,
+                window: Window::from_intervals(&[(0.0, day)]).unwrap(),
+            },
+        ];
+        let report = check_requirements(&requirements);
+        assert!(report.is_satisfied(), "{report}");
+
+        let outside = 100.0 * 365.25 * day;
+        let requirements = [
+            Requirement::FrameDefined("NOT_A_FRAME".to_string()),
+            Requirement::SpkCoverage {
+                body: MOON,
+                window: Window::from_intervals(&[(0.0, day), (outside, outside + day)]).unwrap(),
+            },
+            Requirement::BodyConstant {
+                body: MOON,
+                item: "GM".to_string(),
+            },
+        ];
+        let report = check_requirements(&requirements);
+        assert_eq!(report.unmet.len(), 3, "{report}");
+        assert!(matches!(report.unmet[0].missing, Missing::Frame));
+        match &report.unmet[1].missing {
+            Missing::SpkCoverage(gaps) => {
+                assert_eq!(gaps, &vec![(Et(outside), Et(outside + day))])
+            }
+            m => panic!("{m:?}"),
+        }
+        assert!(matches!(report.unmet[2].missing, Missing::BodyConstant));
+        assert_eq!(
+            report.to_string().lines().next(),
+            Some("Missing frame NOT_A_FRAME")
+        );
+    }
+}
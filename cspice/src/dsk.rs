@@ -0,0 +1,148 @@
+//! Functions relating to the Digital Shape Kernel (DSK) subsystem of SPICE.
+//!
+//! DSK segments model the surface of a body, e.g. as a plate model or digital elevation model.
+//! A body may have several surfaces, each identified by a surface ID code that is unique for the
+//! body, and optionally a name defined in a text kernel. Geometry functions use all loaded
+//! surfaces with [ShapeMethod::dsk()](crate::geometry::ShapeMethod::dsk), or a subset with
+//! [ShapeMethod::dsk_surfaces()](crate::geometry::ShapeMethod::dsk_surfaces).
+//!
+//! See [DSK required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/dsk.html).
+use crate::body::BodyRef;
+use crate::error::get_last_error;
+use crate::string::{SpiceString, StringParam};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{srfc2s_c, srfs2c_c, SpiceBoolean, SpiceInt, SPICETRUE};
+use std::fmt::Write;
+
+/// The ID code of a DSK surface, which is unique for a given body.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SurfaceId(pub SpiceInt);
+
+impl SurfaceId {
+    /// Look up the ID code of a surface of a body by name, or from a string containing the ID
+    /// code. Returns `None` if the surface is not recognised.
+    ///
+    /// See [srfs2c_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/srfs2c_c.html).
+    pub fn from_name<'s, 'b, S, B>(name: S, body: B) -> Result<Option<Self>, Error>
+    where
+        S: Into<StringParam<'s>>,
+        B: Into<BodyRef<'b>>,
+    {
+        let body = body.into();
+        with_spice_lock_or_panic(|| {
+            let mut code: SpiceInt = 0;
+            let mut found: SpiceBoolean = 0;
+            unsafe {
+                srfs2c_c(
+                    name.into().as_mut_ptr()?,
+                    body.to_string_param().as_mut_ptr()?,
+                    &mut code,
+                    &mut found,
+                )
+            };
+            get_last_error()?;
+            Ok((found == SPICETRUE as SpiceBoolean).then_some(SurfaceId(code)))
+        })
+    }
+
+    /// Look up the name of the surface of a body, returns `None` if no name is defined.
+    ///
+    /// See [srfc2s_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/srfc2s_c.html).
+    pub fn name<'b, B: Into<BodyRef<'b>>>(&self, body: B) -> Result<Option<String>, Error> {
+        let body = body.into().to_id()?;
+        // Surface names are limited to 36 characters
+        let mut buffer = vec![0; 37];
+        with_spice_lock_or_panic(|| {
+            let mut is_name: SpiceBoolean = 0;
+            unsafe {
+                srfc2s_c(
+                    self.0,
+                    body,
+                    buffer.len() as SpiceInt,
+                    buffer.as_mut_ptr(),
+                    &mut is_name,
+                )
+            };
+            get_last_error()?;
+            Ok((is_name == SPICETRUE as SpiceBoolean)
+                .then(|| SpiceString::from_buffer(buffer).to_string()))
+        })
+    }
+}
+
+/// A list of DSK surfaces to use for a geometry computation, see
+/// [ShapeMethod::dsk_surfaces()](crate::geometry::ShapeMethod::dsk_surfaces).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SurfaceList {
+    surfaces: Vec<SurfaceId>,
+    method: SpiceString,
+}
+
+impl SurfaceList {
+    /// If `surfaces` is empty then all surfaces of the target are used.
+    pub fn new<I: IntoIterator<Item = SurfaceId>>(surfaces: I) -> Self {
+        let surfaces: Vec<_> = surfaces.into_iter().collect();
+        let mut method = String::from("DSK/UNPRIORITIZED");
+        for (i, surface) in surfaces.iter().enumerate() {
+            let separator = if i == 0 { "/SURFACES = " } else { ", " };
+            write!(method, "{separator}{}", surface.0).unwrap();
+        }
+        Self {
+            surfaces,
+            method: SpiceString::from(method),
+        }
+    }
+
+    pub fn surfaces(&self) -> &[SurfaceId] {
+        &self.surfaces
+    }
+
+    /// The method string passed to SPICE, e.g. `"DSK/UNPRIORITIZED/SURFACES = 1, 2"`.
+    pub fn method(&self) -> &SpiceString {
+        &self.method
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::furnish;
+    use crate::naif_ids::MOON;
+
+    #[test]
+    fn test_surface_names() {
+        let path = std::env::temp_dir().join("cspice_rs_surface_test.tk");
+        std::fs::write(
+            &path,
+            "\\begindata
+NAIF_SURFACE_NAME += 'CSPICE_RS_LOW_RES'
+NAIF_SURFACE_CODE += 1001
+NAIF_SURFACE_BODY += 301
+\\begintext
+",
+        )
+        .unwrap();
+        furnish(path.to_string_lossy()).unwrap();
+
+        let low_res = SurfaceId::from_name("CSPICE_RS_LOW_RES", MOON).unwrap();
+        assert_eq!(low_res, Some(SurfaceId(1001)));
+        assert_eq!(
+            SurfaceId::from_name("CSPICE_RS_LOW_RES", "EARTH").unwrap(),
+            None
+        );
+        assert_eq!(SurfaceId::from_name("7", MOON).unwrap(), Some(SurfaceId(7)));
+        assert_eq!(
+            SurfaceId(1001).name("MOON").unwrap().as_deref(),
+            Some("CSPICE_RS_LOW_RES")
+        );
+        assert_eq!(SurfaceId(7).name(MOON).unwrap(), None);
+    }
+
+    #[test]
+    fn test_surface_list() {
+        assert_eq!(SurfaceList::new([]).method().as_str(), "DSK/UNPRIORITIZED");
+        let list = SurfaceList::new([SurfaceId(1), SurfaceId(2)]);
+        assert_eq!(list.surfaces(), &[SurfaceId(1), SurfaceId(2)]);
+        assert_eq!(list.method().as_str(), "DSK/UNPRIORITIZED/SURFACES = 1, 2");
+    }
+}
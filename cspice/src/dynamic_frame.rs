@@ -0,0 +1,271 @@
+//! Two-vector dynamic frames defined from Rust, without writing a frame kernel.
+//!
+//! A two-vector frame is defined by a primary vector, which one axis of the frame is aligned
+//! with, and a secondary vector, which another axis is aligned with as closely as possible. For
+//! example a Sun-Earth line frame has +X from the Sun to the Earth and +Y along the Earth's
+//! velocity.
+//!
+//! See [Two-Vector Frames](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/frames.html#Two-Vector%20Frames).
+use crate::common::AberrationCorrection;
+use crate::kernel_pool::{put_integers, put_numbers, put_strings};
+use crate::vector::Vector3D;
+use crate::Error;
+use cspice_sys::{SpiceDouble, SpiceInt};
+
+/// An axis of a two-vector frame, optionally negated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameAxis {
+    X,
+    Y,
+    Z,
+    NegX,
+    NegY,
+    NegZ,
+}
+
+impl FrameAxis {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FrameAxis::X => "X",
+            FrameAxis::Y => "Y",
+            FrameAxis::Z => "Z",
+            FrameAxis::NegX => "-X",
+            FrameAxis::NegY => "-Y",
+            FrameAxis::NegZ => "-Z",
+        }
+    }
+}
+
+/// The definition of a primary or secondary vector of a [TwoVectorFrame].
+#[derive(Clone, Debug, PartialEq)]
+pub enum VectorDefinition {
+    /// The position of `target` relative to `observer`.
+    ObserverTargetPosition {
+        observer: String,
+        target: String,
+        abcorr: AberrationCorrection,
+    },
+    /// The velocity of `target` relative to `observer`, in `frame`.
+    ObserverTargetVelocity {
+        observer: String,
+        target: String,
+        abcorr: AberrationCorrection,
+        frame: String,
+    },
+    /// The vector from `observer` to the nearest point on the reference ellipsoid of `target`.
+    TargetNearPoint {
+        observer: String,
+        target: String,
+        abcorr: AberrationCorrection,
+    },
+    /// A constant vector in `frame`.
+    Constant { frame: String, vector: Vector3D },
+}
+
+/// A value of a kernel pool variable, see [TwoVectorFrame::assignments()].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PoolValue {
+    Numbers(Vec<SpiceDouble>),
+    Integers(Vec<SpiceInt>),
+    Strings(Vec<String>),
+}
+
+/// Builder for a two-vector dynamic frame, see the [module documentation](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwoVectorFrame {
+    name: String,
+    id: SpiceInt,
+    center: SpiceInt,
+    relative: String,
+    primary: (FrameAxis, VectorDefinition),
+    secondary: (FrameAxis, VectorDefinition),
+}
+
+impl TwoVectorFrame {
+    /// Create a new frame with the required parameters. The frame is defined relative to `J2000`
+    /// by default.
+    ///
+    /// `id` should be in the range reserved for user frames, 1400000 to 2000000, and `center` is
+    /// the NAIF ID code of the center of the frame, e.g. 399 for the Earth.
+    pub fn new<N: Into<String>>(
+        name: N,
+        id: SpiceInt,
+        center: SpiceInt,
+        primary: (FrameAxis, VectorDefinition),
+        secondary: (FrameAxis, VectorDefinition),
+    ) -> Self {
+        Self {
+            name: name.into(),
+            id,
+            center,
+            relative: String::from("J2000"),
+            primary,
+            secondary,
+        }
+    }
+
+    /// Set the frame the orientation of this frame is given relative to (the `RELATIVE` frame),
+    /// the default is `J2000`. It doesn't need to be inertial.
+    pub fn relative<F: Into<String>>(mut self, frame: F) -> Self {
+        self.relative = frame.into();
+        self
+    }
+
+    /// Generate the kernel pool assignments defining the frame.
+    pub fn assignments(&self) -> Vec<(String, PoolValue)> {
+        let id = self.id;
+        let string = |s: &str| PoolValue::Strings(vec![s.to_string()]);
+        let mut assignments = vec![
+            (
+                format!("FRAME_{}", self.name),
+                PoolValue::Integers(vec![id]),
+            ),
+            (format!("FRAME_{id}_NAME"), string(&self.name)),
+            (format!("FRAME_{id}_CLASS"), PoolValue::Integers(vec![5])),
+            (
+                format!("FRAME_{id}_CLASS_ID"),
+                PoolValue::Integers(vec![id]),
+            ),
+            (
+                format!("FRAME_{id}_CENTER"),
+                PoolValue::Integers(vec![self.center]),
+            ),
+            (format!("FRAME_{id}_RELATIVE"), string(&self.relative)),
+            (format!("FRAME_{id}_DEF_STYLE"), string("PARAMETERIZED")),
+            (format!("FRAME_{id}_FAMILY"), string("TWO-VECTOR")),
+        ];
+        for (prefix, (axis, definition)) in [("PRI", &self.primary), ("SEC", &self.secondary)] {
+            let key = |k: &str| format!("FRAME_{id}_{prefix}_{k}");
+            assignments.push((key("AXIS"), string(axis.as_str())));
+            let (style, observer, target, abcorr) = match definition {
+                VectorDefinition::ObserverTargetPosition {
+                    observer,
+                    target,
+                    abcorr,
+                } => ("OBSERVER_TARGET_POSITION", observer, target, abcorr),
+                VectorDefinition::ObserverTargetVelocity {
+                    observer,
+                    target,
+                    abcorr,
+                    frame,
+                } => {
+                    assignments.push((key("FRAME"), string(frame)));
+                    ("OBSERVER_TARGET_VELOCITY", observer, target, abcorr)
+                }
+                VectorDefinition::TargetNearPoint {
+                    observer,
+                    target,
+                    abcorr,
+                } => ("TARGET_NEAR_POINT", observer, target, abcorr),
+                VectorDefinition::Constant { frame, vector } => {
+                    assignments.push((key("VECTOR_DEF"), string("CONSTANT")));
+                    assignments.push((key("FRAME"), string(frame)));
+                    assignments.push((key("SPEC"), string("RECTANGULAR")));
+                    assignments.push((key("VECTOR"), PoolValue::Numbers(vector.0.to_vec())));
+                    continue;
+                }
+            };
+            assignments.push((key("VECTOR_DEF"), string(style)));
+            assignments.push((key("OBSERVER"), string(observer)));
+            assignments.push((key("TARGET"), string(target)));
+            assignments.push((key("ABCORR"), string(abcorr.as_str())));
+        }
+        assignments
+    }
+
+    /// Define the frame by inserting its [assignments](TwoVectorFrame::assignments()) into the
+    /// kernel pool.
+    pub fn load(&self) -> Result<(), Error> {
+        for (name, value) in self.assignments() {
+            match value {
+                PoolValue::Numbers(values) => put_numbers(name, &values)?,
+                PoolValue::Integers(values) => put_integers(name, &values)?,
+                PoolValue::Strings(values) => put_strings(name, &values)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spk::{easier_reader, position};
+    use crate::tests::load_test_data;
+    use crate::time::Et;
+
+    #[test]
+    fn test_sun_earth_frame() {
+        load_test_data();
+        let sun_earth = || VectorDefinition::ObserverTargetPosition {
+            observer: String::from("SUN"),
+            target: String::from("EARTH"),
+            abcorr: AberrationCorrection::NONE,
+        };
+        let velocity = VectorDefinition::ObserverTargetVelocity {
+            observer: String::from("SUN"),
+            target: String::from("EARTH"),
+            abcorr: AberrationCorrection::NONE,
+            frame: String::from("J2000"),
+        };
+        let frame = TwoVectorFrame::new(
+            "CSPICE_RS_SUN_EARTH",
+            1899001,
+            10,
+            (FrameAxis::X, sun_earth()),
+            (FrameAxis::Y, velocity),
+        );
+        let assignments = frame.assignments();
+        assert!(assignments.contains(&(
+            String::from("FRAME_1899001_PRI_VECTOR_DEF"),
+            PoolValue::Strings(vec![String::from("OBSERVER_TARGET_POSITION")])
+        )));
+        assert!(assignments.contains(&(
+            String::from("FRAME_1899001_SEC_FRAME"),
+            PoolValue::Strings(vec![String::from("J2000")])
+        )));
+        frame.load().unwrap();
+
+        let et = Et(1e8);
+        let (earth, _) = position(
+            "EARTH",
+            et,
+            "CSPICE_RS_SUN_EARTH",
+            AberrationCorrection::NONE,
+            "SUN",
+        )
+        .unwrap();
+        let (state, _) =
+            easier_reader("EARTH", et, "J2000", AberrationCorrection::NONE, "SUN").unwrap();
+        assert!((earth.x - state.range()).abs() < 1e-3);
+        assert!(earth.y.abs() < 1e-3);
+        assert!(earth.z.abs() < 1e-3);
+
+        // A constant secondary vector, with the frame defined relative to another frame
+        let frame = TwoVectorFrame::new(
+            "CSPICE_RS_SUN_EARTH_POLE",
+            1899002,
+            10,
+            (FrameAxis::X, sun_earth()),
+            (
+                FrameAxis::Z,
+                VectorDefinition::Constant {
+                    frame: String::from("ECLIPJ2000"),
+                    vector: Vector3D([0.0, 0.0, 1.0]),
+                },
+            ),
+        )
+        .relative("ECLIPJ2000");
+        frame.load().unwrap();
+        let (earth, _) = position(
+            "EARTH",
+            et,
+            "CSPICE_RS_SUN_EARTH_POLE",
+            AberrationCorrection::NONE,
+            "SUN",
+        )
+        .unwrap();
+        assert!((earth.x - state.range()).abs() < 1e-3);
+        assert!(earth.y.abs() < 1e-3);
+    }
+}
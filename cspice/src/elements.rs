@@ -0,0 +1,461 @@
+//! Orbital element sets.
+use crate::error::get_last_error;
+use crate::spk::State;
+use crate::time::Et;
+use crate::units::{Kilometers, Radians, Seconds};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    conics_c, eqncpv_c, evsgp4_c, getelm_c, oscelt_c, oscltx_c, SpiceChar, SpiceDouble, SpiceInt,
+    SPICE_OSCLTX_NELTS,
+};
+
+/// Osculating conic elements of an orbit.
+///
+/// See [oscelt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/oscelt_c.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ConicElements {
+    /// The distance of closest approach (periapsis).
+    pub perifocal_distance: Kilometers,
+    pub eccentricity: SpiceDouble,
+    pub inclination: Radians,
+    /// The longitude of the ascending node.
+    pub node: Radians,
+    pub argument_of_periapsis: Radians,
+    /// The mean anomaly at the epoch.
+    pub mean_anomaly: Radians,
+    /// The epoch of the elements.
+    pub epoch: Et,
+    /// The gravitational parameter of the central body in km<sup>3</sup>/s<sup>2</sup>.
+    pub gm: SpiceDouble,
+}
+
+impl ConicElements {
+    /// Determine the osculating elements of a state relative to a central body at `et`.
+    ///
+    /// See [oscelt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/oscelt_c.html).
+    pub fn from_state(state: &State, et: Et, gm: SpiceDouble) -> Result<Self, Error> {
+        let mut state = state.to_array();
+        with_spice_lock_or_panic(|| {
+            let mut elements = [0.0; 8];
+            unsafe { oscelt_c(state.as_mut_ptr(), et.0, gm, elements.as_mut_ptr()) };
+            get_last_error()?;
+            Ok(Self::from_array(&elements))
+        })
+    }
+
+    /// Compute the state at `et`, relative to the central body in the frame of the elements.
+    ///
+    /// See [conics_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/conics_c.html).
+    pub fn state(&self, et: Et) -> Result<State, Error> {
+        let mut elements = self.to_array();
+        with_spice_lock_or_panic(|| {
+            let mut state = [0.0; 6];
+            unsafe { conics_c(elements.as_mut_ptr(), et.0, state.as_mut_ptr()) };
+            get_last_error()?;
+            Ok(State::from(state))
+        })
+    }
+
+    fn from_array(elements: &[SpiceDouble]) -> Self {
+        Self {
+            perifocal_distance: Kilometers(elements[0]),
+            eccentricity: elements[1],
+            inclination: Radians(elements[2]),
+            node: Radians(elements[3]),
+            argument_of_periapsis: Radians(elements[4]),
+            mean_anomaly: Radians(elements[5]),
+            epoch: Et(elements[6]),
+            gm: elements[7],
+        }
+    }
+
+    fn to_array(self) -> [SpiceDouble; 8] {
+        [
+            self.perifocal_distance.0,
+            self.eccentricity,
+            self.inclination.0,
+            self.node.0,
+            self.argument_of_periapsis.0,
+            self.mean_anomaly.0,
+            self.epoch.0,
+            self.gm,
+        ]
+    }
+}
+
+/// Osculating conic elements together with the extra elements computed by `oscltx_c`.
+///
+/// See [oscltx_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/oscltx_c.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OrbitalElementsExt {
+    pub conic: ConicElements,
+    /// The true anomaly at the epoch.
+    pub true_anomaly: Radians,
+    /// The semi-major axis, which is negative for hyperbolic orbits. `None` for parabolic orbits
+    /// or when it can't be computed accurately.
+    pub semi_major_axis: Option<Kilometers>,
+    /// The orbital period. `None` unless the orbit is elliptical.
+    pub period: Option<Seconds>,
+}
+
+impl OrbitalElementsExt {
+    /// Determine the osculating elements of a state relative to a central body at `et`.
+    ///
+    /// See [oscltx_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/oscltx_c.html).
+    pub fn from_state(state: &State, et: Et, gm: SpiceDouble) -> Result<Self, Error> {
+        let mut state = state.to_array();
+        with_spice_lock_or_panic(|| {
+            let mut elements = [0.0; SPICE_OSCLTX_NELTS as usize];
+            unsafe { oscltx_c(state.as_mut_ptr(), et.0, gm, elements.as_mut_ptr()) };
+            get_last_error()?;
+            // Zero is returned where the value isn't applicable
+            let nonzero = |x: SpiceDouble| (x != 0.0).then_some(x);
+            Ok(Self {
+                conic: ConicElements::from_array(&elements),
+                true_anomaly: Radians(elements[8]),
+                semi_major_axis: nonzero(elements[9]).map(Kilometers),
+                period: nonzero(elements[10]).map(Seconds),
+            })
+        })
+    }
+}
+
+/// Equinoctial elements, as used in the theories of motion of some planetary satellites. Rates
+/// are in radians per second.
+///
+/// See [eqncpv_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/eqncpv_c.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EquinoctialElements {
+    /// The epoch of the elements.
+    pub epoch: Et,
+    pub semi_major_axis: Kilometers,
+    /// `e * sin(argp + node)`
+    pub h: SpiceDouble,
+    /// `e * cos(argp + node)`
+    pub k: SpiceDouble,
+    /// The mean longitude at the epoch.
+    pub mean_longitude: Radians,
+    /// `tan(inc / 2) * sin(node)`
+    pub p: SpiceDouble,
+    /// `tan(inc / 2) * cos(node)`
+    pub q: SpiceDouble,
+    /// The rate of the longitude of periapse.
+    pub periapse_longitude_rate: SpiceDouble,
+    /// The rate of the mean longitude (the mean motion).
+    pub mean_longitude_rate: SpiceDouble,
+    /// The rate of the longitude of the ascending node.
+    pub node_longitude_rate: SpiceDouble,
+}
+
+impl EquinoctialElements {
+    /// Compute the state at `et` relative to the center of motion, in the `J2000` frame.
+    ///
+    /// The elements are relative to a reference plane whose pole has the given right ascension
+    /// and declination in `J2000`, e.g. `(-pi/2, pi/2)` for the `J2000` equator.
+    ///
+    /// See [eqncpv_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/eqncpv_c.html).
+    pub fn state(&self, et: Et, pole_ra: Radians, pole_dec: Radians) -> Result<State, Error> {
+        let mut elements = [
+            self.semi_major_axis.0,
+            self.h,
+            self.k,
+            self.mean_longitude.0,
+            self.p,
+            self.q,
+            self.periapse_longitude_rate,
+            self.mean_longitude_rate,
+            self.node_longitude_rate,
+        ];
+        with_spice_lock_or_panic(|| {
+            let mut state = [0.0; 6];
+            unsafe {
+                eqncpv_c(
+                    et.0,
+                    self.epoch.0,
+                    elements.as_mut_ptr(),
+                    pole_ra.0,
+                    pole_dec.0,
+                    state.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(State::from(state))
+        })
+    }
+}
+
+/// Geophysical constants used by the SGP4 propagator for two-line elements.
+///
+/// See [evsgp4_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/evsgp4_c.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GeophysicalConstants {
+    /// J2 gravitational harmonic for the Earth.
+    pub j2: SpiceDouble,
+    /// J3 gravitational harmonic for the Earth.
+    pub j3: SpiceDouble,
+    /// J4 gravitational harmonic for the Earth.
+    pub j4: SpiceDouble,
+    /// `sqrt(GM)` in earth radii<sup>1.5</sup> per minute.
+    pub ke: SpiceDouble,
+    /// High altitude bound for the atmospheric model in km.
+    pub qo: SpiceDouble,
+    /// Low altitude bound for the atmospheric model in km.
+    pub so: SpiceDouble,
+    /// Equatorial radius of the Earth in km.
+    pub er: SpiceDouble,
+    /// Distance units per earth radius.
+    pub ae: SpiceDouble,
+}
+
+impl GeophysicalConstants {
+    /// The WGS-72 constants, which should be used with NORAD two-line elements.
+    pub const WGS72: Self = Self {
+        j2: 1.082616e-3,
+        j3: -2.53881e-6,
+        j4: -1.65597e-6,
+        ke: 7.43669161e-2,
+        qo: 120.0,
+        so: 78.0,
+        er: 6378.135,
+        ae: 1.0,
+    };
+
+    pub(crate) fn to_array(self) -> [SpiceDouble; 8] {
+        [
+            self.j2, self.j3, self.j4, self.ke, self.qo, self.so, self.er, self.ae,
+        ]
+    }
+}
+
+impl Default for GeophysicalConstants {
+    fn default() -> Self {
+        Self::WGS72
+    }
+}
+
+/// A NORAD two-line element set. Angles are in radians and time in minutes.
+///
+/// See [getelm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/getelm_c.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TwoLineElements {
+    /// The epoch of the elements.
+    pub epoch: Et,
+    /// The first derivative of the mean motion divided by 2.
+    pub mean_motion_rate: SpiceDouble,
+    /// The second derivative of the mean motion divided by 6.
+    pub mean_motion_acceleration: SpiceDouble,
+    /// The drag term.
+    pub bstar: SpiceDouble,
+    pub inclination: Radians,
+    /// The right ascension of the ascending node.
+    pub node: Radians,
+    pub eccentricity: SpiceDouble,
+    pub argument_of_perigee: Radians,
+    pub mean_anomaly: Radians,
+    /// Radians per minute.
+    pub mean_motion: SpiceDouble,
+}
+
+impl TwoLineElements {
+    /// Parse a two-line element set. Two digit years from 57 onwards are in the 20th century.
+    ///
+    /// Requires a leapseconds kernel to be loaded.
+    ///
+    /// See [getelm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/getelm_c.html).
+    pub fn parse(line1: &str, line2: &str) -> Result<Self, Error> {
+        // The lines are passed as a 2D array of null terminated strings
+        let line_length = line1.len().max(line2.len()) + 1;
+        let mut lines = vec![0 as SpiceChar; 2 * line_length];
+        for (i, line) in [line1, line2].iter().enumerate() {
+            for (j, byte) in line.bytes().enumerate() {
+                lines[i * line_length + j] = byte as SpiceChar;
+            }
+        }
+        with_spice_lock_or_panic(|| {
+            let (mut epoch, mut elements) = (0.0, [0.0; 10]);
+            unsafe {
+                getelm_c(
+                    1957,
+                    line_length as SpiceInt,
+                    lines.as_mut_ptr().cast(),
+                    &mut epoch,
+                    elements.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(Self {
+                epoch: Et(epoch),
+                mean_motion_rate: elements[0],
+                mean_motion_acceleration: elements[1],
+                bstar: elements[2],
+                inclination: Radians(elements[3]),
+                node: Radians(elements[4]),
+                eccentricity: elements[5],
+                argument_of_perigee: Radians(elements[6]),
+                mean_anomaly: Radians(elements[7]),
+                mean_motion: elements[8],
+            })
+        })
+    }
+
+    /// Propagate the elements to `et` with SGP4/SDP4, returning the state relative to the Earth
+    /// in the TEME frame.
+    ///
+    /// See [evsgp4_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/evsgp4_c.html).
+    pub fn state(&self, et: Et, constants: &GeophysicalConstants) -> Result<State, Error> {
+        let mut constants = constants.to_array();
+        let mut elements = self.to_array();
+        with_spice_lock_or_panic(|| {
+            let mut state = [0.0; 6];
+            unsafe {
+                evsgp4_c(
+                    et.0,
+                    constants.as_mut_ptr(),
+                    elements.as_mut_ptr(),
+                    state.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(State::from(state))
+        })
+    }
+
+    /// The elements in the order used by SPICE.
+    pub(crate) fn to_array(self) -> [SpiceDouble; 10] {
+        [
+            self.mean_motion_rate,
+            self.mean_motion_acceleration,
+            self.bstar,
+            self.inclination.0,
+            self.node.0,
+            self.eccentricity,
+            self.argument_of_perigee.0,
+            self.mean_anomaly.0,
+            self.mean_motion,
+            self.epoch.0,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    // From the spkw10_c example
+    const TLE: [&str; 4] = [
+        "1 18123U 87 53  A 87324.61041692 -.00000023  00000-0 -75103-5 0 00675",
+        "2 18123  98.8296 152.0074 0014950 168.7820 191.3688 14.12912554 21686",
+        "1 18123U 87 53  A 87326.73487726  .00000045  00000-0  28709-4 0 00684",
+        "2 18123  98.8335 154.1103 0015643 163.5445 196.6235 14.12912902 21988",
+    ];
+
+    #[test]
+    fn test_two_line_elements() {
+        load_test_data();
+        let elements = TwoLineElements::parse(TLE[0], TLE[1]).unwrap();
+        assert!((elements.inclination.0.to_degrees() - 98.8296).abs() < 1e-10);
+        assert!((elements.eccentricity - 0.001495).abs() < 1e-12);
+        // Revolutions per day to radians per minute
+        let mean_motion = 14.12912554 * 2.0 * PI / 1440.0;
+        assert!((elements.mean_motion - mean_motion).abs() < 1e-10);
+        let epoch = Et::from_string("1987-11-20 14:39:00.022 UTC").unwrap();
+        assert!((elements.epoch.0 - epoch.0).abs() < 1e-2);
+
+        let state = elements
+            .state(elements.epoch, &GeophysicalConstants::WGS72)
+            .unwrap();
+        let radius = state.position.to_array();
+        let altitude = radius.iter().map(|x| x * x).sum::<SpiceDouble>().sqrt() - 6378.135;
+        assert!(altitude > 700.0 && altitude < 900.0, "{altitude}");
+
+        assert!(TwoLineElements::parse("1 18123U", TLE[1]).is_err());
+    }
+
+    const GM_EARTH: SpiceDouble = 398600.4418;
+
+    #[test]
+    fn test_conic_round_trip() {
+        let state = State::from([7000.0, 100.0, -300.0, 0.5, 7.2, 1.5]);
+        let elements = ConicElements::from_state(&state, Et(100.0), GM_EARTH).unwrap();
+        assert_eq!(elements.epoch, Et(100.0));
+        assert_eq!(elements.gm, GM_EARTH);
+        assert!(elements.eccentricity < 1.0);
+        assert!(elements.perifocal_distance.0 <= 7000.0 + 1e-6);
+        let back = elements.state(Et(100.0)).unwrap();
+        for (a, b) in back.to_array().iter().zip(state.to_array()) {
+            assert!((a - b).abs() < 1e-8, "{back:?} != {state:?}");
+        }
+
+        let err = ConicElements::from_state(&State::default(), Et(0.0), GM_EARTH).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(DEGENERATECASE)");
+    }
+
+    #[test]
+    fn test_extended_elements() {
+        // Periapsis of an orbit with semi-major axis 8000 km and eccentricity 0.125
+        let (a, e): (SpiceDouble, SpiceDouble) = (8000.0, 0.125);
+        let rp = a * (1.0 - e);
+        let vp = (GM_EARTH * (1.0 + e) / rp).sqrt();
+        let state = State::from([rp, 0.0, 0.0, 0.0, vp, 0.0]);
+        let elements = OrbitalElementsExt::from_state(&state, Et(0.0), GM_EARTH).unwrap();
+        assert!((elements.conic.eccentricity - e).abs() < 1e-12);
+        assert!((elements.true_anomaly.0.cos() - 1.0).abs() < 1e-9);
+        assert!((elements.semi_major_axis.unwrap().0 - a).abs() < 1e-6);
+        let period = 2.0 * PI * (a.powi(3) / GM_EARTH).sqrt();
+        assert!((elements.period.unwrap().0 - period).abs() < 1e-6);
+        assert_eq!(
+            elements.conic,
+            ConicElements::from_state(&state, Et(0.0), GM_EARTH).unwrap()
+        );
+
+        // Half an orbit later at apoapsis
+        let apoapsis = elements.conic.state(Et(period / 2.0)).unwrap();
+        let elements =
+            OrbitalElementsExt::from_state(&apoapsis, Et(period / 2.0), GM_EARTH).unwrap();
+        assert!((elements.true_anomaly.0.cos() + 1.0).abs() < 1e-9);
+
+        // Hyperbolic orbits have no period
+        let state = State::from([rp, 0.0, 0.0, 0.0, 2.0 * vp, 0.0]);
+        let elements = OrbitalElementsExt::from_state(&state, Et(0.0), GM_EARTH).unwrap();
+        assert!(elements.conic.eccentricity > 1.0);
+        assert!(elements.semi_major_axis.unwrap().0 < 0.0);
+        assert_eq!(elements.period, None);
+    }
+
+    #[test]
+    fn test_equinoctial_state() {
+        let period = 86400.0;
+        let elements = EquinoctialElements {
+            epoch: Et(0.0),
+            semi_major_axis: Kilometers(7000.0),
+            h: 0.0,
+            k: 0.0,
+            mean_longitude: Radians(0.0),
+            p: 0.0,
+            q: 0.0,
+            periapse_longitude_rate: 0.0,
+            mean_longitude_rate: 2.0 * PI / period,
+            node_longitude_rate: 0.0,
+        };
+        let (ra, dec) = (Radians(-FRAC_PI_2), Radians(FRAC_PI_2));
+
+        let state = elements.state(Et(0.0), ra, dec).unwrap();
+        assert!((state.position.x - 7000.0).abs() < 1e-6);
+        assert!((state.velocity[1] - 7000.0 * 2.0 * PI / period).abs() < 1e-9);
+
+        // A quarter of an orbit later
+        let state = elements.state(Et(period / 4.0), ra, dec).unwrap();
+        assert!((state.position.y - 7000.0).abs() < 1e-6);
+        assert!(state.position.x.abs() < 1e-6);
+
+        let err = EquinoctialElements {
+            semi_major_axis: Kilometers(-1.0),
+            ..elements
+        }
+        .state(Et(0.0), ra, dec)
+        .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(BADSEMIAXIS)");
+    }
+}
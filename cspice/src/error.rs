@@ -1,24 +1,188 @@
 //! Functions relating to error handling.
 use crate::common::{GET, SET};
-use crate::string::{SpiceStr, SpiceString};
+use crate::string::{static_spice_str, SpiceStr, SpiceString, StaticSpiceStr, StringParam};
 use crate::with_spice_lock_or_panic;
 use cspice_sys::{
-    erract_c, errdev_c, failed_c, getmsg_c, qcktrc_c, reset_c, SpiceInt, SPICE_ERROR_LMSGLN,
-    SPICE_ERROR_SMSGLN, SPICE_ERROR_TRCLEN, SPICE_ERROR_XMSGLN,
+    chkin_c, chkout_c, erract_c, errdev_c, failed_c, getmsg_c, qcktrc_c, reset_c, setmsg_c,
+    sigerr_c, SpiceInt, SPICE_ERROR_LMSGLN, SPICE_ERROR_SMSGLN, SPICE_ERROR_TRCLEN,
+    SPICE_ERROR_XMSGLN,
 };
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::Read;
 use thiserror::Error;
 
 const FILEN: SpiceInt = 255;
 
 /// An error that occurred in SPICE.
-#[derive(Debug, Clone, Error)]
-#[error("{short_message}\n\n{explanation}\n\n{long_message}\n\nTraceback:\n{traceback}")]
+///
+/// The `{}` format prints every message and the traceback on separate paragraphs, whereas the
+/// alternate `{:#}` format prints a single line suitable for logs, e.g.
+/// `SPICE(EMPTYSTRING) in str2et_c: The input string is empty.`
+///
+/// When serialized the [code](Error::code) and [failed function](Error::failed_function) are
+/// included as fields.
+///
+/// Errors caused by a required kernel not being loaded include guidance on what to load, see
+/// [Error::missing_data()].
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[serde(into = "SerializedError", from = "SerializedError")]
 pub struct Error {
     pub short_message: String,
     pub explanation: String,
     pub long_message: String,
     pub traceback: String,
+    /// Output written to the [ErrorDevice::Filename] device, only populated by
+    /// [get_last_error_with_device_output()].
+    pub device_output: Option<String>,
+}
+
+/// The kind of kernel data that is missing, see [MissingData].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MissingDataKind {
+    /// No leapseconds kernel (LSK) is loaded, `SPICE(NOLEAPSECONDS)`.
+    Leapseconds,
+    /// No loaded SPK has data for the requested bodies and time, `SPICE(SPKINSUFFDATA)` or
+    /// `SPICE(NOLOADEDFILES)`.
+    Ephemeris,
+}
+
+impl MissingDataKind {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "NOLEAPSECONDS" => Some(MissingDataKind::Leapseconds),
+            "SPKINSUFFDATA" | "NOLOADEDFILES" => Some(MissingDataKind::Ephemeris),
+            _ => None,
+        }
+    }
+
+    /// What to load to fix the error.
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            MissingDataKind::Leapseconds => {
+                "Load a leapseconds kernel (LSK), e.g. naif0012.tls, with furnish() before \
+                converting between UTC and Ephemeris Time"
+            }
+            MissingDataKind::Ephemeris => {
+                "Load SPKs with furnish() that cover the target, the observer and any bodies \
+                connecting them at the requested time, the coverage can be checked with \
+                diagnostics::check_requirements()"
+            }
+        }
+    }
+}
+
+/// Details of an error caused by a required kernel not being loaded, see
+/// [Error::missing_data()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingData {
+    pub kind: MissingDataKind,
+    /// What to load to fix the error.
+    pub guidance: &'static str,
+    /// The kernels currently loaded with [furnish()](crate::data::furnish).
+    pub loaded_kernels: Vec<String>,
+}
+
+impl Error {
+    /// If the error was caused by a required kernel not being loaded, the kind of data that is
+    /// missing, guidance on what to load, and the kernels that are currently loaded.
+    pub fn missing_data(&self) -> Option<MissingData> {
+        let kind = MissingDataKind::from_code(self.code())?;
+        Some(MissingData {
+            kind,
+            guidance: kind.guidance(),
+            loaded_kernels: crate::data::loaded_kernels(),
+        })
+    }
+
+    /// The short message without the `SPICE(...)` wrapper, e.g. `EMPTYSTRING`.
+    pub fn code(&self) -> &str {
+        self.short_message
+            .strip_prefix("SPICE(")
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap_or(&self.short_message)
+    }
+
+    /// The innermost module in the traceback, i.e. the function that signalled the error.
+    pub fn failed_function(&self) -> Option<&str> {
+        self.traceback
+            .rsplit("-->")
+            .next()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedError {
+    short_message: String,
+    code: String,
+    explanation: String,
+    long_message: String,
+    traceback: String,
+    failed_function: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device_output: Option<String>,
+}
+
+impl From<Error> for SerializedError {
+    fn from(e: Error) -> Self {
+        Self {
+            code: e.code().to_string(),
+            failed_function: e.failed_function().map(String::from),
+            short_message: e.short_message,
+            explanation: e.explanation,
+            long_message: e.long_message,
+            traceback: e.traceback,
+            device_output: e.device_output,
+        }
+    }
+}
+
+impl From<SerializedError> for Error {
+    fn from(e: SerializedError) -> Self {
+        Self {
+            short_message: e.short_message,
+            explanation: e.explanation,
+            long_message: e.long_message,
+            traceback: e.traceback,
+            device_output: e.device_output,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            f.write_str(&self.short_message)?;
+            if let Some(function) = self.failed_function() {
+                write!(f, " in {function}")?;
+            }
+            let message = if self.long_message.is_empty() {
+                &self.explanation
+            } else {
+                &self.long_message
+            };
+            if !message.is_empty() {
+                write!(f, ": {message}")?;
+            }
+            if let Some(kind) = MissingDataKind::from_code(self.code()) {
+                write!(f, " ({})", kind.guidance())?;
+            }
+            Ok(())
+        } else {
+            write!(
+                f,
+                "{}\n\n{}\n\n{}\n\n",
+                self.short_message, self.explanation, self.long_message
+            )?;
+            if let Some(kind) = MissingDataKind::from_code(self.code()) {
+                write!(f, "{}\n\n", kind.guidance())?;
+            }
+            write!(f, "Traceback:\n{}", self.traceback)
+        }
+    }
 }
 
 /// See [Choosing the Error Response Action](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/error.html#Choosing%20the%20Error%20Response%20Action).
@@ -42,6 +206,9 @@ pub enum ErrorDevice {
 
 /// Tests, retrieves, and resets the last error if it is present. Otherwise returns Ok.
 ///
+/// To retrieve an error raised by a preceding SPICE call, both must be made while holding the
+/// SPICE lock, see [signal_error()].
+///
 /// For context see [CSPICE Error Handling](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/error.html#Testing%20the%20Error%20Status).
 #[inline]
 pub fn get_last_error() -> Result<(), Error> {
@@ -52,24 +219,21 @@ pub fn get_last_error() -> Result<(), Error> {
             }
 
             // Gather error info
-            let option = SpiceString::from("SHORT");
             let mut short_message = [0; SPICE_ERROR_SMSGLN as usize];
             getmsg_c(
-                option.as_mut_ptr(),
+                static_spice_str!("SHORT").as_mut_ptr(),
                 short_message.len() as SpiceInt,
                 short_message.as_mut_ptr(),
             );
-            let option = SpiceString::from("EXPLAIN");
             let mut explanation = [0; SPICE_ERROR_XMSGLN as usize];
             getmsg_c(
-                option.as_mut_ptr(),
+                static_spice_str!("EXPLAIN").as_mut_ptr(),
                 explanation.len() as SpiceInt,
                 explanation.as_mut_ptr(),
             );
-            let option = SpiceString::from("LONG");
             let mut long_message = [0; SPICE_ERROR_LMSGLN as usize];
             getmsg_c(
-                option.as_mut_ptr(),
+                static_spice_str!("LONG").as_mut_ptr(),
                 long_message.len() as SpiceInt,
                 long_message.as_mut_ptr(),
             );
@@ -84,18 +248,42 @@ pub fn get_last_error() -> Result<(), Error> {
                 explanation: SpiceStr::from_buffer(&explanation).to_string(),
                 long_message: SpiceStr::from_buffer(&long_message).to_string(),
                 traceback: SpiceStr::from_buffer(&traceback).to_string(),
+                device_output: None,
             })
         }
     })
 }
 
+/// Equivalent to [get_last_error()], but if the error output device is set to a file then its
+/// contents are attached to the error as [Error::device_output], and the file is truncated.
+///
+/// Any failure to read the file is ignored, leaving [Error::device_output] as `None`.
+pub fn get_last_error_with_device_output() -> Result<(), Error> {
+    with_spice_lock_or_panic(|| {
+        get_last_error().map_err(|mut e| {
+            if let Ok(ErrorDevice::Filename(path)) = get_error_output_device() {
+                e.device_output = take_file_contents(&path).ok();
+            }
+            e
+        })
+    })
+}
+
+fn take_file_contents(path: &str) -> std::io::Result<String> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+    file.set_len(0)?;
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
 /// Set the action when an error occurs in a SPICE function.
 ///
 /// See [erract_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/erract_c.html).
 pub fn set_error_action(action: ErrorAction) -> Result<(), Error> {
     let action = SpiceString::from(serde_plain::to_string(&action).unwrap());
     with_spice_lock_or_panic(|| {
-        unsafe { erract_c(SET.as_mut_ptr(), 0, action.as_mut_ptr()) };
+        unsafe { erract_c(SET.as_mut_ptr(), 0, action.as_mut_ptr()?) };
         get_last_error()
     })
 }
@@ -129,7 +317,7 @@ pub fn set_error_output_device(device: ErrorDevice) -> Result<(), Error> {
         ErrorDevice::Filename(filename) => SpiceString::from(filename),
     };
     with_spice_lock_or_panic(|| {
-        unsafe { errdev_c(SET.as_mut_ptr(), 0, device.as_mut_ptr()) };
+        unsafe { errdev_c(SET.as_mut_ptr(), 0, device.as_mut_ptr()?) };
         get_last_error()
     })
 }
@@ -157,6 +345,84 @@ pub fn get_error_output_device() -> Result<ErrorDevice, Error> {
     })
 }
 
+/// Set the long error message for the next error to be signalled with [signal_error()].
+///
+/// The message is global state, so the SPICE lock must be held until the error has been
+/// signalled, see [signal_error()]. A message containing a nul byte is truncated at the nul.
+///
+/// See [setmsg_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/setmsg_c.html).
+pub fn set_error_message<'m, M: Into<StringParam<'m>>>(message: M) {
+    with_spice_lock_or_panic(|| unsafe { setmsg_c(message.into().as_mut_ptr_truncated()) })
+}
+
+/// Signal an error through the SPICE error subsystem, with the given short message, e.g.
+/// `"SPICE(MYERROR)"`.
+///
+/// The error will be returned by the next call to [get_last_error()], which allows callback code
+/// invoked by SPICE to report errors back to the caller. A short message containing a nul byte is
+/// truncated at the nul.
+///
+/// # Thread safety
+///
+/// The error status is global, so the SPICE lock must be held from signalling the error until it
+/// has been retrieved with [get_last_error()]. Otherwise a SPICE call made by another thread in
+/// between could observe (and reset) the error, or fail because of it. Callbacks invoked by SPICE
+/// already run under the lock of the wrapper that invoked them, elsewhere use
+/// [with_spice_lock()](crate::with_spice_lock):
+///
+/// ```
+/// use cspice::error::{get_last_error, set_error_message, signal_error};
+///
+/// let error = cspice::with_spice_lock(|| {
+///     set_error_message("Something went wrong");
+///     signal_error("SPICE(MYERROR)");
+///     get_last_error().unwrap_err()
+/// });
+/// assert_eq!(error.short_message, "SPICE(MYERROR)");
+/// ```
+///
+/// See [sigerr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sigerr_c.html).
+pub fn signal_error<'s, S: Into<StringParam<'s>>>(short_message: S) {
+    with_spice_lock_or_panic(|| unsafe { sigerr_c(short_message.into().as_mut_ptr_truncated()) })
+}
+
+/// Add a module name to the SPICE traceback.
+///
+/// Every call must be matched by a call to [check_out()] with the same name, prefer using
+/// [trace()] which does this automatically. A name containing a nul byte is truncated at the nul.
+///
+/// See [chkin_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/chkin_c.html).
+pub fn check_in<'n, N: Into<StringParam<'n>>>(module: N) {
+    with_spice_lock_or_panic(|| unsafe { chkin_c(module.into().as_mut_ptr_truncated()) })
+}
+
+/// Remove a module name from the SPICE traceback.
+///
+/// See [chkout_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/chkout_c.html).
+pub fn check_out<'n, N: Into<StringParam<'n>>>(module: N) {
+    with_spice_lock_or_panic(|| unsafe { chkout_c(module.into().as_mut_ptr_truncated()) })
+}
+
+/// Guard returned by [trace()], removes the module name from the traceback when dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct TraceGuard(SpiceString);
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        check_out(&self.0);
+    }
+}
+
+/// Add a module name to the SPICE traceback until the returned guard is dropped.
+///
+/// Errors signalled while the guard is held will include the name in [Error::traceback].
+pub fn trace<S: AsRef<str>>(module: S) -> TraceGuard {
+    let module = SpiceString::from(module);
+    check_in(&module);
+    TraceGuard(module)
+}
+
 pub(crate) fn set_error_defaults() {
     set_error_action(ErrorAction::Return).unwrap();
     set_error_output_device(ErrorDevice::Null).unwrap();
@@ -192,4 +458,86 @@ mod tests {
         // Reset so we don't interfere with other tests
         set_error_defaults();
     }
+
+    #[test]
+    fn test_get_last_error_with_device_output() {
+        let path = std::env::temp_dir().join("cspice_rs_error_device_test.txt");
+        let _ = std::fs::remove_file(&path);
+        let device = ErrorDevice::Filename(path.to_string_lossy().into_owned());
+        set_error_output_device(device).unwrap();
+
+        signal_error("SPICE(TESTERROR)");
+        let e = get_last_error_with_device_output().unwrap_err();
+        let output = e.device_output.unwrap();
+        assert!(output.contains("SPICE(TESTERROR)"), "{output}");
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        // Reset so we don't interfere with other tests
+        set_error_defaults();
+    }
+
+    #[test]
+    fn test_signal_error() {
+        let e = with_spice_lock_or_panic(|| {
+            {
+                let _outer = trace("rust_outer");
+                let _inner = trace("rust_inner");
+                set_error_message("Something went wrong");
+                signal_error("SPICE(RUSTERROR)");
+            }
+            get_last_error().unwrap_err()
+        });
+        assert_eq!(e.short_message, "SPICE(RUSTERROR)");
+        assert_eq!(e.long_message, "Something went wrong");
+        assert!(
+            e.traceback.contains("rust_outer --> rust_inner"),
+            "{}",
+            e.traceback
+        );
+        get_last_error().unwrap();
+    }
+
+    #[test]
+    fn test_error_fields_and_formats() {
+        let e = with_spice_lock_or_panic(|| {
+            {
+                let _guard = trace("rust_module");
+                set_error_message("Something went wrong");
+                signal_error("SPICE(RUSTERROR)");
+            }
+            get_last_error().unwrap_err()
+        });
+        assert_eq!(e.code(), "RUSTERROR");
+        assert_eq!(e.failed_function(), Some("rust_module"));
+        assert_eq!(
+            format!("{e:#}"),
+            "SPICE(RUSTERROR) in rust_module: Something went wrong"
+        );
+        assert!(format!("{e}").contains("Traceback:\nrust_module"));
+
+        let json = serde_json::to_string(&e).unwrap();
+        assert!(json.contains("\"code\":\"RUSTERROR\""), "{json}");
+        assert!(!json.contains("device_output"), "{json}");
+        let parsed: Error = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.failed_function(), e.failed_function());
+        assert_eq!(parsed.long_message, e.long_message);
+    }
+
+    #[test]
+    fn test_missing_data() {
+        set_error_message("No leapseconds");
+        signal_error("SPICE(NOLEAPSECONDS)");
+        let e = get_last_error().unwrap_err();
+        let missing = e.missing_data().unwrap();
+        assert_eq!(missing.kind, MissingDataKind::Leapseconds);
+        assert_eq!(missing.loaded_kernels, crate::data::loaded_kernels());
+        assert!(format!("{e:#}").ends_with(&format!("({})", missing.guidance)));
+        assert!(format!("{e}").contains(missing.guidance));
+
+        signal_error("SPICE(SPKINSUFFDATA)");
+        let e = get_last_error().unwrap_err();
+        assert_eq!(e.missing_data().unwrap().kind, MissingDataKind::Ephemeris);
+        signal_error("SPICE(RUSTERROR)");
+        assert_eq!(get_last_error().unwrap_err().missing_data(), None);
+    }
 }
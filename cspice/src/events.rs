@@ -0,0 +1,158 @@
+//! Packaged Geometry Finder queries for common events.
+use crate::body::BodyRef;
+use crate::cell::Window;
+use crate::common::{AberrationCorrection, ReceptionCorrection};
+use crate::coordinates::Rectangular;
+use crate::error::get_last_error;
+use crate::gf::{estimate_output_size, OutputWindowError, MAX_OUTPUT_WINDOW_SIZE};
+use crate::string::{static_spice_str, StaticSpiceStr, StringParam};
+use crate::time::Et;
+use crate::units::Seconds;
+use crate::with_spice_lock_or_panic;
+use cspice_sys::{gfilum_c, SpiceDouble, SpiceInt};
+use std::f64::consts::FRAC_PI_2;
+
+/// The kind of a [TerminatorCrossing].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TerminatorEvent {
+    /// The Sun rises, the point becomes lit.
+    Sunrise,
+    /// The Sun sets, the point becomes dark.
+    Sunset,
+}
+
+/// A time at which a surface point crosses the day/night terminator, see
+/// [terminator_crossings()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerminatorCrossing {
+    pub et: Et,
+    pub event: TerminatorEvent,
+}
+
+/// Find the sunrise and sunset times at a point on the surface of a target body, given in the
+/// body-fixed frame `fixed_frame`, within the `confine` window.
+///
+/// These are the times the solar incidence angle, modelling the target as an ellipsoid, crosses
+/// 90 degrees. The observer is only used for aberration corrections, which must be reception
+/// corrections as `gfilum_c` doesn't support transmission. Sunrises and sunsets must be separated
+/// by more than `step_size`, e.g. a few hours for the Earth, or a day for the Moon.
+///
+/// The boundaries of the `confine` window are not crossings, even if the point is lit there.
+///
+/// See [gfilum_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gfilum_c.html).
+#[allow(clippy::too_many_arguments)]
+pub fn terminator_crossings<'t, 'f, 'o, T, F, O>(
+    target: T,
+    fixed_frame: F,
+    point: &Rectangular,
+    aberration_correction: ReceptionCorrection,
+    observer: O,
+    step_size: Seconds,
+    confine: &Window,
+) -> Result<Vec<TerminatorCrossing>, OutputWindowError>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target, fixed_frame, observer) = (target.into(), fixed_frame.into(), observer.into());
+    let size = estimate_output_size(confine, step_size, MAX_OUTPUT_WINDOW_SIZE)?;
+    let mut lit = Window::new_double(size);
+    with_spice_lock_or_panic(|| {
+        let mut point: [SpiceDouble; 3] = (*point).into();
+        unsafe {
+            gfilum_c(
+                static_spice_str!("ELLIPSOID").as_mut_ptr(),
+                static_spice_str!("INCIDENCE").as_mut_ptr(),
+                target.to_string_param().as_mut_ptr()?,
+                static_spice_str!("SUN").as_mut_ptr(),
+                fixed_frame.as_mut_ptr()?,
+                AberrationCorrection::from(aberration_correction).as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                point.as_mut_ptr(),
+                static_spice_str!("<").as_mut_ptr(),
+                FRAC_PI_2,
+                0.0,
+                step_size.0,
+                // Each day can split a confinement interval
+                (size / 2) as SpiceInt,
+                confine.as_const_cell(),
+                lit.as_mut_cell(),
+            )
+        };
+        get_last_error()
+    })
+    .map_err(|e| OutputWindowError::from_spice(e, size))?;
+
+    // The endpoints of the confinement intervals are copied exactly into the results
+    let boundaries = (0..confine.window_cardinality()? as usize)
+        .map(|i| confine.window_interval(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut crossings = Vec::new();
+    for i in 0..lit.window_cardinality()? as usize {
+        let (start, end) = lit.window_interval(i)?;
+        if !boundaries.iter().any(|(left, _)| *left == start) {
+            crossings.push(TerminatorCrossing {
+                et: Et(start),
+                event: TerminatorEvent::Sunrise,
+            });
+        }
+        if !boundaries.iter().any(|(_, right)| *right == end) {
+            crossings.push(TerminatorCrossing {
+                et: Et(end),
+                event: TerminatorEvent::Sunset,
+            });
+        }
+    }
+    Ok(crossings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{surface_lighting, ShapeMethod};
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    #[test]
+    fn test_terminator_crossings() {
+        load_test_data();
+        load_moon_constants();
+        let point = Rectangular {
+            x: 1737.4,
+            y: 0.0,
+            z: 0.0,
+        };
+        let confine = Window::from_intervals(&[(0.0, 60.0 * 86400.0)]).unwrap();
+        let crossings = terminator_crossings(
+            "MOON",
+            "IAU_MOON",
+            &point,
+            ReceptionCorrection::NONE,
+            "EARTH",
+            Seconds(86400.0),
+            &confine,
+        )
+        .unwrap();
+        // About two lunar days
+        assert!((3..=5).contains(&crossings.len()), "{crossings:?}");
+        for pair in crossings.windows(2) {
+            assert_ne!(pair[0].event, pair[1].event);
+            let days = (pair[1].et.0 - pair[0].et.0) / 86400.0;
+            assert!(days > 13.0 && days < 16.5, "{days}");
+        }
+        for crossing in &crossings {
+            let lighting = surface_lighting(
+                &ShapeMethod::ellipsoid(),
+                "MOON",
+                "SUN",
+                crossing.et,
+                "IAU_MOON",
+                AberrationCorrection::NONE,
+                "EARTH",
+                &point,
+            )
+            .unwrap();
+            assert!((lighting.angles.incidence.0 - FRAC_PI_2).abs() < 1e-4);
+        }
+    }
+}
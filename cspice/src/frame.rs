@@ -0,0 +1,645 @@
+//! Reference frame ID codes, and values tagged with the frame they are expressed in.
+//!
+//! Tagging positions and states with their frame using [Framed] allows code that consumes them
+//! to check the frame, catching bugs such as mixing inertial (e.g. `J2000`) and body-fixed
+//! vectors.
+//!
+//! When SPICE can't relate two frames it signals `SPICE(NOFRAMECONNECT)` or similar, use
+//! [explain_chain()] to find which link in the chain of frames is missing.
+//!
+//! See [Frames required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/frames.html).
+use crate::cell::Window;
+use crate::coordinates::Rectangular;
+use crate::daf;
+use crate::data::loaded_files;
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::kernel_pool;
+use crate::matrix::{Matrix3x3, StateTransformMatrix6x6};
+use crate::spk::State;
+use crate::string::{static_spice_str, SpiceString, StaticSpiceStr, StringParam};
+use crate::time::Et;
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    ckcov_c, ckfrot_c, frinfo_c, frmnam_c, namfrm_c, tkfram_c, SpiceBoolean, SpiceInt, SPICEFALSE,
+    SPICETRUE,
+};
+use std::fmt::{Display, Formatter};
+
+/// The maximum number of links followed by [explain_chain()], to guard against cycles.
+const MAX_CHAIN_LENGTH: usize = 32;
+
+/// The NAIF ID code of a reference frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FrameId(pub SpiceInt);
+
+impl FrameId {
+    /// The `J2000` inertial frame.
+    pub const J2000: FrameId = FrameId(1);
+
+    /// Look up the ID code of a frame by name, returns `None` if the frame is not recognised.
+    ///
+    /// See [namfrm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/namfrm_c.html).
+    pub fn from_name<'n, N: Into<StringParam<'n>>>(name: N) -> Result<Option<Self>, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut code: SpiceInt = 0;
+            unsafe { namfrm_c(name.into().as_mut_ptr()?, &mut code) };
+            get_last_error()?;
+            Ok((code != 0).then_some(FrameId(code)))
+        })
+    }
+
+    /// Look up the name of the frame, returns `None` if the frame is not recognised.
+    ///
+    /// See [frmnam_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/frmnam_c.html).
+    pub fn name(&self) -> Option<String> {
+        // Frame names are limited to 32 characters
+        let mut buffer = vec![0; 33];
+        with_spice_lock_or_panic(|| unsafe {
+            frmnam_c(self.0, buffer.len() as SpiceInt, buffer.as_mut_ptr())
+        });
+        let name = SpiceString::from_buffer(buffer).to_string();
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// Get the center, class and class ID of the frame, returns `None` if the frame is not
+    /// recognised.
+    ///
+    /// See [frinfo_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/frinfo_c.html).
+    pub fn info(&self) -> Result<Option<FrameInfo>, Error> {
+        with_spice_lock_or_panic(|| {
+            let (mut center, mut class, mut class_id) = (0, 0, 0);
+            let mut found: SpiceBoolean = 0;
+            unsafe { frinfo_c(self.0, &mut center, &mut class, &mut class_id, &mut found) };
+            get_last_error()?;
+            Ok((found == SPICETRUE as SpiceBoolean).then_some(FrameInfo {
+                center,
+                class: FrameClass::from_code(class),
+                class_id,
+            }))
+        })
+    }
+
+    /// The name of the frame, signalling `SPICE(UNKNOWNFRAME)` if it is not recognised.
+    pub(crate) fn checked_name(&self) -> Result<String, Error> {
+        self.name().map(Ok).unwrap_or_else(|| {
+            with_spice_lock_or_panic(|| {
+                set_error_message(format!("The frame ID code {} is not recognised", self.0));
+                signal_error("SPICE(UNKNOWNFRAME)");
+                get_last_error()?;
+                unreachable!("signalled error was not reported")
+            })
+        })
+    }
+}
+
+impl Display for FrameId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(&name),
+            None => write!(f, "frame {}", self.0),
+        }
+    }
+}
+
+/// The class of a frame, which determines where SPICE finds its orientation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameClass {
+    /// Built in inertial frames.
+    Inertial,
+    /// Body-fixed frames, oriented by text or binary PCKs.
+    Pck,
+    /// Frames oriented by C-kernels.
+    Ck,
+    /// Fixed offset frames defined in a frame kernel.
+    Tk,
+    /// Dynamic frames defined in a frame kernel.
+    Dynamic,
+    /// Switch frames defined in a frame kernel.
+    Switch,
+    Unknown(SpiceInt),
+}
+
+impl FrameClass {
+    /// Convert from a SPICE frame class code.
+    pub fn from_code(code: SpiceInt) -> Self {
+        match code {
+            1 => FrameClass::Inertial,
+            2 => FrameClass::Pck,
+            3 => FrameClass::Ck,
+            4 => FrameClass::Tk,
+            5 => FrameClass::Dynamic,
+            7 => FrameClass::Switch,
+            code => FrameClass::Unknown(code),
+        }
+    }
+}
+
+/// Information about a frame, see [FrameId::info()].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// The NAIF ID code of the center of the frame.
+    pub center: SpiceInt,
+    pub class: FrameClass,
+    /// The ID used to look up the orientation data for the frame, e.g. the body ID of a PCK frame
+    /// or the instrument ID of a CK frame.
+    pub class_id: SpiceInt,
+}
+
+/// Where the orientation of a frame relative to its parent comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkSource {
+    /// Built in to SPICE.
+    BuiltIn,
+    /// Kernel pool variables with the given prefix, loaded from a text kernel such as a frame
+    /// kernel or text PCK.
+    KernelPool(String),
+    /// Binary kernels (CKs or binary PCKs) with data covering the epoch.
+    Kernels(Vec<String>),
+}
+
+/// A link in a chain of frames, see [explain_chain()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameLink {
+    pub frame: FrameId,
+    /// `None` if the frame is not defined.
+    pub info: Option<FrameInfo>,
+    /// The frame that this frame is oriented relative to, `None` at the root of the chain
+    /// (`J2000`) or if the link is broken.
+    pub parent: Option<FrameId>,
+    pub source: Option<LinkSource>,
+    /// Why the parent could not be found, if the link is broken.
+    pub problem: Option<String>,
+}
+
+impl FrameLink {
+    /// Look up the parent of `frame` at `et`.
+    fn find(frame: FrameId, et: Et) -> Result<Self, Error> {
+        let mut link = FrameLink {
+            frame,
+            info: frame.info()?,
+            parent: None,
+            source: None,
+            problem: None,
+        };
+        let Some(info) = link.info else {
+            link.problem = Some("The frame is not defined".to_string());
+            return Ok(link);
+        };
+        let result = match info.class {
+            FrameClass::Inertial if frame == FrameId::J2000 => {
+                link.source = Some(LinkSource::BuiltIn);
+                return Ok(link);
+            }
+            FrameClass::Inertial => Ok((FrameId::J2000, LinkSource::BuiltIn)),
+            FrameClass::Pck => pck_parent(info.class_id, et),
+            FrameClass::Ck => ck_parent(info.class_id, et),
+            FrameClass::Tk => tk_parent(frame),
+            FrameClass::Dynamic => dynamic_parent(frame),
+            class => Err(format!("Frames of class {class:?} are not supported")),
+        };
+        match result {
+            Ok((parent, source)) => {
+                link.parent = Some(parent);
+                link.source = Some(source);
+            }
+            Err(problem) => link.problem = Some(problem),
+        }
+        Ok(link)
+    }
+}
+
+impl Display for FrameLink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.frame, self.frame.0)?;
+        if let Some(info) = &self.info {
+            write!(f, " [{:?}, class ID {}]", info.class, info.class_id)?;
+        }
+        if let Some(parent) = &self.parent {
+            write!(f, " -> {parent}")?;
+        }
+        match &self.source {
+            Some(LinkSource::BuiltIn) => write!(f, ", built in")?,
+            Some(LinkSource::KernelPool(prefix)) => {
+                write!(f, ", from kernel pool variables {prefix}*")?
+            }
+            Some(LinkSource::Kernels(files)) => write!(f, ", from {}", files.join(", "))?,
+            None => {}
+        }
+        if let Some(problem) = &self.problem {
+            write!(f, ", BROKEN: {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The chains of frames SPICE uses to relate two frames, see [explain_chain()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrameChain {
+    /// The links from the first frame towards `J2000`.
+    pub from: Vec<FrameLink>,
+    /// The links from the second frame towards `J2000`.
+    pub to: Vec<FrameLink>,
+    /// The first frame common to both chains, `None` if the frames can't be related.
+    pub common: Option<FrameId>,
+}
+
+impl FrameChain {
+    /// Whether SPICE should be able to transform between the two frames.
+    pub fn is_connected(&self) -> bool {
+        self.common.is_some()
+    }
+
+    /// The first broken link, if any.
+    pub fn broken_link(&self) -> Option<&FrameLink> {
+        self.from
+            .iter()
+            .chain(&self.to)
+            .find(|link| link.problem.is_some())
+    }
+}
+
+impl Display for FrameChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (label, links) in [("From", &self.from), ("To", &self.to)] {
+            writeln!(f, "{label}:")?;
+            for link in links {
+                writeln!(f, "  {link}")?;
+            }
+        }
+        match self.common {
+            Some(common) => write!(f, "Connected via {common}"),
+            None => write!(f, "Not connected"),
+        }
+    }
+}
+
+/// Explain how SPICE relates two frames at an epoch, for diagnosing errors such as
+/// `SPICE(NOFRAMECONNECT)`.
+///
+/// Each frame is followed through its parents towards `J2000`, reporting which kernels supply
+/// the orientation of each link. Binary kernels are identified exactly, for text kernels only the
+/// kernel pool variables are reported, as SPICE doesn't record which file they were loaded from.
+///
+/// This follows the same rules as SPICE in common cases, but doesn't model switch frames, or the
+/// frames used to evaluate dynamic frames. The [Display] implementation of the result gives a
+/// readable report.
+pub fn explain_chain(from: FrameId, to: FrameId, et: Et) -> Result<FrameChain, Error> {
+    with_spice_lock_or_panic(|| {
+        let from = follow_chain(from, et)?;
+        let to = follow_chain(to, et)?;
+        let reachable = |links: &[FrameLink]| {
+            let mut frames: Vec<_> = links.iter().map(|l| l.frame).collect();
+            frames.extend(links.last().and_then(|l| l.parent));
+            frames
+        };
+        let to_frames = reachable(&to);
+        let common = reachable(&from)
+            .into_iter()
+            .find(|frame| to_frames.contains(frame));
+        Ok(FrameChain { from, to, common })
+    })
+}
+
+fn follow_chain(frame: FrameId, et: Et) -> Result<Vec<FrameLink>, Error> {
+    let mut links: Vec<FrameLink> = Vec::new();
+    let mut next = Some(frame);
+    while let Some(frame) = next {
+        if links.len() == MAX_CHAIN_LENGTH || links.iter().any(|l| l.frame == frame) {
+            break;
+        }
+        let link = FrameLink::find(frame, et)?;
+        next = link.parent;
+        links.push(link);
+    }
+    Ok(links)
+}
+
+/// The parent of a frame and the source of its orientation, or a description of why it is
+/// missing.
+type Parent = Result<(FrameId, LinkSource), String>;
+
+fn describe(e: Error) -> String {
+    format!("{e:#}")
+}
+
+/// Binary PCKs take priority over text PCK constants.
+fn pck_parent(body: SpiceInt, et: Et) -> Parent {
+    let mut parent = None;
+    let mut files = Vec::new();
+    for (file, handle) in loaded_files("PCK").map_err(describe)? {
+        let mut covered = false;
+        for segment in daf::segments(handle).map_err(describe)? {
+            let (dc, ic) = (&segment.doubles, &segment.integers);
+            // Later segments and files take priority
+            if ic[0] == body && dc[0] <= et.0 && et.0 <= dc[1] {
+                parent = Some(FrameId(ic[1]));
+                covered = true;
+            }
+        }
+        if covered {
+            files.push(file);
+        }
+    }
+    if let Some(parent) = parent {
+        return Ok((parent, LinkSource::Kernels(files)));
+    }
+    let prefix = format!("BODY{body}_");
+    if kernel_pool::get_integer(format!("{prefix}PM"))
+        .map_err(describe)?
+        .is_none()
+    {
+        return Err(format!(
+            "No binary PCK covers the epoch and the text PCK constants {prefix}PM are not loaded"
+        ));
+    }
+    let parent = kernel_pool::get_integer(format!("{prefix}CONSTANTS_REF_FRAME"))
+        .map_err(describe)?
+        .map(FrameId)
+        .unwrap_or(FrameId::J2000);
+    Ok((parent, LinkSource::KernelPool(prefix)))
+}
+
+fn ck_parent(instrument: SpiceInt, et: Et) -> Parent {
+    let mut rotation = [[0.0; 3]; 3];
+    let (mut parent, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+    unsafe {
+        ckfrot_c(
+            instrument,
+            et.0,
+            rotation.as_mut_ptr(),
+            &mut parent,
+            &mut found,
+        )
+    };
+    get_last_error().map_err(describe)?;
+    if found != SPICETRUE as SpiceBoolean {
+        return Err(format!(
+            "No loaded CK has pointing for instrument {instrument} at the epoch"
+        ));
+    }
+    let mut files = Vec::new();
+    for (file, _) in loaded_files("CK").map_err(describe)? {
+        let mut cover = Window::new_double(2000);
+        unsafe {
+            ckcov_c(
+                SpiceString::from(file.as_str())
+                    .as_mut_ptr()
+                    .map_err(describe)?,
+                instrument,
+                SPICEFALSE as SpiceBoolean,
+                static_spice_str!("INTERVAL").as_mut_ptr(),
+                0.0,
+                static_spice_str!("TDB").as_mut_ptr(),
+                cover.as_mut_cell(),
+            )
+        };
+        get_last_error().map_err(describe)?;
+        if cover.window_contains_element(et.0).map_err(describe)? {
+            files.push(file);
+        }
+    }
+    Ok((FrameId(parent), LinkSource::Kernels(files)))
+}
+
+fn tk_parent(frame: FrameId) -> Parent {
+    let mut rotation = [[0.0; 3]; 3];
+    let (mut parent, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+    unsafe { tkfram_c(frame.0, rotation.as_mut_ptr(), &mut parent, &mut found) };
+    get_last_error().map_err(describe)?;
+    if found != SPICETRUE as SpiceBoolean {
+        return Err("The TKFRAME variables defining the frame are not loaded".to_string());
+    }
+    Ok((
+        FrameId(parent),
+        LinkSource::KernelPool(format!("TKFRAME_{}_", frame.0)),
+    ))
+}
+
+fn dynamic_parent(frame: FrameId) -> Parent {
+    // The variables may be named by frame ID code or by name
+    let mut names = vec![frame.0.to_string()];
+    names.extend(frame.name());
+    for name in names {
+        let prefix = format!("FRAME_{name}_");
+        let relative = kernel_pool::get_string(format!("{prefix}RELATIVE")).map_err(describe)?;
+        if let Some(relative) = relative {
+            return match FrameId::from_name(relative.as_str()).map_err(describe)? {
+                Some(parent) => Ok((parent, LinkSource::KernelPool(prefix))),
+                None => Err(format!("The relative frame {relative} is not defined")),
+            };
+        }
+    }
+    Err("The FRAME_*_RELATIVE variable defining the frame is not loaded".to_string())
+}
+
+/// Error returned when a [Framed] value is not in the expected frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Expected a value in {expected} but it is in {actual}")]
+pub struct FrameMismatch {
+    pub expected: FrameId,
+    pub actual: FrameId,
+}
+
+/// A value, such as a [Rectangular] position or a [State], tagged with the frame it is
+/// expressed in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Framed<T> {
+    pub value: T,
+    pub frame: FrameId,
+}
+
+impl<T> Framed<T> {
+    pub fn new(value: T, frame: FrameId) -> Self {
+        Self { value, frame }
+    }
+
+    /// Get the value, checking that it is in the `expected` frame.
+    pub fn in_frame(&self, expected: FrameId) -> Result<&T, FrameMismatch> {
+        if self.frame == expected {
+            Ok(&self.value)
+        } else {
+            Err(FrameMismatch {
+                expected,
+                actual: self.frame,
+            })
+        }
+    }
+
+    /// Take the value, discarding the frame.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl Framed<Rectangular> {
+    /// Transform the position into another frame at a specified epoch.
+    ///
+    /// See [Matrix3x3::from_frames].
+    pub fn to_frame(&self, frame: FrameId, et: Et) -> Result<Self, Error> {
+        let matrix = Matrix3x3::from_frames(self.frame.checked_name()?, frame.checked_name()?, et)?;
+        let value = matrix.mul_vec(&Vector3D::from(self.value));
+        Ok(Framed::new(value.into(), frame))
+    }
+}
+
+impl Framed<State> {
+    /// Transform the state into another frame at a specified epoch.
+    ///
+    /// See [StateTransformMatrix6x6::from_frames].
+    pub fn to_frame(&self, frame: FrameId, et: Et) -> Result<Self, Error> {
+        let matrix = StateTransformMatrix6x6::from_frames(
+            self.frame.checked_name()?,
+            frame.checked_name()?,
+            et,
+        )?;
+        Ok(Framed::new(self.value.transform(&matrix), frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::AberrationCorrection;
+    use crate::data::furnish;
+    use crate::spk;
+    use crate::tests::{load_moon_constants, load_test_data};
+    use std::sync::Once;
+
+    const TK_FRAME: FrameId = FrameId(-999001);
+    const CK_FRAME: FrameId = FrameId(-999002);
+
+    /// A TK frame relative to `IAU_MOON`, and a CK frame with no pointing data
+    fn load_test_frames() {
+        static LOAD: Once = Once::new();
+        LOAD.call_once(|| {
+            let path = std::env::temp_dir().join("cspice_rs_frame_test.tf");
+            std::fs::write(
+                &path,
+                "\\begindata
+FRAME_CSPICE_RS_TK      = -999001
+FRAME_-999001_NAME      = 'CSPICE_RS_TK'
+FRAME_-999001_CLASS     = 4
+FRAME_-999001_CLASS_ID  = -999001
+FRAME_-999001_CENTER    = 301
+TKFRAME_-999001_RELATIVE = 'IAU_MOON'
+TKFRAME_-999001_SPEC    = 'ANGLES'
+TKFRAME_-999001_UNITS   = 'DEGREES'
+TKFRAME_-999001_AXES    = ( 3, 2, 1 )
+TKFRAME_-999001_ANGLES  = ( 10.0, 0.0, 0.0 )
+FRAME_CSPICE_RS_CK      = -999002
+FRAME_-999002_NAME      = 'CSPICE_RS_CK'
+FRAME_-999002_CLASS     = 3
+FRAME_-999002_CLASS_ID  = -999002
+FRAME_-999002_CENTER    = -999
+\\begintext
+",
+            )
+            .unwrap();
+            furnish(path.to_string_lossy()).unwrap();
+        });
+        load_moon_constants();
+    }
+
+    #[test]
+    fn test_frame_id() {
+        assert_eq!(FrameId::from_name("J2000").unwrap(), Some(FrameId::J2000));
+        assert_eq!(FrameId::from_name("NOT A FRAME").unwrap(), None);
+        assert_eq!(FrameId::J2000.name().as_deref(), Some("J2000"));
+        assert_eq!(FrameId(-123456).name(), None);
+        assert_eq!(FrameId(-123456).to_string(), "frame -123456");
+    }
+
+    #[test]
+    fn test_framed() {
+        load_test_data();
+        let ecliptic = FrameId::from_name("ECLIPJ2000").unwrap().unwrap();
+        let (state, _) = spk::framed_state(
+            "MOON",
+            Et(0.0),
+            FrameId::J2000,
+            AberrationCorrection::NONE,
+            "EARTH",
+        )
+        .unwrap();
+        assert!(state.in_frame(FrameId::J2000).is_ok());
+        assert_eq!(
+            state.in_frame(ecliptic).unwrap_err(),
+            FrameMismatch {
+                expected: ecliptic,
+                actual: FrameId::J2000
+            }
+        );
+
+        let (expected, _) = spk::easier_reader(
+            "MOON",
+            Et(0.0),
+            "ECLIPJ2000",
+            AberrationCorrection::NONE,
+            "EARTH",
+        )
+        .unwrap();
+        let converted = state.to_frame(ecliptic, Et(0.0)).unwrap();
+        assert_eq!(converted.frame, ecliptic);
+        let position = Framed::new(state.value.position, state.frame)
+            .to_frame(ecliptic, Et(0.0))
+            .unwrap();
+        for (a, b) in [
+            (converted.value.position.z, expected.position.z),
+            (converted.value.velocity[2], expected.velocity[2]),
+            (position.value.z, expected.position.z),
+        ] {
+            assert!((a - b).abs() < 1e-6, "{a} != {b}");
+        }
+
+        let err = state.to_frame(FrameId(-123456), Et(0.0)).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(UNKNOWNFRAME)");
+    }
+
+    #[test]
+    fn test_frame_info() {
+        load_test_frames();
+        let info = FrameId::J2000.info().unwrap().unwrap();
+        assert_eq!(info.class, FrameClass::Inertial);
+        assert_eq!(info.center, 0);
+        let info = TK_FRAME.info().unwrap().unwrap();
+        assert_eq!(info.class, FrameClass::Tk);
+        assert_eq!(info.center, 301);
+        assert_eq!(FrameId(-123456).info().unwrap(), None);
+    }
+
+    #[test]
+    fn test_explain_chain() {
+        load_test_frames();
+        let iau_moon = FrameId::from_name("IAU_MOON").unwrap().unwrap();
+        let ecliptic = FrameId::from_name("ECLIPJ2000").unwrap().unwrap();
+
+        let chain = explain_chain(TK_FRAME, ecliptic, Et(0.0)).unwrap();
+        assert!(chain.is_connected(), "{chain}");
+        assert_eq!(chain.common, Some(FrameId::J2000));
+        assert_eq!(chain.broken_link(), None);
+        let frames: Vec<_> = chain.from.iter().map(|l| l.frame).collect();
+        assert_eq!(frames, vec![TK_FRAME, iau_moon, FrameId::J2000]);
+        assert_eq!(
+            chain.from[0].source,
+            Some(LinkSource::KernelPool("TKFRAME_-999001_".to_string()))
+        );
+        assert_eq!(
+            chain.from[1].source,
+            Some(LinkSource::KernelPool("BODY301_".to_string()))
+        );
+        assert_eq!(chain.to[0].parent, Some(FrameId::J2000));
+        assert_eq!(chain.to[0].source, Some(LinkSource::BuiltIn));
+
+        let chain = explain_chain(CK_FRAME, FrameId::J2000, Et(0.0)).unwrap();
+        assert!(!chain.is_connected());
+        assert_eq!(chain.broken_link().unwrap().frame, CK_FRAME);
+        assert!(chain.to_string().contains("BROKEN"), "{chain}");
+
+        let chain = explain_chain(FrameId(-123456), FrameId::J2000, Et(0.0)).unwrap();
+        assert_eq!(
+            chain.broken_link().unwrap().problem.as_deref(),
+            Some("The frame is not defined")
+        );
+    }
+}
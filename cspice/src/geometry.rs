@@ -0,0 +1,1411 @@
+//! Geometry functions evaluated at a single epoch.
+use crate::body::BodyRef;
+use crate::common::AberrationCorrection;
+use crate::coordinates::Rectangular;
+use crate::dsk::{SurfaceId, SurfaceList};
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::spk;
+use crate::spk::State;
+use crate::string::{static_spice_str, SpiceString, StaticSpiceStr, StringParam};
+use crate::time::Et;
+use crate::units::{Kilometers, Radians};
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    cidfrm_c, dskxsi_c, illumf_c, ilumin_c, latsrf_c, occult_c, phaseq_c, sincpt_c, srfrec_c,
+    subpnt_c, subslr_c, surfpt_c, surfpv_c, SpiceBoolean, SpiceChar, SpiceDLADescr, SpiceDSKDescr,
+    SpiceDouble, SpiceInt, SPICEFALSE, SPICETRUE, SPICE_DSKXSI_DCSIZE, SPICE_DSKXSI_ICSIZE,
+};
+use std::mem::MaybeUninit;
+
+/// How a sub-point is defined, see [ShapeMethod::sub_point()].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SubPointMethod {
+    /// The nearest point on the surface to the observer (or the Sun). For DSK shapes this is the
+    /// nadir point, where the surface is intersected by the ray to the center of the ellipsoid.
+    #[default]
+    NearPoint,
+    /// The intersection of the surface with the ray from the observer to the target's center.
+    Intercept,
+}
+
+/// The shape model of a target body used by geometry functions, which renders the method string
+/// passed to SPICE.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShapeMethod {
+    shape: Shape,
+    sub_point: SubPointMethod,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Shape {
+    Point,
+    Ellipsoid,
+    Dsk(SurfaceList),
+}
+
+impl ShapeMethod {
+    fn new(shape: Shape) -> Self {
+        Self {
+            shape,
+            sub_point: SubPointMethod::default(),
+        }
+    }
+
+    /// Model the target as a point. This is only accepted by functions that don't need a surface,
+    /// such as [occultation_state()].
+    pub fn point() -> Self {
+        Self::new(Shape::Point)
+    }
+
+    /// Model the target as a triaxial ellipsoid using the radii from the kernel pool.
+    pub fn ellipsoid() -> Self {
+        Self::new(Shape::Ellipsoid)
+    }
+
+    /// Model the target using all loaded DSK segments.
+    pub fn dsk() -> Self {
+        Self::dsk_surfaces([])
+    }
+
+    /// Model the target using the loaded DSK segments for the given surfaces, all surfaces are
+    /// used if none are given.
+    pub fn dsk_surfaces<I: IntoIterator<Item = SurfaceId>>(surfaces: I) -> Self {
+        Self::new(Shape::Dsk(SurfaceList::new(surfaces)))
+    }
+
+    /// Set how sub-points are defined by [sub_observer_point()] and [sub_solar_point()], this is
+    /// ignored by other functions. Defaults to [SubPointMethod::NearPoint].
+    pub fn sub_point(mut self, sub_point: SubPointMethod) -> Self {
+        self.sub_point = sub_point;
+        self
+    }
+
+    /// The method string for functions that only need the shape, e.g. `"ELLIPSOID"`.
+    pub fn shape_method(&self) -> String {
+        match &self.shape {
+            Shape::Point => "POINT".to_string(),
+            Shape::Ellipsoid => "ELLIPSOID".to_string(),
+            Shape::Dsk(surfaces) => surfaces.method().to_string(),
+        }
+    }
+
+    /// The method string for sub-point functions, e.g. `"NEAR POINT/ELLIPSOID"`.
+    pub fn sub_point_method(&self) -> String {
+        let prefix = match (self.sub_point, &self.shape) {
+            (SubPointMethod::NearPoint, Shape::Dsk(_)) => "NADIR",
+            (SubPointMethod::NearPoint, _) => "NEAR POINT",
+            (SubPointMethod::Intercept, _) => "INTERCEPT",
+        };
+        format!("{prefix}/{}", self.shape_method())
+    }
+}
+
+/// Which of the two targets passed to [occultation_state()] is occulted (further from the
+/// observer).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Occulted {
+    Target1,
+    Target2,
+}
+
+/// The occultation condition of two targets as seen by an observer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OccultationState {
+    NotOcculted,
+    /// The occulting target partially covers the occulted target.
+    Partial(Occulted),
+    /// The occulting target is entirely within the disk of the occulted target.
+    Annular(Occulted),
+    /// The occulted target is entirely hidden by the occulting target.
+    Total(Occulted),
+}
+
+impl OccultationState {
+    /// Convert from an `occult_c` code, returns `None` for an invalid code.
+    pub fn from_code(code: SpiceInt) -> Option<Self> {
+        let occulted = if code < 0 {
+            Occulted::Target1
+        } else {
+            Occulted::Target2
+        };
+        Some(match code.abs() {
+            0 => OccultationState::NotOcculted,
+            1 => OccultationState::Partial(occulted),
+            2 => OccultationState::Annular(occulted),
+            3 => OccultationState::Total(occulted),
+            _ => return None,
+        })
+    }
+
+    /// The equivalent `occult_c` code.
+    pub fn code(&self) -> SpiceInt {
+        let (magnitude, occulted) = match self {
+            OccultationState::NotOcculted => return 0,
+            OccultationState::Partial(o) => (1, o),
+            OccultationState::Annular(o) => (2, o),
+            OccultationState::Total(o) => (3, o),
+        };
+        match occulted {
+            Occulted::Target1 => -magnitude,
+            Occulted::Target2 => magnitude,
+        }
+    }
+}
+
+/// Determine the occultation condition of one target relative to another, as seen by an
+/// observer at a given time.
+///
+/// Frames must be the body-fixed frame of the corresponding target, and are ignored for
+/// [ShapeMethod::point()] (pass `"NULL"`). At least one target must not be a point.
+///
+/// See [occult_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/occult_c.html)
+#[allow(clippy::too_many_arguments)]
+pub fn occultation_state<'t1, 'f1, 't2, 'f2, 'o, T1, F1, T2, F2, O>(
+    target1: T1,
+    shape1: &ShapeMethod,
+    frame1: F1,
+    target2: T2,
+    shape2: &ShapeMethod,
+    frame2: F2,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    et: Et,
+) -> Result<OccultationState, Error>
+where
+    T1: Into<BodyRef<'t1>>,
+    F1: Into<StringParam<'f1>>,
+    T2: Into<BodyRef<'t2>>,
+    F2: Into<StringParam<'f2>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target1, target2, observer) = (target1.into(), target2.into(), observer.into());
+    with_spice_lock_or_panic(|| {
+        let mut code: SpiceInt = 0;
+        unsafe {
+            occult_c(
+                target1.to_string_param().as_mut_ptr()?,
+                SpiceString::from(shape1.shape_method()).as_mut_ptr()?,
+                frame1.into().as_mut_ptr()?,
+                target2.to_string_param().as_mut_ptr()?,
+                SpiceString::from(shape2.shape_method()).as_mut_ptr()?,
+                frame2.into().as_mut_ptr()?,
+                aberration_correction.as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                et.0,
+                &mut code,
+            )
+        };
+        get_last_error()?;
+        Ok(OccultationState::from_code(code).expect("occult_c returned an invalid code"))
+    })
+}
+
+/// A summary of the geometry of a target body as seen by an observer, see
+/// [observation_geometry()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObservationGeometry {
+    /// The body-fixed frame of the target, in which the surface points are expressed.
+    pub frame: String,
+    /// The distance from the observer to the target center.
+    pub range: Kilometers,
+    /// The angle between the target-to-Sun and target-to-observer vectors.
+    pub phase_angle: Radians,
+    /// The angle between the observer-to-Sun and observer-to-target vectors.
+    pub solar_elongation: Radians,
+    /// The nearest point on the target's reference ellipsoid to the observer.
+    pub sub_observer_point: Rectangular,
+    /// The nearest point on the target's reference ellipsoid to the Sun.
+    pub sub_solar_point: Rectangular,
+    /// The fraction of the target's disk that appears illuminated, `(1 + cos(phase)) / 2`.
+    pub illuminated_fraction: SpiceDouble,
+}
+
+/// Compute a summary of the observation geometry of a target body at a given time.
+///
+/// Positions and surface points are corrected for light time and stellar aberration
+/// ([AberrationCorrection::LT_S]), the phase angle is corrected for light time only since
+/// `phaseq_c` doesn't support stellar aberration. The target is modelled as an ellipsoid, so
+/// its radii and body-fixed frame must be available from loaded kernels.
+///
+/// See [spkpos_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkpos_c.html),
+/// [phaseq_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/phaseq_c.html),
+/// [subpnt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/subpnt_c.html) and
+/// [subslr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/subslr_c.html).
+pub fn observation_geometry<'t, 'o, T, O>(
+    target: T,
+    observer: O,
+    et: Et,
+) -> Result<ObservationGeometry, Error>
+where
+    T: Into<BodyRef<'t>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target, observer) = (target.into(), observer.into());
+    let frame = body_fixed_frame(target.to_id()?)?;
+    let (target, observer) = (target.to_string_param(), observer.to_string_param());
+    with_spice_lock_or_panic(|| {
+        let sun = static_spice_str!("SUN");
+        let abcorr = AberrationCorrection::LT_S;
+
+        let (to_target, _) = spk::position(&*target, et, "J2000", abcorr, &*observer)?;
+        let (to_sun, _) = spk::position("SUN", et, "J2000", abcorr, &*observer)?;
+        let to_target = Vector3D::from(to_target);
+
+        let phase_angle = unsafe {
+            phaseq_c(
+                et.0,
+                target.as_mut_ptr()?,
+                sun.as_mut_ptr(),
+                observer.as_mut_ptr()?,
+                AberrationCorrection::LT.as_spice_char(),
+            )
+        };
+        get_last_error()?;
+
+        let method = static_spice_str!("NEAR POINT/ELLIPSOID");
+        let mut sub_observer_point = [0.0; 3];
+        let mut sub_solar_point = [0.0; 3];
+        let (mut epoch, mut surface_vector) = (0.0, [0.0; 3]);
+        unsafe {
+            subpnt_c(
+                method.as_mut_ptr(),
+                target.as_mut_ptr()?,
+                et.0,
+                frame.as_mut_ptr()?,
+                abcorr.as_spice_char(),
+                observer.as_mut_ptr()?,
+                sub_observer_point.as_mut_ptr(),
+                &mut epoch,
+                surface_vector.as_mut_ptr(),
+            );
+            subslr_c(
+                method.as_mut_ptr(),
+                target.as_mut_ptr()?,
+                et.0,
+                frame.as_mut_ptr()?,
+                abcorr.as_spice_char(),
+                observer.as_mut_ptr()?,
+                sub_solar_point.as_mut_ptr(),
+                &mut epoch,
+                surface_vector.as_mut_ptr(),
+            );
+        }
+        get_last_error()?;
+
+        Ok(ObservationGeometry {
+            frame: frame.as_str().into_owned(),
+            range: Kilometers(to_target.iter().map(|x| x * x).sum::<SpiceDouble>().sqrt()),
+            phase_angle: Radians(phase_angle),
+            solar_elongation: Radians(to_target.separation_angle(&to_sun.into())),
+            sub_observer_point: sub_observer_point.into(),
+            sub_solar_point: sub_solar_point.into(),
+            illuminated_fraction: (1.0 + phase_angle.cos()) / 2.0,
+        })
+    })
+}
+
+/// A point on the surface of a target body as seen by an observer, such as the intersection of a
+/// ray (see [surface_intercept()]) or a sub-point (see [sub_observer_point()]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfacePoint {
+    /// The surface point in the body-fixed frame of the target.
+    pub point: Rectangular,
+    /// The epoch at the target, i.e. `et` less the light time if corrections are used.
+    pub target_epoch: Et,
+    /// The vector from the observer to the intercept point, in the body-fixed frame.
+    pub surface_vector: Vector3D,
+}
+
+/// Find the intersection of a ray from an observer with the surface of a target body, returns
+/// `None` if the ray misses the target.
+///
+/// The shape of the target is given by `method`, and `fixed_frame` is its body-fixed frame. The
+/// direction of the ray is given in `direction_frame`.
+///
+/// See [sincpt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sincpt_c.html).
+#[allow(clippy::too_many_arguments)]
+pub fn surface_intercept<'t, 'f, 'o, 'd, T, F, O, D>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    direction_frame: D,
+    direction: &Vector3D,
+) -> Result<Option<SurfacePoint>, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+    D: Into<StringParam<'d>>,
+{
+    let (target, observer) = (target.into(), observer.into());
+    with_spice_lock_or_panic(|| {
+        let mut point = [0.0; 3];
+        let mut target_epoch = 0.0;
+        let mut surface_vector = Vector3D::default();
+        let mut found: SpiceBoolean = 0;
+        unsafe {
+            sincpt_c(
+                SpiceString::from(method.shape_method()).as_mut_ptr()?,
+                target.to_string_param().as_mut_ptr()?,
+                et.0,
+                fixed_frame.into().as_mut_ptr()?,
+                aberration_correction.as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                direction_frame.into().as_mut_ptr()?,
+                direction.as_ptr() as *mut SpiceDouble,
+                point.as_mut_ptr(),
+                &mut target_epoch,
+                surface_vector.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok(
+            (found == SPICETRUE as SpiceBoolean).then_some(SurfacePoint {
+                point: point.into(),
+                target_epoch: Et(target_epoch),
+                surface_vector,
+            }),
+        )
+    })
+}
+
+/// Find the sub-observer point on a target body, as defined by the [SubPointMethod] of
+/// `method`.
+///
+/// See [subpnt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/subpnt_c.html).
+pub fn sub_observer_point<'t, 'f, 'o, T, F, O>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+) -> Result<SurfacePoint, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    sub_point(
+        subpnt_c,
+        method,
+        target.into(),
+        et,
+        fixed_frame.into(),
+        aberration_correction,
+        observer.into(),
+    )
+}
+
+/// Find the sub-solar point on a target body as seen by an observer, as defined by the
+/// [SubPointMethod] of `method`.
+///
+/// See [subslr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/subslr_c.html).
+pub fn sub_solar_point<'t, 'f, 'o, T, F, O>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+) -> Result<SurfacePoint, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    sub_point(
+        subslr_c,
+        method,
+        target.into(),
+        et,
+        fixed_frame.into(),
+        aberration_correction,
+        observer.into(),
+    )
+}
+
+type SubPointFn = unsafe extern "C" fn(
+    *mut SpiceChar,
+    *mut SpiceChar,
+    SpiceDouble,
+    *mut SpiceChar,
+    *mut SpiceChar,
+    *mut SpiceChar,
+    *mut SpiceDouble,
+    *mut SpiceDouble,
+    *mut SpiceDouble,
+);
+
+fn sub_point(
+    function: SubPointFn,
+    method: &ShapeMethod,
+    target: BodyRef,
+    et: Et,
+    fixed_frame: StringParam,
+    aberration_correction: AberrationCorrection,
+    observer: BodyRef,
+) -> Result<SurfacePoint, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut point = [0.0; 3];
+        let mut target_epoch = 0.0;
+        let mut surface_vector = Vector3D::default();
+        unsafe {
+            function(
+                SpiceString::from(method.sub_point_method()).as_mut_ptr()?,
+                target.to_string_param().as_mut_ptr()?,
+                et.0,
+                fixed_frame.as_mut_ptr()?,
+                aberration_correction.as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                point.as_mut_ptr(),
+                &mut target_epoch,
+                surface_vector.as_mut_ptr(),
+            )
+        };
+        get_last_error()?;
+        Ok(SurfacePoint {
+            point: point.into(),
+            target_epoch: Et(target_epoch),
+            surface_vector,
+        })
+    })
+}
+
+/// The illumination angles at a surface point, see [illumination_angles()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct IlluminationAngles {
+    /// The epoch at the target, i.e. `et` less the light time if corrections are used.
+    pub target_epoch: Et,
+    /// The vector from the observer to the surface point, in the body-fixed frame.
+    pub surface_vector: Vector3D,
+    /// The angle between the vectors from the surface point to the Sun and to the observer.
+    pub phase: Radians,
+    /// The angle between the surface normal and the vector from the surface point to the Sun.
+    pub incidence: Radians,
+    /// The angle between the surface normal and the vector from the surface point to the
+    /// observer.
+    pub emission: Radians,
+}
+
+/// Find the illumination angles (phase, incidence and emission) at a point on the surface of a
+/// target body, given in the body-fixed frame `fixed_frame`.
+///
+/// See [ilumin_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ilumin_c.html).
+#[allow(clippy::too_many_arguments)]
+pub fn illumination_angles<'t, 'f, 'o, T, F, O>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    point: &Rectangular,
+) -> Result<IlluminationAngles, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target, observer) = (target.into(), observer.into());
+    with_spice_lock_or_panic(|| {
+        let mut point: [SpiceDouble; 3] = (*point).into();
+        let mut target_epoch = 0.0;
+        let mut surface_vector = Vector3D::default();
+        let (mut phase, mut incidence, mut emission) = (0.0, 0.0, 0.0);
+        unsafe {
+            ilumin_c(
+                SpiceString::from(method.shape_method()).as_mut_ptr()?,
+                target.to_string_param().as_mut_ptr()?,
+                et.0,
+                fixed_frame.into().as_mut_ptr()?,
+                aberration_correction.as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                point.as_mut_ptr(),
+                &mut target_epoch,
+                surface_vector.as_mut_ptr(),
+                &mut phase,
+                &mut incidence,
+                &mut emission,
+            )
+        };
+        get_last_error()?;
+        Ok(IlluminationAngles {
+            target_epoch: Et(target_epoch),
+            surface_vector,
+            phase: Radians(phase),
+            incidence: Radians(incidence),
+            emission: Radians(emission),
+        })
+    })
+}
+
+/// The illumination angles at a surface point and whether it is visible and lit, see
+/// [surface_lighting()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceLighting {
+    pub angles: IlluminationAngles,
+    /// Whether the point is on the side of the target facing the observer, i.e. the emission
+    /// angle is less than 90 degrees.
+    pub visible: bool,
+    /// Whether the point is on the side of the target facing the illumination source, i.e. the
+    /// incidence angle is less than 90 degrees.
+    pub lit: bool,
+}
+
+/// Find the illumination angles at a point on the surface of a target body, given in the
+/// body-fixed frame `fixed_frame`, using an arbitrary `illumination_source` instead of the Sun,
+/// along with whether the point is visible from the observer and lit by the source.
+///
+/// Visibility and lighting only consider the emission and incidence angles, so the point may still
+/// be blocked by other parts of the target or other bodies.
+///
+/// See [illumf_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/illumf_c.html).
+#[allow(clippy::too_many_arguments)]
+pub fn surface_lighting<'t, 's, 'f, 'o, T, S, F, O>(
+    method: &ShapeMethod,
+    target: T,
+    illumination_source: S,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    point: &Rectangular,
+) -> Result<SurfaceLighting, Error>
+where
+    T: Into<BodyRef<'t>>,
+    S: Into<BodyRef<'s>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target, source, observer) = (target.into(), illumination_source.into(), observer.into());
+    with_spice_lock_or_panic(|| {
+        let mut point: [SpiceDouble; 3] = (*point).into();
+        let mut target_epoch = 0.0;
+        let mut surface_vector = Vector3D::default();
+        let (mut phase, mut incidence, mut emission) = (0.0, 0.0, 0.0);
+        let (mut visible, mut lit): (SpiceBoolean, SpiceBoolean) = (0, 0);
+        unsafe {
+            illumf_c(
+                SpiceString::from(method.shape_method()).as_mut_ptr()?,
+                target.to_string_param().as_mut_ptr()?,
+                source.to_string_param().as_mut_ptr()?,
+                et.0,
+                fixed_frame.into().as_mut_ptr()?,
+                aberration_correction.as_spice_char(),
+                observer.to_string_param().as_mut_ptr()?,
+                point.as_mut_ptr(),
+                &mut target_epoch,
+                surface_vector.as_mut_ptr(),
+                &mut phase,
+                &mut incidence,
+                &mut emission,
+                &mut visible,
+                &mut lit,
+            )
+        };
+        get_last_error()?;
+        Ok(SurfaceLighting {
+            angles: IlluminationAngles {
+                target_epoch: Et(target_epoch),
+                surface_vector,
+                phase: Radians(phase),
+                incidence: Radians(incidence),
+                emission: Radians(emission),
+            },
+            visible: visible == SPICETRUE as SpiceBoolean,
+            lit: lit == SPICETRUE as SpiceBoolean,
+        })
+    })
+}
+
+/// The illumination angles over a grid of planetocentric coordinates, see
+/// [illumination_grid()].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IlluminationGrid {
+    pub longitudes: Vec<Radians>,
+    pub latitudes: Vec<Radians>,
+    /// The angles in row-major order, one row per latitude.
+    pub angles: Vec<IlluminationAngles>,
+}
+
+impl IlluminationGrid {
+    /// The angles at `longitudes[longitude]` and `latitudes[latitude]`.
+    pub fn get(&self, latitude: usize, longitude: usize) -> Option<&IlluminationAngles> {
+        if latitude >= self.latitudes.len() || longitude >= self.longitudes.len() {
+            return None;
+        }
+        self.angles
+            .get(latitude * self.longitudes.len() + longitude)
+    }
+
+    /// Extract a quantity from each grid point into a 2D array, with one row per latitude and
+    /// one column per longitude. For example `grid.map_array(|a| a.incidence.0)`.
+    #[cfg(feature = "ndarray")]
+    pub fn map_array<F>(&self, f: F) -> ndarray::Array2<SpiceDouble>
+    where
+        F: Fn(&IlluminationAngles) -> SpiceDouble,
+    {
+        let shape = (self.latitudes.len(), self.longitudes.len());
+        ndarray::Array2::from_shape_vec(shape, self.angles.iter().map(f).collect())
+            .expect("grid has one angle per point")
+    }
+}
+
+/// Find the illumination angles at every combination of `longitudes` and `latitudes`
+/// (planetocentric) on the surface of a target body, for example for thermal modelling.
+///
+/// The surface points are found with [surface_points()], and all of the SPICE calls are made
+/// under a single acquisition of the SPICE lock.
+///
+/// See [ilumin_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/ilumin_c.html).
+#[allow(clippy::too_many_arguments)]
+pub fn illumination_grid<'t, 'f, 'o, T, F, O>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    longitudes: &[Radians],
+    latitudes: &[Radians],
+) -> Result<IlluminationGrid, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (target, fixed_frame, observer) = (target.into(), fixed_frame.into(), observer.into());
+    let coordinates = latitudes
+        .iter()
+        .flat_map(|latitude| {
+            longitudes
+                .iter()
+                .map(move |longitude| (*longitude, *latitude))
+        })
+        .collect::<Vec<_>>();
+    with_spice_lock_or_panic(|| {
+        let points = surface_points(
+            method,
+            target.to_string_param(),
+            et,
+            &*fixed_frame,
+            &coordinates,
+        )?;
+        let angles = points
+            .iter()
+            .map(|point| {
+                illumination_angles(
+                    method,
+                    target.to_string_param(),
+                    et,
+                    &*fixed_frame,
+                    aberration_correction,
+                    observer.to_string_param(),
+                    point,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(IlluminationGrid {
+            longitudes: longitudes.to_vec(),
+            latitudes: latitudes.to_vec(),
+            angles,
+        })
+    })
+}
+
+/// The intersection of a ray with the surface modelled by DSK data, see [dsk_intercept()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DskIntercept {
+    /// The intercept point in the body-fixed frame.
+    pub point: Rectangular,
+    /// The handle of the DSK file containing the intersected segment.
+    pub handle: SpiceInt,
+    /// The surface ID code of the intersected segment.
+    pub surface: SpiceInt,
+    /// The data type of the intersected segment.
+    pub data_type: SpiceInt,
+    /// For type 2 (plate model) segments, the ID of the intersected plate.
+    pub plate: SpiceInt,
+}
+
+/// Find the intersection of a ray with the surface of a target modelled by loaded DSK data,
+/// returns `None` if the ray misses the surface. The vertex and direction of the ray are given in
+/// `fixed_frame`, which must be the body-fixed frame of the DSK segments.
+///
+/// If `surfaces` is empty all surfaces of the target are considered.
+///
+/// See [dskxsi_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dskxsi_c.html).
+pub fn dsk_intercept<'t, 'f, T, F>(
+    target: T,
+    surfaces: &[SurfaceId],
+    et: Et,
+    fixed_frame: F,
+    vertex: &Vector3D,
+    direction: &Vector3D,
+) -> Result<Option<DskIntercept>, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+{
+    let target = target.into();
+    let mut surfaces = surfaces.iter().map(|s| s.0).collect::<Vec<_>>();
+    with_spice_lock_or_panic(|| {
+        let mut point = [0.0; 3];
+        let mut handle = 0;
+        let mut dla_descriptor = MaybeUninit::<SpiceDLADescr>::uninit();
+        let mut dsk_descriptor = MaybeUninit::<SpiceDSKDescr>::uninit();
+        let mut dc = [0.0; SPICE_DSKXSI_DCSIZE as usize];
+        let mut ic = [0; SPICE_DSKXSI_ICSIZE as usize];
+        let mut found: SpiceBoolean = 0;
+        unsafe {
+            dskxsi_c(
+                SPICEFALSE as SpiceBoolean,
+                target.to_string_param().as_mut_ptr()?,
+                surfaces.len() as SpiceInt,
+                surfaces.as_mut_ptr(),
+                et.0,
+                fixed_frame.into().as_mut_ptr()?,
+                vertex.as_ptr() as *mut SpiceDouble,
+                direction.as_ptr() as *mut SpiceDouble,
+                dc.len() as SpiceInt,
+                ic.len() as SpiceInt,
+                point.as_mut_ptr(),
+                &mut handle,
+                dla_descriptor.as_mut_ptr(),
+                dsk_descriptor.as_mut_ptr(),
+                dc.as_mut_ptr(),
+                ic.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        if found != SPICETRUE as SpiceBoolean {
+            return Ok(None);
+        }
+        // Safety: the descriptors are set when an intercept is found
+        let dsk_descriptor = unsafe { dsk_descriptor.assume_init() };
+        Ok(Some(DskIntercept {
+            point: point.into(),
+            handle,
+            surface: dsk_descriptor.surfce,
+            data_type: dsk_descriptor.dtype,
+            plate: ic[0],
+        }))
+    })
+}
+
+/// Find the intersection of a ray with a triaxial ellipsoid centered at the origin, with
+/// semi-axes `radii` along the x, y and z axes. Returns `None` if the ray misses the ellipsoid.
+///
+/// See [surfpt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/surfpt_c.html).
+pub fn ellipsoid_intercept(
+    vertex: &Vector3D,
+    direction: &Vector3D,
+    radii: [SpiceDouble; 3],
+) -> Result<Option<Rectangular>, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut point = [0.0; 3];
+        let mut found: SpiceBoolean = 0;
+        unsafe {
+            surfpt_c(
+                vertex.as_ptr() as *mut SpiceDouble,
+                direction.as_ptr() as *mut SpiceDouble,
+                radii[0],
+                radii[1],
+                radii[2],
+                point.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok((found == SPICETRUE as SpiceBoolean).then_some(point.into()))
+    })
+}
+
+/// Find the intersection of a ray with a triaxial ellipsoid and its velocity, given the state of
+/// the vertex of the ray and of its direction (i.e. the direction and its derivative). Returns
+/// `None` if the ray misses the ellipsoid.
+///
+/// See [ellipsoid_intercept()] and
+/// [surfpv_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/surfpv_c.html).
+pub fn ellipsoid_intercept_state(
+    vertex: &State,
+    direction: &State,
+    radii: [SpiceDouble; 3],
+) -> Result<Option<State>, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut vertex: [SpiceDouble; 6] = (*vertex).into();
+        let mut direction: [SpiceDouble; 6] = (*direction).into();
+        let mut state = [0.0; 6];
+        let mut found: SpiceBoolean = 0;
+        unsafe {
+            surfpv_c(
+                vertex.as_mut_ptr(),
+                direction.as_mut_ptr(),
+                radii[0],
+                radii[1],
+                radii[2],
+                state.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok((found == SPICETRUE as SpiceBoolean).then_some(state.into()))
+    })
+}
+
+/// Map planetocentric `(longitude, latitude)` coordinates to points on the surface of a target
+/// body, using the target's reference ellipsoid or DSK data (e.g. a digital elevation model)
+/// depending on `method`. The points are in `fixed_frame`, the body-fixed frame of the target.
+///
+/// See [latsrf_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/latsrf_c.html).
+pub fn surface_points<'t, 'f, T, F>(
+    method: &ShapeMethod,
+    target: T,
+    et: Et,
+    fixed_frame: F,
+    coordinates: &[(Radians, Radians)],
+) -> Result<Vec<Rectangular>, Error>
+where
+    T: Into<BodyRef<'t>>,
+    F: Into<StringParam<'f>>,
+{
+    let target = target.into();
+    let mut lon_lat: Vec<[SpiceDouble; 2]> = coordinates
+        .iter()
+        .map(|(longitude, latitude)| [longitude.0, latitude.0])
+        .collect();
+    let mut points = vec![[0.0; 3]; coordinates.len()];
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            latsrf_c(
+                SpiceString::from(method.shape_method()).as_mut_ptr()?,
+                target.to_string_param().as_mut_ptr()?,
+                et.0,
+                fixed_frame.into().as_mut_ptr()?,
+                lon_lat.len() as SpiceInt,
+                lon_lat.as_mut_ptr(),
+                points.as_mut_ptr(),
+            )
+        };
+        get_last_error()
+    })?;
+    Ok(points.into_iter().map(Rectangular::from).collect())
+}
+
+/// Convert planetocentric longitude and latitude to a point on the reference ellipsoid of a
+/// body, in its body-fixed frame.
+///
+/// See [srfrec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/srfrec_c.html).
+pub fn ellipsoid_surface_point<'b, B: Into<BodyRef<'b>>>(
+    body: B,
+    longitude: Radians,
+    latitude: Radians,
+) -> Result<Rectangular, Error> {
+    let body = body.into().to_id()?;
+    with_spice_lock_or_panic(|| {
+        let mut point = [0.0; 3];
+        unsafe { srfrec_c(body, longitude.0, latitude.0, point.as_mut_ptr()) };
+        get_last_error()?;
+        Ok(point.into())
+    })
+}
+
+/// Look up the name of the body-fixed frame associated with a body.
+///
+/// See [cidfrm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/cidfrm_c.html).
+pub(crate) fn body_fixed_frame(id: SpiceInt) -> Result<SpiceString, Error> {
+    with_spice_lock_or_panic(|| {
+        let mut found: SpiceBoolean = 0;
+        let mut frame_code: SpiceInt = 0;
+        let mut buffer = vec![0; 33];
+        unsafe {
+            cidfrm_c(
+                id,
+                buffer.len() as SpiceInt,
+                &mut frame_code,
+                buffer.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        if found != SPICETRUE as SpiceBoolean {
+            set_error_message(format!("No body-fixed frame is associated with body {id}"));
+            signal_error("SPICE(FRAMENOTFOUND)");
+            get_last_error()?;
+        }
+        Ok(SpiceString::from_buffer(buffer))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    #[test]
+    fn test_occultation_codes() {
+        for code in -3..=3 {
+            assert_eq!(OccultationState::from_code(code).unwrap().code(), code);
+        }
+        assert_eq!(
+            OccultationState::from_code(-3),
+            Some(OccultationState::Total(Occulted::Target1))
+        );
+        assert_eq!(OccultationState::from_code(4), None);
+    }
+
+    #[test]
+    fn test_occultation_point_shapes() {
+        load_test_data();
+        let err = occultation_state(
+            "MOON",
+            &ShapeMethod::point(),
+            "NULL",
+            "SUN",
+            &ShapeMethod::point(),
+            "NULL",
+            AberrationCorrection::NONE,
+            "EARTH",
+            Et(0.0),
+        )
+        .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(INVALIDSHAPECOMBO)");
+    }
+
+    #[test]
+    fn test_observation_geometry() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(0.0);
+        let geometry = observation_geometry("MOON", crate::naif_ids::EARTH, et).unwrap();
+        assert_eq!(geometry.frame, "IAU_MOON");
+
+        let (position, _) =
+            spk::position("MOON", et, "J2000", AberrationCorrection::LT_S, "EARTH").unwrap();
+        let norm = |r: Rectangular| (r.x * r.x + r.y * r.y + r.z * r.z).sqrt();
+        assert!((geometry.range.0 - norm(position)).abs() < 1e-6);
+
+        // The angle subtended at the Sun is tiny, so phase and elongation are nearly supplementary
+        let sum = geometry.phase_angle.0 + geometry.solar_elongation.0;
+        assert!((sum - std::f64::consts::PI).abs() < 0.01, "{sum}");
+        assert!((0.0..=1.0).contains(&geometry.illuminated_fraction));
+
+        for point in [geometry.sub_observer_point, geometry.sub_solar_point] {
+            assert!((norm(point) - 1737.4).abs() < 1e-6);
+        }
+
+        let err = observation_geometry("NOT A BODY", "EARTH", et).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(IDCODENOTFOUND)");
+    }
+
+    #[test]
+    fn test_surface_intercept() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(0.0);
+        let (position, _) =
+            spk::position("MOON", et, "J2000", AberrationCorrection::LT_S, "EARTH").unwrap();
+        let towards = Vector3D::from(position);
+        let intercept = surface_intercept(
+            &ShapeMethod::ellipsoid(),
+            "MOON",
+            et,
+            "IAU_MOON",
+            AberrationCorrection::LT_S,
+            "EARTH",
+            "J2000",
+            &towards,
+        )
+        .unwrap()
+        .unwrap();
+        let p = intercept.point;
+        assert!(((p.x * p.x + p.y * p.y + p.z * p.z).sqrt() - 1737.4).abs() < 1e-6);
+        assert!(intercept.target_epoch.0 < et.0);
+
+        let away = Vector3D(towards.map(|x| -x));
+        let miss = surface_intercept(
+            &ShapeMethod::ellipsoid(),
+            "MOON",
+            et,
+            "IAU_MOON",
+            AberrationCorrection::LT_S,
+            "EARTH",
+            "J2000",
+            &away,
+        )
+        .unwrap();
+        assert_eq!(miss, None);
+    }
+
+    #[test]
+    fn test_ellipsoid_intercept() {
+        let radii = [1.0, 2.0, 3.0];
+        let vertex = Vector3D([0.0, 0.0, 10.0]);
+        let point = ellipsoid_intercept(&vertex, &Vector3D([0.0, 0.0, -1.0]), radii).unwrap();
+        assert_eq!(point, Some([0.0, 0.0, 3.0].into()));
+        let point = ellipsoid_intercept(&vertex, &Vector3D([0.0, 0.0, 1.0]), radii).unwrap();
+        assert_eq!(point, None);
+
+        // A vertex moving along x over a unit sphere, looking straight down
+        let vertex = State::from([0.0, 0.0, 10.0, 1.0, 0.0, 0.0]);
+        let direction = State::from([0.0, 0.0, -1.0, 0.0, 0.0, 0.0]);
+        let state = ellipsoid_intercept_state(&vertex, &direction, [1.0; 3])
+            .unwrap()
+            .unwrap();
+        let expected = [0.0, 0.0, 1.0, 1.0, 0.0, 0.0];
+        for (a, b) in <[SpiceDouble; 6]>::from(state).iter().zip(expected) {
+            assert!((a - b).abs() < 1e-12, "{state:?}");
+        }
+        let direction = State::from([0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(
+            ellipsoid_intercept_state(&vertex, &direction, [1.0; 3]).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_surface_points() {
+        load_test_data();
+        load_moon_constants();
+        let half_pi = Radians(std::f64::consts::FRAC_PI_2);
+        let coordinates = [
+            (Radians(0.0), Radians(0.0)),
+            (half_pi, Radians(0.0)),
+            (Radians(0.0), half_pi),
+        ];
+        let expected: [Rectangular; 3] = [
+            [1737.4, 0.0, 0.0].into(),
+            [0.0, 1737.4, 0.0].into(),
+            [0.0, 0.0, 1737.4].into(),
+        ];
+        let points = surface_points(
+            &ShapeMethod::ellipsoid(),
+            "MOON",
+            Et(0.0),
+            "IAU_MOON",
+            &coordinates,
+        )
+        .unwrap();
+        assert_eq!(points.len(), 3);
+        for ((point, expected), (longitude, latitude)) in
+            points.iter().zip(expected).zip(coordinates)
+        {
+            let single = ellipsoid_surface_point("MOON", longitude, latitude).unwrap();
+            for (a, b) in [
+                (point.x, expected.x),
+                (point.y, expected.y),
+                (point.z, expected.z),
+                (single.x, expected.x),
+                (single.y, expected.y),
+                (single.z, expected.z),
+            ] {
+                assert!((a - b).abs() < 1e-9, "{a} != {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_shape_method() {
+        let ellipsoid = ShapeMethod::ellipsoid();
+        assert_eq!(ellipsoid.shape_method(), "ELLIPSOID");
+        assert_eq!(ellipsoid.sub_point_method(), "NEAR POINT/ELLIPSOID");
+        assert_eq!(
+            ellipsoid
+                .sub_point(SubPointMethod::Intercept)
+                .sub_point_method(),
+            "INTERCEPT/ELLIPSOID"
+        );
+        assert_eq!(
+            ShapeMethod::dsk().sub_point_method(),
+            "NADIR/DSK/UNPRIORITIZED"
+        );
+        let surfaces = ShapeMethod::dsk_surfaces([SurfaceId(1), SurfaceId(2)])
+            .sub_point(SubPointMethod::Intercept);
+        assert_eq!(surfaces.shape_method(), "DSK/UNPRIORITIZED/SURFACES = 1, 2");
+        assert_eq!(
+            surfaces.sub_point_method(),
+            "INTERCEPT/DSK/UNPRIORITIZED/SURFACES = 1, 2"
+        );
+    }
+
+    #[test]
+    fn test_sub_points_and_illumination() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(0.0);
+        let method = ShapeMethod::ellipsoid();
+        let abcorr = AberrationCorrection::LT_S;
+        let geometry = observation_geometry("MOON", "EARTH", et).unwrap();
+        let sub_observer =
+            sub_observer_point(&method, "MOON", et, "IAU_MOON", abcorr, "EARTH").unwrap();
+        assert_eq!(sub_observer.point, geometry.sub_observer_point);
+        let sub_solar = sub_solar_point(&method, "MOON", et, "IAU_MOON", abcorr, "EARTH").unwrap();
+        assert_eq!(sub_solar.point, geometry.sub_solar_point);
+
+        // On a sphere the intercept and near point definitions agree
+        let intercept = sub_observer_point(
+            &method.clone().sub_point(SubPointMethod::Intercept),
+            "MOON",
+            et,
+            "IAU_MOON",
+            abcorr,
+            "EARTH",
+        )
+        .unwrap();
+        for (a, b) in [
+            (intercept.point.x, sub_observer.point.x),
+            (intercept.point.y, sub_observer.point.y),
+            (intercept.point.z, sub_observer.point.z),
+        ] {
+            assert!((a - b).abs() < 1e-6, "{a} != {b}");
+        }
+
+        let angles = illumination_angles(
+            &method,
+            "MOON",
+            et,
+            "IAU_MOON",
+            abcorr,
+            "EARTH",
+            &sub_observer.point,
+        )
+        .unwrap();
+        assert!(angles.emission.0.abs() < 1e-6);
+        assert!((angles.phase.0 - geometry.phase_angle.0).abs() < 1e-3);
+        assert!((angles.incidence.0 - angles.phase.0).abs() < 1e-3);
+
+        // With the Sun as the source the angles match ilumin
+        let lighting = surface_lighting(
+            &method,
+            "MOON",
+            "SUN",
+            et,
+            "IAU_MOON",
+            abcorr,
+            "EARTH",
+            &sub_observer.point,
+        )
+        .unwrap();
+        for (a, b) in [
+            (lighting.angles.phase, angles.phase),
+            (lighting.angles.incidence, angles.incidence),
+            (lighting.angles.emission, angles.emission),
+        ] {
+            assert!((a.0 - b.0).abs() < 1e-12, "{a:?} != {b:?}");
+        }
+        assert!(lighting.visible);
+        assert_eq!(
+            lighting.lit,
+            angles.incidence.0 < std::f64::consts::FRAC_PI_2
+        );
+
+        // The point opposite the sub-solar point is dark, and the far side isn't visible
+        let anti_solar = Rectangular {
+            x: -sub_solar.point.x,
+            y: -sub_solar.point.y,
+            z: -sub_solar.point.z,
+        };
+        let lighting = surface_lighting(
+            &method,
+            "MOON",
+            "SUN",
+            et,
+            "IAU_MOON",
+            abcorr,
+            "EARTH",
+            &anti_solar,
+        )
+        .unwrap();
+        assert!(!lighting.lit);
+        let far_side = Rectangular {
+            x: -sub_observer.point.x,
+            y: -sub_observer.point.y,
+            z: -sub_observer.point.z,
+        };
+        let lighting = surface_lighting(
+            &method, "MOON", "SUN", et, "IAU_MOON", abcorr, "EARTH", &far_side,
+        )
+        .unwrap();
+        assert!(!lighting.visible);
+    }
+
+    #[test]
+    fn test_illumination_grid() {
+        load_test_data();
+        load_moon_constants();
+        let method = ShapeMethod::ellipsoid();
+        let (et, abcorr) = (Et(0.0), AberrationCorrection::NONE);
+        let longitudes = [Radians(0.0), Radians(1.0), Radians(2.0)];
+        let latitudes = [Radians(-0.5), Radians(0.5)];
+        let grid = illumination_grid(
+            &method,
+            "MOON",
+            et,
+            "IAU_MOON",
+            abcorr,
+            "EARTH",
+            &longitudes,
+            &latitudes,
+        )
+        .unwrap();
+        assert_eq!(grid.angles.len(), 6);
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(0, 3), None);
+
+        let point = ellipsoid_surface_point("MOON", longitudes[2], latitudes[1]).unwrap();
+        let expected =
+            illumination_angles(&method, "MOON", et, "IAU_MOON", abcorr, "EARTH", &point).unwrap();
+        let angles = grid.get(1, 2).unwrap();
+        assert!((angles.incidence.0 - expected.incidence.0).abs() < 1e-9);
+        assert!((angles.emission.0 - expected.emission.0).abs() < 1e-9);
+        assert!((angles.phase.0 - expected.phase.0).abs() < 1e-9);
+
+        #[cfg(feature = "ndarray")]
+        {
+            let incidence = grid.map_array(|a| a.incidence.0);
+            assert_eq!(incidence.dim(), (2, 3));
+            assert_eq!(incidence[[1, 2]], angles.incidence.0);
+        }
+    }
+
+    /// Write and load a type 2 DSK of an octahedron for the Moon, with vertices `radius` km from
+    /// the center along each axis of IAU_MOON.
+    fn load_octahedron_dsk(surface: SpiceInt, radius: SpiceDouble) -> std::path::PathBuf {
+        let dsk = std::env::temp_dir().join("cspice_rs_octahedron_test.bds");
+        let _ = std::fs::remove_file(&dsk);
+        let mut vertices = [
+            [radius, 0.0, 0.0],
+            [-radius, 0.0, 0.0],
+            [0.0, radius, 0.0],
+            [0.0, -radius, 0.0],
+            [0.0, 0.0, radius],
+            [0.0, 0.0, -radius],
+        ];
+        // One plate per octant, with vertices ordered so that the normal points outwards
+        let mut plates = Vec::new();
+        for (x, sx) in [(1, 1), (2, -1)] {
+            for (y, sy) in [(3, 1), (4, -1)] {
+                for (z, sz) in [(5, 1), (6, -1)] {
+                    plates.push(if sx * sy * sz > 0 {
+                        [x, y, z]
+                    } else {
+                        [x, z, y]
+                    });
+                }
+            }
+        }
+        let (mut spaixd, mut spaixi) = ([0.0; 10], vec![0; 100_000]);
+        let mut work = vec![[0; 2]; 10_000];
+        with_spice_lock_or_panic(|| unsafe {
+            cspice_sys::dskmi2_c(
+                vertices.len() as SpiceInt,
+                vertices.as_mut_ptr(),
+                plates.len() as SpiceInt,
+                plates.as_mut_ptr(),
+                5.0,
+                4,
+                work.len() as SpiceInt,
+                10_000,
+                10_000,
+                SPICETRUE as SpiceBoolean,
+                spaixi.len() as SpiceInt,
+                work.as_mut_ptr(),
+                spaixd.as_mut_ptr(),
+                spaixi.as_mut_ptr(),
+            );
+            let mut handle = 0;
+            cspice_sys::dskopn_c(
+                SpiceString::from(dsk.to_string_lossy())
+                    .as_mut_ptr()
+                    .unwrap(),
+                static_spice_str!("DSK TEST").as_mut_ptr(),
+                0,
+                &mut handle,
+            );
+            let mut corpar = [0.0; 10];
+            cspice_sys::dskw02_c(
+                handle,
+                301,
+                surface,
+                1,
+                static_spice_str!("IAU_MOON").as_mut_ptr(),
+                cspice_sys::SPICE_DSK_LATSYS as SpiceInt,
+                corpar.as_mut_ptr(),
+                -std::f64::consts::PI,
+                std::f64::consts::PI,
+                -std::f64::consts::FRAC_PI_2,
+                std::f64::consts::FRAC_PI_2,
+                radius / 3f64.sqrt(),
+                radius,
+                -1e9,
+                1e9,
+                vertices.len() as SpiceInt,
+                vertices.as_mut_ptr(),
+                plates.len() as SpiceInt,
+                plates.as_mut_ptr(),
+                spaixd.as_mut_ptr(),
+                spaixi.as_mut_ptr(),
+            );
+            cspice_sys::dskcls_c(handle, SPICETRUE as SpiceBoolean);
+            get_last_error().unwrap();
+        });
+        crate::data::furnish(dsk.to_string_lossy()).unwrap();
+        dsk
+    }
+
+    #[test]
+    fn test_dsk_intercept() {
+        load_test_data();
+        let radius = 1000.0;
+        let dsk = load_octahedron_dsk(1002, radius);
+
+        // Straight down onto the face in the +X +Y +Z octant, where x + y + z = radius
+        let vertex = Vector3D::from([0.1 * radius, 0.2 * radius, 10.0 * radius]);
+        let down = Vector3D::from([0.0, 0.0, -1.0]);
+        let intercept = dsk_intercept("MOON", &[], Et(0.0), "IAU_MOON", &vertex, &down)
+            .unwrap()
+            .unwrap();
+        assert!((intercept.point.x - 0.1 * radius).abs() < 1e-9);
+        assert!((intercept.point.y - 0.2 * radius).abs() < 1e-9);
+        assert!((intercept.point.z - 0.7 * radius).abs() < 1e-9);
+        assert_eq!(intercept.surface, 1002);
+        assert_eq!(intercept.data_type, 2);
+        assert_eq!(intercept.plate, 1);
+
+        // Only the listed surfaces are considered
+        let intercept = dsk_intercept(
+            "MOON",
+            &[SurfaceId(1002)],
+            Et(0.0),
+            "IAU_MOON",
+            &vertex,
+            &down,
+        );
+        assert!(intercept.unwrap().is_some());
+        let intercept = dsk_intercept(
+            "MOON",
+            &[SurfaceId(1003)],
+            Et(0.0),
+            "IAU_MOON",
+            &vertex,
+            &down,
+        );
+        assert_eq!(intercept.unwrap(), None);
+        // Missing the surface, and pointing away from it
+        let outside = Vector3D::from([2.0 * radius, 2.0 * radius, 10.0 * radius]);
+        let intercept = dsk_intercept("MOON", &[], Et(0.0), "IAU_MOON", &outside, &down);
+        assert_eq!(intercept.unwrap(), None);
+        let up = Vector3D::from([0.0, 0.0, 1.0]);
+        let intercept = dsk_intercept("MOON", &[], Et(0.0), "IAU_MOON", &vertex, &up);
+        assert_eq!(intercept.unwrap(), None);
+
+        crate::data::unload(dsk.to_string_lossy()).unwrap();
+    }
+}
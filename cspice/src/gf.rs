@@ -1,12 +1,23 @@
 //! Geometry Finder functions.
 
+use crate::body::BodyRef;
 use crate::cell::Window;
 use crate::common::AberrationCorrection;
-use crate::error::get_last_error;
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::geometry::ShapeMethod;
 use crate::string::StaticSpiceStr;
-use crate::string::{static_spice_str, StringParam};
+use crate::string::{static_spice_str, SpiceString, StringParam};
+use crate::time::Et;
+use crate::units::{Radians, Seconds};
+use crate::vector::Vector3D;
 use crate::{with_spice_lock_or_panic, Error};
-use cspice_sys::{gfsep_c, SpiceChar, SpiceDouble, SpiceInt};
+use cspice_sys::{
+    gfbail_c, gffove_c, gfocce_c, gfrefn_c, gfrepf_c, gfrepi_c, gfrepu_c, gfsep_c, SpiceBoolean,
+    SpiceChar, SpiceDouble, SpiceInt, SPICETRUE,
+};
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 
 #[derive(Copy, Clone, Debug)]
 pub enum Shape {
@@ -53,6 +64,14 @@ impl RelationalOperator {
 /// Determine time intervals when the angular separation between the position vectors of two target
 /// bodies relative to an observer satisfies a numerical relationship.
 ///
+/// Bodies may be given by name or by NAIF ID code. For [Shape::Point] the corresponding frame
+/// should be `"NULL"`; see [SeparationSearch] for a builder that defaults the frames accordingly.
+///
+/// The reference value and adjustment are angles, the step size is a duration in TDB seconds.
+///
+/// The search is confined to the intervals of `confine`, and the results are returned in a new
+/// window sized using [estimate_output_size()] with a maximum of [MAX_OUTPUT_WINDOW_SIZE].
+///
 /// See [gfsep_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gfsep_c.html)
 #[allow(clippy::too_many_arguments)]
 pub fn separation_search<'b1, 'f1, 'b2, 'f2, 'o, B1, F1, B2, F2, O>(
@@ -65,40 +84,675 @@ pub fn separation_search<'b1, 'f1, 'b2, 'f2, 'o, B1, F1, B2, F2, O>(
     aberration_correction: AberrationCorrection,
     observing_body: O,
     relational_operator: RelationalOperator,
-    refval: SpiceDouble,
-    adjust: SpiceDouble,
-    step_size: SpiceDouble,
+    refval: Radians,
+    adjust: Radians,
+    step_size: Seconds,
     intervals: usize,
-    confine: &mut Window,
-    output: &mut Window,
-) -> Result<(), Error>
+    confine: &Window,
+) -> Result<Window, OutputWindowError>
 where
-    B1: Into<StringParam<'b1>>,
+    B1: Into<BodyRef<'b1>>,
     F1: Into<StringParam<'f1>>,
-    B2: Into<StringParam<'b2>>,
+    B2: Into<BodyRef<'b2>>,
     F2: Into<StringParam<'f2>>,
-    O: Into<StringParam<'o>>,
+    O: Into<BodyRef<'o>>,
+{
+    SeparationSearch {
+        body1: body1.into(),
+        shape1,
+        frame1: Some(frame1.into()),
+        body2: body2.into(),
+        shape2,
+        frame2: Some(frame2.into()),
+        aberration_correction,
+        observing_body: observing_body.into(),
+        relational_operator,
+        refval,
+        adjust,
+        step_size,
+        intervals,
+        max_output_size: MAX_OUTPUT_WINDOW_SIZE,
+    }
+    .search(confine)
+}
+
+/// Builder for [separation_search()].
+///
+/// Both bodies default to [Shape::Point] with a `"NULL"` frame, as required by CSPICE for point
+/// shapes. A frame must be provided when using [Shape::Sphere].
+pub struct SeparationSearch<'a> {
+    body1: BodyRef<'a>,
+    shape1: Shape,
+    frame1: Option<StringParam<'a>>,
+    body2: BodyRef<'a>,
+    shape2: Shape,
+    frame2: Option<StringParam<'a>>,
+    aberration_correction: AberrationCorrection,
+    observing_body: BodyRef<'a>,
+    relational_operator: RelationalOperator,
+    refval: Radians,
+    adjust: Radians,
+    step_size: Seconds,
+    intervals: usize,
+    max_output_size: usize,
+}
+
+impl<'a> SeparationSearch<'a> {
+    /// The default number of intervals used for workspace windows.
+    pub const DEFAULT_INTERVALS: usize = 1000;
+
+    /// Create a new search with the required parameters.
+    pub fn new<B1, B2, O>(
+        body1: B1,
+        body2: B2,
+        observing_body: O,
+        relational_operator: RelationalOperator,
+        refval: Radians,
+        step_size: Seconds,
+    ) -> Self
+    where
+        B1: Into<BodyRef<'a>>,
+        B2: Into<BodyRef<'a>>,
+        O: Into<BodyRef<'a>>,
+    {
+        Self {
+            body1: body1.into(),
+            shape1: Shape::Point,
+            frame1: None,
+            body2: body2.into(),
+            shape2: Shape::Point,
+            frame2: None,
+            aberration_correction: AberrationCorrection::NONE,
+            observing_body: observing_body.into(),
+            relational_operator,
+            refval,
+            adjust: Radians(0.0),
+            step_size,
+            intervals: Self::DEFAULT_INTERVALS,
+            max_output_size: MAX_OUTPUT_WINDOW_SIZE,
+        }
+    }
+
+    /// Model the first body as a sphere, using the given body-fixed frame.
+    pub fn body1_sphere<F: Into<StringParam<'a>>>(mut self, frame: F) -> Self {
+        self.shape1 = Shape::Sphere;
+        self.frame1 = Some(frame.into());
+        self
+    }
+
+    /// Model the second body as a sphere, using the given body-fixed frame.
+    pub fn body2_sphere<F: Into<StringParam<'a>>>(mut self, frame: F) -> Self {
+        self.shape2 = Shape::Sphere;
+        self.frame2 = Some(frame.into());
+        self
+    }
+
+    /// Set the aberration correction, the default is [AberrationCorrection::NONE].
+    pub fn aberration_correction(mut self, aberration_correction: AberrationCorrection) -> Self {
+        self.aberration_correction = aberration_correction;
+        self
+    }
+
+    /// Set the adjustment value for absolute extrema searches, the default is 0.
+    pub fn adjust(mut self, adjust: Radians) -> Self {
+        self.adjust = adjust;
+        self
+    }
+
+    /// Set the number of intervals used for workspace windows, the default is
+    /// [SeparationSearch::DEFAULT_INTERVALS].
+    pub fn intervals(mut self, intervals: usize) -> Self {
+        self.intervals = intervals;
+        self
+    }
+
+    /// Set the maximum size of the output window allocated by [SeparationSearch::search()], the
+    /// default is [MAX_OUTPUT_WINDOW_SIZE].
+    pub fn max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = max_output_size;
+        self
+    }
+
+    /// Run the search over the `confine` window, returning the results in a new window sized
+    /// using [estimate_output_size()].
+    ///
+    /// See [separation_search()].
+    pub fn search(self, confine: &Window) -> Result<Window, OutputWindowError> {
+        let size = estimate_output_size(confine, self.step_size, self.max_output_size)?;
+        let mut output = Window::new_double(size);
+        let null = StringParam::from("NULL");
+        let frame1 = self.frame1.as_deref().unwrap_or(&null);
+        let frame2 = self.frame2.as_deref().unwrap_or(&null);
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                gfsep_c(
+                    self.body1.to_string_param().as_mut_ptr()?,
+                    self.shape1.as_spice_char(),
+                    frame1.as_mut_ptr()?,
+                    self.body2.to_string_param().as_mut_ptr()?,
+                    self.shape2.as_spice_char(),
+                    frame2.as_mut_ptr()?,
+                    self.aberration_correction.as_spice_char(),
+                    self.observing_body.to_string_param().as_mut_ptr()?,
+                    self.relational_operator.as_spice_char(),
+                    self.refval.0,
+                    self.adjust.0,
+                    self.step_size.0,
+                    self.intervals as SpiceInt,
+                    confine.as_const_cell(),
+                    output.as_mut_cell(),
+                );
+            };
+            get_last_error()
+        })
+        .map_err(|e| OutputWindowError::from_spice(e, size))?;
+        Ok(output)
+    }
+}
+
+/// The default maximum output window size used by [estimate_output_size()] in the GF builders.
+pub const MAX_OUTPUT_WINDOW_SIZE: usize = 200_000;
+
+/// Estimate the output window size (number of endpoints) needed for a search with a given step
+/// size over the `confine` window, clamped to `max`.
+///
+/// A GF search can't find more than one interval per step, so the estimate is an upper bound
+/// when the step is no larger than the shortest event being searched for.
+pub fn estimate_output_size(
+    confine: &Window,
+    step_size: Seconds,
+    max: usize,
+) -> Result<usize, Error> {
+    let mut intervals = 0usize;
+    for i in 0..confine.window_cardinality()? as usize {
+        let (left, right) = confine.window_interval(i)?;
+        let steps = ((right - left) / step_size.0).ceil();
+        intervals = intervals.saturating_add(if steps.is_finite() {
+            steps as usize + 1
+        } else {
+            usize::MAX
+        });
+    }
+    Ok(intervals.saturating_mul(2).clamp(2, max.max(2)))
+}
+
+/// Error returned by GF searches that allocate their own output window.
+#[derive(Debug, thiserror::Error)]
+pub enum OutputWindowError {
+    #[error("The output window of size {size} is too small for the search results, try a larger size (e.g. using `max_output_size()`)")]
+    TooSmall { size: usize, source: Box<Error> },
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+impl OutputWindowError {
+    pub(crate) fn from_spice(e: Error, size: usize) -> Self {
+        match e.short_message.as_str() {
+            "SPICE(WINDOWEXCESS)" | "SPICE(CELLTOOSMALL)" | "SPICE(OUTOFROOM)" => {
+                OutputWindowError::TooSmall {
+                    size,
+                    source: Box::new(e),
+                }
+            }
+            _ => OutputWindowError::Spice(e),
+        }
+    }
+}
+
+/// The type of occultation to search for with [occultation_search()].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OccultationType {
+    Full,
+    Annular,
+    Partial,
+    Any,
+}
+
+impl OccultationType {
+    pub(crate) unsafe fn as_spice_char(&self) -> *mut SpiceChar {
+        match &self {
+            OccultationType::Full => static_spice_str!("FULL"),
+            OccultationType::Annular => static_spice_str!("ANNULAR"),
+            OccultationType::Partial => static_spice_str!("PARTIAL"),
+            OccultationType::Any => static_spice_str!("ANY"),
+        }
+        .as_mut_ptr()
+    }
+}
+
+type RefineFn<'a> = Box<dyn FnMut(Et, Et, bool, bool) -> Et + 'a>;
+
+/// Step size and refinement control for [occultation_search()] and [fov_search()].
+///
+/// The step function returns the step size to use from a given epoch, this must be shorter than
+/// the duration of any event of interest. Allowing it to vary means short events (e.g. near
+/// periapsis of an eccentric orbit) can be found without using a small step everywhere.
+pub struct SearchControl<'a> {
+    step: Box<dyn FnMut(Et) -> Seconds + 'a>,
+    refine: Option<RefineFn<'a>>,
+    tolerance: Seconds,
+    min_step: Option<Seconds>,
+    max_output_size: usize,
+}
+
+impl<'a> SearchControl<'a> {
+    /// The default convergence tolerance, the same as used by the high level GF functions.
+    pub const DEFAULT_TOLERANCE: Seconds = Seconds(1e-6);
+
+    /// Use a step function.
+    pub fn new<F: FnMut(Et) -> Seconds + 'a>(step: F) -> Self {
+        Self {
+            step: Box::new(step),
+            refine: None,
+            tolerance: Self::DEFAULT_TOLERANCE,
+            min_step: None,
+            max_output_size: MAX_OUTPUT_WINDOW_SIZE,
+        }
+    }
+
+    /// Use a constant step size.
+    pub fn constant(step: Seconds) -> Self {
+        Self::new(move |_| step).min_step(step)
+    }
+
+    /// Use a custom refinement function, the default is bisection.
+    ///
+    /// It is given the bracketing times `t1` and `t2`, and the state of the condition at each,
+    /// and must return a time between them.
+    pub fn refine<F: FnMut(Et, Et, bool, bool) -> Et + 'a>(mut self, refine: F) -> Self {
+        self.refine = Some(Box::new(refine));
+        self
+    }
+
+    /// Set the convergence tolerance.
+    pub fn tolerance(mut self, tolerance: Seconds) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the smallest step size returned by the step function, used to size the output window
+    /// with [estimate_output_size()]. If it isn't set (other than by [SearchControl::constant()])
+    /// the output window has the maximum size.
+    pub fn min_step(mut self, min_step: Seconds) -> Self {
+        self.min_step = Some(min_step);
+        self
+    }
+
+    /// Set the maximum size of the output window allocated by the search, the default is
+    /// [MAX_OUTPUT_WINDOW_SIZE].
+    pub fn max_output_size(mut self, max_output_size: usize) -> Self {
+        self.max_output_size = max_output_size;
+        self
+    }
+
+    fn output_size(&self, confine: &Window) -> Result<usize, Error> {
+        match self.min_step {
+            Some(step) => estimate_output_size(confine, step, self.max_output_size),
+            None => Ok(self.max_output_size.max(2)),
+        }
+    }
+}
+
+thread_local! {
+    // The control for the search in progress on this thread, only set while SPICE is running it
+    static ACTIVE_CONTROL: Cell<*mut SearchControl<'static>> = const { Cell::new(std::ptr::null_mut()) };
+    static CALLBACK_PANIC: RefCell<Option<Box<dyn Any + Send>>> = const { RefCell::new(None) };
+}
+
+/// Run a callback on the active control. Panics are caught, as they can't unwind through SPICE,
+/// and instead signalled as a SPICE error to stop the search.
+unsafe fn with_active_control<R, F: FnOnce(&mut SearchControl) -> R>(f: F) -> Option<R> {
+    let control = ACTIVE_CONTROL.with(|c| c.get());
+    match catch_unwind(AssertUnwindSafe(|| f(&mut *control))) {
+        Ok(r) => Some(r),
+        Err(payload) => {
+            CALLBACK_PANIC.with(|p| *p.borrow_mut() = Some(payload));
+            set_error_message("A Rust callback panicked");
+            signal_error("SPICE(RUSTPANIC)");
+            None
+        }
+    }
+}
+
+unsafe extern "C" fn step_trampoline(et: SpiceDouble, step: *mut SpiceDouble) {
+    *step = with_active_control(|c| (c.step)(Et(et)).0).unwrap_or(1.0);
+}
+
+unsafe extern "C" fn refine_trampoline(
+    t1: SpiceDouble,
+    t2: SpiceDouble,
+    s1: SpiceBoolean,
+    s2: SpiceBoolean,
+    t: *mut SpiceDouble,
+) {
+    let (b1, b2) = (
+        s1 == SPICETRUE as SpiceBoolean,
+        s2 == SPICETRUE as SpiceBoolean,
+    );
+    *t = with_active_control(|c| match &mut c.refine {
+        Some(refine) => refine(Et(t1), Et(t2), b1, b2).0,
+        None => (t1 + t2) / 2.0,
+    })
+    .unwrap_or((t1 + t2) / 2.0);
+}
+
+type Refine =
+    unsafe extern "C" fn(SpiceDouble, SpiceDouble, SpiceBoolean, SpiceBoolean, *mut SpiceDouble);
+
+/// Run a callback driven GF search, with `control` available to the trampolines, returning the
+/// results in a new window sized by `SearchControl::output_size()`.
+fn run_with_control<F>(
+    control: &mut SearchControl,
+    confine: &Window,
+    search: F,
+) -> Result<Window, OutputWindowError>
+where
+    F: FnOnce(Refine, *mut cspice_sys::SpiceCell) -> Result<(), Error>,
 {
+    let size = control.output_size(confine)?;
+    let mut output = Window::new_double(size);
     with_spice_lock_or_panic(|| {
-        unsafe {
-            gfsep_c(
-                body1.into().as_mut_ptr(),
-                shape1.as_spice_char(),
-                frame1.into().as_mut_ptr(),
-                body2.into().as_mut_ptr(),
-                shape2.as_spice_char(),
-                frame2.into().as_mut_ptr(),
-                aberration_correction.as_spice_char(),
-                observing_body.into().as_mut_ptr(),
-                relational_operator.as_spice_char(),
-                refval,
-                adjust,
-                step_size,
-                intervals as SpiceInt,
-                confine.as_mut_cell(),
-                output.as_mut_cell(),
-            );
+        let refine: Refine = match control.refine {
+            Some(_) => refine_trampoline,
+            None => gfrefn_c,
         };
-        get_last_error()
+        let control: *mut SearchControl = control;
+        let previous = ACTIVE_CONTROL.with(|c| c.replace(control.cast()));
+        let called = search(refine, output.as_mut_cell());
+        ACTIVE_CONTROL.with(|c| c.set(previous));
+        let result = called.and_then(|_| get_last_error());
+        if let Some(payload) = CALLBACK_PANIC.with(|p| p.borrow_mut().take()) {
+            resume_unwind(payload);
+        }
+        result
     })
+    .map_err(|e| OutputWindowError::from_spice(e, size))?;
+    Ok(output)
+}
+
+/// Determine time intervals when an observer sees one target occulted by another, using
+/// user-controlled step size and refinement.
+///
+/// Frames must be the body-fixed frame of the corresponding target, and are ignored for
+/// [ShapeMethod::point()] (pass `"NULL"`).
+///
+/// The search is confined to the intervals of `confine`, and the results are returned in a new
+/// window sized by `control`, see [SearchControl::min_step()].
+///
+/// See [gfocce_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gfocce_c.html)
+#[allow(clippy::too_many_arguments)]
+pub fn occultation_search<'f, 'ff, 'b, 'bf, 'o, F, FF, B, BF, O>(
+    occultation_type: OccultationType,
+    front: F,
+    front_shape: &ShapeMethod,
+    front_frame: FF,
+    back: B,
+    back_shape: &ShapeMethod,
+    back_frame: BF,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    control: &mut SearchControl,
+    confine: &Window,
+) -> Result<Window, OutputWindowError>
+where
+    F: Into<BodyRef<'f>>,
+    FF: Into<StringParam<'ff>>,
+    B: Into<BodyRef<'b>>,
+    BF: Into<StringParam<'bf>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (front, back, observer) = (front.into(), back.into(), observer.into());
+    let (front_frame, back_frame) = (front_frame.into(), back_frame.into());
+    let tolerance = control.tolerance.0;
+    run_with_control(control, confine, |refine, output| unsafe {
+        gfocce_c(
+            occultation_type.as_spice_char(),
+            front.to_string_param().as_mut_ptr()?,
+            SpiceString::from(front_shape.shape_method()).as_mut_ptr()?,
+            front_frame.as_mut_ptr()?,
+            back.to_string_param().as_mut_ptr()?,
+            SpiceString::from(back_shape.shape_method()).as_mut_ptr()?,
+            back_frame.as_mut_ptr()?,
+            aberration_correction.as_spice_char(),
+            observer.to_string_param().as_mut_ptr()?,
+            tolerance,
+            Some(step_trampoline),
+            Some(refine),
+            0,
+            Some(gfrepi_c),
+            Some(gfrepu_c),
+            Some(gfrepf_c),
+            0,
+            Some(gfbail_c),
+            confine.as_const_cell(),
+            output,
+        );
+        Ok(())
+    })
+}
+
+/// The target of a [fov_search()].
+pub enum FovTarget<'a> {
+    /// A ray with the given direction, in the given frame.
+    Ray(Vector3D, StringParam<'a>),
+    /// A body modelled as a [ShapeMethod::point()] or [ShapeMethod::ellipsoid()], with its
+    /// body-fixed frame.
+    Body(BodyRef<'a>, ShapeMethod, StringParam<'a>),
+}
+
+/// Determine time intervals when a target intersects the field of view of an instrument, using
+/// user-controlled step size and refinement.
+///
+/// The search is confined to the intervals of `confine`, and the results are returned in a new
+/// window sized by `control`, see [SearchControl::min_step()].
+///
+/// See [gffove_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gffove_c.html)
+pub fn fov_search<'i, 'o, I, O>(
+    instrument: I,
+    target: FovTarget,
+    aberration_correction: AberrationCorrection,
+    observer: O,
+    control: &mut SearchControl,
+    confine: &Window,
+) -> Result<Window, OutputWindowError>
+where
+    I: Into<StringParam<'i>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (instrument, observer) = (instrument.into(), observer.into());
+    let tolerance = control.tolerance.0;
+    let (shape, mut direction, body, frame) = match &target {
+        FovTarget::Ray(direction, frame) => (
+            SpiceString::from("RAY"),
+            *direction,
+            StringParam::from(" "),
+            StringParam::Ref(frame),
+        ),
+        FovTarget::Body(body, shape, frame) => (
+            SpiceString::from(shape.shape_method()),
+            Vector3D::default(),
+            body.to_string_param(),
+            StringParam::Ref(frame),
+        ),
+    };
+    run_with_control(control, confine, |refine, output| unsafe {
+        gffove_c(
+            instrument.as_mut_ptr()?,
+            shape.as_mut_ptr()?,
+            direction.as_mut_ptr(),
+            body.as_mut_ptr()?,
+            frame.as_mut_ptr()?,
+            aberration_correction.as_spice_char(),
+            observer.to_string_param().as_mut_ptr()?,
+            tolerance,
+            Some(step_trampoline),
+            Some(refine),
+            0,
+            Some(gfrepi_c),
+            Some(gfrepu_c),
+            Some(gfrepf_c),
+            0,
+            Some(gfbail_c),
+            confine.as_const_cell(),
+            output,
+        );
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    #[test]
+    fn test_occultation_search_step_callback() {
+        load_test_data();
+        load_moon_constants();
+        let mut calls = 0;
+        let mut control = SearchControl::new(|_| {
+            calls += 1;
+            Seconds(600.0)
+        });
+        let confine = Window::from_intervals(&[(0.0, 86400.0)]).unwrap();
+        let output = occultation_search(
+            OccultationType::Any,
+            "MOON",
+            &ShapeMethod::ellipsoid(),
+            "IAU_MOON",
+            "SUN",
+            &ShapeMethod::point(),
+            "NULL",
+            AberrationCorrection::NONE,
+            "EARTH",
+            &mut control,
+            &confine,
+        )
+        .unwrap();
+        drop(control);
+        assert!(calls > 0);
+        assert_eq!(output.window_cardinality().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_search_callback_panic() {
+        load_test_data();
+        load_moon_constants();
+        let mut control = SearchControl::new(|_| panic!("step failed"));
+        let confine = Window::from_intervals(&[(0.0, 86400.0)]).unwrap();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            occultation_search(
+                OccultationType::Any,
+                "MOON",
+                &ShapeMethod::ellipsoid(),
+                "IAU_MOON",
+                "SUN",
+                &ShapeMethod::point(),
+                "NULL",
+                AberrationCorrection::NONE,
+                "EARTH",
+                &mut control,
+                &confine,
+            )
+            .map(drop)
+        }));
+        assert_eq!(
+            *result.unwrap_err().downcast::<&str>().unwrap(),
+            "step failed"
+        );
+        get_last_error().unwrap();
+    }
+
+    #[test]
+    fn test_search_control_output_size() {
+        let confine = Window::from_intervals(&[(0.0, 100.0)]).unwrap();
+        let control = SearchControl::constant(Seconds(10.0));
+        assert_eq!(control.output_size(&confine).unwrap(), 22);
+        let control = SearchControl::new(|_| Seconds(10.0)).max_output_size(1000);
+        assert_eq!(control.output_size(&confine).unwrap(), 1000);
+        let control = control.min_step(Seconds(50.0));
+        assert_eq!(control.output_size(&confine).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_estimate_output_size() {
+        let confine = Window::from_intervals(&[(0.0, 100.0), (200.0, 250.0)]).unwrap();
+        // 10 + 1 and 5 + 1 intervals
+        assert_eq!(
+            estimate_output_size(&confine, Seconds(10.0), 1000).unwrap(),
+            34
+        );
+        assert_eq!(
+            estimate_output_size(&confine, Seconds(10.0), 20).unwrap(),
+            20
+        );
+        assert_eq!(
+            estimate_output_size(&confine, Seconds(0.0), 1000).unwrap(),
+            1000
+        );
+        let empty = Window::new_double(0);
+        assert_eq!(estimate_output_size(&empty, Seconds(1.0), 1000).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_separation_search_output_too_small() {
+        load_test_data();
+        // The Moon-Sun separation as seen from Earth drops below 90 degrees once per synodic month,
+        // so twice in the 60 day window
+        let confine = Window::from_intervals(&[(0.0, 86400.0 * 60.0)]).unwrap();
+        let search = SeparationSearch::new(
+            "MOON",
+            "SUN",
+            "EARTH",
+            RelationalOperator::LT,
+            Radians(std::f64::consts::FRAC_PI_2),
+            Seconds(86400.0),
+        );
+        let mut output = search.max_output_size(2).search(&confine);
+        assert!(matches!(
+            output,
+            Err(OutputWindowError::TooSmall { size: 2, .. })
+        ));
+
+        let search = SeparationSearch::new(
+            "MOON",
+            "SUN",
+            "EARTH",
+            RelationalOperator::LT,
+            Radians(std::f64::consts::FRAC_PI_2),
+            Seconds(86400.0),
+        );
+        output = search.search(&confine);
+        assert!(output.unwrap().window_cardinality().unwrap() >= 2);
+    }
+
+    #[test]
+    fn test_separation_search() {
+        load_test_data();
+        let confine = Window::from_intervals(&[(0.0, 86400.0 * 30.0)]).unwrap();
+        let output = separation_search(
+            "MOON",
+            Shape::Point,
+            "NULL",
+            "SUN",
+            Shape::Point,
+            "NULL",
+            AberrationCorrection::NONE,
+            "EARTH",
+            RelationalOperator::LT,
+            Radians(std::f64::consts::FRAC_PI_2),
+            Radians(0.0),
+            Seconds(86400.0),
+            SeparationSearch::DEFAULT_INTERVALS,
+            &confine,
+        )
+        .unwrap();
+        assert!(output.window_cardinality().unwrap() >= 1);
+        for i in 0..output.window_cardinality().unwrap() as usize {
+            let (left, right) = output.window_interval(i).unwrap();
+            assert!(0.0 <= left && left <= right && right <= 86400.0 * 30.0);
+        }
+        // The confinement window is left unchanged
+        assert_eq!(confine.window_interval(0).unwrap(), (0.0, 86400.0 * 30.0));
+    }
 }
@@ -0,0 +1,375 @@
+//! Ground tracks, the path of the sub-observer point over the surface of a target body, and
+//! detection of when the track crosses given latitudes or longitudes.
+//!
+//! This is intended for planning, such as finding when a spacecraft passes over a region of the
+//! Earth. The track is sampled at a fixed step and crossings are found by linear interpolation
+//! between samples, so the step should be small compared to the time taken to cross the region
+//! of interest.
+use crate::body::BodyRef;
+use crate::cell::Window;
+use crate::common::AberrationCorrection;
+use crate::coordinates::{Latitudinal, Rectangular};
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::geometry::body_fixed_frame;
+use crate::string::{static_spice_str, StaticSpiceStr};
+use crate::time::Et;
+use crate::units::{Kilometers, Radians, Seconds};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::SpiceDouble;
+use std::f64::consts::{PI, TAU};
+
+/// A sample of a ground track.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GroundTrackPoint {
+    pub et: Et,
+    /// The sub-observer point in the body-fixed frame of the target.
+    pub point: Rectangular,
+    /// The planetocentric coordinates of the sub-observer point.
+    pub coordinates: Latitudinal,
+    /// The distance from the observer to the sub-observer point.
+    pub altitude: Kilometers,
+}
+
+/// A crossing of a latitude or longitude by a [GroundTrack].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Crossing {
+    /// The estimated time of the crossing.
+    pub et: Et,
+    /// True if the coordinate was increasing (moving north or east) at the crossing.
+    pub increasing: bool,
+}
+
+/// The ground track of an observer over a target body, sampled over a window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroundTrack {
+    segments: Vec<Vec<GroundTrackPoint>>,
+}
+
+impl GroundTrack {
+    /// Sample the sub-observer point (using the `NEAR POINT/ELLIPSOID` method) at intervals of
+    /// `step` within each interval of `confine`, the end of every interval is always sampled.
+    ///
+    /// See [subpnt_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/subpnt_c.html).
+    pub fn sample<'t, 'o, T, O>(
+        target: T,
+        observer: O,
+        aberration_correction: AberrationCorrection,
+        confine: &Window,
+        step: Seconds,
+    ) -> Result<Self, Error>
+    where
+        T: Into<BodyRef<'t>>,
+        O: Into<BodyRef<'o>>,
+    {
+        let (target, observer) = (target.into(), observer.into());
+        let frame = body_fixed_frame(target.to_id()?)?;
+        let (target, observer) = (target.to_string_param(), observer.to_string_param());
+        with_spice_lock_or_panic(|| {
+            if step.0 <= 0.0 {
+                set_error_message(format!("The step size {} must be positive", step.0));
+                signal_error("SPICE(INVALIDSTEP)");
+                get_last_error()?;
+            }
+            let method = static_spice_str!("NEAR POINT/ELLIPSOID");
+            let mut segments = Vec::new();
+            for i in 0..confine.window_cardinality()? as usize {
+                let (start, stop) = confine.window_interval(i)?;
+                let samples = ((stop - start) / step.0).ceil() as usize;
+                let mut segment = Vec::with_capacity(samples + 1);
+                for j in 0..=samples {
+                    let et = (start + j as SpiceDouble * step.0).min(stop);
+                    let mut point = [0.0; 3];
+                    let (mut epoch, mut surface_vector) = (0.0, [0.0; 3]);
+                    unsafe {
+                        cspice_sys::subpnt_c(
+                            method.as_mut_ptr(),
+                            target.as_mut_ptr()?,
+                            et,
+                            frame.as_mut_ptr()?,
+                            aberration_correction.as_spice_char(),
+                            observer.as_mut_ptr()?,
+                            point.as_mut_ptr(),
+                            &mut epoch,
+                            surface_vector.as_mut_ptr(),
+                        )
+                    };
+                    get_last_error()?;
+                    let point = Rectangular::from(point);
+                    segment.push(GroundTrackPoint {
+                        et: Et(et),
+                        point,
+                        coordinates: point.into(),
+                        altitude: Kilometers(
+                            surface_vector
+                                .iter()
+                                .map(|x| x * x)
+                                .sum::<SpiceDouble>()
+                                .sqrt(),
+                        ),
+                    });
+                }
+                segments.push(segment);
+            }
+            Ok(Self { segments })
+        })
+    }
+
+    /// The samples, grouped by the interval of the window they were taken in.
+    pub fn segments(&self) -> &[Vec<GroundTrackPoint>] {
+        &self.segments
+    }
+
+    /// Iterate over all the samples.
+    pub fn points(&self) -> impl Iterator<Item = &GroundTrackPoint> {
+        self.segments.iter().flatten()
+    }
+
+    /// Find when the track crosses a latitude.
+    pub fn latitude_crossings(&self, latitude: Radians) -> Vec<Crossing> {
+        self.crossings(|p| p.coordinates.latitude - latitude.0)
+    }
+
+    /// Find when the track crosses a meridian of longitude.
+    ///
+    /// Longitudes are compared modulo a full turn, crossings of the opposite meridian are
+    /// ignored.
+    pub fn longitude_crossings(&self, longitude: Radians) -> Vec<Crossing> {
+        self.crossings(|p| {
+            let difference = (p.coordinates.longitude - longitude.0).rem_euclid(TAU);
+            if difference > PI {
+                difference - TAU
+            } else {
+                difference
+            }
+        })
+    }
+
+    /// Convert to a GeoJSON `FeatureCollection` for display on a web map.
+    ///
+    /// Each segment becomes a `LineString` feature, split where it crosses the antimeridian.
+    /// Positions are `[longitude, latitude, altitude]` in degrees and meters, and the UTC time of
+    /// each position is given in the `times` property. The latitudes are planetocentric, which
+    /// for the Earth differs slightly from the geodetic latitudes expected by most web maps.
+    ///
+    /// Requires a leapseconds kernel to be loaded.
+    #[cfg(feature = "geojson")]
+    pub fn to_geojson(&self) -> Result<serde_json::Value, Error> {
+        use serde_json::json;
+
+        let mut features = Vec::new();
+        for segment in &self.segments {
+            let mut lines: Vec<Vec<&GroundTrackPoint>> = Vec::new();
+            for (i, point) in segment.iter().enumerate() {
+                let wrapped = i > 0
+                    && (point.coordinates.longitude - segment[i - 1].coordinates.longitude).abs()
+                        > PI;
+                match lines.last_mut() {
+                    Some(line) if !wrapped => line.push(point),
+                    _ => lines.push(vec![point]),
+                }
+            }
+            for line in lines {
+                let coordinates: Vec<_> = line
+                    .iter()
+                    .map(|p| {
+                        json!([
+                            p.coordinates.longitude.to_degrees(),
+                            p.coordinates.latitude.to_degrees(),
+                            p.altitude.0 * 1000.0,
+                        ])
+                    })
+                    .collect();
+                let times = line
+                    .iter()
+                    .map(|p| utc(p.et))
+                    .collect::<Result<Vec<_>, _>>()?;
+                features.push(json!({
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": coordinates,
+                    },
+                    "properties": {
+                        "times": times,
+                    },
+                }));
+            }
+        }
+        Ok(json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))
+    }
+
+    /// Write the samples as CSV, with a header row and columns of UTC time, latitude and
+    /// longitude in degrees, and altitude in kilometers.
+    ///
+    /// Requires a leapseconds kernel to be loaded.
+    #[cfg(feature = "geojson")]
+    pub fn write_csv<W: std::io::Write>(&self, mut writer: W) -> Result<(), ExportError> {
+        writeln!(writer, "time,latitude,longitude,altitude")?;
+        for p in self.points() {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                utc(p.et)?,
+                p.coordinates.latitude.to_degrees(),
+                p.coordinates.longitude.to_degrees(),
+                p.altitude.0
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Find where `f` changes sign between consecutive samples within a segment, interpolating
+    /// linearly to estimate the time of the zero.
+    fn crossings<F: Fn(&GroundTrackPoint) -> SpiceDouble>(&self, f: F) -> Vec<Crossing> {
+        let mut crossings = Vec::new();
+        for segment in &self.segments {
+            for pair in segment.windows(2) {
+                let (a, b) = (f(&pair[0]), f(&pair[1]));
+                let increasing = match (a < 0.0, b < 0.0) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    _ => continue,
+                };
+                // A jump of more than half a turn is a wrap around, not a crossing
+                if (b - a).abs() >= PI {
+                    continue;
+                }
+                let fraction = a / (a - b);
+                let et = pair[0].et.0 + fraction * (pair[1].et.0 - pair[0].et.0);
+                crossings.push(Crossing {
+                    et: Et(et),
+                    increasing,
+                });
+            }
+        }
+        crossings
+    }
+}
+
+/// Error returned by [GroundTrack::write_csv()].
+#[cfg(feature = "geojson")]
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Spice(#[from] Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Format as an ISO 8601 UTC time, e.g. `2000-01-01T11:58:55.816Z`.
+#[cfg(feature = "geojson")]
+fn utc(et: Et) -> Result<String, Error> {
+    Ok(et.time_out_auto("YYYY-MM-DDTHR:MN:SC.### ::UTC")? + "Z")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    const DAY: SpiceDouble = 86400.0;
+
+    fn sub_earth_track(confine: &Window) -> GroundTrack {
+        load_test_data();
+        load_moon_constants();
+        GroundTrack::sample(
+            "MOON",
+            "EARTH",
+            AberrationCorrection::LT_S,
+            confine,
+            Seconds(3600.0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sample() {
+        let confine = Window::from_intervals(&[(0.0, DAY), (2.0 * DAY, 2.0 * DAY + 1.5)]).unwrap();
+        let track = sub_earth_track(&confine);
+        let lengths: Vec<_> = track.segments().iter().map(Vec::len).collect();
+        assert_eq!(lengths, vec![25, 2]);
+        assert_eq!(track.points().last().unwrap().et, Et(2.0 * DAY + 1.5));
+        for p in track.points() {
+            assert!((p.coordinates.radius - 1737.4).abs() < 1e-6);
+            assert!((350_000.0..410_000.0).contains(&p.altitude.0));
+        }
+
+        let err = GroundTrack::sample(
+            "MOON",
+            "EARTH",
+            AberrationCorrection::LT_S,
+            &confine,
+            Seconds(0.0),
+        )
+        .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(INVALIDSTEP)");
+    }
+
+    #[test]
+    fn test_crossings() {
+        // The sub-Earth point on the Moon librates about the equator and prime meridian
+        let confine = Window::from_intervals(&[(0.0, 60.0 * DAY)]).unwrap();
+        let track = sub_earth_track(&confine);
+        let fine = |et: Et| {
+            let window = Window::from_intervals(&[(et.0, et.0)]).unwrap();
+            sub_earth_track(&window).segments()[0][0].coordinates
+        };
+
+        let latitude = track.latitude_crossings(Radians(0.0));
+        assert!((3..=5).contains(&latitude.len()), "{latitude:?}");
+        for pair in latitude.windows(2) {
+            assert_ne!(pair[0].increasing, pair[1].increasing);
+        }
+        for crossing in &latitude {
+            assert!(fine(crossing.et).latitude.abs() < 1e-4);
+        }
+
+        let longitude = track.longitude_crossings(Radians(0.0));
+        assert!((3..=5).contains(&longitude.len()), "{longitude:?}");
+        for crossing in &longitude {
+            assert!(fine(crossing.et).longitude.abs() < 1e-4);
+        }
+        assert!(track.longitude_crossings(Radians(PI)).is_empty());
+    }
+
+    #[cfg(feature = "geojson")]
+    #[test]
+    fn test_export() {
+        let confine = Window::from_intervals(&[(0.0, 7200.0), (DAY, DAY)]).unwrap();
+        let track = sub_earth_track(&confine);
+
+        let geojson = track.to_geojson().unwrap();
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0]["geometry"]["type"], "LineString");
+        assert_eq!(
+            features[0]["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            features[0]["properties"]["times"][0],
+            "2000-01-01T11:58:55.816Z"
+        );
+        let first = track.points().next().unwrap();
+        assert_eq!(
+            features[0]["geometry"]["coordinates"][0][2],
+            first.altitude.0 * 1000.0
+        );
+
+        let mut csv = Vec::new();
+        track.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "time,latitude,longitude,altitude");
+        assert!(lines[1].starts_with("2000-01-01T11:58:55.816Z,"));
+        assert!(lines[4].starts_with("2000-01-02T11:58:55.816Z,"));
+    }
+}
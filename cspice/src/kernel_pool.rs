@@ -0,0 +1,334 @@
+//! Functions for working with the kernel pool, which holds the variables loaded from text
+//! kernels.
+//!
+//! See [Kernel required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/kernel.html).
+use crate::error::get_last_error;
+use crate::string::SpiceString;
+use crate::string::StringParam;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    cvpool_c, gcpool_c, gipool_c, gnpool_c, pcpool_c, pdpool_c, pipool_c, swpool_c, SpiceBoolean,
+    SpiceChar, SpiceDouble, SpiceInt, SPICETRUE,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The maximum length of a kernel pool variable name.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// A watcher for changes to a set of kernel pool variables, created by [watch()].
+#[derive(Debug)]
+pub struct Watcher {
+    agent: SpiceString,
+}
+
+/// Watch a set of kernel pool variables for changes, such as when a kernel is furnished or
+/// unloaded, or the variables are set directly.
+///
+/// Each watcher registers a new agent with SPICE, these can't be removed so avoid creating
+/// watchers in a loop.
+///
+/// See [swpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/swpool_c.html).
+pub fn watch<I, S>(vars: I) -> Result<Watcher, Error>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    static NEXT_AGENT: AtomicUsize = AtomicUsize::new(0);
+    let agent = SpiceString::from(format!(
+        "CSPICE_RS_WATCHER_{}",
+        NEXT_AGENT.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let vars: Vec<_> = vars.into_iter().collect();
+    let length = vars
+        .iter()
+        .map(|v| v.as_ref().len())
+        .max()
+        .unwrap_or(0)
+        .max(MAX_NAME_LENGTH)
+        + 1;
+    let mut names: Vec<SpiceChar> = vec![0; length * vars.len()];
+    for (name, chunk) in vars.iter().zip(names.chunks_mut(length)) {
+        for (c, b) in chunk.iter_mut().zip(name.as_ref().bytes()) {
+            *c = b as SpiceChar;
+        }
+    }
+
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            swpool_c(
+                agent.as_mut_ptr()?,
+                vars.len() as SpiceInt,
+                length as SpiceInt,
+                names.as_mut_ptr().cast(),
+            )
+        };
+        get_last_error()
+    })?;
+    Ok(Watcher { agent })
+}
+
+impl Watcher {
+    /// The name of the agent registered with SPICE.
+    pub fn agent(&self) -> &SpiceString {
+        &self.agent
+    }
+
+    /// Check whether any of the watched variables have been updated since the last check. The
+    /// first check after creating the watcher always returns true.
+    ///
+    /// Note that a variable is considered updated whenever it is assigned, even if the value is
+    /// unchanged.
+    ///
+    /// See [cvpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/cvpool_c.html).
+    pub fn has_changed(&self) -> Result<bool, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut update: SpiceBoolean = 0;
+            unsafe { cvpool_c(self.agent.as_mut_ptr()?, &mut update) };
+            get_last_error()?;
+            Ok(update == SPICETRUE as SpiceBoolean)
+        })
+    }
+}
+
+/// Get the names of the kernel pool variables matching a template, where `*` matches any
+/// substring and `%` matches any single character, e.g. `"BODY399_*"`.
+///
+/// See [gnpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gnpool_c.html) and
+/// [matches_wildcard()](crate::string::matches_wildcard).
+pub fn names<'t, T: Into<StringParam<'t>>>(template: T) -> Result<Vec<String>, Error> {
+    const ROOM: usize = 100;
+    let length = MAX_NAME_LENGTH + 1;
+    let template = template.into();
+    let mut buffer: Vec<SpiceChar> = vec![0; ROOM * length];
+    let mut names = Vec::new();
+    with_spice_lock_or_panic(|| loop {
+        let (mut n, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+        unsafe {
+            gnpool_c(
+                template.as_mut_ptr()?,
+                names.len() as SpiceInt,
+                ROOM as SpiceInt,
+                length as SpiceInt,
+                &mut n,
+                buffer.as_mut_ptr().cast(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        if found != SPICETRUE as SpiceBoolean {
+            return Ok(names);
+        }
+        names.extend(
+            buffer
+                .chunks(length)
+                .take(n as usize)
+                .map(|chunk| SpiceString::from_buffer(chunk.to_vec()).to_string()),
+        );
+        if (n as usize) < ROOM {
+            return Ok(names);
+        }
+    })
+}
+
+/// Insert a numeric variable into the kernel pool, replacing any existing values.
+///
+/// See [pdpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pdpool_c.html).
+pub fn put_numbers<'n, N: Into<StringParam<'n>>>(
+    name: N,
+    values: &[SpiceDouble],
+) -> Result<(), Error> {
+    let mut values = values.to_vec();
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            pdpool_c(
+                name.into().as_mut_ptr()?,
+                values.len() as SpiceInt,
+                values.as_mut_ptr(),
+            )
+        };
+        get_last_error()
+    })
+}
+
+/// Insert an integer variable into the kernel pool, replacing any existing values.
+///
+/// See [pipool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pipool_c.html).
+pub fn put_integers<'n, N: Into<StringParam<'n>>>(
+    name: N,
+    values: &[SpiceInt],
+) -> Result<(), Error> {
+    let mut values = values.to_vec();
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            pipool_c(
+                name.into().as_mut_ptr()?,
+                values.len() as SpiceInt,
+                values.as_mut_ptr(),
+            )
+        };
+        get_last_error()
+    })
+}
+
+/// Insert a character variable into the kernel pool, replacing any existing values.
+///
+/// See [pcpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pcpool_c.html).
+pub fn put_strings<'n, N, S>(name: N, values: &[S]) -> Result<(), Error>
+where
+    N: Into<StringParam<'n>>,
+    S: AsRef<str>,
+{
+    let length = values.iter().map(|v| v.as_ref().len()).max().unwrap_or(0) + 1;
+    let mut buffer: Vec<SpiceChar> = vec![0; values.len() * length];
+    for (chunk, value) in buffer.chunks_mut(length).zip(values) {
+        for (c, b) in chunk.iter_mut().zip(value.as_ref().bytes()) {
+            *c = b as SpiceChar;
+        }
+    }
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            pcpool_c(
+                name.into().as_mut_ptr()?,
+                values.len() as SpiceInt,
+                length as SpiceInt,
+                buffer.as_mut_ptr().cast(),
+            )
+        };
+        get_last_error()
+    })
+}
+
+/// Get the first value of an integer (or numeric) kernel pool variable, returns `None` if the
+/// variable is not present.
+///
+/// See [gipool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gipool_c.html).
+pub(crate) fn get_integer<'n, N: Into<StringParam<'n>>>(
+    name: N,
+) -> Result<Option<SpiceInt>, Error> {
+    with_spice_lock_or_panic(|| {
+        let (mut n, mut value, mut found): (SpiceInt, SpiceInt, SpiceBoolean) = (0, 0, 0);
+        unsafe {
+            gipool_c(
+                name.into().as_mut_ptr()?,
+                0,
+                1,
+                &mut n,
+                &mut value,
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok((found == SPICETRUE as SpiceBoolean && n > 0).then_some(value))
+    })
+}
+
+/// Get the first value of a string kernel pool variable, returns `None` if the variable is not
+/// present.
+///
+/// See [gcpool_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/gcpool_c.html).
+pub(crate) fn get_string<'n, N: Into<StringParam<'n>>>(name: N) -> Result<Option<String>, Error> {
+    // Kernel pool string values are limited to 80 characters
+    let mut buffer: Vec<SpiceChar> = vec![0; 81];
+    with_spice_lock_or_panic(|| {
+        let (mut n, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+        unsafe {
+            gcpool_c(
+                name.into().as_mut_ptr()?,
+                0,
+                1,
+                buffer.len() as SpiceInt,
+                &mut n,
+                buffer.as_mut_ptr().cast(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        Ok((found == SPICETRUE as SpiceBoolean && n > 0)
+            .then(|| SpiceString::from_buffer(buffer).to_string()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string::{static_spice_str, StaticSpiceStr};
+
+    #[test]
+    fn test_watch() {
+        let watcher = watch(["CSPICE_RS_WATCH_A", "CSPICE_RS_WATCH_B"]).unwrap();
+        let other = watch(["CSPICE_RS_WATCH_C"]).unwrap();
+        assert_ne!(watcher.agent(), other.agent());
+        assert!(watcher.has_changed().unwrap());
+        assert!(!watcher.has_changed().unwrap());
+        assert!(other.has_changed().unwrap());
+
+        with_spice_lock_or_panic(|| unsafe {
+            let mut values = [1.0];
+            cspice_sys::pdpool_c(
+                static_spice_str!("CSPICE_RS_WATCH_B").as_mut_ptr(),
+                1,
+                values.as_mut_ptr(),
+            );
+        });
+        assert!(watcher.has_changed().unwrap());
+        assert!(!watcher.has_changed().unwrap());
+        assert!(!other.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_names() {
+        crate::tests::load_moon_constants();
+        let mut names = names("BODY301_*").unwrap();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "BODY301_PM",
+                "BODY301_POLE_DEC",
+                "BODY301_POLE_RA",
+                "BODY301_RADII"
+            ]
+        );
+        assert!(super::names("CSPICE_RS_NO_SUCH_*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get() {
+        with_spice_lock_or_panic(|| unsafe {
+            let mut values = [42.0];
+            cspice_sys::pdpool_c(
+                static_spice_str!("CSPICE_RS_GET_INT").as_mut_ptr(),
+                1,
+                values.as_mut_ptr(),
+            );
+            let mut value = *b"HELLO\0";
+            cspice_sys::pcpool_c(
+                static_spice_str!("CSPICE_RS_GET_STRING").as_mut_ptr(),
+                1,
+                value.len() as SpiceInt,
+                value.as_mut_ptr().cast(),
+            );
+        });
+        assert_eq!(get_integer("CSPICE_RS_GET_INT").unwrap(), Some(42));
+        assert_eq!(get_integer("CSPICE_RS_GET_MISSING").unwrap(), None);
+        assert_eq!(
+            get_string("CSPICE_RS_GET_STRING").unwrap().as_deref(),
+            Some("HELLO")
+        );
+        assert_eq!(get_string("CSPICE_RS_GET_MISSING").unwrap(), None);
+    }
+
+    #[test]
+    fn test_put() {
+        put_numbers("CSPICE_RS_PUT_NUMBERS", &[2.0, 3.0]).unwrap();
+        put_integers("CSPICE_RS_PUT_INTEGERS", &[7]).unwrap();
+        put_strings("CSPICE_RS_PUT_STRINGS", &["A", "LONGER"]).unwrap();
+        assert_eq!(get_integer("CSPICE_RS_PUT_NUMBERS").unwrap(), Some(2));
+        assert_eq!(get_integer("CSPICE_RS_PUT_INTEGERS").unwrap(), Some(7));
+        assert_eq!(
+            get_string("CSPICE_RS_PUT_STRINGS").unwrap().as_deref(),
+            Some("A")
+        );
+    }
+}
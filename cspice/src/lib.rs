@@ -1,17 +1,55 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod body;
 pub mod cell;
+pub mod ck;
 pub mod common;
+pub mod compare;
+pub mod convert;
 pub mod coordinates;
+mod core_types;
+pub mod daf;
 pub mod data;
+pub mod diagnostics;
+pub mod dsk;
+pub mod dynamic_frame;
+pub mod elements;
 pub mod error;
+pub mod events;
+pub mod frame;
+pub mod geometry;
 pub mod gf;
+pub mod groundtrack;
+pub mod kernel_pool;
+pub mod lighttime;
+pub mod matrix;
+pub mod naif_ids;
+pub mod orbits;
+pub mod pck;
+#[cfg(feature = "perf-counters")]
+pub mod perf;
+pub mod plane;
+pub mod provider;
+pub mod quaternion;
+pub mod search;
+pub mod snapshot;
 pub mod spk;
+pub mod stars;
 pub mod string;
 pub mod time;
+pub mod timeline;
+pub mod topocentric;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod units;
 pub mod vector;
 
 use crate::error::set_error_defaults;
 pub use crate::error::Error;
+#[cfg(feature = "perf-counters")]
+pub use crate::perf::spice_stats;
 use crate::string::SpiceString;
+pub use cspice_types;
 use parking_lot::{ReentrantMutex, ReentrantMutexGuard};
 use std::cell::RefCell;
 use std::fmt::Debug;
@@ -21,10 +59,24 @@ use thiserror::Error;
 // Boolean indicates if library has been initialised
 static SPICE_LOCK: ReentrantMutex<RefCell<bool>> = ReentrantMutex::new(RefCell::new(false));
 
+#[cfg_attr(any(feature = "perf-counters", feature = "tracing"), track_caller)]
 pub(crate) fn with_spice_lock_or_panic<R, F>(f: F) -> R
 where
     F: FnOnce() -> R,
 {
+    #[cfg(feature = "tracing")]
+    let f = {
+        let location = std::panic::Location::caller();
+        move || trace::in_call_span(location, f)
+    };
+    #[cfg(feature = "perf-counters")]
+    let f = {
+        let site = std::panic::Location::caller();
+        move || {
+            let _guard = perf::enter_call_site(site);
+            f()
+        }
+    };
     match try_with_spice_lock(f) {
         Ok(k) => k,
         Err(e) => {
@@ -40,7 +92,14 @@ pub fn try_with_spice_lock<R, F>(f: F) -> Result<R, SpiceLockError>
 where
     F: FnOnce() -> R,
 {
-    let guard = SPICE_LOCK.try_lock().ok_or(SpiceLockError)?;
+    let guard = SPICE_LOCK.try_lock();
+    #[cfg(feature = "tracing")]
+    if guard.is_none() {
+        tracing::warn!("SPICE lock is held by another thread");
+    }
+    let guard = guard.ok_or(SpiceLockError)?;
+    #[cfg(feature = "perf-counters")]
+    perf::record_lock_acquisition();
     initialise_library(&guard);
     Ok(f())
 }
@@ -53,7 +112,16 @@ pub fn with_spice_lock<R, F>(f: F) -> R
 where
     F: FnOnce() -> R,
 {
+    #[cfg(feature = "tracing")]
+    let start = std::time::Instant::now();
     let guard = SPICE_LOCK.lock();
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        wait_us = start.elapsed().as_micros() as u64,
+        "Acquired SPICE lock"
+    );
+    #[cfg(feature = "perf-counters")]
+    perf::record_lock_acquisition();
     initialise_library(&guard);
     f()
 }
@@ -68,6 +136,24 @@ fn initialise_library(guard: &ReentrantMutexGuard<'static, RefCell<bool>>) {
 #[derive(Debug)]
 pub struct SpiceLock(ReentrantMutexGuard<'static, RefCell<bool>>);
 
+impl SpiceLock {
+    /// Acquire the SPICE lock, blocking until it is available, and hold it until dropped.
+    pub(crate) fn acquire() -> Self {
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+        let guard = SPICE_LOCK.lock();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            wait_us = start.elapsed().as_micros() as u64,
+            "Acquired SPICE lock"
+        );
+        #[cfg(feature = "perf-counters")]
+        perf::record_lock_acquisition();
+        initialise_library(&guard);
+        Self(guard)
+    }
+}
+
 /// Error returned from [try_with_spice_lock()].
 #[derive(Debug, Clone, Error)]
 #[cfg_attr(not(test), error("SPICE is already in use by another thread. If multi-threaded use is intentional wrap the call using `with_spice_lock()`."))]
@@ -77,6 +163,7 @@ pub struct SpiceLockError;
 #[cfg(test)]
 mod tests {
     use crate::data::furnish;
+    use crate::string::{static_spice_str, StaticSpiceStr};
     use std::path::PathBuf;
     use std::sync::Once;
 
@@ -88,4 +175,33 @@ mod tests {
             furnish(data_dir.join("testkernel.txt").to_string_lossy()).unwrap();
         });
     }
+
+    /// Insert the Moon's radii and IAU rotation constants into the kernel pool, since the test
+    /// data doesn't include a PCK
+    pub fn load_moon_constants() {
+        crate::with_spice_lock_or_panic(|| unsafe {
+            let mut radii = [1737.4, 1737.4, 1737.4];
+            cspice_sys::pdpool_c(
+                static_spice_str!("BODY301_RADII").as_mut_ptr(),
+                3,
+                radii.as_mut_ptr(),
+            );
+            for (name, mut values) in [
+                (
+                    static_spice_str!("BODY301_POLE_RA"),
+                    [269.9949, 0.0031, 0.0],
+                ),
+                (
+                    static_spice_str!("BODY301_POLE_DEC"),
+                    [66.5392, 0.0130, 0.0],
+                ),
+                (
+                    static_spice_str!("BODY301_PM"),
+                    [38.3213, 13.17635815, -1.4e-12],
+                ),
+            ] {
+                cspice_sys::pdpool_c(name.as_mut_ptr(), 3, values.as_mut_ptr());
+            }
+        });
+    }
 }
@@ -0,0 +1,179 @@
+//! Light time correction for arbitrary ephemerides.
+//!
+//! This allows the same light time correction that SPICE applies to SPK data to be applied to
+//! states from any source, such as a table or a locally propagated spacecraft.
+use crate::spk::State;
+use crate::time::Et;
+use cspice_sys::SpiceDouble;
+use std::convert::Infallible;
+
+/// Speed of light in a vacuum (km/s), the same value as returned by
+/// [clight_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/clight_c.html).
+pub const SPEED_OF_LIGHT: SpiceDouble = 299792.458;
+
+/// Maximum number of iterations used to converge the light time.
+pub const MAX_ITERATIONS: usize = 5;
+
+/// The direction of the signal relative to the observer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// The observer receives a signal emitted by the target at `et - light_time`.
+    /// Equivalent to the "LT" / "CN" aberration corrections.
+    Reception,
+    /// The observer transmits a signal that arrives at the target at `et + light_time`.
+    /// Equivalent to the "XLT" / "XCN" aberration corrections.
+    Transmission,
+}
+
+/// The output of [solve()].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct LightTimeSolution {
+    /// State of the target relative to the observer, corrected for light time.
+    pub state: State,
+    /// One way light time between the observer and target (seconds).
+    pub light_time: SpiceDouble,
+    /// Rate of change of the light time with respect to the observer's epoch.
+    pub light_time_rate: SpiceDouble,
+}
+
+/// Solve the light time equation using a converged Newtonian iteration (equivalent to the "CN"
+/// and "XCN" aberration corrections without stellar aberration).
+///
+/// `target` must return the state of the target at a given epoch, and `observer` is the state
+/// of the observer at `et`. Both must be relative to the same inertial origin (e.g. the solar
+/// system barycenter) and in the same inertial frame.
+pub fn solve<F>(mut target: F, observer: &State, et: Et, direction: Direction) -> LightTimeSolution
+where
+    F: FnMut(Et) -> State,
+{
+    let result: Result<_, Infallible> = try_solve(|et| Ok(target(et)), observer, et, direction);
+    match result {
+        Ok(solution) => solution,
+        Err(e) => match e {},
+    }
+}
+
+/// A fallible version of [solve()], returning the first error from `target`.
+pub fn try_solve<F, E>(
+    mut target: F,
+    observer: &State,
+    et: Et,
+    direction: Direction,
+) -> Result<LightTimeSolution, E>
+where
+    F: FnMut(Et) -> Result<State, E>,
+{
+    let sign = match direction {
+        Direction::Reception => -1.0,
+        Direction::Transmission => 1.0,
+    };
+
+    // Initial guess uses the geometric state of the target
+    let mut target_state = target(et)?;
+    let mut light_time = (target_state - *observer).range() / SPEED_OF_LIGHT;
+
+    for _ in 0..MAX_ITERATIONS {
+        target_state = target(Et(et.0 + sign * light_time))?;
+        let previous = light_time;
+        light_time = (target_state - *observer).range() / SPEED_OF_LIGHT;
+        if (light_time - previous).abs() <= SpiceDouble::EPSILON * light_time.abs().max(1.0) {
+            break;
+        }
+    }
+
+    // Differentiate the light time equation, the target state was evaluated at (et +/- lt)
+    let relative = target_state - *observer;
+    let distance = relative.range();
+    let unit: [SpiceDouble; 3] = if distance == 0.0 {
+        [0.0; 3]
+    } else {
+        [
+            relative.position.x / distance,
+            relative.position.y / distance,
+            relative.position.z / distance,
+        ]
+    };
+    let dot = |v: &[SpiceDouble; 3]| -> SpiceDouble {
+        unit.iter().zip(v.iter()).map(|(a, b)| a * b).sum()
+    };
+    let target_velocity = dot(&target_state.velocity.0) / SPEED_OF_LIGHT;
+    let relative_velocity = dot(&relative.velocity.0) / SPEED_OF_LIGHT;
+    let light_time_rate = relative_velocity / (1.0 - sign * target_velocity);
+
+    // The target's velocity must be scaled by d(et +/- lt)/d(et)
+    let scale = 1.0 + sign * light_time_rate;
+    let mut state = relative;
+    for i in 0..3 {
+        state.velocity[i] = target_state.velocity[i] * scale - observer.velocity[i];
+    }
+
+    Ok(LightTimeSolution {
+        state,
+        light_time,
+        light_time_rate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::AberrationCorrection;
+    use crate::spk::easier_reader;
+    use crate::tests::load_test_data;
+
+    #[test]
+    fn test_stationary() {
+        let target = State::from([10.0 * SPEED_OF_LIGHT, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let solution = solve(|_| target, &State::default(), Et(0.0), Direction::Reception);
+        assert_eq!(solution.light_time, 10.0);
+        assert_eq!(solution.light_time_rate, 0.0);
+        assert_eq!(solution.state, target);
+    }
+
+    #[test]
+    fn test_receding() {
+        // Target receding at 0.001c, starting 10 light seconds away at et 0
+        let v = 0.001 * SPEED_OF_LIGHT;
+        let target =
+            |et: Et| State::from([10.0 * SPEED_OF_LIGHT + v * et.0, 0.0, 0.0, v, 0.0, 0.0]);
+        let observer = State::default();
+
+        // Reception at et 100: c lt = 10c + 0.001c (100 - lt) => lt = 10.1 / 1.001
+        let solution = solve(target, &observer, Et(100.0), Direction::Reception);
+        assert!((solution.light_time - 10.1 / 1.001).abs() < 1e-12);
+        assert!((solution.light_time_rate - 0.001 / 1.001).abs() < 1e-12);
+        assert!((solution.state.velocity[0] - v / 1.001).abs() < 1e-9);
+
+        // Transmission at et 100: c lt = 10c + 0.001c (100 + lt) => lt = 10.1 / 0.999
+        let solution = solve(target, &observer, Et(100.0), Direction::Transmission);
+        assert!((solution.light_time - 10.1 / 0.999).abs() < 1e-12);
+        assert!((solution.light_time_rate - 0.001 / 0.999).abs() < 1e-12);
+        assert!((solution.state.velocity[0] - v / 0.999).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_matches_spk() {
+        load_test_data();
+        let et = Et(3600.0);
+        let state_ssb = |body: &str, et: Et| {
+            easier_reader(body, et, "J2000", AberrationCorrection::NONE, "SSB").map(|(s, _)| s)
+        };
+        let observer = state_ssb("earth", et).unwrap();
+        let solution = try_solve(
+            |et| state_ssb("moon", et),
+            &observer,
+            et,
+            Direction::Reception,
+        )
+        .unwrap();
+        let (expected, lt) =
+            easier_reader("moon", et, "J2000", AberrationCorrection::CN, "earth").unwrap();
+        assert!((solution.light_time - lt).abs() < 1e-12);
+        assert!((solution.state.position.x - expected.position.x).abs() < 1e-6);
+        assert!((solution.state.position.y - expected.position.y).abs() < 1e-6);
+        assert!((solution.state.position.z - expected.position.z).abs() < 1e-6);
+        for i in 0..3 {
+            assert!((solution.state.velocity[i] - expected.velocity[i]).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Functions for working with matrices.
+use crate::error::get_last_error;
+use crate::spk::State;
+use crate::string::StringParam;
+use crate::time::Et;
+use crate::units::Radians;
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    det_c, invert_c, invort_c, invstm_c, isrot_c, pxform_c, rav2xf_c, rotmat_c, sxform_c, trace_c,
+    twovec_c, xf2rav_c, SpiceBoolean, SpiceDouble, SpiceInt, SPICETRUE,
+};
+use derive_more::{Deref, DerefMut, From, Into};
+use std::ops::{Mul, MulAssign};
+
+/// A coordinate axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X = 1,
+    Y = 2,
+    Z = 3,
+}
+
+/// A 3x3 matrix, typically a rotation from one frame to another.
+#[derive(Copy, Clone, Debug, Default, PartialEq, From, Into, Deref, DerefMut)]
+pub struct Matrix3x3(pub [[SpiceDouble; 3]; 3]);
+
+/// Error returned when attempting to invert a singular matrix.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Matrix is singular")]
+pub struct SingularMatrix;
+
+impl Matrix3x3 {
+    /// Find the transformation to the right-handed frame having a given vector as a specified
+    /// axis and having a second given vector lying in a specified coordinate plane.
+    ///
+    /// The axes `axis` and `plane_axis` must be different, and the vectors must be linearly
+    /// independent.
+    ///
+    /// See [twovec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/twovec_c.html).
+    pub fn from_two_vectors(
+        axis_vector: &Vector3D,
+        axis: Axis,
+        plane_vector: &Vector3D,
+        plane_axis: Axis,
+    ) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut mout = [[0.0; 3]; 3];
+            unsafe {
+                twovec_c(
+                    axis_vector.as_ptr() as *mut SpiceDouble,
+                    axis as SpiceInt,
+                    plane_vector.as_ptr() as *mut SpiceDouble,
+                    plane_axis as SpiceInt,
+                    mout.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(Self(mout))
+        })
+    }
+
+    /// Return the matrix that transforms position vectors from one frame to another at a
+    /// specified epoch.
+    ///
+    /// See [pxform_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pxform_c.html).
+    pub fn from_frames<'f, 't, F, T>(from: F, to: T, et: Et) -> Result<Self, Error>
+    where
+        F: Into<StringParam<'f>>,
+        T: Into<StringParam<'t>>,
+    {
+        with_spice_lock_or_panic(|| {
+            let mut rotate = [[0.0; 3]; 3];
+            unsafe {
+                pxform_c(
+                    from.into().as_mut_ptr()?,
+                    to.into().as_mut_ptr()?,
+                    et.0,
+                    rotate.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(Self(rotate))
+        })
+    }
+
+    /// The identity matrix.
+    pub fn identity() -> Self {
+        let mut m = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Self(m)
+    }
+
+    /// Apply a rotation of `angle` about `axis` to this matrix, returning `[angle]axis * self`.
+    ///
+    /// See [rotmat_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/rotmat_c.html).
+    pub fn rotate(&self, angle: Radians, axis: Axis) -> Self {
+        with_spice_lock_or_panic(|| {
+            let mut mout = [[0.0; 3]; 3];
+            unsafe {
+                rotmat_c(
+                    self.0.as_ptr() as *mut _,
+                    angle.0,
+                    axis as SpiceInt,
+                    mout.as_mut_ptr(),
+                )
+            };
+            Self(mout)
+        })
+    }
+
+    /// Multiply this matrix by a 3-dimensional vector.
+    pub fn mul_vec(&self, v: &Vector3D) -> Vector3D {
+        let mut out = [0.0; 3];
+        for (o, row) in out.iter_mut().zip(self.0.iter()) {
+            *o = row.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+        }
+        Vector3D(out)
+    }
+
+    /// Compute the determinant of this matrix.
+    ///
+    /// See [det_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/det_c.html).
+    pub fn determinant(&self) -> SpiceDouble {
+        with_spice_lock_or_panic(|| unsafe { det_c(self.0.as_ptr() as *mut _) })
+    }
+
+    /// Compute the trace (sum of the diagonal elements) of this matrix.
+    ///
+    /// See [trace_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/trace_c.html).
+    pub fn trace(&self) -> SpiceDouble {
+        with_spice_lock_or_panic(|| unsafe { trace_c(self.0.as_ptr() as *mut _) })
+    }
+
+    /// Compute the inverse of this matrix, failing if it is singular (or too close to singular
+    /// for the inverse to be computed).
+    ///
+    /// See [invert_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/invert_c.html).
+    pub fn invert(&self) -> Result<Self, SingularMatrix> {
+        with_spice_lock_or_panic(|| {
+            let mut mout = [[0.0; 3]; 3];
+            unsafe { invert_c(self.0.as_ptr() as *mut _, mout.as_mut_ptr()) };
+            // invert_c returns the zero matrix when the input is singular
+            if mout.iter().flatten().all(|x| *x == 0.0) {
+                return Err(SingularMatrix);
+            }
+            Ok(Self(mout))
+        })
+    }
+
+    /// Compute the inverse of a matrix whose columns are mutually orthogonal but not necessarily
+    /// of unit length. For a rotation matrix this is simply the transpose.
+    ///
+    /// See [invort_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/invort_c.html).
+    pub fn invert_orthogonal(&self) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut mout = [[0.0; 3]; 3];
+            unsafe { invort_c(self.0.as_ptr() as *mut _, mout.as_mut_ptr()) };
+            get_last_error()?;
+            Ok(Self(mout))
+        })
+    }
+
+    /// Check whether this is a rotation matrix, i.e. its columns have unit norm to within
+    /// `norm_tolerance` and its determinant is one to within `det_tolerance`.
+    ///
+    /// See [isrot_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/isrot_c.html).
+    pub fn is_rotation(
+        &self,
+        norm_tolerance: SpiceDouble,
+        det_tolerance: SpiceDouble,
+    ) -> Result<bool, Error> {
+        with_spice_lock_or_panic(|| {
+            let result =
+                unsafe { isrot_c(self.0.as_ptr() as *mut _, norm_tolerance, det_tolerance) };
+            get_last_error()?;
+            Ok(result == SPICETRUE as SpiceBoolean)
+        })
+    }
+}
+
+/// A 6x6 matrix that transforms states (position and velocity) from one frame to another.
+#[derive(Copy, Clone, Debug, Default, PartialEq, From, Into, Deref, DerefMut)]
+pub struct StateTransformMatrix6x6(pub [[SpiceDouble; 6]; 6]);
+
+impl StateTransformMatrix6x6 {
+    /// Return the state transformation matrix from one frame to another at a specified epoch.
+    ///
+    /// See [sxform_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sxform_c.html).
+    pub fn from_frames<'f, 't, F, T>(from: F, to: T, et: Et) -> Result<Self, Error>
+    where
+        F: Into<StringParam<'f>>,
+        T: Into<StringParam<'t>>,
+    {
+        with_spice_lock_or_panic(|| {
+            let mut xform = [[0.0; 6]; 6];
+            unsafe {
+                sxform_c(
+                    from.into().as_mut_ptr()?,
+                    to.into().as_mut_ptr()?,
+                    et.0,
+                    xform.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(Self(xform))
+        })
+    }
+
+    /// Build a state transformation from a rotation matrix and the angular velocity of the
+    /// rotating frame (rad/s, expressed in the `from` frame), the inverse of
+    /// [StateTransformMatrix6x6::angular_velocity()].
+    ///
+    /// See [rav2xf_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/rav2xf_c.html).
+    pub fn from_rotation_and_av(rotation: &Matrix3x3, angular_velocity: &Vector3D) -> Self {
+        with_spice_lock_or_panic(|| {
+            let mut rotation = *rotation;
+            let mut angular_velocity = *angular_velocity;
+            let mut xform = [[0.0; 6]; 6];
+            unsafe {
+                rav2xf_c(
+                    rotation.as_mut_ptr(),
+                    angular_velocity.as_mut_ptr(),
+                    xform.as_mut_ptr(),
+                )
+            };
+            Self(xform)
+        })
+    }
+
+    /// Split this state transformation into its rotation matrix and the angular velocity (rad/s)
+    /// of the `to` frame relative to the `from` frame, expressed in the `from` frame.
+    ///
+    /// For a body-fixed frame this is the body's spin vector, as needed for the Coriolis and
+    /// centrifugal terms of dynamics in that frame.
+    ///
+    /// See [xf2rav_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/xf2rav_c.html).
+    pub fn angular_velocity(&self) -> (Matrix3x3, Vector3D) {
+        with_spice_lock_or_panic(|| {
+            let mut xform = self.0;
+            let mut rotation = Matrix3x3::default();
+            let mut angular_velocity = Vector3D::default();
+            unsafe {
+                xf2rav_c(
+                    xform.as_mut_ptr(),
+                    rotation.as_mut_ptr(),
+                    angular_velocity.as_mut_ptr(),
+                )
+            };
+            (rotation, angular_velocity)
+        })
+    }
+
+    /// The inverse transformation, i.e. from the `to` frame back to the `from` frame.
+    ///
+    /// See [invstm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/invstm_c.html).
+    pub fn invert(&self) -> Self {
+        with_spice_lock_or_panic(|| {
+            let mut xform = self.0;
+            let mut inverse = [[0.0; 6]; 6];
+            unsafe { invstm_c(xform.as_mut_ptr(), inverse.as_mut_ptr()) };
+            Self(inverse)
+        })
+    }
+
+    /// Chain this transformation (from frame A to B) with `next` (from B to C), giving the
+    /// transformation from A to C. Equivalent to `next * self`.
+    pub fn then(&self, next: &StateTransformMatrix6x6) -> Self {
+        *next * *self
+    }
+
+    /// The identity transformation.
+    pub fn identity() -> Self {
+        let mut m = [[0.0; 6]; 6];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Self(m)
+    }
+
+    /// Transform a state by this matrix.
+    pub fn mul_state(&self, state: &State) -> State {
+        let v: [SpiceDouble; 6] = (*state).into();
+        let mut out = [0.0; 6];
+        for (o, row) in out.iter_mut().zip(self.0.iter()) {
+            *o = row.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+        }
+        out.into()
+    }
+}
+
+fn mul_square<const N: usize>(
+    a: &[[SpiceDouble; N]; N],
+    b: &[[SpiceDouble; N]; N],
+) -> [[SpiceDouble; N]; N] {
+    let mut out = [[0.0; N]; N];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, o) in row.iter_mut().enumerate() {
+            *o = (0..N).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// Matrix product, for rotations `b_to_c * a_to_b` is the rotation from frame A to C.
+///
+/// Equivalent to [mxm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/mxm_c.html).
+impl Mul for Matrix3x3 {
+    type Output = Matrix3x3;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(mul_square(&self.0, &rhs.0))
+    }
+}
+
+/// Matrix product, `b_to_c * a_to_b` is the state transformation from frame A to C, including
+/// the effect of both frames' rotation on velocities. See also
+/// [StateTransformMatrix6x6::then()].
+impl Mul for StateTransformMatrix6x6 {
+    type Output = StateTransformMatrix6x6;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(mul_square(&self.0, &rhs.0))
+    }
+}
+
+/// Rotate a vector, equivalent to [Matrix3x3::mul_vec()].
+impl Mul<Vector3D> for Matrix3x3 {
+    type Output = Vector3D;
+
+    fn mul(self, rhs: Vector3D) -> Self::Output {
+        self.mul_vec(&rhs)
+    }
+}
+
+/// Transform a state, equivalent to [StateTransformMatrix6x6::mul_state()].
+impl Mul<State> for StateTransformMatrix6x6 {
+    type Output = State;
+
+    fn mul(self, rhs: State) -> Self::Output {
+        self.mul_state(&rhs)
+    }
+}
+
+/// `a *= b` is the matrix product `a * b`, so `b_to_c *= a_to_b` gives the transformation from
+/// frame A to C.
+impl MulAssign for StateTransformMatrix6x6 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{load_moon_constants, load_test_data};
+    use std::f64::consts::FRAC_PI_2;
+
+    fn assert_vec_eq(a: Vector3D, b: [SpiceDouble; 3]) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-12, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn test_from_two_vectors() {
+        let velocity = Vector3D([0.0, 2.0, 0.0]);
+        let normal = Vector3D([0.0, 0.0, 5.0]);
+        let m = Matrix3x3::from_two_vectors(&velocity, Axis::X, &normal, Axis::Y).unwrap();
+        assert_vec_eq(m.mul_vec(&velocity), [2.0, 0.0, 0.0]);
+        assert_vec_eq(m.mul_vec(&normal), [0.0, 5.0, 0.0]);
+        assert_vec_eq(m.mul_vec(&Vector3D([1.0, 0.0, 0.0])), [0.0, 0.0, 1.0]);
+
+        let err = Matrix3x3::from_two_vectors(&velocity, Axis::X, &velocity, Axis::Y).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(DEPENDENTVECTORS)");
+    }
+
+    #[test]
+    fn test_angular_velocity() {
+        load_test_data();
+        load_moon_constants();
+        let xform = StateTransformMatrix6x6::from_frames("J2000", "IAU_MOON", Et(0.0)).unwrap();
+        let (rotation, av) = xform.angular_velocity();
+        assert_eq!(
+            rotation,
+            Matrix3x3::from_frames("J2000", "IAU_MOON", Et(0.0)).unwrap()
+        );
+        // The spin rate from BODY301_PM, about the pole
+        let rate = 13.17635815f64.to_radians() / 86400.0;
+        let magnitude = av.iter().map(|x| x * x).sum::<SpiceDouble>().sqrt();
+        assert!((magnitude - rate).abs() < 1e-12, "{magnitude} != {rate}");
+        let pole = rotation
+            .invert_orthogonal()
+            .unwrap()
+            .mul_vec(&Vector3D([0.0, 0.0, 1.0]));
+        assert!(av.separation_angle(&pole) < 1e-6);
+
+        let rebuilt = StateTransformMatrix6x6::from_rotation_and_av(&rotation, &av);
+        for (a, b) in rebuilt.iter().flatten().zip(xform.iter().flatten()) {
+            assert!((a - b).abs() < 1e-12, "{a} != {b}");
+        }
+        let (_, identity_av) = StateTransformMatrix6x6::identity().angular_velocity();
+        assert_eq!(identity_av, Vector3D::default());
+    }
+
+    #[test]
+    fn test_composition() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(1e6);
+        let j2000_to_eclip =
+            StateTransformMatrix6x6::from_frames("J2000", "ECLIPJ2000", et).unwrap();
+        let eclip_to_moon =
+            StateTransformMatrix6x6::from_frames("ECLIPJ2000", "IAU_MOON", et).unwrap();
+        let direct = StateTransformMatrix6x6::from_frames("J2000", "IAU_MOON", et).unwrap();
+        let assert_close = |a: &StateTransformMatrix6x6, b: &StateTransformMatrix6x6| {
+            for (x, y) in a.iter().flatten().zip(b.iter().flatten()) {
+                assert!((x - y).abs() < 1e-12, "{a:?} != {b:?}");
+            }
+        };
+        assert_close(&j2000_to_eclip.then(&eclip_to_moon), &direct);
+        assert_close(&(eclip_to_moon * j2000_to_eclip), &direct);
+        let mut chained = eclip_to_moon;
+        chained *= j2000_to_eclip;
+        assert_close(&chained, &direct);
+
+        let state = State::from([1e5, 2e5, 3e5, 1.0, 2.0, 3.0]);
+        assert_eq!(direct * state, direct.mul_state(&state));
+        let (rotation, _) = direct.angular_velocity();
+        assert_eq!(rotation * state.velocity, rotation.mul_vec(&state.velocity));
+        assert_close(
+            &(direct.invert() * direct),
+            &StateTransformMatrix6x6::identity(),
+        );
+        assert_close(
+            &direct.invert(),
+            &StateTransformMatrix6x6::from_frames("IAU_MOON", "J2000", et).unwrap(),
+        );
+
+        let (r1, _) = j2000_to_eclip.angular_velocity();
+        let (r2, _) = eclip_to_moon.angular_velocity();
+        let (r, _) = direct.angular_velocity();
+        for (x, y) in (r2 * r1).iter().flatten().zip(r.iter().flatten()) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_rotate() {
+        let m = Matrix3x3::identity().rotate(Radians(FRAC_PI_2), Axis::Z);
+        assert_vec_eq(m.mul_vec(&Vector3D([1.0, 0.0, 0.0])), [0.0, -1.0, 0.0]);
+        assert_vec_eq(m.mul_vec(&Vector3D([0.0, 0.0, 1.0])), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_linear_algebra() {
+        let m = Matrix3x3([[2.0, 0.0, 0.0], [0.0, 3.0, 0.0], [1.0, 0.0, 4.0]]);
+        assert_eq!(m.determinant(), 24.0);
+        assert_eq!(m.trace(), 9.0);
+        let inv = m.invert().unwrap();
+        assert_vec_eq(
+            inv.mul_vec(&m.mul_vec(&Vector3D([1.0, 2.0, 3.0]))),
+            [1.0, 2.0, 3.0],
+        );
+
+        let singular = Matrix3x3([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(singular.invert().unwrap_err(), SingularMatrix);
+
+        let rotation = Matrix3x3::identity().rotate(Radians(0.3), Axis::X);
+        assert!(rotation.is_rotation(1e-12, 1e-12).unwrap());
+        assert!(!m.is_rotation(1e-12, 1e-12).unwrap());
+        let inv = rotation.invert_orthogonal().unwrap();
+        assert_vec_eq(
+            inv.mul_vec(&rotation.mul_vec(&Vector3D([1.0, 2.0, 3.0]))),
+            [1.0, 2.0, 3.0],
+        );
+
+        let err = Matrix3x3([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0]])
+            .invert_orthogonal()
+            .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(ZEROLENGTHCOLUMN)");
+        let err = rotation.is_rotation(-1.0, 1e-12).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(VALUEOUTOFRANGE)");
+    }
+}
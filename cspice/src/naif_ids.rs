@@ -0,0 +1,296 @@
+//! NAIF integer ID codes for the standard bodies, barycenters and DSN stations.
+//!
+//! See [NAIF Integer ID codes](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/naif_ids.html).
+//!
+//! The [body_id!](crate::body_id) macro can be used to look up a code by name at compile time:
+//!
+//! ```
+//! use cspice::body_id;
+//! use cspice::naif_ids::MARS_BARYCENTER;
+//!
+//! const MARS: i32 = body_id!("MARS BARYCENTER");
+//! assert_eq!(MARS, MARS_BARYCENTER);
+//! ```
+use cspice_sys::SpiceInt;
+
+/// The type of a NAIF ID code.
+pub type Id = SpiceInt;
+
+// Each code is named by its constant, unless SPICE names are given explicitly (required
+// whenever the constant name is not itself accepted by SPICE, e.g. it contains an underscore).
+macro_rules! naif_ids {
+    (@name $default:expr) => { $default };
+    (@name $default:expr, $name:literal) => { $name };
+    ($($(#[$meta:meta])* $name:ident = $id:literal $(, $spice:literal $(, $alias:literal)*)?;)*) => {
+        $(
+            $(#[$meta])*
+            pub const $name: Id = $id;
+        )*
+
+        /// Names (and aliases) of every code in this module, in the form accepted by SPICE.
+        pub const NAMES: &[(&str, Id)] = &[
+            $(
+                (naif_ids!(@name stringify!($name) $(, $spice)?), $id),
+                $($(($alias, $id),)*)?
+            )*
+        ];
+    };
+}
+
+naif_ids! {
+    SOLAR_SYSTEM_BARYCENTER = 0, "SOLAR SYSTEM BARYCENTER", "SSB";
+    MERCURY_BARYCENTER = 1, "MERCURY BARYCENTER";
+    VENUS_BARYCENTER = 2, "VENUS BARYCENTER";
+    EARTH_BARYCENTER = 3, "EARTH BARYCENTER", "EMB", "EARTH MOON BARYCENTER", "EARTH-MOON BARYCENTER";
+    MARS_BARYCENTER = 4, "MARS BARYCENTER";
+    JUPITER_BARYCENTER = 5, "JUPITER BARYCENTER";
+    SATURN_BARYCENTER = 6, "SATURN BARYCENTER";
+    URANUS_BARYCENTER = 7, "URANUS BARYCENTER";
+    NEPTUNE_BARYCENTER = 8, "NEPTUNE BARYCENTER";
+    PLUTO_BARYCENTER = 9, "PLUTO BARYCENTER";
+    SUN = 10;
+
+    MERCURY = 199;
+
+    VENUS = 299;
+
+    EARTH = 399;
+    MOON = 301;
+
+    MARS = 499;
+    PHOBOS = 401;
+    DEIMOS = 402;
+
+    JUPITER = 599;
+    IO = 501;
+    EUROPA = 502;
+    GANYMEDE = 503;
+    CALLISTO = 504;
+    AMALTHEA = 505;
+    HIMALIA = 506;
+    ELARA = 507;
+    PASIPHAE = 508;
+    SINOPE = 509;
+    LYSITHEA = 510;
+    CARME = 511;
+    ANANKE = 512;
+    LEDA = 513;
+    THEBE = 514;
+    ADRASTEA = 515;
+    METIS = 516;
+
+    SATURN = 699;
+    MIMAS = 601;
+    ENCELADUS = 602;
+    TETHYS = 603;
+    DIONE = 604;
+    RHEA = 605;
+    TITAN = 606;
+    HYPERION = 607;
+    IAPETUS = 608;
+    PHOEBE = 609;
+    JANUS = 610;
+    EPIMETHEUS = 611;
+    HELENE = 612;
+    TELESTO = 613;
+    CALYPSO = 614;
+    ATLAS = 615;
+    PROMETHEUS = 616;
+    PANDORA = 617;
+    PAN = 618;
+
+    URANUS = 799;
+    ARIEL = 701;
+    UMBRIEL = 702;
+    TITANIA = 703;
+    OBERON = 704;
+    MIRANDA = 705;
+    CORDELIA = 706;
+    OPHELIA = 707;
+    BIANCA = 708;
+    CRESSIDA = 709;
+    DESDEMONA = 710;
+    JULIET = 711;
+    PORTIA = 712;
+    ROSALIND = 713;
+    BELINDA = 714;
+    PUCK = 715;
+
+    NEPTUNE = 899;
+    TRITON = 801;
+    NEREID = 802;
+    NAIAD = 803;
+    THALASSA = 804;
+    DESPINA = 805;
+    GALATEA = 806;
+    LARISSA = 807;
+    PROTEUS = 808;
+
+    PLUTO = 999;
+    CHARON = 901;
+    NIX = 902;
+    HYDRA = 903;
+    KERBEROS = 904;
+    STYX = 905;
+
+    /// Goldstone
+    DSS_12 = 399012, "DSS-12";
+    /// Goldstone
+    DSS_13 = 399013, "DSS-13";
+    /// Goldstone
+    DSS_14 = 399014, "DSS-14";
+    /// Goldstone
+    DSS_15 = 399015, "DSS-15";
+    /// Goldstone
+    DSS_16 = 399016, "DSS-16";
+    /// Goldstone
+    DSS_17 = 399017, "DSS-17";
+    /// Goldstone
+    DSS_23 = 399023, "DSS-23";
+    /// Goldstone
+    DSS_24 = 399024, "DSS-24";
+    /// Goldstone
+    DSS_25 = 399025, "DSS-25";
+    /// Goldstone
+    DSS_26 = 399026, "DSS-26";
+    /// Goldstone
+    DSS_27 = 399027, "DSS-27";
+    /// Goldstone
+    DSS_28 = 399028, "DSS-28";
+    /// Canberra
+    DSS_33 = 399033, "DSS-33";
+    /// Canberra
+    DSS_34 = 399034, "DSS-34";
+    /// Canberra
+    DSS_35 = 399035, "DSS-35";
+    /// Canberra
+    DSS_36 = 399036, "DSS-36";
+    /// Canberra
+    DSS_42 = 399042, "DSS-42";
+    /// Canberra
+    DSS_43 = 399043, "DSS-43";
+    /// Canberra
+    DSS_45 = 399045, "DSS-45";
+    /// Canberra
+    DSS_46 = 399046, "DSS-46";
+    /// Canberra (Parkes)
+    DSS_49 = 399049, "DSS-49";
+    /// Madrid
+    DSS_53 = 399053, "DSS-53";
+    /// Madrid
+    DSS_54 = 399054, "DSS-54";
+    /// Madrid
+    DSS_55 = 399055, "DSS-55";
+    /// Madrid
+    DSS_56 = 399056, "DSS-56";
+    /// Madrid
+    DSS_61 = 399061, "DSS-61";
+    /// Madrid
+    DSS_63 = 399063, "DSS-63";
+    /// Madrid
+    DSS_65 = 399065, "DSS-65";
+    /// Madrid
+    DSS_66 = 399066, "DSS-66";
+}
+
+/// Look up the NAIF ID code of a body in [NAMES].
+///
+/// As with SPICE the comparison ignores case, leading and trailing whitespace, and repeated
+/// spaces between words. This is a `const fn` so can be evaluated at compile time, see
+/// [body_id!](crate::body_id).
+pub const fn lookup(name: &str) -> Option<Id> {
+    let mut i = 0;
+    while i < NAMES.len() {
+        if names_equal(name.as_bytes(), NAMES[i].0.as_bytes()) {
+            return Some(NAMES[i].1);
+        }
+        i += 1;
+    }
+    None
+}
+
+const fn names_equal(a: &[u8], b: &[u8]) -> bool {
+    let (mut i, mut j) = (skip_spaces(a, 0), skip_spaces(b, 0));
+    loop {
+        match (i < a.len(), j < b.len()) {
+            (false, false) => return true,
+            (true, true) => {
+                if a[i] == b' ' || b[j] == b' ' {
+                    // Any run of spaces must match a run of spaces, unless it is trailing
+                    let (next_i, next_j) = (skip_spaces(a, i), skip_spaces(b, j));
+                    let (end_a, end_b) = (next_i == a.len(), next_j == b.len());
+                    if end_a && end_b {
+                        return true;
+                    }
+                    if a[i] != b' ' || b[j] != b' ' || end_a || end_b {
+                        return false;
+                    }
+                    i = next_i;
+                    j = next_j;
+                } else if !a[i].eq_ignore_ascii_case(&b[j]) {
+                    return false;
+                } else {
+                    i += 1;
+                    j += 1;
+                }
+            }
+            (true, false) => return skip_spaces(a, i) == a.len(),
+            (false, true) => return skip_spaces(b, j) == b.len(),
+        }
+    }
+}
+
+const fn skip_spaces(s: &[u8], mut i: usize) -> usize {
+    while i < s.len() && s[i] == b' ' {
+        i += 1;
+    }
+    i
+}
+
+/// Get the NAIF ID code of a standard body, barycenter or DSN station at compile time.
+///
+/// Fails to compile if the name is not present in [naif_ids::NAMES](crate::naif_ids::NAMES).
+/// Use the SPICE kernel pool at runtime for any other bodies.
+///
+/// ```
+/// use cspice::body_id;
+/// assert_eq!(body_id!("Earth"), 399);
+/// assert_eq!(body_id!("MARS BARYCENTER"), 4);
+/// ```
+#[macro_export]
+macro_rules! body_id {
+    ($name:literal) => {{
+        const ID: $crate::naif_ids::Id = match $crate::naif_ids::lookup($name) {
+            Some(id) => id,
+            None => panic!(concat!("Unknown NAIF body name: ", $name)),
+        };
+        ID
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("EARTH"), Some(EARTH));
+        assert_eq!(lookup("earth"), Some(EARTH));
+        assert_eq!(lookup("  Mars   Barycenter "), Some(MARS_BARYCENTER));
+        assert_eq!(lookup("DSS-14"), Some(DSS_14));
+        assert_eq!(lookup("SSB"), Some(SOLAR_SYSTEM_BARYCENTER));
+        // Constant names are not SPICE names
+        assert_eq!(lookup("MARS_BARYCENTER"), None);
+        assert_eq!(lookup("DSS_14"), None);
+        assert_eq!(lookup("MARSBARYCENTER"), None);
+        assert_eq!(lookup("MARS BARYCENTER X"), None);
+        assert_eq!(lookup(""), None);
+    }
+
+    #[test]
+    fn test_body_id_macro() {
+        const MOON_ID: Id = body_id!("Moon");
+        assert_eq!(MOON_ID, MOON);
+        assert_eq!(body_id!("EARTH MOON BARYCENTER"), EARTH_BARYCENTER);
+    }
+}
@@ -0,0 +1,51 @@
+//! Functions for propagating orbits.
+use crate::error::get_last_error;
+use crate::spk::State;
+use crate::units::Seconds;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{prop2b_c, SpiceDouble};
+
+/// Propagate a state using two-body (Keplerian) motion about a central body with gravitational
+/// parameter `gm` (km^3/s^2), returning the state `dt` seconds after `state`.
+///
+/// See [prop2b_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/prop2b_c.html).
+pub fn prop2b(gm: SpiceDouble, state: &State, dt: Seconds) -> Result<State, Error> {
+    let mut pvinit: [SpiceDouble; 6] = (*state).into();
+    with_spice_lock_or_panic(|| {
+        let mut pvprop = [0.0; 6];
+        unsafe { prop2b_c(gm, pvinit.as_mut_ptr(), dt.0, pvprop.as_mut_ptr()) };
+        get_last_error()?;
+        Ok(State::from(pvprop))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_prop2b() {
+        let gm = 398600.4418;
+        let r: SpiceDouble = 7000.0;
+        let v = (gm / r).sqrt();
+        let period = 2.0 * PI * (r.powi(3) / gm).sqrt();
+        let state = State::from([r, 0.0, 0.0, 0.0, v, 0.0]);
+
+        let quarter = prop2b(gm, &state, Seconds(period / 4.0)).unwrap();
+        assert!(quarter.position.x.abs() < 1e-6);
+        assert!((quarter.position.y - r).abs() < 1e-6);
+        assert!((quarter.velocity[0] + v).abs() < 1e-9);
+
+        let full = prop2b(gm, &state, Seconds(period)).unwrap();
+        let (a, b): ([SpiceDouble; 6], [SpiceDouble; 6]) = (full.into(), state.into());
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-6, "{a:?} != {b:?}");
+        }
+
+        let err = prop2b(-1.0, &state, Seconds(0.0)).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(NONPOSITIVEMASS)");
+        let err = prop2b(gm, &State::default(), Seconds(0.0)).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(ZEROPOSITION)");
+    }
+}
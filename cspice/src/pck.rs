@@ -0,0 +1,270 @@
+//! Functions relating to the Planetary Constants Kernel (PCK) subsystem of SPICE.
+//!
+//! Text PCKs (e.g. `pck00010.tpc`) and binary PCKs (e.g. the high precision Earth and Moon
+//! orientation files) are both loaded with [furnish()](crate::data::furnish). Binary PCKs only
+//! provide orientation over a limited time span, which can be checked with [coverage()].
+use crate::body::BodyRef;
+use crate::cell::Window;
+use crate::data::loaded_files;
+use crate::error::get_last_error;
+use crate::frame::{FrameClass, FrameId};
+use crate::matrix::StateTransformMatrix6x6;
+use crate::string::StringParam;
+use crate::time::Et;
+use crate::units::Radians;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{m2eul_c, pckcov_c, tisbod_c, SpiceDouble, SpiceInt};
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Size of the window used by [checked_state_transform()] to hold the coverage of a frame.
+pub const COVERAGE_WINDOW_SIZE: usize = 2000;
+
+/// Error returned from [checked_state_transform()].
+#[derive(Debug, thiserror::Error)]
+pub enum PckError {
+    #[error("Epoch {et:?} is outside of the coverage of frame class ID {class_id}")]
+    OutsideCoverage { class_id: SpiceInt, et: Et },
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+/// Find the coverage window for a specified reference frame in a binary PCK file, adding it to
+/// `cover`.
+///
+/// `class_id` is the frame class ID, e.g. 3000 for `ITRF93`.
+///
+/// See [pckcov_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pckcov_c.html).
+pub fn coverage<'f, F: Into<StringParam<'f>>>(
+    file: F,
+    class_id: SpiceInt,
+    cover: &mut Window,
+) -> Result<(), Error> {
+    with_spice_lock_or_panic(|| {
+        unsafe { pckcov_c(file.into().as_mut_ptr()?, class_id, cover.as_mut_cell()) };
+        get_last_error()
+    })
+}
+
+/// Return the state transformation matrix between two frames, after checking that `et` is
+/// within the coverage of the binary PCKs for each of the frames that is a PCK frame.
+///
+/// The coverage is the union of the coverage of the frame's class ID in every loaded binary PCK.
+/// This gives a clear error for epochs outside of the binary PCKs, rather than SPICE silently
+/// falling back to a lower priority source of orientation data (or failing with a less specific
+/// error). PCK frames with no binary PCK data at all, e.g. those only defined by a text PCK, are
+/// not checked.
+///
+/// See [StateTransformMatrix6x6::from_frames()].
+pub fn checked_state_transform<'f, 't, F, T>(
+    from: F,
+    to: T,
+    et: Et,
+) -> Result<StateTransformMatrix6x6, PckError>
+where
+    F: Into<StringParam<'f>>,
+    T: Into<StringParam<'t>>,
+{
+    let (from, to) = (from.into(), to.into());
+    with_spice_lock_or_panic(|| {
+        let files = loaded_files("PCK")?;
+        for frame in [&from, &to] {
+            let info = match FrameId::from_name(&**frame)? {
+                Some(id) => id.info()?,
+                None => None,
+            };
+            let class_id = match info {
+                Some(info) if info.class == FrameClass::Pck => info.class_id,
+                _ => continue,
+            };
+            let mut cover = Window::new_double(COVERAGE_WINDOW_SIZE);
+            for (file, _) in &files {
+                coverage(file, class_id, &mut cover)?;
+            }
+            if cover.window_cardinality()? > 0 && !cover.window_contains_element(et.0)? {
+                return Err(PckError::OutsideCoverage { class_id, et });
+            }
+        }
+        Ok(StateTransformMatrix6x6::from_frames(&*from, &*to, et)?)
+    })
+}
+
+/// The orientation of a body's pole and prime meridian relative to an inertial frame, see
+/// [body_orientation()].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodyOrientation {
+    /// Right ascension of the north pole, in `[0, 2pi)`.
+    pub pole_ra: Radians,
+    /// Declination of the north pole.
+    pub pole_dec: Radians,
+    /// Angle of the prime meridian (W) measured along the equator from the ascending node of the
+    /// equator on the inertial frame's XY plane, in `[0, 2pi)`.
+    pub prime_meridian: Radians,
+}
+
+/// Find the right ascension and declination of the north pole and the prime meridian angle of a
+/// body at `et`, relative to the inertial frame `reference`, using the body's PCK orientation
+/// data.
+///
+/// The angles are recovered from the body's rotation matrix, so they include any nutation and
+/// precession terms, and account for PCK data defined relative to a different inertial frame.
+///
+/// See [tisbod_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/tisbod_c.html).
+pub fn body_orientation<'b, 'r, B, R>(
+    body: B,
+    reference: R,
+    et: Et,
+) -> Result<BodyOrientation, Error>
+where
+    B: Into<BodyRef<'b>>,
+    R: Into<StringParam<'r>>,
+{
+    let (body, reference) = (body.into(), reference.into());
+    with_spice_lock_or_panic(|| {
+        let id = body.to_id()?;
+        let mut transform = [[0.0 as SpiceDouble; 6]; 6];
+        unsafe { tisbod_c(reference.as_mut_ptr()?, id, et.0, transform.as_mut_ptr()) };
+        get_last_error()?;
+        // The rotation is [W]3 [pi/2 - dec]1 [pi/2 + ra]3
+        let mut rotation = [[0.0; 3]; 3];
+        for (row, transform_row) in rotation.iter_mut().zip(transform.iter()) {
+            row.copy_from_slice(&transform_row[..3]);
+        }
+        let (mut w, mut colatitude, mut ra) = (0.0, 0.0, 0.0);
+        unsafe {
+            m2eul_c(
+                rotation.as_mut_ptr(),
+                3,
+                1,
+                3,
+                &mut w,
+                &mut colatitude,
+                &mut ra,
+            )
+        };
+        get_last_error()?;
+        Ok(BodyOrientation {
+            pole_ra: Radians((ra - FRAC_PI_2).rem_euclid(TAU)),
+            pole_dec: Radians(FRAC_PI_2 - colatitude),
+            prime_meridian: Radians(w.rem_euclid(TAU)),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{furnish, unload};
+    use crate::string::{static_spice_str, SpiceString, StaticSpiceStr};
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    const CLASS_ID: SpiceInt = 3999;
+
+    #[test]
+    fn test_body_orientation() {
+        load_test_data();
+        load_moon_constants();
+        let assert_degrees = |angle: Radians, expected: SpiceDouble| {
+            let degrees = angle.to_degrees();
+            assert!((degrees - expected).abs() < 1e-9, "{degrees} != {expected}");
+        };
+        let orientation = body_orientation("MOON", "J2000", Et(0.0)).unwrap();
+        assert_degrees(orientation.pole_ra, 269.9949);
+        assert_degrees(orientation.pole_dec, 66.5392);
+        assert_degrees(orientation.prime_meridian, 38.3213);
+
+        // One day later, the rates are per century for the pole and per day for the meridian
+        let orientation = body_orientation(301, "J2000", Et(86400.0)).unwrap();
+        let centuries = 1.0 / 36525.0;
+        assert_degrees(orientation.pole_ra, 269.9949 + 0.0031 * centuries);
+        assert_degrees(orientation.pole_dec, 66.5392 + 0.0130 * centuries);
+        assert_degrees(orientation.prime_meridian, 38.3213 + 13.17635815 - 1.4e-12);
+
+        assert!(body_orientation("NOT A BODY", "J2000", Et(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_coverage_missing_file() {
+        let mut cover = Window::new_double(COVERAGE_WINDOW_SIZE);
+        let err = coverage("NON_EXISTENT_FILE.bpc", 3000, &mut cover).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(FILENOTFOUND)");
+    }
+
+    #[test]
+    fn test_checked_state_transform() {
+        load_test_data();
+        let dir = std::env::temp_dir();
+        let fk = dir.join("cspice_rs_pck_test.tf");
+        std::fs::write(
+            &fk,
+            "\\begindata
+FRAME_CSPICE_RS_PCK_FRAME = -999100
+FRAME_-999100_NAME        = 'CSPICE_RS_PCK_FRAME'
+FRAME_-999100_CLASS       = 2
+FRAME_-999100_CLASS_ID    = 3999
+FRAME_-999100_CENTER      = 399
+\\begintext
+",
+        )
+        .unwrap();
+        furnish(fk.to_string_lossy()).unwrap();
+
+        // A binary PCK with constant orientation from 0 to 1 day
+        let pck = dir.join("cspice_rs_pck_test.bpc");
+        let _ = std::fs::remove_file(&pck);
+        let mut cdata = [0.0; 3];
+        with_spice_lock_or_panic(|| unsafe {
+            let mut handle = 0;
+            cspice_sys::pckopn_c(
+                SpiceString::from(pck.to_string_lossy())
+                    .as_mut_ptr()
+                    .unwrap(),
+                static_spice_str!("PCK TEST").as_mut_ptr(),
+                0,
+                &mut handle,
+            );
+            cspice_sys::pckw02_c(
+                handle,
+                CLASS_ID,
+                static_spice_str!("J2000").as_mut_ptr(),
+                0.0,
+                86400.0,
+                static_spice_str!("CSPICE_RS_PCK").as_mut_ptr(),
+                86400.0,
+                1,
+                0,
+                cdata.as_mut_ptr(),
+                0.0,
+            );
+            cspice_sys::pckcls_c(handle);
+            get_last_error().unwrap();
+        });
+        furnish(pck.to_string_lossy()).unwrap();
+
+        let pck = pck.to_string_lossy();
+        let frame = "CSPICE_RS_PCK_FRAME";
+        let transform = checked_state_transform("J2000", frame, Et(3600.0)).unwrap();
+        assert_eq!(
+            transform,
+            StateTransformMatrix6x6::from_frames("J2000", frame, Et(3600.0)).unwrap()
+        );
+        for et in [Et(-3600.0), Et(2.0 * 86400.0)] {
+            for (from, to) in [("J2000", frame), (frame, "J2000")] {
+                match checked_state_transform(from, to, et) {
+                    Err(PckError::OutsideCoverage { class_id, et: e }) => {
+                        assert_eq!((class_id, e), (CLASS_ID, et));
+                    }
+                    other => panic!("Expected outside coverage, got {other:?}"),
+                }
+            }
+        }
+        // Frames without binary PCK data aren't checked
+        load_moon_constants();
+        assert_eq!(
+            checked_state_transform("J2000", "IAU_MOON", Et(-3600.0)).unwrap(),
+            StateTransformMatrix6x6::from_frames("J2000", "IAU_MOON", Et(-3600.0)).unwrap()
+        );
+
+        unload(&*pck).unwrap();
+        unload(fk.to_string_lossy()).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! Performance counters for SPICE calls, enabled by the `perf-counters` feature.
+//!
+//! Calls are attributed to the call site inside this crate that acquired the SPICE lock, i.e. the
+//! safe wrapper function, so [spice_stats()] shows where FFI overhead is spent. Nested
+//! (reentrant) lock acquisitions are counted against their own call site.
+use parking_lot::Mutex;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::panic::Location;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The location in the source code of a call site.
+pub type CallSite = &'static Location<'static>;
+
+/// Counters for a single call site.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallSiteStats {
+    /// The number of times the call site acquired the SPICE lock.
+    pub calls: u64,
+    /// The number of C strings allocated while the call site held the lock.
+    pub string_allocations: u64,
+}
+
+/// A snapshot of the performance counters, see [spice_stats()].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SpiceStats {
+    /// The total number of times the SPICE lock was acquired, including by
+    /// [with_spice_lock()](crate::with_spice_lock) from outside this crate.
+    pub lock_acquisitions: u64,
+    /// The number of C strings allocated while no call site held the lock, e.g. converting
+    /// arguments before calling into SPICE.
+    pub unattributed_string_allocations: u64,
+    /// Counters for each call site.
+    pub call_sites: BTreeMap<CallSite, CallSiteStats>,
+}
+
+impl SpiceStats {
+    /// The total number of calls over all call sites.
+    pub fn total_calls(&self) -> u64 {
+        self.call_sites.values().map(|s| s.calls).sum()
+    }
+
+    /// The total number of C strings allocated.
+    pub fn total_string_allocations(&self) -> u64 {
+        self.unattributed_string_allocations
+            + self
+                .call_sites
+                .values()
+                .map(|s| s.string_allocations)
+                .sum::<u64>()
+    }
+}
+
+static LOCK_ACQUISITIONS: AtomicU64 = AtomicU64::new(0);
+static UNATTRIBUTED_STRING_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static CALL_SITES: Mutex<BTreeMap<CallSite, CallSiteStats>> =
+    parking_lot::const_mutex(BTreeMap::new());
+
+thread_local! {
+    static CURRENT_SITE: Cell<Option<CallSite>> = const { Cell::new(None) };
+}
+
+/// Take a snapshot of the performance counters.
+pub fn spice_stats() -> SpiceStats {
+    SpiceStats {
+        lock_acquisitions: LOCK_ACQUISITIONS.load(Ordering::Relaxed),
+        unattributed_string_allocations: UNATTRIBUTED_STRING_ALLOCATIONS.load(Ordering::Relaxed),
+        call_sites: CALL_SITES.lock().clone(),
+    }
+}
+
+/// Reset all the performance counters to zero.
+pub fn reset_spice_stats() {
+    LOCK_ACQUISITIONS.store(0, Ordering::Relaxed);
+    UNATTRIBUTED_STRING_ALLOCATIONS.store(0, Ordering::Relaxed);
+    CALL_SITES.lock().clear();
+}
+
+pub(crate) fn record_lock_acquisition() {
+    LOCK_ACQUISITIONS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn record_string_allocation() {
+    match CURRENT_SITE.with(Cell::get) {
+        Some(site) => {
+            CALL_SITES
+                .lock()
+                .entry(site)
+                .or_default()
+                .string_allocations += 1
+        }
+        None => {
+            UNATTRIBUTED_STRING_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Record a call from `site`, attributing any string allocations to it until the returned guard
+/// is dropped.
+pub(crate) fn enter_call_site(site: CallSite) -> CallSiteGuard {
+    CALL_SITES.lock().entry(site).or_default().calls += 1;
+    CallSiteGuard(CURRENT_SITE.with(|c| c.replace(Some(site))))
+}
+
+pub(crate) struct CallSiteGuard(Option<CallSite>);
+
+impl Drop for CallSiteGuard {
+    fn drop(&mut self) {
+        CURRENT_SITE.with(|c| c.set(self.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::string::SpiceString;
+    use crate::time::Et;
+
+    #[test]
+    fn test_spice_stats() {
+        let before = spice_stats();
+        Et::from_string("2000-01-01T12:00:00").ok();
+        let _ = SpiceString::from("outside");
+        let after = spice_stats();
+
+        assert!(after.lock_acquisitions > before.lock_acquisitions);
+        assert!(after.total_calls() > before.total_calls());
+        assert!(after.unattributed_string_allocations > before.unattributed_string_allocations);
+        assert!(after
+            .call_sites
+            .keys()
+            .any(|site| site.file().ends_with("time/mod.rs")));
+    }
+}
@@ -0,0 +1,177 @@
+//! Functions for working with planes.
+//!
+//! See [Planes required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/planes.html).
+use crate::error::get_last_error;
+use crate::vector::Vector3D;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    nvc2pl_c, nvp2pl_c, pl2nvc_c, psv2pl_c, vprjp_c, vprjpi_c, SpiceBoolean, SpiceDouble,
+    SpicePlane, SPICETRUE,
+};
+
+/// A plane in 3D space.
+#[derive(Copy, Clone, Debug)]
+pub struct Plane(pub(crate) SpicePlane);
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self(SpicePlane {
+            normal: [0.0, 0.0, 1.0],
+            constant: 0.0,
+        })
+    }
+}
+
+impl Plane {
+    /// Make a plane from a normal vector and a constant, the plane is the set of points `x`
+    /// where `<x, normal> = constant`.
+    ///
+    /// See [nvc2pl_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/nvc2pl_c.html).
+    pub fn from_normal_and_constant(
+        normal: &Vector3D,
+        constant: SpiceDouble,
+    ) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut plane = Plane::default();
+            unsafe { nvc2pl_c(normal.as_ptr() as *mut SpiceDouble, constant, &mut plane.0) };
+            get_last_error()?;
+            Ok(plane)
+        })
+    }
+
+    /// Make a plane from a normal vector and a point in the plane.
+    ///
+    /// See [nvp2pl_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/nvp2pl_c.html).
+    pub fn from_normal_and_point(normal: &Vector3D, point: &Vector3D) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut plane = Plane::default();
+            unsafe {
+                nvp2pl_c(
+                    normal.as_ptr() as *mut SpiceDouble,
+                    point.as_ptr() as *mut SpiceDouble,
+                    &mut plane.0,
+                )
+            };
+            get_last_error()?;
+            Ok(plane)
+        })
+    }
+
+    /// Make a plane from a point and two spanning vectors.
+    ///
+    /// See [psv2pl_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/psv2pl_c.html).
+    pub fn from_point_and_spanning_vectors(
+        point: &Vector3D,
+        span1: &Vector3D,
+        span2: &Vector3D,
+    ) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut plane = Plane::default();
+            unsafe {
+                psv2pl_c(
+                    point.as_ptr() as *mut SpiceDouble,
+                    span1.as_ptr() as *mut SpiceDouble,
+                    span2.as_ptr() as *mut SpiceDouble,
+                    &mut plane.0,
+                )
+            };
+            get_last_error()?;
+            Ok(plane)
+        })
+    }
+
+    /// Return the unit normal vector and constant of the plane.
+    ///
+    /// See [pl2nvc_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/pl2nvc_c.html).
+    pub fn normal_and_constant(&self) -> (Vector3D, SpiceDouble) {
+        with_spice_lock_or_panic(|| {
+            let mut normal = Vector3D::default();
+            let mut constant = 0.0;
+            unsafe {
+                pl2nvc_c(
+                    &self.0 as *const SpicePlane as *mut SpicePlane,
+                    normal.as_mut_ptr(),
+                    &mut constant,
+                )
+            };
+            (normal, constant)
+        })
+    }
+
+    /// Project a vector orthogonally onto the plane.
+    ///
+    /// See [vprjp_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vprjp_c.html).
+    pub fn project(&self, vector: &Vector3D) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                vprjp_c(
+                    vector.as_ptr() as *mut SpiceDouble,
+                    &self.0 as *const SpicePlane as *mut SpicePlane,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
+    /// Find the vector in `inverse_plane` whose orthogonal projection onto this plane is
+    /// `vector`. Returns `None` if the planes are nearly perpendicular so the inverse can't be
+    /// computed reliably.
+    ///
+    /// See [vprjpi_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vprjpi_c.html).
+    pub fn inverse_project(
+        &self,
+        vector: &Vector3D,
+        inverse_plane: &Plane,
+    ) -> Result<Option<Vector3D>, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            let mut found: SpiceBoolean = 0;
+            unsafe {
+                vprjpi_c(
+                    vector.as_ptr() as *mut SpiceDouble,
+                    &self.0 as *const SpicePlane as *mut SpicePlane,
+                    &inverse_plane.0 as *const SpicePlane as *mut SpicePlane,
+                    out.as_mut_ptr(),
+                    &mut found,
+                )
+            };
+            get_last_error()?;
+            Ok((found == SPICETRUE as SpiceBoolean).then_some(out))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plane_projection() {
+        let xy = Plane::from_normal_and_constant(&Vector3D([0.0, 0.0, 2.0]), 0.0).unwrap();
+        let (normal, constant) = xy.normal_and_constant();
+        assert_eq!(normal, Vector3D([0.0, 0.0, 1.0]));
+        assert_eq!(constant, 0.0);
+
+        let v = Vector3D([1.0, 2.0, 3.0]);
+        assert_eq!(xy.project(&v), Vector3D([1.0, 2.0, 0.0]));
+
+        let tilted = Plane::from_point_and_spanning_vectors(
+            &Vector3D([0.0, 0.0, 1.0]),
+            &Vector3D([1.0, 0.0, 0.0]),
+            &Vector3D([0.0, 1.0, 1.0]),
+        )
+        .unwrap();
+        let inverse = xy
+            .inverse_project(&Vector3D([1.0, 2.0, 0.0]), &tilted)
+            .unwrap()
+            .unwrap();
+        assert!((inverse[0] - 1.0).abs() < 1e-12);
+        assert!((inverse[1] - 2.0).abs() < 1e-12);
+        assert!((inverse[2] - 3.0).abs() < 1e-12);
+
+        let err = Plane::from_normal_and_point(&Vector3D::default(), &v).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(ZEROVECTOR)");
+    }
+}
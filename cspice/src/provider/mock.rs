@@ -0,0 +1,398 @@
+//! In-memory implementations of the provider traits for unit tests, which need neither kernels
+//! nor the SPICE lock.
+use super::{EphemerisProvider, OrientationProvider, TimeConverter};
+use crate::common::AberrationCorrection;
+use crate::lighttime::SPEED_OF_LIGHT;
+use crate::matrix::Matrix3x3;
+use crate::spk::State;
+use crate::time::Et;
+use crate::Error;
+use cspice_sys::SpiceDouble;
+use std::collections::HashMap;
+
+fn mock_error(short_message: &str, long_message: String) -> Error {
+    Error {
+        short_message: short_message.to_string(),
+        explanation: String::new(),
+        long_message,
+        traceback: String::from("mock"),
+        device_output: None,
+    }
+}
+
+fn normalise(name: &str) -> String {
+    name.split_whitespace().collect::<String>().to_uppercase()
+}
+
+/// The motion of a body in a [MockEphemeris].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Motion {
+    /// The same state at all times.
+    Fixed(State),
+    /// Constant velocity, the position is `state.position` at `epoch`.
+    Linear { epoch: Et, state: State },
+}
+
+impl Motion {
+    fn state_at(&self, et: Et) -> State {
+        match self {
+            Motion::Fixed(state) => *state,
+            Motion::Linear { epoch, state } => {
+                let dt = et.0 - epoch.0;
+                let s = state.to_array();
+                State::from([
+                    s[0] + s[3] * dt,
+                    s[1] + s[4] * dt,
+                    s[2] + s[5] * dt,
+                    s[3],
+                    s[4],
+                    s[5],
+                ])
+            }
+        }
+    }
+}
+
+/// An [EphemerisProvider] returning states defined in memory.
+///
+/// Names are matched ignoring case and whitespace, like SPICE. A state defined for a target
+/// relative to an observer is also used, negated, for the observer relative to the target. The
+/// aberration correction is ignored, and the light time is the geometric range divided by the
+/// speed of light. Missing states return a `SPICE(SPKINSUFFDATA)` error, like SPICE.
+#[derive(Clone, Debug, Default)]
+pub struct MockEphemeris {
+    motions: HashMap<(String, String, String), Motion>,
+}
+
+impl MockEphemeris {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define the motion of `target` relative to `observer` in `frame`.
+    pub fn with_motion(
+        mut self,
+        target: &str,
+        observer: &str,
+        frame: &str,
+        motion: Motion,
+    ) -> Self {
+        self.motions.insert(
+            (normalise(target), normalise(observer), normalise(frame)),
+            motion,
+        );
+        self
+    }
+
+    /// Define a fixed state of `target` relative to `observer` in `frame`.
+    pub fn with_fixed(self, target: &str, observer: &str, frame: &str, state: State) -> Self {
+        self.with_motion(target, observer, frame, Motion::Fixed(state))
+    }
+
+    /// Define the state of `target` relative to `observer` in `frame` at `epoch`, moving with
+    /// constant velocity.
+    pub fn with_linear(
+        self,
+        target: &str,
+        observer: &str,
+        frame: &str,
+        epoch: Et,
+        state: State,
+    ) -> Self {
+        self.with_motion(target, observer, frame, Motion::Linear { epoch, state })
+    }
+}
+
+impl EphemerisProvider for MockEphemeris {
+    fn state(
+        &self,
+        target: &str,
+        et: Et,
+        frame: &str,
+        _abcorr: AberrationCorrection,
+        observer: &str,
+    ) -> Result<(State, SpiceDouble), Error> {
+        let (target, observer, frame) = (normalise(target), normalise(observer), normalise(frame));
+        let state = if target == observer {
+            State::default()
+        } else if let Some(motion) =
+            self.motions
+                .get(&(target.clone(), observer.clone(), frame.clone()))
+        {
+            motion.state_at(et)
+        } else if let Some(motion) =
+            self.motions
+                .get(&(observer.clone(), target.clone(), frame.clone()))
+        {
+            -motion.state_at(et)
+        } else {
+            return Err(mock_error(
+                "SPICE(SPKINSUFFDATA)",
+                format!("No mock state of {target} relative to {observer} in {frame}"),
+            ));
+        };
+        Ok((state, state.range() / SPEED_OF_LIGHT))
+    }
+}
+
+/// An [OrientationProvider] returning constant rotations defined in memory.
+///
+/// Frame names are matched ignoring case and whitespace. A rotation defined from one frame to
+/// another is also used, transposed, for the opposite direction, and the rotation from a frame to
+/// itself is the identity. Missing rotations return a `SPICE(FRAMEDATANOTFOUND)` error.
+#[derive(Clone, Debug, Default)]
+pub struct MockOrientation {
+    rotations: HashMap<(String, String), Matrix3x3>,
+}
+
+impl MockOrientation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define the matrix rotating position vectors from frame `from` to frame `to`.
+    pub fn with_rotation(mut self, from: &str, to: &str, rotation: Matrix3x3) -> Self {
+        self.rotations
+            .insert((normalise(from), normalise(to)), rotation);
+        self
+    }
+}
+
+impl OrientationProvider for MockOrientation {
+    fn rotation(&self, from: &str, to: &str, _et: Et) -> Result<Matrix3x3, Error> {
+        let (from, to) = (normalise(from), normalise(to));
+        if from == to {
+            return Ok(Matrix3x3::identity());
+        }
+        if let Some(rotation) = self.rotations.get(&(from.clone(), to.clone())) {
+            return Ok(*rotation);
+        }
+        if let Some(m) = self.rotations.get(&(to.clone(), from.clone())) {
+            // The inverse of a rotation is its transpose
+            return Ok(Matrix3x3(std::array::from_fn(|i| {
+                std::array::from_fn(|j| m.0[j][i])
+            })));
+        }
+        Err(mock_error(
+            "SPICE(FRAMEDATANOTFOUND)",
+            format!("No mock rotation from {from} to {to}"),
+        ))
+    }
+}
+
+/// A [TimeConverter] without leap seconds, where Ephemeris Time is the number of seconds since
+/// 2000-01-01T12:00:00 UTC plus a constant offset.
+///
+/// Only ISO 8601 calendar strings are accepted, e.g. `2000-01-01`, `2000-01-01T12:00` or
+/// `2000-01-01T12:00:00.5`, other strings return a `SPICE(UNPARSEDTIME)` error.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MockTimeConverter {
+    /// ET - UTC in seconds.
+    pub offset: SpiceDouble,
+}
+
+impl Default for MockTimeConverter {
+    /// ET - UTC at 2000-01-01, 64.184 seconds.
+    fn default() -> Self {
+        Self { offset: 64.184 }
+    }
+}
+
+// Days between 1970-01-01 and the given proleptic Gregorian date
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (
+        if month <= 2 {
+            yoe + era * 400 + 1
+        } else {
+            yoe + era * 400
+        },
+        month,
+        day,
+    )
+}
+
+const J2000_DAYS: i64 = 10957;
+
+fn parse_iso(utc: &str) -> Option<SpiceDouble> {
+    let (date, time) = utc
+        .trim()
+        .split_once('T')
+        .unwrap_or((utc.trim(), "00:00:00"));
+    let date = date
+        .split('-')
+        .map(str::parse)
+        .collect::<Result<Vec<i64>, _>>()
+        .ok()?;
+    let [year, month, day] = date[..] else {
+        return None;
+    };
+    let time = time.split(':').collect::<Vec<_>>();
+    let (hour, minute, second) = match time[..] {
+        [h, m] => (h.parse::<i64>().ok()?, m.parse::<i64>().ok()?, 0.0),
+        [h, m, s] => (
+            h.parse::<i64>().ok()?,
+            m.parse::<i64>().ok()?,
+            s.parse::<SpiceDouble>().ok()?,
+        ),
+        _ => return None,
+    };
+    let valid = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && (0..24).contains(&hour)
+        && (0..60).contains(&minute)
+        && (0.0..60.0).contains(&second);
+    valid.then(|| {
+        let days = days_from_civil(year, month, day) - J2000_DAYS;
+        (days * 86400 + hour * 3600 + minute * 60 - 43200) as SpiceDouble + second
+    })
+}
+
+impl TimeConverter for MockTimeConverter {
+    fn utc_to_et(&self, utc: &str) -> Result<Et, Error> {
+        parse_iso(utc)
+            .map(|seconds| Et(seconds + self.offset))
+            .ok_or_else(|| {
+                mock_error(
+                    "SPICE(UNPARSEDTIME)",
+                    format!("Mock time converter can't parse {utc:?}"),
+                )
+            })
+    }
+
+    fn et_to_utc(&self, et: Et) -> Result<String, Error> {
+        let millis = ((et.0 - self.offset + 43200.0) * 1000.0).round() as i64;
+        let days = millis.div_euclid(86_400_000);
+        let millis = millis.rem_euclid(86_400_000);
+        let (year, month, day) = civil_from_days(days + J2000_DAYS);
+        Ok(format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinates::Rectangular;
+    use crate::vector::Vector3D;
+
+    #[test]
+    fn test_mock_ephemeris() {
+        let state = State::from([1000.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        let mock = MockEphemeris::new()
+            .with_fixed("MOON", "EARTH", "J2000", state)
+            .with_linear("probe", "earth", "j2000", Et(100.0), state);
+        let (moon, _) = mock
+            .state(
+                "Moon",
+                Et(5.0),
+                "J2000",
+                AberrationCorrection::NONE,
+                "EARTH",
+            )
+            .unwrap();
+        assert_eq!(moon, state);
+        let (earth, lt) = mock
+            .state("EARTH", Et(5.0), "J2000", AberrationCorrection::LT, "MOON")
+            .unwrap();
+        assert_eq!(earth, -state);
+        assert_eq!(lt, 1000.0 / SPEED_OF_LIGHT);
+        let (probe, _) = mock
+            .position(
+                "PROBE",
+                Et(110.0),
+                "J2000",
+                AberrationCorrection::NONE,
+                "EARTH",
+            )
+            .unwrap();
+        assert_eq!(probe, Rectangular::from([1000.0, 10.0, 0.0]));
+        let err = mock
+            .state(
+                "MARS",
+                Et(0.0),
+                "J2000",
+                AberrationCorrection::NONE,
+                "EARTH",
+            )
+            .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(SPKINSUFFDATA)");
+    }
+
+    #[test]
+    fn test_mock_orientation() {
+        // 90 degrees about Z
+        let m = Matrix3x3([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        let mock = MockOrientation::new().with_rotation("J2000", "BODY_FIXED", m);
+        assert_eq!(mock.rotation("j2000", "body_fixed", Et(0.0)).unwrap(), m);
+        let v = mock
+            .rotate(&Vector3D([1.0, 0.0, 0.0]), "BODY_FIXED", "J2000", Et(0.0))
+            .unwrap();
+        assert_eq!(v, Vector3D([0.0, 1.0, 0.0]));
+        assert_eq!(
+            mock.rotate(&Vector3D([1.0, 2.0, 3.0]), "J2000", "J2000", Et(0.0))
+                .unwrap(),
+            Vector3D([1.0, 2.0, 3.0])
+        );
+        let err = mock.rotation("J2000", "IAU_MARS", Et(0.0)).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(FRAMEDATANOTFOUND)");
+    }
+
+    #[test]
+    fn test_mock_time_converter() {
+        let time = MockTimeConverter::default();
+        assert_eq!(time.utc_to_et("2000-01-01T12:00:00").unwrap(), Et(64.184));
+        assert_eq!(time.utc_to_et("2000-01-01").unwrap(), Et(64.184 - 43200.0));
+        assert_eq!(
+            time.utc_to_et("2000-03-01T00:00:00.5").unwrap().0,
+            (60 * 86400 - 43200) as SpiceDouble + 0.5 + 64.184
+        );
+        assert_eq!(
+            time.et_to_utc(Et(64.184)).unwrap(),
+            "2000-01-01T12:00:00.000"
+        );
+        let et = time.utc_to_et("1969-07-20T20:17:40.25").unwrap();
+        assert_eq!(time.et_to_utc(et).unwrap(), "1969-07-20T20:17:40.250");
+        let err = time.utc_to_et("JD 2451545").unwrap_err();
+        assert_eq!(err.short_message, "SPICE(UNPARSEDTIME)");
+        assert!(time.utc_to_et("2000-13-01").is_err());
+        assert!(time.utc_to_et("2000-02-29").is_ok());
+        assert!(time.utc_to_et("2000-02-30").is_err());
+        assert!(time.utc_to_et("2000-02-31").is_err());
+        assert!(time.utc_to_et("1900-02-29").is_err());
+        assert!(time.utc_to_et("2001-04-31").is_err());
+        assert!(time.utc_to_et("2001-12-31").is_ok());
+    }
+}
@@ -0,0 +1,194 @@
+//! Traits abstracting the ephemeris, orientation and time conversion services of SPICE.
+//!
+//! Application code that is generic over [EphemerisProvider], [OrientationProvider] and
+//! [TimeConverter] can use the real SPICE implementation, [Spice], in production, and swap in
+//! other sources such as tables or network services by implementing the traits. The in-memory
+//! implementations in `mock` (with the `mock` feature) are intended for unit tests, and need
+//! neither kernels nor the SPICE lock.
+//!
+//! The traits are object safe, and are implemented for references, [Box] and [Arc], so a
+//! provider can be chosen at runtime as a `Box<dyn EphemerisProvider>`.
+#[cfg(feature = "mock")]
+pub mod mock;
+
+use crate::common::AberrationCorrection;
+use crate::coordinates::Rectangular;
+use crate::matrix::Matrix3x3;
+use crate::spk::{easier_reader, State};
+use crate::time::{Et, OutputFormat};
+use crate::vector::Vector3D;
+use crate::Error;
+use cspice_sys::SpiceDouble;
+use std::sync::Arc;
+
+/// A source of states of one body relative to another.
+pub trait EphemerisProvider {
+    /// The state of `target` relative to `observer` in `frame` at `et`, and the one way light
+    /// time between them in seconds.
+    fn state(
+        &self,
+        target: &str,
+        et: Et,
+        frame: &str,
+        abcorr: AberrationCorrection,
+        observer: &str,
+    ) -> Result<(State, SpiceDouble), Error>;
+
+    /// The position of `target` relative to `observer` in `frame` at `et`, and the one way light
+    /// time between them in seconds.
+    fn position(
+        &self,
+        target: &str,
+        et: Et,
+        frame: &str,
+        abcorr: AberrationCorrection,
+        observer: &str,
+    ) -> Result<(Rectangular, SpiceDouble), Error> {
+        self.state(target, et, frame, abcorr, observer)
+            .map(|(state, lt)| (state.position, lt))
+    }
+}
+
+/// A source of the orientation of one reference frame relative to another.
+pub trait OrientationProvider {
+    /// The matrix rotating position vectors from frame `from` to frame `to` at `et`.
+    fn rotation(&self, from: &str, to: &str, et: Et) -> Result<Matrix3x3, Error>;
+
+    /// Rotate a position vector from frame `from` to frame `to` at `et`.
+    fn rotate(&self, vector: &Vector3D, from: &str, to: &str, et: Et) -> Result<Vector3D, Error> {
+        self.rotation(from, to, et).map(|m| m.mul_vec(vector))
+    }
+}
+
+/// Conversion between UTC strings and Ephemeris Time.
+pub trait TimeConverter {
+    /// Convert a UTC time string, e.g. `2000-01-01T12:00:00`, to Ephemeris Time.
+    fn utc_to_et(&self, utc: &str) -> Result<Et, Error>;
+
+    /// Convert Ephemeris Time to an ISO 8601 UTC string with millisecond precision, e.g.
+    /// `2000-01-01T11:58:55.816`.
+    fn et_to_utc(&self, et: Et) -> Result<String, Error>;
+}
+
+/// The real SPICE implementation of the provider traits, which requires the relevant kernels to
+/// be loaded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Spice;
+
+impl EphemerisProvider for Spice {
+    /// See [easier_reader()].
+    fn state(
+        &self,
+        target: &str,
+        et: Et,
+        frame: &str,
+        abcorr: AberrationCorrection,
+        observer: &str,
+    ) -> Result<(State, SpiceDouble), Error> {
+        easier_reader(target, et, frame, abcorr, observer)
+    }
+}
+
+impl OrientationProvider for Spice {
+    /// See [Matrix3x3::from_frames()].
+    fn rotation(&self, from: &str, to: &str, et: Et) -> Result<Matrix3x3, Error> {
+        Matrix3x3::from_frames(from, to, et)
+    }
+}
+
+impl TimeConverter for Spice {
+    fn utc_to_et(&self, utc: &str) -> Result<Et, Error> {
+        Et::from_string(utc)
+    }
+
+    fn et_to_utc(&self, et: Et) -> Result<String, Error> {
+        et.format(OutputFormat::IsoCalendar { precision: 3 })
+    }
+}
+
+macro_rules! forward_providers {
+    ($($ptr:ty),*) => {
+        $(
+            impl<P: EphemerisProvider + ?Sized> EphemerisProvider for $ptr {
+                fn state(
+                    &self,
+                    target: &str,
+                    et: Et,
+                    frame: &str,
+                    abcorr: AberrationCorrection,
+                    observer: &str,
+                ) -> Result<(State, SpiceDouble), Error> {
+                    (**self).state(target, et, frame, abcorr, observer)
+                }
+            }
+
+            impl<P: OrientationProvider + ?Sized> OrientationProvider for $ptr {
+                fn rotation(&self, from: &str, to: &str, et: Et) -> Result<Matrix3x3, Error> {
+                    (**self).rotation(from, to, et)
+                }
+            }
+
+            impl<P: TimeConverter + ?Sized> TimeConverter for $ptr {
+                fn utc_to_et(&self, utc: &str) -> Result<Et, Error> {
+                    (**self).utc_to_et(utc)
+                }
+
+                fn et_to_utc(&self, et: Et) -> Result<String, Error> {
+                    (**self).et_to_utc(et)
+                }
+            }
+        )*
+    };
+}
+
+forward_providers!(&P, Box<P>, Arc<P>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+
+    fn earth_range<E: EphemerisProvider, T: TimeConverter>(ephemeris: &E, time: &T) -> SpiceDouble {
+        let et = time.utc_to_et("2000-01-01T12:00:00").unwrap();
+        let (state, _) = ephemeris
+            .state("EARTH", et, "J2000", AberrationCorrection::NONE, "SUN")
+            .unwrap();
+        state.range()
+    }
+
+    #[test]
+    fn test_spice() {
+        load_test_data();
+        let range = earth_range(&Spice, &Spice);
+        assert!(range > 1.4e8 && range < 1.6e8);
+        let et = Spice.utc_to_et("2000-01-01T12:00:00").unwrap();
+        assert_eq!(Spice.et_to_utc(et).unwrap(), "2000-01-01T12:00:00.000");
+        let (position, lt) = Spice
+            .position("EARTH", et, "J2000", AberrationCorrection::NONE, "SUN")
+            .unwrap();
+        let (state, _) =
+            easier_reader("EARTH", et, "J2000", AberrationCorrection::NONE, "SUN").unwrap();
+        assert_eq!(position, state.position);
+        assert!(lt > 490.0 && lt < 510.0);
+    }
+
+    #[test]
+    fn test_spice_orientation() {
+        load_test_data();
+        let et = Et(0.0);
+        let provider: Box<dyn OrientationProvider> = Box::new(Spice);
+        let m = provider.rotation("J2000", "ECLIPJ2000", et).unwrap();
+        assert_eq!(
+            m,
+            Matrix3x3::from_frames("J2000", "ECLIPJ2000", et).unwrap()
+        );
+        let v = provider
+            .rotate(&Vector3D([0.0, 0.0, 1.0]), "ECLIPJ2000", "ECLIPJ2000", et)
+            .unwrap();
+        assert_eq!(v, Vector3D([0.0, 0.0, 1.0]));
+        assert!(provider.rotation("J2000", "NOT_A_FRAME", et).is_err());
+
+        let ephemeris: Arc<dyn EphemerisProvider> = Arc::new(Spice);
+        assert!(earth_range(&ephemeris, &Spice) > 1.4e8);
+    }
+}
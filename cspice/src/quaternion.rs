@@ -0,0 +1,122 @@
+//! Functions for working with quaternions.
+//!
+//! See [Rotations required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/rotation.html#Quaternions)
+use crate::error::get_last_error;
+use crate::matrix::Matrix3x3;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{m2q_c, q2m_c, SpiceDouble};
+use derive_more::{Deref, DerefMut, From, Into};
+
+/// A unit quaternion representing a rotation, using the SPICE convention where the scalar part
+/// is the first element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, From, Into, Deref, DerefMut)]
+pub struct Quaternion(pub [SpiceDouble; 4]);
+
+impl Quaternion {
+    /// The identity rotation.
+    pub fn identity() -> Self {
+        Self([1.0, 0.0, 0.0, 0.0])
+    }
+
+    /// Find a unit quaternion corresponding to a rotation matrix.
+    ///
+    /// See [m2q_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/m2q_c.html).
+    pub fn from_matrix(matrix: &Matrix3x3) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut q = Quaternion::default();
+            unsafe { m2q_c(matrix.as_ptr() as *mut _, q.as_mut_ptr()) };
+            get_last_error()?;
+            Ok(q)
+        })
+    }
+
+    /// Find the rotation matrix corresponding to this quaternion.
+    ///
+    /// See [q2m_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/q2m_c.html).
+    pub fn to_matrix(&self) -> Matrix3x3 {
+        with_spice_lock_or_panic(|| {
+            let mut m = Matrix3x3::default();
+            unsafe { q2m_c(self.as_ptr() as *mut SpiceDouble, m.as_mut_ptr()) };
+            m
+        })
+    }
+
+    /// Spherical linear interpolation between this rotation (`t = 0`) and `other` (`t = 1`),
+    /// following the shortest path.
+    pub fn slerp(&self, other: &Quaternion, t: SpiceDouble) -> Self {
+        let a = self.0;
+        let mut b = other.0;
+        let mut cos_theta: SpiceDouble = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+        if cos_theta < 0.0 {
+            b.iter_mut().for_each(|x| *x = -*x);
+            cos_theta = -cos_theta;
+        }
+        let (wa, wb) = if cos_theta > 0.9995 {
+            // Nearly parallel, linear interpolation avoids dividing by sin(theta) ~ 0
+            (1.0 - t, t)
+        } else {
+            let theta = cos_theta.acos();
+            let sin_theta = theta.sin();
+            (
+                ((1.0 - t) * theta).sin() / sin_theta,
+                (t * theta).sin() / sin_theta,
+            )
+        };
+        let mut out = [0.0; 4];
+        for (o, (x, y)) in out.iter_mut().zip(a.iter().zip(b.iter())) {
+            *o = wa * x + wb * y;
+        }
+        let norm = out.iter().map(|x| x * x).sum::<SpiceDouble>().sqrt();
+        out.iter_mut().for_each(|x| *x /= norm);
+        Self(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::Axis;
+    use crate::units::Radians;
+    use std::f64::consts::FRAC_PI_4;
+
+    const EPSILON: f64 = 1e-12;
+
+    fn assert_quat_eq(a: Quaternion, b: [SpiceDouble; 4]) {
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < EPSILON, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn test_slerp() {
+        let half = FRAC_PI_4 / 2.0;
+        let q90 = Quaternion([FRAC_PI_4.cos(), 0.0, 0.0, FRAC_PI_4.sin()]);
+        let identity = Quaternion::identity();
+        assert_quat_eq(identity.slerp(&q90, 0.0), identity.0);
+        assert_quat_eq(identity.slerp(&q90, 1.0), q90.0);
+        assert_quat_eq(
+            identity.slerp(&q90, 0.5),
+            [half.cos(), 0.0, 0.0, half.sin()],
+        );
+
+        // -q is the same rotation, so should take the short path
+        let neg = Quaternion(q90.map(|x| -x));
+        assert_quat_eq(
+            identity.slerp(&neg, 0.5),
+            [half.cos(), 0.0, 0.0, half.sin()],
+        );
+    }
+
+    #[test]
+    fn test_matrix_conversion() {
+        let m = Matrix3x3::identity().rotate(Radians(0.3), Axis::X);
+        let q = Quaternion::from_matrix(&m).unwrap();
+        let m_ = q.to_matrix();
+        for (a, b) in m.iter().flatten().zip(m_.iter().flatten()) {
+            assert!((a - b).abs() < EPSILON);
+        }
+
+        let err = Quaternion::from_matrix(&Matrix3x3([[2.0; 3]; 3])).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(NOTAROTATION)");
+    }
+}
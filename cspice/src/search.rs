@@ -0,0 +1,57 @@
+//! Numeric helpers for bracketing values and searching ordered arrays, e.g. when post-processing
+//! a time series of ephemeris times for custom event detection.
+use crate::with_spice_lock_or_panic;
+use cspice_sys::{brcktd_c, lstled_c, lstltd_c, SpiceDouble, SpiceInt};
+
+/// Bracket a number, i.e. clamp it to the interval between `end1` and `end2`, which may be given
+/// in either order.
+///
+/// See [brcktd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/brcktd_c.html).
+pub fn bracket(number: SpiceDouble, end1: SpiceDouble, end2: SpiceDouble) -> SpiceDouble {
+    with_spice_lock_or_panic(|| unsafe { brcktd_c(number, end1, end2) })
+}
+
+/// Find the index of the last element of a non-decreasing array that is less than or equal to
+/// `x`, returns `None` if there is no such element.
+///
+/// See [lstled_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/lstled_c.html).
+pub fn last_less_or_equal(x: SpiceDouble, array: &[SpiceDouble]) -> Option<usize> {
+    let index = with_spice_lock_or_panic(|| unsafe {
+        lstled_c(x, array.len() as SpiceInt, array.as_ptr() as *mut _)
+    });
+    usize::try_from(index).ok()
+}
+
+/// Find the index of the last element of a non-decreasing array that is strictly less than `x`,
+/// returns `None` if there is no such element.
+///
+/// See [lstltd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/lstltd_c.html).
+pub fn last_less_than(x: SpiceDouble, array: &[SpiceDouble]) -> Option<usize> {
+    let index = with_spice_lock_or_panic(|| unsafe {
+        lstltd_c(x, array.len() as SpiceInt, array.as_ptr() as *mut _)
+    });
+    usize::try_from(index).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracket() {
+        assert_eq!(bracket(5.0, 0.0, 10.0), 5.0);
+        assert_eq!(bracket(-1.0, 0.0, 10.0), 0.0);
+        assert_eq!(bracket(11.0, 10.0, 0.0), 10.0);
+    }
+
+    #[test]
+    fn test_last_less() {
+        let times = [0.0, 60.0, 60.0, 120.0];
+        assert_eq!(last_less_or_equal(60.0, &times), Some(2));
+        assert_eq!(last_less_than(60.0, &times), Some(0));
+        assert_eq!(last_less_or_equal(1000.0, &times), Some(3));
+        assert_eq!(last_less_or_equal(-1.0, &times), None);
+        assert_eq!(last_less_than(0.0, &times), None);
+        assert_eq!(last_less_than(0.0, &[]), None);
+    }
+}
@@ -0,0 +1,144 @@
+//! Positions and orientations of many bodies at one instant, for feeding renderers.
+//!
+//! A [SolarSystemSnapshot] places every body relative to the solar system barycenter, so that
+//! a scene graph can use a single common origin, and gives the rotation of each body's
+//! body-fixed frame (typically an `IAU_*` frame defined by a PCK).
+use crate::body::BodyRef;
+use crate::common::AberrationCorrection;
+use crate::coordinates::Rectangular;
+use crate::geometry::body_fixed_frame;
+use crate::matrix::Matrix3x3;
+use crate::naif_ids::SOLAR_SYSTEM_BARYCENTER;
+use crate::spk::easy_position;
+use crate::string::StringParam;
+use crate::time::Et;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::SpiceInt;
+
+/// The position and orientation of a single body in a [SolarSystemSnapshot].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodySnapshot {
+    /// NAIF ID code of the body.
+    pub id: SpiceInt,
+    /// Geometric position relative to the solar system barycenter.
+    pub position: Rectangular,
+    /// Rotation from the snapshot frame to the body-fixed frame, `None` if the body has no
+    /// body-fixed frame (for example the solar system barycenter).
+    pub orientation: Option<Matrix3x3>,
+}
+
+/// The positions and orientations of a set of bodies at one instant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolarSystemSnapshot {
+    pub et: Et,
+    /// The reference frame of the positions, e.g. `J2000` or `ECLIPJ2000`.
+    pub frame: String,
+    pub bodies: Vec<BodySnapshot>,
+}
+
+impl SolarSystemSnapshot {
+    /// Capture the positions and orientations of `bodies` at `et`, in the order given.
+    ///
+    /// The orientation data of every body that has a body-fixed frame must be loaded. All of the
+    /// bodies are queried under a single acquisition of the SPICE lock.
+    pub fn capture<'f, F: Into<StringParam<'f>>>(
+        et: Et,
+        frame: F,
+        bodies: &[BodyRef],
+    ) -> Result<Self, Error> {
+        let frame = frame.into();
+        with_spice_lock_or_panic(|| {
+            let bodies = bodies
+                .iter()
+                .map(|body| {
+                    let id = body.to_id()?;
+                    let (position, _) = easy_position(
+                        id,
+                        et,
+                        &*frame,
+                        AberrationCorrection::NONE,
+                        SOLAR_SYSTEM_BARYCENTER,
+                    )?;
+                    let orientation = match body_fixed_frame(id) {
+                        Ok(body_frame) => Some(Matrix3x3::from_frames(&*frame, &body_frame, et)?),
+                        Err(e) if e.short_message == "SPICE(FRAMENOTFOUND)" => None,
+                        Err(e) => return Err(e),
+                    };
+                    Ok(BodySnapshot {
+                        id,
+                        position,
+                        orientation,
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Ok(Self {
+                et,
+                frame: frame.to_string(),
+                bodies,
+            })
+        })
+    }
+
+    /// Find a body in the snapshot by NAIF ID code.
+    pub fn body(&self, id: SpiceInt) -> Option<&BodySnapshot> {
+        self.bodies.iter().find(|b| b.id == id)
+    }
+
+    /// The position of `body` relative to `origin`, for example to center a scene on a planet.
+    /// Returns `None` unless both bodies are in the snapshot.
+    pub fn relative_position(&self, body: SpiceInt, origin: SpiceInt) -> Option<Rectangular> {
+        let (body, origin) = (self.body(body)?.position, self.body(origin)?.position);
+        Some(Rectangular::new(
+            body.x - origin.x,
+            body.y - origin.y,
+            body.z - origin.z,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::naif_ids::{EARTH, MOON};
+    use crate::spk::position;
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    #[test]
+    fn test_capture() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(3600.0);
+        let snapshot = SolarSystemSnapshot::capture(
+            et,
+            "J2000",
+            &[BodyRef::from(MOON), BodyRef::from(SOLAR_SYSTEM_BARYCENTER)],
+        )
+        .unwrap();
+        assert_eq!(snapshot.frame, "J2000");
+        assert_eq!(snapshot.bodies.len(), 2);
+
+        let moon = snapshot.body(MOON).unwrap();
+        let (expected, _) =
+            position("MOON", et, "J2000", AberrationCorrection::NONE, "SSB").unwrap();
+        assert_eq!(moon.position, expected);
+        assert_eq!(
+            moon.orientation,
+            Some(Matrix3x3::from_frames("J2000", "IAU_MOON", et).unwrap())
+        );
+        let ssb = snapshot.body(SOLAR_SYSTEM_BARYCENTER).unwrap();
+        assert_eq!(ssb.position, Rectangular::default());
+        assert_eq!(ssb.orientation, None);
+
+        assert_eq!(
+            snapshot
+                .relative_position(MOON, SOLAR_SYSTEM_BARYCENTER)
+                .unwrap(),
+            expected
+        );
+        assert_eq!(snapshot.relative_position(MOON, EARTH), None);
+        assert_eq!(
+            snapshot.relative_position(EARTH, SOLAR_SYSTEM_BARYCENTER),
+            None
+        );
+    }
+}
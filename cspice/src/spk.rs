@@ -1,17 +1,34 @@
 //! Functions relating to the Spacecraft and Planet Ephemeris (SPK) subsystem of SPICE.
+use crate::body::BodyRef;
+use crate::cell::Window;
 use crate::common::AberrationCorrection;
 use crate::coordinates::Rectangular;
-use crate::error::get_last_error;
-use crate::string::StringParam;
+use crate::daf::{self, DafSegment};
+use crate::elements::{GeophysicalConstants, TwoLineElements};
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::frame::{FrameId, Framed};
+use crate::lighttime::SPEED_OF_LIGHT;
+use crate::matrix::StateTransformMatrix6x6;
+use crate::string::{static_spice_str, SpiceStr, SpiceString, StaticSpiceStr, StringParam};
 use crate::time::Et;
+use crate::units::{Kilometers, Seconds};
 use crate::vector::Vector3D;
 use crate::{with_spice_lock_or_panic, Error};
-use cspice_sys::{spkez_c, spkezp_c, spkezr_c, spkpos_c, SpiceDouble};
-use derive_more::Into;
+use cspice_sys::{
+    dafcls_c, dafopr_c, dvcrss_c, dvdot_c, dvhat_c, dvsep_c, spkcls_c, spkez_c, spkezp_c, spkezr_c,
+    spklef_c, spkopn_c, spkpos_c, spksfs_c, spksub_c, spkuef_c, spkw08_c, spkw10_c, SpiceBoolean,
+    SpiceChar, SpiceDouble, SpiceInt, SPICETRUE,
+};
+use derive_more::{From, Into};
+#[cfg(feature = "ndarray")]
+use ndarray::{Array1, Array2, ArrayView1};
+use std::array::TryFromSliceError;
+use std::collections::HashMap;
+use std::ops::{Add, Neg, Sub};
 
 /// A Cartesian state vector representing the position and velocity of the target body
 /// relative to the specified observer
-#[derive(Copy, Clone, Debug, Default, PartialEq, Into)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, From, Into)]
 pub struct State {
     pub position: Rectangular,
     pub velocity: Vector3D,
@@ -26,6 +43,192 @@ impl From<[SpiceDouble; 6]> for State {
     }
 }
 
+impl From<State> for [SpiceDouble; 6] {
+    fn from(state: State) -> Self {
+        [
+            state.position.x,
+            state.position.y,
+            state.position.z,
+            state.velocity[0],
+            state.velocity[1],
+            state.velocity[2],
+        ]
+    }
+}
+
+/// Fails unless the slice has exactly 6 elements, position followed by velocity.
+impl TryFrom<&[SpiceDouble]> for State {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[SpiceDouble]) -> Result<Self, Self::Error> {
+        <[SpiceDouble; 6]>::try_from(slice).map(Self::from)
+    }
+}
+
+impl State {
+    /// Create a state from a position and velocity.
+    pub const fn new(position: Rectangular, velocity: Vector3D) -> Self {
+        Self { position, velocity }
+    }
+
+    /// Copy the state into an array, position followed by velocity.
+    pub fn to_array(&self) -> [SpiceDouble; 6] {
+        (*self).into()
+    }
+
+    /// Transform the state into another frame, for example using a matrix obtained from
+    /// [StateTransformMatrix6x6::from_frames].
+    pub fn transform(&self, matrix: &StateTransformMatrix6x6) -> Self {
+        matrix.mul_state(self)
+    }
+
+    /// Transform the state from frame `from` into frame `to` at `et`.
+    ///
+    /// When `to` rotates relative to `from` (e.g. a body-fixed frame such as `IAU_EARTH`), the
+    /// velocity in `to` is not just the rotated velocity: it also includes the apparent motion
+    /// caused by the rotation of the frame. Rotating the position and velocity separately with
+    /// [Matrix3x3::from_frames](crate::matrix::Matrix3x3::from_frames) (i.e. `pxform`) omits
+    /// that term, so this uses the state transformation matrix from `sxform` instead.
+    ///
+    /// See [sxform_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sxform_c.html).
+    pub fn to_frame<'f, 't, F, T>(&self, from: F, to: T, et: Et) -> Result<Self, Error>
+    where
+        F: Into<StringParam<'f>>,
+        T: Into<StringParam<'t>>,
+    {
+        let matrix = StateTransformMatrix6x6::from_frames(from, to, et)?;
+        Ok(self.transform(&matrix))
+    }
+
+    /// The distance from the observer to the target.
+    pub fn range(&self) -> SpiceDouble {
+        let p = self.position;
+        (p.x * p.x + p.y * p.y + p.z * p.z).sqrt()
+    }
+
+    /// The rate of change of [State::range()], i.e. the radial velocity of the target relative
+    /// to the observer.
+    ///
+    /// Positive when the target is moving away from the observer, zero if the range is zero.
+    pub fn range_rate(&self) -> SpiceDouble {
+        let range = self.range();
+        if range == 0.0 {
+            return 0.0;
+        }
+        let (p, v) = (self.position, self.velocity);
+        (p.x * v[0] + p.y * v[1] + p.z * v[2]) / range
+    }
+
+    /// The time derivative of the dot product of the positions of two states.
+    ///
+    /// See [dvdot_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dvdot_c.html).
+    pub fn dot_derivative(&self, other: &State) -> SpiceDouble {
+        let (mut s1, mut s2): ([SpiceDouble; 6], [SpiceDouble; 6]) =
+            ((*self).into(), (*other).into());
+        with_spice_lock_or_panic(|| unsafe { dvdot_c(s1.as_mut_ptr(), s2.as_mut_ptr()) })
+    }
+
+    /// The unit vector of the position and its time derivative. If the position is zero then the
+    /// result is zero.
+    ///
+    /// See [dvhat_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dvhat_c.html).
+    pub fn unit(&self) -> State {
+        let mut s1: [SpiceDouble; 6] = (*self).into();
+        let mut out = [0.0; 6];
+        with_spice_lock_or_panic(|| unsafe { dvhat_c(s1.as_mut_ptr(), out.as_mut_ptr()) });
+        out.into()
+    }
+
+    /// The cross product of the positions of two states and its time derivative.
+    ///
+    /// See [dvcrss_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dvcrss_c.html).
+    pub fn cross(&self, other: &State) -> State {
+        let (mut s1, mut s2): ([SpiceDouble; 6], [SpiceDouble; 6]) =
+            ((*self).into(), (*other).into());
+        let mut out = [0.0; 6];
+        with_spice_lock_or_panic(|| unsafe {
+            dvcrss_c(s1.as_mut_ptr(), s2.as_mut_ptr(), out.as_mut_ptr())
+        });
+        out.into()
+    }
+
+    /// The time derivative of the angular separation between the positions of two states, in
+    /// radians per second.
+    ///
+    /// See [dvsep_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/dvsep_c.html).
+    pub fn separation_rate(&self, other: &State) -> Result<SpiceDouble, Error> {
+        let (mut s1, mut s2): ([SpiceDouble; 6], [SpiceDouble; 6]) =
+            ((*self).into(), (*other).into());
+        with_spice_lock_or_panic(|| {
+            let rate = unsafe { dvsep_c(s1.as_mut_ptr(), s2.as_mut_ptr()) };
+            get_last_error()?;
+            Ok(rate)
+        })
+    }
+
+    /// The specific angular momentum `position x velocity`, in km^2/s.
+    pub fn angular_momentum(&self) -> Vector3D {
+        let (p, v) = (self.position, self.velocity);
+        Vector3D([
+            p.y * v[2] - p.z * v[1],
+            p.z * v[0] - p.x * v[2],
+            p.x * v[1] - p.y * v[0],
+        ])
+    }
+
+    /// The unit normal of the orbital plane, i.e. the direction of the
+    /// [angular momentum](State::angular_momentum()). Returns `None` if the position and velocity
+    /// are parallel (or either is zero) so the plane is undefined.
+    pub fn orbital_plane_normal(&self) -> Option<Vector3D> {
+        let h = self.angular_momentum();
+        let norm = h.iter().map(|x| x * x).sum::<SpiceDouble>().sqrt();
+        (norm > 0.0).then(|| Vector3D([h[0] / norm, h[1] / norm, h[2] / norm]))
+    }
+
+    /// The specific orbital energy `v^2 / 2 - gm / r` in km^2/s^2, for a central body with
+    /// gravitational parameter `gm` (km^3/s^2). Negative for a bound orbit.
+    pub fn specific_energy(&self, gm: SpiceDouble) -> SpiceDouble {
+        let speed_squared = self.velocity.iter().map(|x| x * x).sum::<SpiceDouble>();
+        speed_squared / 2.0 - gm / self.range()
+    }
+}
+
+/// Compose relative states, e.g. the state of A relative to B plus the state of B relative to C
+/// gives the state of A relative to C. Both states must be in the same frame.
+impl Add for State {
+    type Output = State;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let (lhs, rhs): ([SpiceDouble; 6], [SpiceDouble; 6]) = (self.into(), rhs.into());
+        let mut out = [0.0; 6];
+        for i in 0..6 {
+            out[i] = lhs[i] + rhs[i];
+        }
+        out.into()
+    }
+}
+
+/// Difference of two states, e.g. the state of a spacecraft minus the state of a station (both
+/// relative to the same observer) gives the state of the spacecraft relative to the station.
+/// Both states must be in the same frame.
+impl Sub for State {
+    type Output = State;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Neg for State {
+    type Output = State;
+
+    fn neg(self) -> Self::Output {
+        let mut state: [SpiceDouble; 6] = self.into();
+        state.iter_mut().for_each(|x| *x = -*x);
+        state.into()
+    }
+}
+
 /// Return the position of a target body relative to an observing body, optionally corrected for
 /// light time (planetary aberration) and stellar aberration.
 ///
@@ -47,11 +250,11 @@ where
         let mut light_time = 0.0;
         unsafe {
             spkpos_c(
-                target.into().as_mut_ptr(),
+                target.into().as_mut_ptr()?,
                 et.0,
-                reference_frame.into().as_mut_ptr(),
+                reference_frame.into().as_mut_ptr()?,
                 aberration_correction.as_spice_char(),
-                observing_body.into().as_mut_ptr(),
+                observing_body.into().as_mut_ptr()?,
                 position.as_mut_ptr(),
                 &mut light_time,
             )
@@ -83,7 +286,7 @@ where
             spkez_c(
                 target,
                 et.0,
-                reference_frame.into().as_mut_ptr(),
+                reference_frame.into().as_mut_ptr()?,
                 aberration_correction.as_spice_char(),
                 observing_body,
                 pos_vel.as_mut_ptr(),
@@ -117,7 +320,7 @@ where
             spkezp_c(
                 target,
                 et.0,
-                reference_frame.into().as_mut_ptr(),
+                reference_frame.into().as_mut_ptr()?,
                 aberration_correction.as_spice_char(),
                 observing_body,
                 position.as_mut_ptr(),
@@ -151,11 +354,11 @@ where
         let mut light_time = 0.0;
         unsafe {
             spkezr_c(
-                target.into().as_mut_ptr(),
+                target.into().as_mut_ptr()?,
                 et.0,
-                reference_frame.into().as_mut_ptr(),
+                reference_frame.into().as_mut_ptr()?,
                 aberration_correction.as_spice_char(),
-                observing_body.into().as_mut_ptr(),
+                observing_body.into().as_mut_ptr()?,
                 pos_vel.as_mut_ptr(),
                 &mut light_time,
             )
@@ -165,10 +368,696 @@ where
     })
 }
 
+/// The arguments of [easier_reader()] as a struct, so a state can be requested with named
+/// fields, similar to calling `spkezr` with keyword arguments in SpiceyPy.
+///
+/// Defaults to the `J2000` frame, no aberration correction and an ET of zero, the target and
+/// observer must always be given.
+///
+/// ```no_run
+/// # use cspice::spk::StateRequest;
+/// # use cspice::common::AberrationCorrection;
+/// let (state, light_time) = StateRequest {
+///     target: "MOON",
+///     observer: "EARTH",
+///     abcorr: AberrationCorrection::LT_S,
+///     ..Default::default()
+/// }
+/// .get()?;
+/// # Ok::<(), cspice::Error>(())
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StateRequest<'a> {
+    pub target: &'a str,
+    pub observer: &'a str,
+    pub frame: &'a str,
+    pub abcorr: AberrationCorrection,
+    pub et: Et,
+}
+
+impl Default for StateRequest<'_> {
+    fn default() -> Self {
+        Self {
+            target: "",
+            observer: "",
+            frame: "J2000",
+            abcorr: AberrationCorrection::NONE,
+            et: Et(0.0),
+        }
+    }
+}
+
+impl StateRequest<'_> {
+    /// Return the state of the target relative to the observer and the one way light time
+    /// between them in seconds.
+    ///
+    /// See [easier_reader()].
+    pub fn get(&self) -> Result<(State, SpiceDouble), Error> {
+        easier_reader(self.target, self.et, self.frame, self.abcorr, self.observer)
+    }
+}
+
+/// Return the states of several target bodies relative to an observing body at the same time,
+/// keyed by the NAIF ID code of each target. The SPICE lock is only acquired once, which is
+/// cheaper than querying each body separately, e.g. when drawing many bodies every frame.
+///
+/// See [easy_reader()].
+pub fn states_of<'b, 'r, 'o, R, O>(
+    targets: &[BodyRef<'b>],
+    et: Et,
+    reference_frame: R,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<HashMap<SpiceInt, State>, Error>
+where
+    R: Into<StringParam<'r>>,
+    O: Into<BodyRef<'o>>,
+{
+    let (reference_frame, observing_body) = (reference_frame.into(), observing_body.into());
+    with_spice_lock_or_panic(|| {
+        let observer = observing_body.to_id()?;
+        targets
+            .iter()
+            .map(|target| {
+                let target = target.to_id()?;
+                let (state, _) = easy_reader(
+                    target,
+                    et,
+                    &*reference_frame,
+                    aberration_correction,
+                    observer,
+                )?;
+                Ok((target, state))
+            })
+            .collect()
+    })
+}
+
+/// Equivalent to [easier_reader()], but the reference frame is given by ID code and the state is
+/// tagged with it.
+pub fn framed_state<'t, 'o, T, O>(
+    target: T,
+    et: Et,
+    reference_frame: FrameId,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<(Framed<State>, SpiceDouble), Error>
+where
+    T: Into<StringParam<'t>>,
+    O: Into<StringParam<'o>>,
+{
+    let frame = reference_frame.checked_name()?;
+    let (state, light_time) =
+        easier_reader(target, et, frame, aberration_correction, observing_body)?;
+    Ok((Framed::new(state, reference_frame), light_time))
+}
+
+/// Return the range rate (radial velocity) in km/s of a target body relative to an observing
+/// body, optionally corrected for light time and stellar aberration.
+///
+/// The range rate is positive when the target is moving away from the observer. It does not
+/// depend on the (inertial) reference frame, so the state is computed in `J2000`.
+///
+/// See [easier_reader()] and [State::range_rate()].
+pub fn range_rate<'t, 'o, T, O>(
+    target: T,
+    et: Et,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<SpiceDouble, Error>
+where
+    T: Into<StringParam<'t>>,
+    O: Into<StringParam<'o>>,
+{
+    let (state, _) = easier_reader(target, et, "J2000", aberration_correction, observing_body)?;
+    Ok(state.range_rate())
+}
+
+/// Return the first order two-way Doppler shift in the same units as `frequency`, for a signal
+/// transmitted at `frequency` and reflected or transponded back by a target with the given
+/// `range_rate` in km/s.
+///
+/// The shift is negative (the received frequency is lower) when the target is moving away,
+/// i.e. `-2 * range_rate / c * frequency`.
+pub fn two_way_doppler_shift(range_rate: SpiceDouble, frequency: SpiceDouble) -> SpiceDouble {
+    -2.0 * range_rate / SPEED_OF_LIGHT * frequency
+}
+
+/// Sample the position of a target relative to an observer over the intervals of `window`,
+/// choosing the sample spacing adaptively so that the polyline through the samples stays within
+/// roughly `tolerance` of the true trajectory. Positions are geometric (uncorrected).
+///
+/// Each interval is first divided into a fixed number of segments, which are bisected wherever
+/// the trajectory at the midpoint deviates from the straight line between the endpoints by more
+/// than `tolerance`. Where the trajectory is nearly straight few samples are used, so this needs
+/// far fewer points than fixed-step sampling to plot an orbit. The samples of consecutive
+/// intervals are returned in a single polyline.
+///
+/// See [position()].
+pub fn sample_adaptive<'t, 'o, 'r, T, O, R>(
+    target: T,
+    observing_body: O,
+    reference_frame: R,
+    window: &Window,
+    tolerance: Kilometers,
+) -> Result<Vec<(Et, Rectangular)>, Error>
+where
+    T: Into<StringParam<'t>>,
+    O: Into<StringParam<'o>>,
+    R: Into<StringParam<'r>>,
+{
+    const INITIAL_SEGMENTS: usize = 16;
+    let (target, observing_body, reference_frame) =
+        (target.into(), observing_body.into(), reference_frame.into());
+    with_spice_lock_or_panic(|| {
+        if tolerance.0.is_nan() || tolerance.0 <= 0.0 {
+            set_error_message(format!("The tolerance {} must be positive", tolerance.0));
+            signal_error("SPICE(INVALIDTOLERANCE)");
+            get_last_error()?;
+        }
+        let sample = |et: SpiceDouble| {
+            let (position, _) = position(
+                &*target,
+                Et(et),
+                &*reference_frame,
+                AberrationCorrection::NONE,
+                &*observing_body,
+            )?;
+            Ok((Et(et), position))
+        };
+        let mut polyline = Vec::new();
+        for i in 0..window.window_cardinality()? as usize {
+            let (start, stop) = window.window_interval(i)?;
+            let mut left = sample(start)?;
+            polyline.push(left);
+            if stop <= start {
+                continue;
+            }
+            let step = (stop - start) / INITIAL_SEGMENTS as SpiceDouble;
+            for j in 1..=INITIAL_SEGMENTS {
+                let et = if j == INITIAL_SEGMENTS {
+                    stop
+                } else {
+                    start + j as SpiceDouble * step
+                };
+                let right = sample(et)?;
+                refine(&sample, left, right, tolerance.0, &mut polyline)?;
+                left = right;
+            }
+        }
+        Ok(polyline)
+    })
+}
+
+/// Append samples between `left` (exclusive) and `right` (inclusive) to `polyline`, bisecting
+/// until the midpoint is within `tolerance` of the chord.
+fn refine<F>(
+    sample: &F,
+    left: (Et, Rectangular),
+    right: (Et, Rectangular),
+    tolerance: SpiceDouble,
+    polyline: &mut Vec<(Et, Rectangular)>,
+) -> Result<(), Error>
+where
+    F: Fn(SpiceDouble) -> Result<(Et, Rectangular), Error>,
+{
+    let et = (left.0 .0 + right.0 .0) / 2.0;
+    // Stop once the times can't be split any further
+    if et > left.0 .0 && et < right.0 .0 {
+        let middle = sample(et)?;
+        let deviation = [
+            middle.1.x - (left.1.x + right.1.x) / 2.0,
+            middle.1.y - (left.1.y + right.1.y) / 2.0,
+            middle.1.z - (left.1.z + right.1.z) / 2.0,
+        ];
+        if deviation.iter().map(|d| d * d).sum::<SpiceDouble>().sqrt() > tolerance {
+            refine(sample, left, middle, tolerance, polyline)?;
+            return refine(sample, middle, right, tolerance, polyline);
+        }
+    }
+    polyline.push(right);
+    Ok(())
+}
+
+/// Sample the position of a target body relative to an observing body at each of `ets`,
+/// returning the ETs as an array of length `n` and the positions as an `n × 3` array.
+///
+/// See [position()].
+#[cfg(feature = "ndarray")]
+pub fn positions_array<'t, 'r, 'o, T, R, O>(
+    target: T,
+    ets: &[Et],
+    reference_frame: R,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<(Array1<SpiceDouble>, Array2<SpiceDouble>), Error>
+where
+    T: Into<StringParam<'t>>,
+    R: Into<StringParam<'r>>,
+    O: Into<StringParam<'o>>,
+{
+    let (target, reference_frame, observing_body) =
+        (target.into(), reference_frame.into(), observing_body.into());
+    let mut positions = Array2::zeros((ets.len(), 3));
+    with_spice_lock_or_panic(|| {
+        for (et, mut row) in ets.iter().zip(positions.rows_mut()) {
+            let (p, _) = position(
+                &*target,
+                *et,
+                &*reference_frame,
+                aberration_correction,
+                &*observing_body,
+            )?;
+            row.assign(&ArrayView1::from(&p.to_array()));
+        }
+        Ok::<_, Error>(())
+    })?;
+    Ok((ets.iter().map(|et| et.0).collect(), positions))
+}
+
+/// Sample the state of a target body relative to an observing body at each of `ets`,
+/// returning the ETs as an array of length `n` and the states as an `n × 6` array, with the
+/// position followed by the velocity in each row.
+///
+/// See [easier_reader()].
+#[cfg(feature = "ndarray")]
+pub fn states_array<'t, 'r, 'o, T, R, O>(
+    target: T,
+    ets: &[Et],
+    reference_frame: R,
+    aberration_correction: AberrationCorrection,
+    observing_body: O,
+) -> Result<(Array1<SpiceDouble>, Array2<SpiceDouble>), Error>
+where
+    T: Into<StringParam<'t>>,
+    R: Into<StringParam<'r>>,
+    O: Into<StringParam<'o>>,
+{
+    let (target, reference_frame, observing_body) =
+        (target.into(), reference_frame.into(), observing_body.into());
+    let mut states = Array2::zeros((ets.len(), 6));
+    with_spice_lock_or_panic(|| {
+        for (et, mut row) in ets.iter().zip(states.rows_mut()) {
+            let (state, _) = easier_reader(
+                &*target,
+                *et,
+                &*reference_frame,
+                aberration_correction,
+                &*observing_body,
+            )?;
+            row.assign(&ArrayView1::from(&state.to_array()));
+        }
+        Ok::<_, Error>(())
+    })?;
+    Ok((ets.iter().map(|et| et.0).collect(), states))
+}
+
+/// Write a new SPK file containing the parts of the segments in `input` that overlap the
+/// intervals of `window`, similar to NAIF's `spkmerge` utility. If `body` is given then only
+/// segments for that target are copied.
+///
+/// The output file must not already exist. Returns the number of segments written.
+///
+/// See [spksub_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spksub_c.html).
+pub fn subset<'i, 'o, I, O>(
+    input: I,
+    output: O,
+    body: Option<SpiceInt>,
+    window: &Window,
+) -> Result<usize, Error>
+where
+    I: Into<StringParam<'i>>,
+    O: Into<StringParam<'o>>,
+{
+    let intervals = (0..window.window_cardinality()? as usize)
+        .map(|i| window.window_interval(i))
+        .collect::<Result<Vec<_>, _>>()?;
+    with_spice_lock_or_panic(|| {
+        let (mut input_handle, mut output_handle) = (0, 0);
+        unsafe { dafopr_c(input.into().as_mut_ptr()?, &mut input_handle) };
+        get_last_error()?;
+        unsafe {
+            spkopn_c(
+                output.into().as_mut_ptr()?,
+                static_spice_str!("SPK SUBSET").as_mut_ptr(),
+                0,
+                &mut output_handle,
+            )
+        };
+        if let Err(e) = get_last_error() {
+            unsafe { dafcls_c(input_handle) };
+            return Err(e);
+        }
+        let result = copy_segments(input_handle, output_handle, body, &intervals);
+        unsafe {
+            spkcls_c(output_handle);
+            dafcls_c(input_handle);
+        }
+        let closed = get_last_error();
+        let count = result?;
+        closed?;
+        Ok(count)
+    })
+}
+
+fn copy_segments(
+    input_handle: SpiceInt,
+    output_handle: SpiceInt,
+    body: Option<SpiceInt>,
+    intervals: &[(SpiceDouble, SpiceDouble)],
+) -> Result<usize, Error> {
+    let mut count = 0;
+    for segment in daf::segments(input_handle)? {
+        let summary = SegmentSummary::from_daf(&segment);
+        if matches!(body, Some(b) if b != summary.body) {
+            continue;
+        }
+        // spksub_c needs the descriptor as it is packed in the file
+        let mut descriptor = segment.pack()?;
+        let name = SpiceString::from(&segment.name);
+        for &(left, right) in intervals {
+            let (begin, end) = (summary.start.0.max(left), summary.end.0.min(right));
+            if begin > end {
+                continue;
+            }
+            unsafe {
+                spksub_c(
+                    input_handle,
+                    descriptor.as_mut_ptr(),
+                    name.as_mut_ptr()?,
+                    begin,
+                    end,
+                    output_handle,
+                )
+            };
+            get_last_error()?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// The number of double precision components of an SPK segment summary.
+const SUMMARY_DOUBLES: SpiceInt = 2;
+/// The number of integer components of an SPK segment summary.
+const SUMMARY_INTEGERS: SpiceInt = 6;
+
+/// The summary of a segment of an SPK file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SegmentSummary {
+    /// NAIF ID code of the target body.
+    pub body: SpiceInt,
+    /// NAIF ID code of the center of motion.
+    pub center: SpiceInt,
+    /// Reference frame of the states.
+    pub frame: FrameId,
+    /// The SPK data type, e.g. 2 for Chebyshev position only.
+    pub data_type: SpiceInt,
+    /// Start of the coverage of the segment.
+    pub start: Et,
+    /// End of the coverage of the segment.
+    pub end: Et,
+    /// The segment identifier.
+    pub id: String,
+}
+
+impl SegmentSummary {
+    /// Convert the summary of a segment read from an SPK, which always has
+    /// [SUMMARY_DOUBLES] double precision and [SUMMARY_INTEGERS] integer components.
+    fn from_daf(segment: &DafSegment) -> Self {
+        let (doubles, integers) = (&segment.doubles, &segment.integers);
+        Self {
+            body: integers[0],
+            center: integers[1],
+            frame: FrameId(integers[2]),
+            data_type: integers[3],
+            start: Et(doubles[0]),
+            end: Et(doubles[1]),
+            id: segment.name.clone(),
+        }
+    }
+}
+
+/// An SPK file loaded directly into the SPK subsystem, bypassing [furnish()](crate::data::furnish).
+/// The file is unloaded when dropped.
+///
+/// Files loaded later take priority over files loaded earlier, including those loaded with
+/// [furnish()](crate::data::furnish).
+#[derive(Debug)]
+pub struct SpkHandle {
+    handle: Option<SpiceInt>,
+}
+
+impl SpkHandle {
+    /// Load an SPK file.
+    ///
+    /// See [spklef_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spklef_c.html).
+    pub fn load<'p, P: Into<StringParam<'p>>>(path: P) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut handle = 0;
+            unsafe { spklef_c(path.into().as_mut_ptr()?, &mut handle) };
+            get_last_error()?;
+            Ok(Self {
+                handle: Some(handle),
+            })
+        })
+    }
+
+    /// The DAF handle of the file.
+    pub fn handle(&self) -> SpiceInt {
+        // Only None after being unloaded, which consumes the SpkHandle
+        self.handle.unwrap()
+    }
+
+    /// List the segments in the file, in the order they appear.
+    pub fn segments(&self) -> Result<Vec<SegmentSummary>, Error> {
+        Ok(daf::segments(self.handle())?
+            .iter()
+            .map(SegmentSummary::from_daf)
+            .collect())
+    }
+
+    /// Unload the file.
+    ///
+    /// See [spkuef_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkuef_c.html).
+    pub fn unload(mut self) -> Result<(), Error> {
+        let handle = self.handle.take().unwrap();
+        with_spice_lock_or_panic(|| {
+            unsafe { spkuef_c(handle) };
+            get_last_error()
+        })
+    }
+}
+
+impl Drop for SpkHandle {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            with_spice_lock_or_panic(|| {
+                unsafe { spkuef_c(handle) };
+                let _ = get_last_error();
+            })
+        }
+    }
+}
+
+/// Find the highest priority loaded segment with data for `body` at `et`, returning the handle
+/// of the file containing it and its summary.
+///
+/// See [spksfs_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spksfs_c.html).
+pub fn find_segment<'b, B: Into<BodyRef<'b>>>(
+    body: B,
+    et: Et,
+) -> Result<Option<(SpiceInt, SegmentSummary)>, Error> {
+    let body = body.into();
+    with_spice_lock_or_panic(|| {
+        let body = body.to_id()?;
+        let (mut handle, mut found): (SpiceInt, SpiceBoolean) = (0, 0);
+        let mut descriptor = [0.0; 5];
+        let mut id = [0 as SpiceChar; 41];
+        unsafe {
+            spksfs_c(
+                body,
+                et.0,
+                id.len() as SpiceInt,
+                &mut handle,
+                descriptor.as_mut_ptr(),
+                id.as_mut_ptr(),
+                &mut found,
+            )
+        };
+        get_last_error()?;
+        if found != SPICETRUE as SpiceBoolean {
+            return Ok(None);
+        }
+        let id = SpiceStr::from_buffer(&id).as_str().into_owned();
+        let segment = DafSegment::unpack(&descriptor, SUMMARY_DOUBLES, SUMMARY_INTEGERS, id)?;
+        Ok(Some((handle, SegmentSummary::from_daf(&segment))))
+    })
+}
+
+/// The metadata of an SPK segment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SegmentHeader<'a> {
+    /// NAIF ID code of the target body.
+    pub body: SpiceInt,
+    /// NAIF ID code of the center of motion.
+    pub center: SpiceInt,
+    /// Reference frame of the states.
+    pub frame: &'a str,
+    /// Start of the coverage of the segment.
+    pub start: Et,
+    /// End of the coverage of the segment.
+    pub end: Et,
+    /// Segment identifier, at most 40 printable characters.
+    pub id: &'a str,
+}
+
+/// A new SPK file open for writing segments. The file is closed when dropped, use
+/// [SpkWriter::close()] to check for errors.
+#[derive(Debug)]
+pub struct SpkWriter {
+    handle: Option<SpiceInt>,
+}
+
+impl SpkWriter {
+    /// Create a new SPK file, which must not already exist.
+    ///
+    /// See [spkopn_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkopn_c.html).
+    pub fn create<'p, 'i, P, I>(path: P, internal_name: I) -> Result<Self, Error>
+    where
+        P: Into<StringParam<'p>>,
+        I: Into<StringParam<'i>>,
+    {
+        with_spice_lock_or_panic(|| {
+            let mut handle = 0;
+            unsafe {
+                spkopn_c(
+                    path.into().as_mut_ptr()?,
+                    internal_name.into().as_mut_ptr()?,
+                    0,
+                    &mut handle,
+                )
+            };
+            get_last_error()?;
+            Ok(Self {
+                handle: Some(handle),
+            })
+        })
+    }
+
+    /// Write a type 8 segment, containing `states` spaced evenly by `step` starting at
+    /// `first_epoch`, which are interpolated with Lagrange polynomials of the given degree.
+    ///
+    /// See [spkw08_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkw08_c.html).
+    pub fn write_type_08(
+        &mut self,
+        header: &SegmentHeader,
+        degree: usize,
+        states: &[State],
+        first_epoch: Et,
+        step: Seconds,
+    ) -> Result<(), Error> {
+        let (frame, id) = (
+            SpiceString::from(header.frame),
+            SpiceString::from(header.id),
+        );
+        let mut states = states.iter().map(State::to_array).collect::<Vec<_>>();
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                spkw08_c(
+                    self.handle(),
+                    header.body,
+                    header.center,
+                    frame.as_mut_ptr()?,
+                    header.start.0,
+                    header.end.0,
+                    id.as_mut_ptr()?,
+                    degree as SpiceInt,
+                    states.len() as SpiceInt,
+                    states.as_mut_ptr(),
+                    first_epoch.0,
+                    step.0,
+                )
+            };
+            get_last_error()
+        })
+    }
+
+    /// Write a type 10 segment from NORAD two-line element sets, ordered by increasing epoch.
+    /// The frame of the header should be `J2000`.
+    ///
+    /// See [spkw10_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkw10_c.html).
+    pub fn write_type_10(
+        &mut self,
+        header: &SegmentHeader,
+        constants: &GeophysicalConstants,
+        elements: &[TwoLineElements],
+    ) -> Result<(), Error> {
+        let (frame, id) = (
+            SpiceString::from(header.frame),
+            SpiceString::from(header.id),
+        );
+        let mut constants = constants.to_array();
+        let mut epochs = elements.iter().map(|e| e.epoch.0).collect::<Vec<_>>();
+        let mut elements = elements
+            .iter()
+            .flat_map(|e| e.to_array())
+            .collect::<Vec<_>>();
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                spkw10_c(
+                    self.handle(),
+                    header.body,
+                    header.center,
+                    frame.as_mut_ptr()?,
+                    header.start.0,
+                    header.end.0,
+                    id.as_mut_ptr()?,
+                    constants.as_mut_ptr(),
+                    epochs.len() as SpiceInt,
+                    elements.as_mut_ptr(),
+                    epochs.as_mut_ptr(),
+                )
+            };
+            get_last_error()
+        })
+    }
+
+    /// Close the file.
+    ///
+    /// See [spkcls_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/spkcls_c.html).
+    pub fn close(mut self) -> Result<(), Error> {
+        let handle = self.handle.take().unwrap();
+        with_spice_lock_or_panic(|| {
+            unsafe { spkcls_c(handle) };
+            get_last_error()
+        })
+    }
+
+    fn handle(&self) -> SpiceInt {
+        // Only None after being closed, which consumes the writer
+        self.handle.unwrap()
+    }
+}
+
+impl Drop for SpkWriter {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            with_spice_lock_or_panic(|| {
+                unsafe { spkcls_c(handle) };
+                let _ = get_last_error();
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::{furnish, unload};
+    use crate::naif_ids::{EARTH, MOON};
     use crate::tests::load_test_data;
+    use std::path::PathBuf;
     const EPSILON: f64 = 1e-10;
     const ETS: [Et; 3] = [Et(0.0), Et(3600.0), Et(120000.0)];
     const LTS: [SpiceDouble; 3] = [
@@ -177,6 +1066,18 @@ mod tests {
         1.3519329044685606f64,
     ];
 
+    #[test]
+    fn test_state_conversions() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let state = State::new(
+            Rectangular::new(1.0, 2.0, 3.0),
+            Vector3D::new(4.0, 5.0, 6.0),
+        );
+        assert_eq!(State::try_from(&values[..]).unwrap(), state);
+        assert_eq!(state.to_array(), values);
+        assert!(State::try_from(&values[..5]).is_err());
+    }
+
     // Test data generated via spiceypy using the above ephemeris times
     fn gen_test_data() -> [State; 3] {
         [
@@ -210,6 +1111,109 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn test_state_request() {
+        load_test_data();
+        let request = StateRequest {
+            target: "MOON",
+            observer: "EARTH",
+            abcorr: AberrationCorrection::LT,
+            et: ETS[1],
+            ..Default::default()
+        };
+        assert_eq!(
+            request.get().unwrap(),
+            easier_reader("MOON", ETS[1], "J2000", AberrationCorrection::LT, "EARTH").unwrap()
+        );
+        assert!(StateRequest::default().get().is_err());
+    }
+
+    #[test]
+    fn test_states_of() {
+        load_test_data();
+        let targets = [BodyRef::from(MOON), BodyRef::from("SUN")];
+        let states = states_of(
+            &targets,
+            ETS[0],
+            "J2000",
+            AberrationCorrection::NONE,
+            "EARTH",
+        )
+        .unwrap();
+        assert_eq!(states.len(), 2);
+        let (moon, _) =
+            easier_reader("MOON", ETS[0], "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+        assert_eq!(states[&MOON], moon);
+        assert!(states.contains_key(&10));
+
+        let targets = [BodyRef::from("NOT A BODY")];
+        let err =
+            states_of(&targets, ETS[0], "J2000", AberrationCorrection::NONE, EARTH).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(IDCODENOTFOUND)");
+    }
+
+    #[test]
+    fn test_to_frame() {
+        load_test_data();
+        crate::tests::load_moon_constants();
+        let et = ETS[0];
+        let (inertial, _) =
+            easier_reader("EARTH", et, "J2000", AberrationCorrection::NONE, "MOON").unwrap();
+        let (expected, _) =
+            easier_reader("EARTH", et, "IAU_MOON", AberrationCorrection::NONE, "MOON").unwrap();
+        let fixed = inertial.to_frame("J2000", "IAU_MOON", et).unwrap();
+        let error = (fixed - expected).to_array();
+        assert!(error[..3].iter().all(|e| e.abs() < 1e-6));
+        assert!(error[3..].iter().all(|e| e.abs() < 1e-9));
+
+        // The pitfall: rotating the velocity alone ignores the rotation of the frame, which for
+        // the Earth seen from the Moon is an error of around 1 km/s
+        let rotation = crate::matrix::Matrix3x3::from_frames("J2000", "IAU_MOON", et).unwrap();
+        let rotated = rotation.mul_vec(&inertial.velocity);
+        let speed_error = (0..3)
+            .map(|i| (rotated[i] - expected.velocity[i]).powi(2))
+            .sum::<SpiceDouble>()
+            .sqrt();
+        assert!(speed_error > 0.5);
+
+        assert!(inertial.to_frame("J2000", "NOT A FRAME", et).is_err());
+    }
+
+    #[test]
+    fn test_state_arithmetic() {
+        let a = State::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let b = State::from([0.5, 0.5, 0.5, 1.0, 1.0, 1.0]);
+        assert_eq!(a + b, State::from([1.5, 2.5, 3.5, 5.0, 6.0, 7.0]));
+        assert_eq!(a - b, State::from([0.5, 1.5, 2.5, 3.0, 4.0, 5.0]));
+        assert_eq!(a - a, State::default());
+        assert_eq!(a.transform(&StateTransformMatrix6x6::identity()), a);
+
+        let (position, velocity): (Rectangular, Vector3D) = a.into();
+        assert_eq!(Rectangular::from(Vector3D([1.0, 2.0, 3.0])), position);
+        assert_eq!(State::from((position, velocity)), a);
+    }
+
+    #[test]
+    fn moon_earth_frame_transform_test() {
+        load_test_data();
+        let (j2000, _) =
+            easier_reader("moon", ETS[1], "J2000", AberrationCorrection::NONE, "earth").unwrap();
+        let (eclip, _) = easier_reader(
+            "moon",
+            ETS[1],
+            "ECLIPJ2000",
+            AberrationCorrection::NONE,
+            "earth",
+        )
+        .unwrap();
+        let xform = StateTransformMatrix6x6::from_frames("J2000", "ECLIPJ2000", ETS[1]).unwrap();
+        let transformed: [SpiceDouble; 6] = j2000.transform(&xform).into();
+        let expected: [SpiceDouble; 6] = eclip.into();
+        for i in 0..6 {
+            assert!((transformed[i] - expected[i]).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn moon_earth_spkpos_test() {
         load_test_data();
@@ -230,7 +1234,7 @@ mod tests {
         let test_data = gen_test_data();
         for i in 0..3 {
             let (state, lt) =
-                easy_reader(301, ETS[i], "J2000", AberrationCorrection::LT, 399).unwrap();
+                easy_reader(MOON, ETS[i], "J2000", AberrationCorrection::LT, EARTH).unwrap();
             assert!((state.position.x - test_data[i].position.x).abs() < EPSILON);
             assert!((state.position.y - test_data[i].position.y).abs() < EPSILON);
             assert!((state.position.z - test_data[i].position.z).abs() < EPSILON);
@@ -247,7 +1251,7 @@ mod tests {
         let test_data = gen_test_data();
         for i in 0..3 {
             let (pos, lt) =
-                easy_position(301, ETS[i], "J2000", AberrationCorrection::LT, 399).unwrap();
+                easy_position(MOON, ETS[i], "J2000", AberrationCorrection::LT, EARTH).unwrap();
             assert!((pos.x - test_data[i].position.x).abs() < EPSILON);
             assert!((pos.y - test_data[i].position.y).abs() < EPSILON);
             assert!((pos.z - test_data[i].position.z).abs() < EPSILON);
@@ -271,4 +1275,301 @@ mod tests {
             assert!((lt - LTS[i]).abs() < EPSILON);
         }
     }
+
+    #[test]
+    fn test_range_rate() {
+        load_test_data();
+        let state = State::from([3.0, 4.0, 0.0, 3.0, 4.0, 1.0]);
+        assert_eq!(state.range(), 5.0);
+        assert_eq!(state.range_rate(), 5.0);
+        assert_eq!((-state).range_rate(), 5.0);
+        assert_eq!(State::default().range_rate(), 0.0);
+
+        for (i, expected) in gen_test_data().iter().enumerate() {
+            let rr = range_rate("moon", ETS[i], AberrationCorrection::LT, "earth").unwrap();
+            assert!((rr - expected.range_rate()).abs() < 1e-6);
+        }
+
+        let shift = two_way_doppler_shift(1.0, 8.4e9);
+        assert!((shift + 2.0 / SPEED_OF_LIGHT * 8.4e9).abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_orbital_quantities() {
+        let gm = 398600.4418;
+        let r: SpiceDouble = 7000.0;
+        let v = (gm / r).sqrt();
+        let state = State::from([r, 0.0, 0.0, 0.0, v, 0.0]);
+        assert_eq!(state.angular_momentum(), Vector3D([0.0, 0.0, r * v]));
+        assert_eq!(
+            state.orbital_plane_normal(),
+            Some(Vector3D([0.0, 0.0, 1.0]))
+        );
+        // Circular orbit energy is -gm / 2a
+        assert!((state.specific_energy(gm) + gm / (2.0 * r)).abs() < EPSILON);
+
+        let radial = State::from([r, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        assert_eq!(radial.orbital_plane_normal(), None);
+        assert_eq!(State::default().orbital_plane_normal(), None);
+    }
+
+    #[test]
+    fn test_state_derivatives() {
+        // a rotates anticlockwise and b rotates clockwise, both at 1 rad/s
+        let a = State::from([1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        let b = State::from([0.0, 2.0, 0.0, 2.0, 0.0, 0.0]);
+
+        // d/dt (a . b) = a' . b + a . b'
+        assert_eq!(a.dot_derivative(&b), 4.0);
+
+        let unit = State::from([2.0, 0.0, 0.0, 0.0, 2.0, 0.0]).unit();
+        assert_eq!(unit, State::from([1.0, 0.0, 0.0, 0.0, 1.0, 0.0]));
+
+        let cross = a.cross(&b);
+        assert_eq!(cross.position, Rectangular::from([0.0, 0.0, 2.0]));
+        assert_eq!(cross.velocity, Vector3D([0.0, 0.0, 0.0]));
+
+        let rate = a.separation_rate(&b).unwrap();
+        assert!((rate + 2.0).abs() < EPSILON, "{rate}");
+    }
+
+    #[test]
+    fn test_sample_adaptive() {
+        load_test_data();
+        const DAY: SpiceDouble = 86400.0;
+        let window = Window::from_intervals(&[(0.0, 28.0 * DAY)]).unwrap();
+        let coarse = sample_adaptive("MOON", "EARTH", "J2000", &window, Kilometers(100.0)).unwrap();
+        let fine = sample_adaptive("MOON", "EARTH", "J2000", &window, Kilometers(1.0)).unwrap();
+        assert!(coarse.len() < fine.len());
+        // Fixed step sampling every minute would need over 40,000 points
+        assert!(fine.len() < 4000, "{}", fine.len());
+        assert_eq!(fine.first().unwrap().0, Et(0.0));
+        assert_eq!(fine.last().unwrap().0, Et(28.0 * DAY));
+
+        // Check the polyline between samples is close to the trajectory
+        for pair in fine.windows(2) {
+            let ((a, pa), (b, pb)) = (pair[0], pair[1]);
+            assert!(a.0 < b.0);
+            let (actual, _) = position(
+                "MOON",
+                Et((a.0 + b.0) / 2.0),
+                "J2000",
+                AberrationCorrection::NONE,
+                "EARTH",
+            )
+            .unwrap();
+            let deviation = [
+                actual.x - (pa.x + pb.x) / 2.0,
+                actual.y - (pa.y + pb.y) / 2.0,
+                actual.z - (pa.z + pb.z) / 2.0,
+            ];
+            assert!(deviation.iter().map(|d| d * d).sum::<SpiceDouble>().sqrt() <= 1.0);
+        }
+
+        let err = sample_adaptive("MOON", "EARTH", "J2000", &window, Kilometers(0.0)).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(INVALIDTOLERANCE)");
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_arrays() {
+        load_test_data();
+        let (ets, positions) =
+            positions_array("MOON", &ETS, "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+        let (state_ets, states) =
+            states_array("MOON", &ETS, "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+        assert_eq!(ets.to_vec(), ETS.map(|et| et.0).to_vec());
+        assert_eq!(state_ets, ets);
+        assert_eq!(positions.dim(), (3, 3));
+        assert_eq!(states.dim(), (3, 6));
+        for (i, et) in ETS.iter().enumerate() {
+            let (state, _) =
+                easier_reader("MOON", *et, "J2000", AberrationCorrection::NONE, "EARTH").unwrap();
+            assert_eq!(states.row(i).to_vec(), state.to_array().to_vec());
+            assert_eq!(positions.row(i).to_vec(), states.row(i).to_vec()[..3]);
+        }
+
+        let err =
+            positions_array("MOON", &ETS, "J2000", AberrationCorrection::NONE, "MARS").unwrap_err();
+        assert_eq!(err.short_message, "SPICE(SPKINSUFFDATA)");
+    }
+
+    #[test]
+    fn test_subset() {
+        load_test_data();
+        let input = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test_data")
+            .join("de432s.bsp");
+        let output = std::env::temp_dir().join("cspice_rs_subset_test.bsp");
+        let _ = std::fs::remove_file(&output);
+        let window = Window::from_intervals(&[(0.0, 86400.0)]).unwrap();
+        let count = subset(
+            input.to_string_lossy(),
+            output.to_string_lossy(),
+            Some(MOON),
+            &window,
+        )
+        .unwrap();
+        assert_eq!(count, 1);
+        assert!(output.metadata().unwrap().len() < input.metadata().unwrap().len());
+
+        let spk = SpkHandle::load(output.to_string_lossy()).unwrap();
+        let segments = spk.segments().unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].body, MOON);
+        assert_eq!(segments[0].start, Et(0.0));
+        assert_eq!(segments[0].end, Et(86400.0));
+        spk.unload().unwrap();
+
+        // Output file already exists
+        assert!(subset(
+            input.to_string_lossy(),
+            output.to_string_lossy(),
+            None,
+            &window
+        )
+        .is_err());
+        std::fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_writer_type_08() {
+        load_test_data();
+        let path = std::env::temp_dir().join("cspice_rs_type_08_test.bsp");
+        let _ = std::fs::remove_file(&path);
+        // Uniform motion along the x axis at 2 km/s
+        let states = (0..5)
+            .map(|i| State::from([2.0 * 100.0 * i as f64, 0.0, 0.0, 2.0, 0.0, 0.0]))
+            .collect::<Vec<_>>();
+        let header = SegmentHeader {
+            body: -999801,
+            center: 399,
+            frame: "J2000",
+            start: Et(0.0),
+            end: Et(400.0),
+            id: "CSPICE_RS_TYPE_08",
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "TYPE 08 TEST").unwrap();
+        writer
+            .write_type_08(&header, 3, &states, Et(0.0), Seconds(100.0))
+            .unwrap();
+        writer.close().unwrap();
+
+        furnish(path.to_string_lossy()).unwrap();
+        let (state, _) = easier_reader(
+            "-999801",
+            Et(250.0),
+            "J2000",
+            AberrationCorrection::NONE,
+            "399",
+        )
+        .unwrap();
+        assert!((state.position.x - 500.0).abs() < 1e-8);
+        assert!((state.velocity[0] - 2.0).abs() < 1e-8);
+        unload(path.to_string_lossy()).unwrap();
+
+        // The file already exists
+        assert!(SpkWriter::create(path.to_string_lossy(), "TYPE 08 TEST").is_err());
+        std::fs::remove_file(&path).unwrap();
+
+        // SPICE isn't called, so the file isn't created
+        let error = SpkWriter::create(path.to_string_lossy(), "TYPE 08\0TEST").unwrap_err();
+        assert_eq!(error.short_message, crate::string::INTERIOR_NUL);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_writer_type_10() {
+        load_test_data();
+        let path = std::env::temp_dir().join("cspice_rs_type_10_test.bsp");
+        let _ = std::fs::remove_file(&path);
+        // From the spkw10_c example
+        let elements = [
+            TwoLineElements::parse(
+                "1 18123U 87 53  A 87324.61041692 -.00000023  00000-0 -75103-5 0 00675",
+                "2 18123  98.8296 152.0074 0014950 168.7820 191.3688 14.12912554 21686",
+            )
+            .unwrap(),
+            TwoLineElements::parse(
+                "1 18123U 87 53  A 87326.73487726  .00000045  00000-0  28709-4 0 00684",
+                "2 18123  98.8335 154.1103 0015643 163.5445 196.6235 14.12912902 21988",
+            )
+            .unwrap(),
+        ];
+        let header = SegmentHeader {
+            body: -118123,
+            center: 399,
+            frame: "J2000",
+            start: elements[0].epoch,
+            end: elements[1].epoch,
+            id: "CSPICE_RS_TYPE_10",
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "TYPE 10 TEST").unwrap();
+        writer
+            .write_type_10(&header, &GeophysicalConstants::WGS72, &elements)
+            .unwrap();
+        writer.close().unwrap();
+
+        furnish(path.to_string_lossy()).unwrap();
+        let (state, _) = easier_reader(
+            "-118123",
+            elements[0].epoch,
+            "J2000",
+            AberrationCorrection::NONE,
+            "399",
+        )
+        .unwrap();
+        // The SPK is in J2000 rather than TEME, but the distance should be about the same
+        let teme = elements[0]
+            .state(elements[0].epoch, &GeophysicalConstants::WGS72)
+            .unwrap();
+        assert!((state.range() - teme.range()).abs() < 0.1);
+        unload(path.to_string_lossy()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_spk_handle() {
+        load_test_data();
+        let path = std::env::temp_dir().join("cspice_rs_handle_test.bsp");
+        let _ = std::fs::remove_file(&path);
+        let state = State::from([1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+        let header = SegmentHeader {
+            body: -999802,
+            center: 399,
+            frame: "J2000",
+            start: Et(0.0),
+            end: Et(100.0),
+            id: "CSPICE_RS_HANDLE",
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "HANDLE TEST").unwrap();
+        writer
+            .write_type_08(&header, 1, &[state, state], Et(0.0), Seconds(100.0))
+            .unwrap();
+        writer.close().unwrap();
+        assert_eq!(find_segment(-999802, Et(50.0)).unwrap(), None);
+
+        let spk = SpkHandle::load(path.to_string_lossy()).unwrap();
+        let expected = SegmentSummary {
+            body: -999802,
+            center: 399,
+            frame: FrameId::J2000,
+            data_type: 8,
+            start: Et(0.0),
+            end: Et(100.0),
+            id: String::from("CSPICE_RS_HANDLE"),
+        };
+        assert_eq!(spk.segments().unwrap(), vec![expected.clone()]);
+        assert_eq!(
+            find_segment(-999802, Et(50.0)).unwrap(),
+            Some((spk.handle(), expected))
+        );
+        assert_eq!(find_segment(-999802, Et(150.0)).unwrap(), None);
+        let (moon, _) = find_segment(MOON, Et(0.0)).unwrap().unwrap();
+        assert_ne!(moon, spk.handle());
+
+        spk.unload().unwrap();
+        assert_eq!(find_segment(-999802, Et(50.0)).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,167 @@
+//! Apparent directions of catalog stars.
+//!
+//! Stars are not in SPK files, so the corrections SPICE applies to SPK lookups have to be applied
+//! manually; space motion, parallax, gravitational light deflection by the Sun and stellar
+//! aberration.
+use crate::coordinates::Rectangular;
+use crate::lighttime::SPEED_OF_LIGHT;
+use crate::spk::State;
+use crate::time::Et;
+use crate::units::Radians;
+use crate::vector::Vector3D;
+use crate::Error;
+use cspice_sys::SpiceDouble;
+
+/// The astronomical unit (km).
+pub const AU: SpiceDouble = 149597870.7;
+
+/// Heliocentric gravitational constant (km^3/s^2).
+pub const GM_SUN: SpiceDouble = 1.32712440018e11;
+
+/// Length of a Julian year (seconds).
+pub const JULIAN_YEAR: SpiceDouble = 365.25 * 86400.0;
+
+/// Astrometric position of a star from a catalog, e.g. Hipparcos or Gaia, referred to the
+/// J2000 (ICRF) frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CatalogStar {
+    /// Right ascension at the catalog epoch.
+    pub ra: Radians,
+    /// Declination at the catalog epoch.
+    pub dec: Radians,
+    /// Proper motion in right ascension per Julian year, including the `cos(dec)` factor.
+    pub pm_ra: Radians,
+    /// Proper motion in declination per Julian year.
+    pub pm_dec: Radians,
+    /// Annual parallax, zero if unknown.
+    pub parallax: Radians,
+    /// Radial velocity (km/s), positive when receding.
+    pub radial_velocity: SpiceDouble,
+    /// Catalog epoch.
+    pub epoch: Et,
+}
+
+impl CatalogStar {
+    /// The barycentric position of the star at `et` in km, ignoring light time.
+    ///
+    /// Stars with no parallax are placed at an arbitrarily large distance, and their radial
+    /// velocity is ignored.
+    pub fn barycentric_position(&self, et: Et) -> Rectangular {
+        let (distance, radial_velocity) = if self.parallax.0 > 0.0 {
+            (AU / self.parallax.0, self.radial_velocity)
+        } else {
+            (1e15 * AU, 0.0)
+        };
+        let (sin_ra, cos_ra) = self.ra.0.sin_cos();
+        let (sin_dec, cos_dec) = self.dec.0.sin_cos();
+        let u = [cos_dec * cos_ra, cos_dec * sin_ra, sin_dec];
+        let east = [-sin_ra, cos_ra, 0.0];
+        let north = [-sin_dec * cos_ra, -sin_dec * sin_ra, cos_dec];
+        let dt = et.0 - self.epoch.0;
+        let mut p = [0.0; 3];
+        for i in 0..3 {
+            let v = distance * (self.pm_ra.0 * east[i] + self.pm_dec.0 * north[i]) / JULIAN_YEAR
+                + radial_velocity * u[i];
+            p[i] = distance * u[i] + v * dt;
+        }
+        p.into()
+    }
+
+    /// The apparent direction (unit vector) of the star for an observer at `et`.
+    ///
+    /// `observer` is the state of the observer relative to the solar system barycenter in the
+    /// J2000 frame. If the position of the Sun relative to the barycenter is given then
+    /// gravitational light deflection by the Sun is included. Stellar aberration is applied
+    /// using [Vector3D::stellar_aberration()].
+    pub fn apparent_direction(
+        &self,
+        et: Et,
+        observer: &State,
+        sun: Option<&Rectangular>,
+    ) -> Result<Vector3D, Error> {
+        let star = Vector3D::from(self.barycentric_position(et));
+        let obs = Vector3D::from(observer.position);
+        let mut p = star.subtract(&obs).unit();
+        if let Some(sun) = sun {
+            let sun_to_obs = obs.subtract(&(*sun).into());
+            let e = sun_to_obs.unit();
+            let g = 2.0 * GM_SUN / (SPEED_OF_LIGHT * SPEED_OF_LIGHT * sun_to_obs.norm());
+            let p_dot_e = p.dot(&e);
+            for i in 0..3 {
+                p[i] += g * (e[i] - p_dot_e * p[i]) / (1.0 + p_dot_e);
+            }
+            p = p.unit();
+        }
+        Ok(p.stellar_aberration(&observer.velocity)?.unit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f64 = 1e-12;
+
+    fn star(ra: f64, dec: f64) -> CatalogStar {
+        CatalogStar {
+            ra: Radians(ra),
+            dec: Radians(dec),
+            pm_ra: Radians(0.0),
+            pm_dec: Radians(0.0),
+            parallax: Radians(0.0),
+            radial_velocity: 0.0,
+            epoch: Et(0.0),
+        }
+    }
+
+    #[test]
+    fn test_stationary_observer() {
+        let observer = State::default();
+        let d = star(0.0, 0.0)
+            .apparent_direction(Et(0.0), &observer, None)
+            .unwrap();
+        assert!((d[0] - 1.0).abs() < EPSILON);
+        assert!(d[1].abs() < EPSILON && d[2].abs() < EPSILON);
+    }
+
+    #[test]
+    fn test_proper_motion() {
+        let mut s = star(0.0, 0.0);
+        s.pm_dec = Radians(1e-3);
+        s.parallax = Radians(1e-6);
+        let d = s
+            .apparent_direction(Et(JULIAN_YEAR), &State::default(), None)
+            .unwrap();
+        assert!((d[2].asin() - 1e-3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parallax() {
+        // Observer 1 AU along +Y sees a nearby star on the X axis displaced towards -Y
+        let mut s = star(0.0, 0.0);
+        s.parallax = Radians(1e-5);
+        let observer = State::from([0.0, AU, 0.0, 0.0, 0.0, 0.0]);
+        let d = s.apparent_direction(Et(0.0), &observer, None).unwrap();
+        assert!((d[1].asin() + 1e-5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_aberration_and_deflection() {
+        let s = star(0.0, 0.0);
+        let observer = State::from([0.0, -AU, 0.0, 0.0, 30.0, 0.0]);
+        let sun = Rectangular::default();
+        let aberrated = s.apparent_direction(Et(0.0), &observer, None).unwrap();
+        let expected = Vector3D([1.0, 0.0, 0.0])
+            .stellar_aberration(&observer.velocity)
+            .unwrap();
+        assert!((expected.unit().separation_angle(&aberrated)).abs() < EPSILON);
+
+        // At 90 degrees from the Sun the deflection is ~4 mas, away from the Sun
+        let observer = State::from([0.0, -AU, 0.0, 0.0, 0.0, 0.0]);
+        let deflected = s
+            .apparent_direction(Et(0.0), &observer, Some(&sun))
+            .unwrap();
+        let expected = 2.0 * GM_SUN / (SPEED_OF_LIGHT * SPEED_OF_LIGHT * AU);
+        assert!((deflected[1].asin() + expected).abs() < 1e-15);
+    }
+}
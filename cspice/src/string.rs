@@ -1,7 +1,10 @@
 //! Functions for converting between Rust strings and SPICE (C) strings.
-use cspice_sys::SpiceChar;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{eqstr_c, matchi_c, matchw_c, SpiceBoolean, SpiceChar, SPICETRUE};
 use std::borrow::Cow;
-use std::ffi::{CStr, CString};
+#[cfg(feature = "string-interning")]
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString, NulError};
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
 
@@ -12,7 +15,7 @@ use std::ops::Deref;
 /// An existing dynamically sized buffer can be converted in-place into a SpiceString using
 /// [SpiceString::from_buffer].
 #[derive(Clone, Eq, PartialEq, Hash)]
-pub struct SpiceString(pub CString);
+pub struct SpiceString(pub CString, Option<usize>);
 
 impl Debug for SpiceString {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -26,26 +29,92 @@ impl Display for SpiceString {
     }
 }
 
+/// The short message of the error returned when a string containing an interior nul byte is
+/// passed to SPICE.
+pub const INTERIOR_NUL: &str = "SPICE(INTERIORNUL)";
+
 /// A SpiceString can be created from a Rust string.
+///
+/// C strings can't contain interior nul bytes, so if the string contains one the conversion
+/// still succeeds, but the SpiceString is truncated at the nul and remembers its position. Functions
+/// it is passed to return an [INTERIOR_NUL] error without calling SPICE. Use
+/// [SpiceString::try_new] to check for nul bytes up front.
 impl<T: AsRef<str>> From<T> for SpiceString {
     #[inline]
     fn from(s: T) -> Self {
-        Self(CString::new(s.as_ref()).unwrap())
+        Self::try_new(s).unwrap_or_else(|e| {
+            let position = e.nul_position();
+            let mut bytes = e.into_vec();
+            bytes.truncate(position);
+            // Can't fail, the bytes end before the first nul
+            Self(CString::new(bytes).unwrap_or_default(), Some(position))
+        })
     }
 }
 
 impl SpiceString {
+    /// Create a SpiceString from a Rust string, returning an error if it contains an interior nul
+    /// byte.
+    #[inline]
+    pub fn try_new<T: AsRef<str>>(s: T) -> Result<Self, NulError> {
+        #[cfg(feature = "perf-counters")]
+        crate::perf::record_string_allocation();
+        CString::new(s.as_ref()).map(|s| Self(s, None))
+    }
+
     /// Get the pointer to the SpiceString's data. Intended for use passing string input to SPICE.
     ///
+    /// Returns an [INTERIOR_NUL] error if the string was converted from a Rust string containing
+    /// a nul byte. Get the pointers to all of the strings passed to a SPICE function before
+    /// calling it (e.g. `furnsh_c(file.as_mut_ptr()?)`), so that SPICE isn't called at all.
+    ///
     /// # Safety
     ///
     /// This is a mut pointer for compatibility with the SPICE APIs, however it must not actually
     /// be mutated.
     #[inline]
-    pub unsafe fn as_mut_ptr(&self) -> *mut SpiceChar {
+    pub unsafe fn as_mut_ptr(&self) -> Result<*mut SpiceChar, Error> {
+        self.check()?;
+        Ok(self.0.as_ptr() as *mut SpiceChar)
+    }
+
+    /// Get the pointer to the SpiceString's data, which is truncated at the first nul byte if the
+    /// Rust string it was converted from contained one. Only for functions that can't return an
+    /// error, such as [signal_error()](crate::error::signal_error), or that have already checked
+    /// the string.
+    ///
+    /// # Safety
+    ///
+    /// See [SpiceString::as_mut_ptr()].
+    #[inline]
+    pub(crate) unsafe fn as_mut_ptr_truncated(&self) -> *mut SpiceChar {
         self.0.as_ptr() as *mut SpiceChar
     }
 
+    /// Returns an [INTERIOR_NUL] error if the string was converted from a Rust string containing
+    /// a nul byte, without calling SPICE.
+    #[inline]
+    pub fn check(&self) -> Result<(), Error> {
+        match self.1 {
+            None => Ok(()),
+            Some(position) => Err(Error {
+                short_message: INTERIOR_NUL.to_string(),
+                explanation: String::new(),
+                long_message: format!(
+                    "String passed to SPICE contains a nul byte at position {position}"
+                ),
+                traceback: String::new(),
+                device_output: None,
+            }),
+        }
+    }
+
+    /// The position of the interior nul byte in the string this was converted from, if any.
+    #[inline]
+    pub fn nul_position(&self) -> Option<usize> {
+        self.1
+    }
+
     /// Convert a buffer of SpiceChar into a SpiceString.
     ///
     /// This will panic if the buffer is not nul terminated.
@@ -66,7 +135,7 @@ impl SpiceString {
 
         unsafe {
             let s = Vec::from_raw_parts(ptr as *mut u8, len, cap);
-            Self(CString::from_vec_unchecked(s))
+            Self(CString::from_vec_unchecked(s), None)
         }
     }
 
@@ -154,6 +223,25 @@ impl<S: AsRef<str>> From<S> for StringParam<'_> {
     }
 }
 
+impl StringParam<'static> {
+    /// Create a StringParam from a string literal, such as a frame or body name, that is passed
+    /// to SPICE repeatedly.
+    ///
+    /// With the `string-interning` feature (enabled by default) the string is converted once
+    /// using [intern()] and every later call borrows the same C string instead of allocating.
+    /// Without the feature this is the same as [StringParam::from].
+    ///
+    /// Stable Rust can't specialise the blanket `From` implementation for `&'static str`, so
+    /// literals passed directly to functions are still converted on each call.
+    pub fn from_static(s: &'static str) -> Self {
+        #[cfg(feature = "string-interning")]
+        if let Ok(interned) = intern(s) {
+            return StringParam::Ref(interned);
+        }
+        StringParam::Owned(SpiceString::from(s))
+    }
+}
+
 impl<'a> From<&'a SpiceString> for StringParam<'a> {
     fn from(s: &'a SpiceString) -> Self {
         StringParam::Ref(s)
@@ -177,9 +265,104 @@ impl Deref for StringParam<'_> {
     }
 }
 
+#[cfg(feature = "string-interning")]
+static INTERNED: parking_lot::Mutex<BTreeMap<&'static str, &'static SpiceString>> =
+    parking_lot::const_mutex(BTreeMap::new());
+
+/// Get the SpiceString for a string literal from a global cache, converting and caching it on
+/// first use. Returns an error if the string contains an interior nul byte.
+///
+/// Interned strings are never freed, so this should only be used for a bounded set of strings,
+/// e.g. names known at compile time. Disable the `string-interning` feature to remove the cache.
+#[cfg(feature = "string-interning")]
+pub fn intern(s: &'static str) -> Result<&'static SpiceString, NulError> {
+    let mut interned = INTERNED.lock();
+    if let Some(spice_string) = interned.get(s) {
+        return Ok(spice_string);
+    }
+    let spice_string: &'static SpiceString = Box::leak(Box::new(SpiceString::try_new(s)?));
+    interned.insert(s, spice_string);
+    Ok(spice_string)
+}
+
+/// Determine whether two strings are equivalent, i.e. equal when blanks are ignored and
+/// uppercase and lowercase letters are considered equal. This is how SPICE compares names, e.g.
+/// `"EARTH BARYCENTER"` is equivalent to `"earth barycenter"` and `"EarthBarycenter"`.
+///
+/// Strings containing a nul byte aren't equivalent to anything.
+///
+/// See [eqstr_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/eqstr_c.html).
+pub fn equivalent<'a, 'b, A, B>(a: A, b: B) -> bool
+where
+    A: Into<StringParam<'a>>,
+    B: Into<StringParam<'b>>,
+{
+    let (a, b) = (a.into(), b.into());
+    if a.nul_position().is_some() || b.nul_position().is_some() {
+        return false;
+    }
+    with_spice_lock_or_panic(|| unsafe {
+        eqstr_c(a.as_mut_ptr_truncated(), b.as_mut_ptr_truncated())
+    }) == SPICETRUE as SpiceBoolean
+}
+
+/// Determine whether a string matches a wildcard template, where `*` matches any substring
+/// (including the empty string) and `%` matches any single character. Matching is case
+/// sensitive, see [matches_wildcard_ignore_case()].
+///
+/// This uses the same wildcards as kernel pool name templates, e.g.
+/// [kernel_pool::names()](crate::kernel_pool::names). Strings and templates containing a nul
+/// byte never match.
+///
+/// See [matchw_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/matchw_c.html).
+pub fn matches_wildcard<'s, 't, S, T>(string: S, template: T) -> bool
+where
+    S: Into<StringParam<'s>>,
+    T: Into<StringParam<'t>>,
+{
+    let (string, template) = (string.into(), template.into());
+    if string.nul_position().is_some() || template.nul_position().is_some() {
+        return false;
+    }
+    with_spice_lock_or_panic(|| unsafe {
+        matchw_c(
+            string.as_mut_ptr_truncated(),
+            template.as_mut_ptr_truncated(),
+            b'*' as SpiceChar,
+            b'%' as SpiceChar,
+        )
+    }) == SPICETRUE as SpiceBoolean
+}
+
+/// Equivalent to [matches_wildcard()], but ignoring case.
+///
+/// See [matchi_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/matchi_c.html).
+pub fn matches_wildcard_ignore_case<'s, 't, S, T>(string: S, template: T) -> bool
+where
+    S: Into<StringParam<'s>>,
+    T: Into<StringParam<'t>>,
+{
+    let (string, template) = (string.into(), template.into());
+    if string.nul_position().is_some() || template.nul_position().is_some() {
+        return false;
+    }
+    with_spice_lock_or_panic(|| unsafe {
+        matchi_c(
+            string.as_mut_ptr_truncated(),
+            template.as_mut_ptr_truncated(),
+            b'*' as SpiceChar,
+            b'%' as SpiceChar,
+        )
+    }) == SPICETRUE as SpiceBoolean
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::AberrationCorrection;
+    use crate::spk;
+    use crate::tests::load_test_data;
+    use crate::time::Et;
 
     #[test]
     fn test_from_buffer() {
@@ -197,4 +380,107 @@ mod tests {
         .err()
         .expect("Expected to panic");
     }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(SpiceString::try_new("ab").unwrap().as_str(), "ab");
+        assert_eq!(SpiceString::try_new("a\0b").unwrap_err().nul_position(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "string-interning")]
+    fn test_intern() {
+        let a = intern("CSPICE_RS_INTERNED").unwrap();
+        let b = intern("CSPICE_RS_INTERNED").unwrap();
+        assert!(std::ptr::eq(a, b));
+        assert_eq!(a.as_str(), "CSPICE_RS_INTERNED");
+        assert!(intern("CSPICE_RS\0INTERNED").is_err());
+        match StringParam::from_static("CSPICE_RS_INTERNED") {
+            StringParam::Ref(r) => assert!(std::ptr::eq(r, a)),
+            StringParam::Owned(_) => panic!("expected an interned string"),
+        }
+    }
+
+    #[test]
+    fn test_from_static() {
+        assert_eq!(StringParam::from_static("EARTH").as_str(), "EARTH");
+        // Strings with a nul byte can't be interned, they are converted as usual without an
+        // error, which is only returned by the function the string is passed to
+        load_test_data();
+        let param = StringParam::from_static("2000-01-01\0");
+        assert_eq!(param.nul_position(), Some(10));
+        crate::error::get_last_error().unwrap();
+        let err = Et::from_string(param).unwrap_err();
+        assert_eq!(err.short_message, INTERIOR_NUL);
+        assert!(Et::from_string(StringParam::from_static("2000-01-01")).is_ok());
+    }
+
+    #[test]
+    fn test_equivalent() {
+        assert!(equivalent("EARTH BARYCENTER", "earth barycenter"));
+        assert!(equivalent("EARTH BARYCENTER", " EarthBarycenter "));
+        assert!(!equivalent("EARTH", "MOON"));
+    }
+
+    #[test]
+    fn test_matches_wildcard() {
+        assert!(matches_wildcard("BODY399_RADII", "BODY399_*"));
+        assert!(matches_wildcard("BODY399_RADII", "BODY%%%_RADII"));
+        assert!(!matches_wildcard("BODY399_RADII", "BODY%%_RADII"));
+        assert!(!matches_wildcard("body399_radii", "BODY399_*"));
+        assert!(matches_wildcard_ignore_case("body399_radii", "BODY399_*"));
+        assert!(!matches_wildcard_ignore_case("BODY301_RADII", "BODY399_*"));
+    }
+
+    #[test]
+    fn test_interior_nul_returns_error() {
+        load_test_data();
+        for input in ["\0", "2000-01-01\0T12:00:00", "\0\0\0"] {
+            let err = Et::from_string(input).unwrap_err();
+            assert_eq!(err.short_message, INTERIOR_NUL);
+        }
+
+        let err = spk::easier_reader(
+            "MOON\0",
+            Et(0.0),
+            "J2000",
+            AberrationCorrection::NONE,
+            "EARTH",
+        )
+        .unwrap_err();
+        assert_eq!(err.short_message, INTERIOR_NUL);
+        let err = spk::easier_reader(
+            "MOON",
+            Et(0.0),
+            "J2000",
+            AberrationCorrection::NONE,
+            "\0EARTH",
+        )
+        .unwrap_err();
+        assert_eq!(err.short_message, INTERIOR_NUL);
+
+        match crate::data::furnish("test_data/testkernel.txt\0") {
+            Err(crate::data::KernelError::Spice(e)) => assert_eq!(e.short_message, INTERIOR_NUL),
+            other => panic!("Expected an interior nul error, got {other:?}"),
+        }
+
+        // SPICE isn't called, so no error is left pending
+        crate::error::get_last_error().unwrap();
+        assert!(Et::from_string("2000-01-01T12:00:00").is_ok());
+    }
+
+    #[test]
+    fn test_interior_nul_conversion() {
+        let s = SpiceString::from("ab\0c");
+        assert_eq!(s.nul_position(), Some(2));
+        assert_eq!(s.as_str(), "ab");
+        assert!(s.check().is_err());
+        assert!(SpiceString::from("abc").check().is_ok());
+        assert_eq!(SpiceString::from("abc").nul_position(), None);
+        // Converting doesn't signal an error, only passing the string to SPICE does
+        crate::error::get_last_error().unwrap();
+        assert!(!equivalent("EARTH\0", "EARTH"));
+        assert!(!matches_wildcard("EARTH\0", "*"));
+        crate::error::get_last_error().unwrap();
+    }
 }
@@ -4,6 +4,33 @@
 pub trait Calendar {
     fn short_name() -> &'static str;
     fn name() -> &'static str;
+    fn kind() -> CalendarKind;
+}
+
+/// A calendar selected at runtime, see [DynDateTime](super::DynDateTime).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CalendarKind {
+    Mixed,
+    Gregorian,
+    Julian,
+}
+
+impl CalendarKind {
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            CalendarKind::Mixed => Mixed::short_name(),
+            CalendarKind::Gregorian => Gregorian::short_name(),
+            CalendarKind::Julian => Julian::short_name(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CalendarKind::Mixed => Mixed::name(),
+            CalendarKind::Gregorian => Gregorian::name(),
+            CalendarKind::Julian => Julian::name(),
+        }
+    }
 }
 
 /// Uses the Julian calendar for dates prior to Oct 5, 1582, and the Gregorian calendar for dates
@@ -21,6 +48,10 @@ pub struct Gregorian;
 pub struct Julian;
 
 impl Calendar for Mixed {
+    fn kind() -> CalendarKind {
+        CalendarKind::Mixed
+    }
+
     fn short_name() -> &'static str {
         "MCAL"
     }
@@ -31,6 +62,10 @@ impl Calendar for Mixed {
 }
 
 impl Calendar for Gregorian {
+    fn kind() -> CalendarKind {
+        CalendarKind::Gregorian
+    }
+
     fn short_name() -> &'static str {
         "GCAL"
     }
@@ -41,6 +76,10 @@ impl Calendar for Gregorian {
 }
 
 impl Calendar for Julian {
+    fn kind() -> CalendarKind {
+        CalendarKind::Julian
+    }
+
     fn short_name() -> &'static str {
         "JCAL"
     }
@@ -1,16 +1,112 @@
-use crate::common::{CALENDAR, GET, SET};
 use crate::error::get_last_error;
 use crate::string::SpiceStr;
 use crate::time::calendar::Calendar;
 use crate::time::julian_date::JulianDate;
 use crate::time::system::System;
-use crate::time::{set_default_calendar, Et};
-use crate::{with_spice_lock_or_panic, SpiceString};
-use cspice_sys::{timdef_c, timout_c, SpiceInt};
+use crate::time::{Et, ScopedTimDef};
+use crate::{with_spice_lock_or_panic, Error, SpiceString};
+use cspice_sys::{timout_c, SpiceInt};
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// A calendar era.
+///
+/// [DateTime] uses astronomical year numbering, where year 0 is 1 BCE, year -1 is 2 BCE and so on.
+/// This type can be used to convert to and from the historical convention of a year within an
+/// era, which has no year 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Era {
+    /// Before the Common Era (B.C.)
+    BCE,
+    /// Common Era (A.D.)
+    CE,
+}
+
+impl Era {
+    /// Split an astronomical year into its era and year of era.
+    #[inline]
+    pub fn from_astronomical_year(year: i16) -> (Era, u16) {
+        if year > 0 {
+            (Era::CE, year as u16)
+        } else {
+            (Era::BCE, (1 - year as i32) as u16)
+        }
+    }
+
+    /// Convert a year of this era into an astronomical year.
+    ///
+    /// Returns Err if the astronomical year doesn't fit in an [i16].
+    #[inline]
+    pub fn to_astronomical_year(self, year_of_era: u16) -> Result<i16, YearOutOfRangeError> {
+        let year = match self {
+            Era::CE => year_of_era as i32,
+            Era::BCE => 1 - year_of_era as i32,
+        };
+        i16::try_from(year).map_err(|_| YearOutOfRangeError(self, year_of_era))
+    }
+
+    /// The era as formatted by SPICE.
+    pub fn spice_name(&self) -> &'static str {
+        match self {
+            Era::BCE => "B.C.",
+            Era::CE => "A.D.",
+        }
+    }
+}
+
+/// Error returned when parsing an [Era] from a string.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Unknown era: {0}")]
+pub struct ParseEraError(pub String);
+
+/// Error returned when a year of an [Era] is out of range for an astronomical year.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("Year {1} {0} is out of range")]
+pub struct YearOutOfRangeError(pub Era, pub u16);
+
+/// Error returned from [DateTime::try_from_et()].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DateTimeError {
+    /// The year is out of range for a [DateTime].
+    #[error(transparent)]
+    YearOutOfRange(#[from] YearOutOfRangeError),
+    /// The output of `timout_c` could not be parsed.
+    #[error("Unexpected output from timout_c: {0:?}")]
+    UnexpectedOutput(String),
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+/// Parse one field of the output of `timout_c`.
+fn parse_field<T: FromStr>(field: &str, output: &str) -> Result<T, DateTimeError> {
+    field
+        .trim()
+        .parse()
+        .map_err(|_| DateTimeError::UnexpectedOutput(output.to_string()))
+}
+
+impl FromStr for Era {
+    type Err = ParseEraError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "B.C." | "BC" | "BCE" | "B.C.E." => Ok(Era::BCE),
+            "A.D." | "AD" | "CE" | "C.E." => Ok(Era::CE),
+            _ => Err(ParseEraError(s.to_string())),
+        }
+    }
+}
+
+impl Display for Era {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.spice_name())
+    }
+}
 
 /// An instant in time, typically expressed as a date and time of day.
+///
+/// The year uses astronomical year numbering, see [Era].
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct DateTime<T: Calendar, S: System> {
     pub year: i16,
@@ -47,8 +143,19 @@ impl<C: Calendar, S: System> DateTime<C, S> {
     }
 
     /// Convert an Ephemeris Time (TDB) to a DateTime.
+    ///
+    /// # Panics
+    ///
+    /// If SPICE signals an error, for example when converting to UTC without a leapseconds kernel
+    /// loaded. Use [DateTime::try_from_et()] instead.
+    #[deprecated(note = "panics on SPICE errors, use `try_from_et()` instead")]
     #[inline]
     pub fn from_et(et: Et, system: S) -> Self {
+        Self::try_from_et(et, system).unwrap()
+    }
+
+    /// Convert an Ephemeris Time (TDB) to a DateTime.
+    pub fn try_from_et(et: Et, system: S) -> Result<Self, DateTimeError> {
         let pictur = SpiceString::from(format!(
             "ERA:YYYY:MM:DD:HR:MN:SC.##### ::{} ::{}",
             system.meta_marker(),
@@ -59,84 +166,72 @@ impl<C: Calendar, S: System> DateTime<C, S> {
             unsafe {
                 timout_c(
                     et.0,
-                    pictur.as_mut_ptr(),
+                    pictur.as_mut_ptr()?,
                     buffer.len() as SpiceInt,
                     buffer.as_mut_ptr(),
                 );
             };
-            get_last_error().unwrap();
-        });
+            get_last_error()
+        })?;
         let output = SpiceStr::from_buffer(&buffer);
-        let cow = output.as_str();
-        let split: Vec<&str> = cow.split(':').collect();
-        let year: i16 = if split[0] == "B.C." {
-            1 - split[1].trim().parse::<i16>().unwrap()
-        } else {
-            split[1].trim().parse().unwrap()
-        };
-        DateTime::new(
+        let output = output.as_str();
+        let split: Vec<&str> = output.split(':').collect();
+        if split.len() != 7 {
+            return Err(DateTimeError::UnexpectedOutput(output.to_string()));
+        }
+        let era: Era = parse_field(split[0], &output)?;
+        let year = era.to_astronomical_year(parse_field(split[1], &output)?)?;
+        Ok(DateTime::new(
             year,
-            split[2].parse().unwrap(),
-            split[3].parse().unwrap(),
-            split[4].parse().unwrap(),
-            split[5].parse().unwrap(),
-            split[6].parse().unwrap(),
+            parse_field(split[2], &output)?,
+            parse_field(split[3], &output)?,
+            parse_field(split[4], &output)?,
+            parse_field(split[5], &output)?,
+            parse_field(split[6], &output)?,
             system,
-        )
+        ))
+    }
+
+    /// Convert the DateTime to Ephemeris Time (TDB).
+    pub fn try_to_et(&self) -> Result<Et, Error> {
+        let year = match Era::from_astronomical_year(self.year) {
+            (Era::CE, year) => year.to_string(),
+            (era, year) => format!("{year} {era}"),
+        };
+        let date = format!(
+            "{year}-{}-{} {}:{}:{} {}",
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.system.meta_marker(),
+        );
+        // The default calendar is restored when dropped, even if parsing fails
+        let mut timdef = ScopedTimDef::new();
+        timdef.set_calendar::<C>()?;
+        Et::from_string(date)
     }
 }
 
+/// # Panics
+///
+/// If SPICE signals an error, use [DateTime::try_from_et()] instead.
 impl<C: Calendar, S: System> From<Et> for DateTime<C, S> {
     #[inline]
     fn from(et: Et) -> Self {
-        DateTime::from_et(et, S::default())
+        DateTime::try_from_et(et, S::default()).unwrap()
     }
 }
 
+/// # Panics
+///
+/// If SPICE signals an error, use [DateTime::try_to_et()] instead.
 impl<C: Calendar, S: System> From<DateTime<C, S>> for Et {
     /// Convert a DateTime to Ephemeris Time (TDB)
     #[inline]
     fn from(dt: DateTime<C, S>) -> Self {
-        with_spice_lock_or_panic(|| {
-            // Get default calendar setting
-            let mut original_cal = [0; 12];
-            unsafe {
-                timdef_c(
-                    GET.as_mut_ptr(),
-                    CALENDAR.as_mut_ptr(),
-                    original_cal.len() as SpiceInt,
-                    original_cal.as_mut_ptr(),
-                );
-            };
-            get_last_error().unwrap();
-            let year = if dt.year > 0 {
-                dt.year.to_string()
-            } else {
-                format!("{} BC", dt.year.abs() + 1)
-            };
-            let date = format!(
-                "{year}-{}-{} {}:{}:{} {}",
-                dt.month,
-                dt.day,
-                dt.hour,
-                dt.minute,
-                dt.second,
-                dt.system.meta_marker(),
-            );
-            set_default_calendar::<C>();
-            let et = Et::from_string(date).unwrap();
-            // Restore default calendar
-            unsafe {
-                timdef_c(
-                    SET.as_mut_ptr(),
-                    CALENDAR.as_mut_ptr(),
-                    0,
-                    original_cal.as_mut_ptr(),
-                );
-            };
-            get_last_error().unwrap();
-            et
-        })
+        dt.try_to_et().unwrap()
     }
 }
 
@@ -190,13 +285,98 @@ impl From<DateTime<super::calendar::Gregorian, super::system::Utc>>
     fn from(t: DateTime<super::calendar::Gregorian, super::system::Utc>) -> Self {
         use chrono::TimeZone;
         let ns = t.second.fract() * 1_000_000_f32;
-        chrono::FixedOffset::east(t.system.to_zone_seconds())
-            .ymd(t.year as i32, t.month as u32, t.day as u32)
-            .and_hms_nano(
-                t.hour as u32,
-                t.minute as u32,
-                t.second.floor() as u32,
-                ns as u32,
-            )
+        let offset = chrono::FixedOffset::east_opt(t.system.to_zone_seconds())
+            .expect("UTC offset out of bounds");
+        let local = chrono::NaiveDate::from_ymd_opt(t.year as i32, t.month as u32, t.day as u32)
+            .and_then(|date| {
+                date.and_hms_nano_opt(
+                    t.hour as u32,
+                    t.minute as u32,
+                    t.second.floor() as u32,
+                    ns as u32,
+                )
+            })
+            .expect("invalid date or time");
+        offset.from_local_datetime(&local).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+    use crate::time::calendar::{Gregorian, Julian, Mixed};
+    use crate::time::system::Tdb;
+
+    #[test]
+    fn test_fallible_conversion() {
+        load_test_data();
+        let invalid = DateTime::<Gregorian, _>::new(2022, 13, 1, 0, 0, 0.0, Tdb);
+        assert!(invalid.try_to_et().is_err());
+        // Conversions still work after an error
+        let dt = DateTime::<Gregorian, _>::new(2000, 1, 1, 12, 0, 0.0, Tdb);
+        assert_eq!(dt.try_to_et().unwrap(), Et(0.0));
+        assert_eq!(DateTime::try_from_et(Et(0.0), Tdb).unwrap(), dt);
+    }
+
+    #[test]
+    fn test_era_years() {
+        assert_eq!(Era::from_astronomical_year(2022), (Era::CE, 2022));
+        assert_eq!(Era::from_astronomical_year(1), (Era::CE, 1));
+        assert_eq!(Era::from_astronomical_year(0), (Era::BCE, 1));
+        assert_eq!(Era::from_astronomical_year(-1), (Era::BCE, 2));
+        assert_eq!(Era::from_astronomical_year(-599), (Era::BCE, 600));
+        for year in [-32000, -600, -1, 0, 1, 2, 1582, 32000] {
+            let (era, year_of_era) = Era::from_astronomical_year(year);
+            assert_eq!(era.to_astronomical_year(year_of_era).unwrap(), year);
+        }
+        assert_eq!(Era::CE.to_astronomical_year(32767).unwrap(), i16::MAX);
+        assert!(Era::CE.to_astronomical_year(32768).is_err());
+        assert_eq!(Era::BCE.to_astronomical_year(32769).unwrap(), i16::MIN);
+        assert!(Era::BCE.to_astronomical_year(32770).is_err());
+        assert_eq!("B.C.".parse::<Era>().unwrap(), Era::BCE);
+        assert_eq!("ad".parse::<Era>().unwrap(), Era::CE);
+        assert!("XX".parse::<Era>().is_err());
+    }
+
+    fn check<C: Calendar>(year: i16, month: u8, day: u8, jd: f64) {
+        let dt = DateTime::<C, _>::new(year, month, day, 0, 0, 0.0, Tdb);
+        let label = dt.to_string();
+        assert_eq!(JulianDate::from(dt), JulianDate::new(jd), "{label}");
+        let back = DateTime::<C, _>::from(JulianDate::<Tdb>::new(jd));
+        assert_eq!(
+            (back.year, back.month, back.day),
+            (year, month, day),
+            "{back}"
+        );
+    }
+
+    #[test]
+    fn test_switchover_matrix() {
+        load_test_data();
+        // Last Julian day and first Gregorian day of the mixed calendar
+        check::<Mixed>(1582, 10, 4, 2299159.5);
+        check::<Mixed>(1582, 10, 15, 2299160.5);
+        check::<Julian>(1582, 10, 4, 2299159.5);
+        check::<Julian>(1582, 10, 15, 2299170.5);
+        check::<Gregorian>(1582, 10, 4, 2299149.5);
+        check::<Gregorian>(1582, 10, 15, 2299160.5);
+    }
+
+    #[test]
+    fn test_negative_year_matrix() {
+        load_test_data();
+        // Year 0 is a leap year in both the Julian and proleptic Gregorian calendars
+        check::<Julian>(1, 1, 1, 1721423.5);
+        check::<Julian>(0, 1, 1, 1721057.5);
+        check::<Julian>(-1, 1, 1, 1720692.5);
+        check::<Mixed>(1, 1, 1, 1721423.5);
+        check::<Mixed>(0, 1, 1, 1721057.5);
+        check::<Mixed>(0, 12, 31, 1721422.5);
+        check::<Mixed>(-1, 1, 1, 1720692.5);
+        check::<Mixed>(-599, 1, 1, 1502273.5);
+        check::<Gregorian>(1, 1, 1, 1721425.5);
+        check::<Gregorian>(0, 1, 1, 1721059.5);
+        check::<Gregorian>(-1, 1, 1, 1720694.5);
     }
 }
@@ -0,0 +1,252 @@
+use crate::time::calendar::{Calendar, CalendarKind, Gregorian, Julian, Mixed};
+use crate::time::date_time::{DateTime, DateTimeError};
+use crate::time::system::{System, Tdb, Tdt, TimeSystem};
+use crate::time::Et;
+use crate::Error;
+use std::fmt::{Display, Formatter};
+
+/// A [DateTime] where the calendar and time system are chosen at runtime rather than by type
+/// parameters, for example when they are selected by a user.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DynDateTime {
+    pub year: i16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: f32,
+    pub calendar: CalendarKind,
+    pub system: TimeSystem,
+}
+
+/// Error returned when converting a [DynDateTime] into a [DateTime] with a different calendar or
+/// time system.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("DynDateTime ({calendar:?}, {system:?}) does not match the target DateTime type")]
+pub struct DateTimeKindMismatch {
+    pub calendar: CalendarKind,
+    pub system: TimeSystem,
+}
+
+impl DynDateTime {
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn new(
+        year: i16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: f32,
+        calendar: CalendarKind,
+        system: TimeSystem,
+    ) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            calendar,
+            system,
+        }
+    }
+
+    /// Convert an Ephemeris Time (TDB) to a DynDateTime.
+    ///
+    /// # Panics
+    ///
+    /// If SPICE signals an error. Use [DynDateTime::try_from_et()] instead.
+    #[deprecated(note = "panics on SPICE errors, use `try_from_et()` instead")]
+    pub fn from_et(et: Et, calendar: CalendarKind, system: TimeSystem) -> Self {
+        Self::try_from_et(et, calendar, system).unwrap()
+    }
+
+    /// Convert an Ephemeris Time (TDB) to a DynDateTime.
+    pub fn try_from_et(
+        et: Et,
+        calendar: CalendarKind,
+        system: TimeSystem,
+    ) -> Result<Self, DateTimeError> {
+        match calendar {
+            CalendarKind::Mixed => Self::from_et_with_calendar::<Mixed>(et, system),
+            CalendarKind::Gregorian => Self::from_et_with_calendar::<Gregorian>(et, system),
+            CalendarKind::Julian => Self::from_et_with_calendar::<Julian>(et, system),
+        }
+    }
+
+    fn from_et_with_calendar<C: Calendar>(
+        et: Et,
+        system: TimeSystem,
+    ) -> Result<Self, DateTimeError> {
+        Ok(match system {
+            TimeSystem::Tdt => DateTime::<C, _>::try_from_et(et, Tdt)?.into(),
+            TimeSystem::Tdb => DateTime::<C, _>::try_from_et(et, Tdb)?.into(),
+            TimeSystem::Utc(utc) => DateTime::<C, _>::try_from_et(et, utc)?.into(),
+        })
+    }
+
+    /// Convert the DynDateTime to Ephemeris Time (TDB).
+    ///
+    /// # Panics
+    ///
+    /// If SPICE signals an error. Use [DynDateTime::try_to_et()] instead.
+    #[deprecated(note = "panics on SPICE errors, use `try_to_et()` instead")]
+    pub fn to_et(self) -> Et {
+        self.try_to_et().unwrap()
+    }
+
+    /// Convert the DynDateTime to Ephemeris Time (TDB).
+    pub fn try_to_et(self) -> Result<Et, Error> {
+        match self.calendar {
+            CalendarKind::Mixed => self.to_et_with_calendar::<Mixed>(),
+            CalendarKind::Gregorian => self.to_et_with_calendar::<Gregorian>(),
+            CalendarKind::Julian => self.to_et_with_calendar::<Julian>(),
+        }
+    }
+
+    fn to_et_with_calendar<C: Calendar>(self) -> Result<Et, Error> {
+        match self.system {
+            TimeSystem::Tdt => self.to_typed::<C, Tdt>(Tdt).try_to_et(),
+            TimeSystem::Tdb => self.to_typed::<C, Tdb>(Tdb).try_to_et(),
+            TimeSystem::Utc(utc) => self.to_typed::<C, _>(utc).try_to_et(),
+        }
+    }
+
+    fn to_typed<C: Calendar, S: System>(self, system: S) -> DateTime<C, S> {
+        DateTime::new(
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            system,
+        )
+    }
+
+    /// Convert to the same instant expressed in a different calendar and time system.
+    ///
+    /// # Panics
+    ///
+    /// If SPICE signals an error. Use [DynDateTime::try_convert()] instead.
+    #[deprecated(note = "panics on SPICE errors, use `try_convert()` instead")]
+    pub fn convert(&self, calendar: CalendarKind, system: TimeSystem) -> Self {
+        self.try_convert(calendar, system).unwrap()
+    }
+
+    /// Convert to the same instant expressed in a different calendar and time system.
+    pub fn try_convert(
+        &self,
+        calendar: CalendarKind,
+        system: TimeSystem,
+    ) -> Result<Self, DateTimeError> {
+        Self::try_from_et(self.try_to_et()?, calendar, system)
+    }
+}
+
+impl<C: Calendar, S: System> From<DateTime<C, S>> for DynDateTime {
+    fn from(dt: DateTime<C, S>) -> Self {
+        Self::new(
+            dt.year,
+            dt.month,
+            dt.day,
+            dt.hour,
+            dt.minute,
+            dt.second,
+            C::kind(),
+            dt.system.time_system(),
+        )
+    }
+}
+
+impl<C: Calendar, S: System> TryFrom<DynDateTime> for DateTime<C, S> {
+    type Error = DateTimeKindMismatch;
+
+    fn try_from(dt: DynDateTime) -> Result<Self, Self::Error> {
+        let mismatch = DateTimeKindMismatch {
+            calendar: dt.calendar,
+            system: dt.system,
+        };
+        if dt.calendar != C::kind() {
+            return Err(mismatch);
+        }
+        let system = S::from_time_system(dt.system).ok_or(mismatch)?;
+        Ok(dt.to_typed(system))
+    }
+}
+
+/// # Panics
+///
+/// If SPICE signals an error, use [DynDateTime::try_to_et()] instead.
+impl From<DynDateTime> for Et {
+    #[inline]
+    fn from(dt: DynDateTime) -> Self {
+        dt.try_to_et().unwrap()
+    }
+}
+
+impl Display for DynDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}-{} {}:{}:{} {} {}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.system.meta_marker(),
+            self.calendar.short_name()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+    use crate::time::system::Utc;
+
+    #[test]
+    fn test_typed_round_trip() {
+        let dt = DateTime::<Gregorian, _>::new(2022, 9, 14, 12, 30, 15.0, Utc::new(2, 0));
+        let dyn_dt = DynDateTime::from(dt);
+        assert_eq!(dyn_dt.calendar, CalendarKind::Gregorian);
+        assert_eq!(dyn_dt.system, TimeSystem::Utc(Utc::new(2, 0)));
+        assert_eq!(DateTime::<Gregorian, Utc>::try_from(dyn_dt).unwrap(), dt);
+        assert!(DateTime::<Julian, Utc>::try_from(dyn_dt).is_err());
+        assert!(DateTime::<Gregorian, Tdb>::try_from(dyn_dt).is_err());
+    }
+
+    #[test]
+    fn test_et_conversion() {
+        load_test_data();
+        let dt =
+            DynDateTime::try_from_et(Et(0.0), CalendarKind::Gregorian, TimeSystem::Tdb).unwrap();
+        assert_eq!(
+            dt,
+            DynDateTime::new(
+                2000,
+                1,
+                1,
+                12,
+                0,
+                0.0,
+                CalendarKind::Gregorian,
+                TimeSystem::Tdb
+            )
+        );
+        assert_eq!(dt.try_to_et().unwrap(), Et(0.0));
+        assert_eq!(
+            Et::from(DateTime::<Gregorian, Tdb>::try_from(dt).unwrap()),
+            dt.try_to_et().unwrap()
+        );
+        let julian = dt
+            .try_convert(CalendarKind::Julian, TimeSystem::Tdb)
+            .unwrap();
+        assert_eq!((julian.month, julian.day), (12, 19));
+    }
+}
@@ -8,6 +8,10 @@ use crate::with_spice_lock_or_panic;
 use cspice_sys::{timout_c, SpiceDouble};
 use std::fmt::{Display, Formatter};
 use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+
+/// The Julian Date of the Modified Julian Date epoch (1858-11-17 00:00).
+pub const MJD_EPOCH_JD: SpiceDouble = 2400000.5;
 
 /// See [Julian Date](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/time.html#Julian%20Date).
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -50,7 +54,7 @@ impl<S: System> From<Et> for JulianDate<S> {
             unsafe {
                 timout_c(
                     et.0,
-                    pictur.as_mut_ptr(),
+                    pictur.as_mut_ptr().unwrap(),
                     buffer.len() as i32,
                     buffer.as_mut_ptr(),
                 )
@@ -73,3 +77,123 @@ impl<S: System> Display for JulianDate<S> {
         write!(f, "JD {} {}", S::system_name(), self.value)
     }
 }
+
+/// Add a number of days.
+impl<S: System> Add<SpiceDouble> for JulianDate<S> {
+    type Output = Self;
+
+    fn add(self, days: SpiceDouble) -> Self::Output {
+        Self::new(self.value + days)
+    }
+}
+
+/// Subtract a number of days.
+impl<S: System> Sub<SpiceDouble> for JulianDate<S> {
+    type Output = Self;
+
+    fn sub(self, days: SpiceDouble) -> Self::Output {
+        Self::new(self.value - days)
+    }
+}
+
+/// The difference in days.
+impl<S: System> Sub for JulianDate<S> {
+    type Output = SpiceDouble;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.value - rhs.value
+    }
+}
+
+/// A Modified Julian Date, the number of days since 1858-11-17 00:00 (JD 2400000.5).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ModifiedJulianDate<S: System> {
+    pub value: SpiceDouble,
+    scale: PhantomData<S>,
+}
+
+impl<S: System> ModifiedJulianDate<S> {
+    #[inline]
+    pub fn new(mjd: SpiceDouble) -> Self {
+        Self {
+            value: mjd,
+            scale: Default::default(),
+        }
+    }
+}
+
+impl<S: System> From<SpiceDouble> for ModifiedJulianDate<S> {
+    fn from(s: SpiceDouble) -> Self {
+        ModifiedJulianDate::new(s)
+    }
+}
+
+impl<S: System> From<JulianDate<S>> for ModifiedJulianDate<S> {
+    #[inline]
+    fn from(jd: JulianDate<S>) -> Self {
+        Self::new(jd.value - MJD_EPOCH_JD)
+    }
+}
+
+impl<S: System> From<ModifiedJulianDate<S>> for JulianDate<S> {
+    #[inline]
+    fn from(mjd: ModifiedJulianDate<S>) -> Self {
+        Self::new(mjd.value + MJD_EPOCH_JD)
+    }
+}
+
+impl<S: System> From<ModifiedJulianDate<S>> for Et {
+    /// Convert a Modified Julian Date to Ephemeris Time (TDB).
+    #[inline]
+    fn from(mjd: ModifiedJulianDate<S>) -> Self {
+        Et::from(JulianDate::from(mjd))
+    }
+}
+
+impl<S: System> From<Et> for ModifiedJulianDate<S> {
+    /// Convert Ephemeris Time (TDB) to a Modified Julian Date.
+    #[inline]
+    fn from(et: Et) -> Self {
+        ModifiedJulianDate::from(JulianDate::from(et))
+    }
+}
+
+impl<C: Calendar, S: System> From<DateTime<C, S>> for ModifiedJulianDate<S> {
+    #[inline]
+    fn from(dt: DateTime<C, S>) -> Self {
+        ModifiedJulianDate::from(Et::from(dt))
+    }
+}
+
+/// Add a number of days.
+impl<S: System> Add<SpiceDouble> for ModifiedJulianDate<S> {
+    type Output = Self;
+
+    fn add(self, days: SpiceDouble) -> Self::Output {
+        Self::new(self.value + days)
+    }
+}
+
+/// Subtract a number of days.
+impl<S: System> Sub<SpiceDouble> for ModifiedJulianDate<S> {
+    type Output = Self;
+
+    fn sub(self, days: SpiceDouble) -> Self::Output {
+        Self::new(self.value - days)
+    }
+}
+
+/// The difference in days.
+impl<S: System> Sub for ModifiedJulianDate<S> {
+    type Output = SpiceDouble;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.value - rhs.value
+    }
+}
+
+impl<S: System> Display for ModifiedJulianDate<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MJD {} {}", S::system_name(), self.value)
+    }
+}
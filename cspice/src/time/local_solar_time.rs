@@ -0,0 +1,146 @@
+use crate::body::BodyRef;
+use crate::error::get_last_error;
+use crate::string::{static_spice_str, SpiceString, StaticSpiceStr};
+use crate::time::Et;
+use crate::units::Radians;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{et2lst_c, SpiceChar, SpiceInt};
+use std::fmt::{Display, Formatter};
+
+/// The type of longitude passed to [LocalSolarTime::new()].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LongitudeType {
+    /// Planetocentric longitude, which is positive east.
+    Planetocentric,
+    /// Planetographic longitude, which is positive west for most bodies.
+    Planetographic,
+}
+
+impl LongitudeType {
+    pub(crate) unsafe fn as_spice_char(&self) -> *mut SpiceChar {
+        match self {
+            LongitudeType::Planetocentric => static_spice_str!("PLANETOCENTRIC"),
+            LongitudeType::Planetographic => static_spice_str!("PLANETOGRAPHIC"),
+        }
+        .as_mut_ptr()
+    }
+}
+
+/// The local solar time at a longitude on the surface of a body, where noon is when the Sun is
+/// on the meridian and a day is divided into 24 "hours" regardless of the body's rotation period.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalSolarTime {
+    pub hour: SpiceInt,
+    pub minute: SpiceInt,
+    pub second: SpiceInt,
+    /// The time on a 24 hour clock, e.g. `"13:05:42"`.
+    pub time: String,
+    /// The time on a 12 hour clock, e.g. `"01:05:42 P.M."`.
+    pub ampm: String,
+}
+
+impl LocalSolarTime {
+    /// Compute the local solar time at a longitude on a body at an epoch. Requires orientation
+    /// data for the body, and ephemeris data for the body and the Sun.
+    ///
+    /// See [et2lst_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/et2lst_c.html).
+    pub fn new<'b, B: Into<BodyRef<'b>>>(
+        et: Et,
+        body: B,
+        longitude: Radians,
+        longitude_type: LongitudeType,
+    ) -> Result<Self, Error> {
+        let body = body.into().to_id()?;
+        // The 12 hour time has the form "HR:MN:SC A.M."
+        let (mut time, mut ampm) = (vec![0; 9], vec![0; 14]);
+        with_spice_lock_or_panic(|| {
+            let (mut hour, mut minute, mut second) = (0, 0, 0);
+            unsafe {
+                et2lst_c(
+                    et.0,
+                    body,
+                    longitude.0,
+                    longitude_type.as_spice_char(),
+                    time.len() as SpiceInt,
+                    ampm.len() as SpiceInt,
+                    &mut hour,
+                    &mut minute,
+                    &mut second,
+                    time.as_mut_ptr(),
+                    ampm.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(Self {
+                hour,
+                minute,
+                second,
+                time: SpiceString::from_buffer(time).to_string(),
+                ampm: SpiceString::from_buffer(ampm).to_string(),
+            })
+        })
+    }
+
+    /// The number of local "seconds" since local midnight.
+    pub fn seconds_of_day(&self) -> SpiceInt {
+        (self.hour * 60 + self.minute) * 60 + self.second
+    }
+}
+
+impl Display for LocalSolarTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::AberrationCorrection;
+    use crate::coordinates::Latitudinal;
+    use crate::geometry::{sub_solar_point, ShapeMethod};
+    use crate::naif_ids::MOON;
+    use crate::tests::{load_moon_constants, load_test_data};
+
+    #[test]
+    fn test_local_solar_time() {
+        load_test_data();
+        load_moon_constants();
+        let et = Et(0.0);
+        let sub_solar = sub_solar_point(
+            &ShapeMethod::ellipsoid(),
+            MOON,
+            et,
+            "IAU_MOON",
+            AberrationCorrection::LT_S,
+            "SUN",
+        )
+        .unwrap();
+        let noon_longitude = Latitudinal::from(sub_solar.point).longitude;
+
+        let noon = LocalSolarTime::new(
+            et,
+            MOON,
+            Radians(noon_longitude),
+            LongitudeType::Planetocentric,
+        )
+        .unwrap();
+        assert!((noon.seconds_of_day() - 12 * 3600).abs() <= 60, "{noon:?}");
+
+        // A quarter turn east is six hours later
+        let evening = LocalSolarTime::new(
+            et,
+            "MOON",
+            Radians(noon_longitude + std::f64::consts::FRAC_PI_2),
+            LongitudeType::Planetocentric,
+        )
+        .unwrap();
+        assert!(
+            (evening.seconds_of_day() - 18 * 3600).abs() <= 60,
+            "{evening:?}"
+        );
+        assert_eq!(evening.time.len(), 8);
+        assert!(evening.ampm.ends_with("P.M."), "{evening:?}");
+        assert_eq!(evening.to_string(), evening.time);
+    }
+}
@@ -1,12 +1,20 @@
 //! Structures and functions for the various SPICE time subsystems.
 mod date_time;
+mod dyn_date_time;
 mod julian_date;
+mod local_solar_time;
+mod timdef;
 
 pub mod calendar;
+pub mod sclk;
 pub mod system;
 
-pub use date_time::DateTime;
-pub use julian_date::JulianDate;
+pub use date_time::{DateTime, DateTimeError, Era, ParseEraError, YearOutOfRangeError};
+pub use dyn_date_time::{DateTimeKindMismatch, DynDateTime};
+pub use julian_date::{JulianDate, ModifiedJulianDate, MJD_EPOCH_JD};
+pub use local_solar_time::{LocalSolarTime, LongitudeType};
+pub use sclk::SclkTime;
+pub use timdef::{get_time_default, ScopedTimDef, TimDefItem};
 
 use crate::common::{CALENDAR, SET};
 use crate::error::get_last_error;
@@ -47,7 +55,7 @@ impl Et {
             unsafe {
                 timout_c(
                     self.0,
-                    pictur.into().as_mut_ptr(),
+                    pictur.into().as_mut_ptr()?,
                     buffer.len() as SpiceInt,
                     buffer.as_mut_ptr(),
                 );
@@ -57,6 +65,38 @@ impl Et {
         Ok(SpiceString::from_buffer(buffer).to_string())
     }
 
+    /// Convert Ephemeris Time to a different time format, automatically sizing the output buffer.
+    ///
+    /// The initial buffer size is derived from the length of the picture. SPICE truncates output
+    /// that doesn't fit in the buffer, so if the output fills the buffer it is retried with a
+    /// larger one.
+    ///
+    /// See [timout_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/timout_c.html).
+    pub fn time_out_auto<'p, P: Into<StringParam<'p>>>(&self, pictur: P) -> Result<String, Error> {
+        let pictur = pictur.into();
+        // Some picture tokens expand, e.g. "Month" to "SEPTEMBER", so allow plenty of slack
+        let mut out_length = pictur.0.as_bytes().len() * 2 + 32;
+        loop {
+            let output = self.time_out(&*pictur, out_length)?;
+            if output.len() < out_length - 1 {
+                return Ok(output);
+            }
+            out_length *= 2;
+        }
+    }
+
+    /// Format in one of the common [OutputFormat]s.
+    ///
+    /// A leapseconds kernel must be loaded, and for [OutputFormat::Sclk] the SCLK kernel of the
+    /// clock.
+    pub fn format(&self, format: OutputFormat) -> Result<String, Error> {
+        match format {
+            OutputFormat::Sclk(spacecraft) => SclkTime::from_et(spacecraft, *self)?.format(),
+            // Every other format has a picture
+            format => self.time_out_auto(format.picture().unwrap()),
+        }
+    }
+
     /// Convert a time string to Ephemeris Time (TDB)
     ///
     /// See [str2et_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/str2et_c.html)
@@ -65,7 +105,7 @@ impl Et {
         with_spice_lock_or_panic(|| {
             let mut output = 0f64;
             unsafe {
-                str2et_c(string.into().as_mut_ptr(), &mut output);
+                str2et_c(string.into().as_mut_ptr()?, &mut output);
             };
             get_last_error()?;
             Ok(Self(output))
@@ -73,8 +113,81 @@ impl Et {
     }
 }
 
+/// Common output formats for [Et::format()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// ISO 8601 calendar date and time in UTC, e.g. `2000-01-01T11:58:55.816` with `precision`
+    /// decimal places of seconds.
+    IsoCalendar { precision: u8 },
+    /// ISO 8601 day of year and time in UTC to milliseconds, e.g. `2000-001T11:58:55.816`.
+    IsoDoy,
+    /// Julian date in TDB to 9 decimal places, e.g. `2451545.000000000`.
+    JulianDateTdb,
+    /// Spacecraft clock string of the clock of a spacecraft, see [SclkTime::format()].
+    Sclk(SpiceInt),
+    /// A [timout_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/timout_c.html)
+    /// picture string.
+    Custom(String),
+}
+
+impl OutputFormat {
+    /// The equivalent timout picture string, `None` for [OutputFormat::Sclk].
+    pub fn picture(&self) -> Option<String> {
+        let seconds = |precision: u8| match precision {
+            0 => String::from("SC"),
+            p => format!("SC.{}", "#".repeat(p as usize)),
+        };
+        match self {
+            OutputFormat::IsoCalendar { precision } => {
+                Some(format!("YYYY-MM-DDTHR:MN:{} ::RND", seconds(*precision)))
+            }
+            OutputFormat::IsoDoy => Some(format!("YYYY-DOYTHR:MN:{} ::RND", seconds(3))),
+            OutputFormat::JulianDateTdb => Some(String::from("JULIAND.######### ::TDB ::RND")),
+            OutputFormat::Sclk(_) => None,
+            OutputFormat::Custom(picture) => Some(picture.clone()),
+        }
+    }
+}
+
+/// Error returned from [parse_time()].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParseTimeError {
+    /// The time string could not be parsed. `explanation` is SPICE's human readable description
+    /// of which part of the string was invalid.
+    #[error("Invalid time string {input:?}: {explanation}")]
+    Invalid { input: String, explanation: String },
+    /// Any other SPICE error, for example if a required kernel has not been loaded.
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+/// Parse a time string to Ephemeris Time (TDB), returning a structured error suitable for
+/// displaying to end users if the string is invalid.
+///
+/// The string is invalid if [str2et_c] fails with one of its syntax errors, in which case the
+/// explanation is the diagnostic message from its parser. Any other failure (e.g. no leapseconds
+/// kernel is loaded) is returned as the SPICE error.
+///
+/// [str2et_c]: https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/str2et_c.html
+pub fn parse_time<S: AsRef<str>>(string: S) -> Result<Et, ParseTimeError> {
+    let input = string.as_ref();
+    Et::from_string(input).map_err(|error| match error.short_message.as_str() {
+        "SPICE(UNPARSEDTIME)"
+        | "SPICE(BADTIMESTRING)"
+        | "SPICE(INVALIDTIMESTRING)"
+        | "SPICE(TIMECONFLICT)" => ParseTimeError::Invalid {
+            input: input.to_string(),
+            explanation: error.long_message,
+        },
+        _ => ParseTimeError::Spice(error),
+    })
+}
+
 /// Sets the default calendar to use with input strings.
 ///
+/// This changes the calendar for all threads until it is set again, use [ScopedTimDef] to
+/// change it temporarily.
+///
 /// See [timdef_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/timdef_c.html).
 #[inline]
 pub fn set_default_calendar<C: Calendar>() {
@@ -85,7 +198,7 @@ pub fn set_default_calendar<C: Calendar>() {
                 SET.as_mut_ptr(),
                 CALENDAR.as_mut_ptr(),
                 0,
-                name.as_mut_ptr(),
+                name.as_mut_ptr().unwrap(),
             );
         };
         get_last_error().unwrap();
@@ -99,6 +212,68 @@ mod tests {
     use crate::time::calendar::{Gregorian, Mixed};
     use crate::time::system::{Tdb, Utc};
 
+    #[test]
+    fn test_time_out_auto() {
+        load_test_data();
+        let et = Et::from_string("2022-09-14 12:00:00 TDB").unwrap();
+        assert_eq!(
+            et.time_out_auto("WEEKDAY, MONTH DD, YYYY ::TDB").unwrap(),
+            "WEDNESDAY, SEPTEMBER 14, 2022"
+        );
+        assert_eq!(et.time_out_auto("JULIAND ::TDB").unwrap(), "2459837");
+    }
+
+    #[test]
+    fn test_format() {
+        load_test_data();
+        let et = Et(0.0);
+        assert_eq!(
+            et.format(OutputFormat::IsoCalendar { precision: 3 })
+                .unwrap(),
+            "2000-01-01T11:58:55.816"
+        );
+        assert_eq!(
+            et.format(OutputFormat::IsoCalendar { precision: 0 })
+                .unwrap(),
+            "2000-01-01T11:58:56"
+        );
+        assert_eq!(
+            et.format(OutputFormat::IsoDoy).unwrap(),
+            "2000-001T11:58:55.816"
+        );
+        assert_eq!(
+            et.format(OutputFormat::JulianDateTdb).unwrap(),
+            "2451545.000000000"
+        );
+        assert_eq!(
+            et.format(OutputFormat::Custom(String::from("YYYY MON DD ::TDB")))
+                .unwrap(),
+            "2000 JAN 01"
+        );
+        // No clock kernel is loaded for this spacecraft
+        assert!(et.format(OutputFormat::Sclk(-999999)).is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        load_test_data();
+        assert_eq!(parse_time("2000-01-01 12:00:00 TDB").unwrap(), Et(0.0));
+        assert!(parse_time("2000-01-01 12:00:00 UTC").is_ok());
+        match parse_time("2000-01-01 12:00:00 FOO") {
+            Err(ParseTimeError::Invalid { input, explanation }) => {
+                assert_eq!(input, "2000-01-01 12:00:00 FOO");
+                assert!(!explanation.is_empty());
+            }
+            other => panic!("Expected invalid time string error, got {other:?}"),
+        }
+        match parse_time("2000-02-30 12:00:00") {
+            Err(ParseTimeError::Invalid { explanation, .. }) => {
+                assert!(explanation.to_lowercase().contains("day"), "{explanation}");
+            }
+            other => panic!("Expected invalid time string error, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_et_to_jd() {
         load_test_data();
@@ -108,11 +283,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_jd_arithmetic() {
+        let jd = JulianDate::<Tdb>::new(2451545.0);
+        assert_eq!(jd + 1.5, JulianDate::new(2451546.5));
+        assert_eq!(jd - 0.5, JulianDate::new(2451544.5));
+        assert_eq!(JulianDate::new(2451546.0) - jd, 1.0);
+
+        let mjd = ModifiedJulianDate::<Tdb>::from(jd);
+        assert_eq!(mjd, ModifiedJulianDate::new(51544.5));
+        assert_eq!(JulianDate::from(mjd), jd);
+        assert_eq!(mjd + 0.5, ModifiedJulianDate::new(51545.0));
+        assert_eq!(ModifiedJulianDate::new(51545.0) - mjd, 0.5);
+    }
+
+    #[test]
+    fn test_et_to_mjd() {
+        load_test_data();
+        assert_eq!(
+            ModifiedJulianDate::from(Et(0f64)),
+            ModifiedJulianDate::<Tdb>::new(51544.5)
+        );
+        assert_eq!(Et::from(ModifiedJulianDate::<Tdb>::new(51544.5)), Et(0.0));
+    }
+
     #[test]
     fn test_jd_to_date_time() {
         load_test_data();
         let et = Et::from(JulianDate::<Tdb>::new(1502273.5));
-        let dt = DateTime::<Mixed, _>::from_et(et, Tdb);
+        let dt = DateTime::<Mixed, _>::try_from_et(et, Tdb).unwrap();
         assert_eq!(dt, DateTime::new(-599, 1, 1, 0, 0, 0.0, Tdb));
     }
 
@@ -0,0 +1,279 @@
+//! Spacecraft clock (SCLK) times and partitions.
+use crate::error::{get_last_error, set_error_message, signal_error};
+use crate::string::{SpiceString, StringParam};
+use crate::time::Et;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{scdecd_c, sce2c_c, scencd_c, scpart_c, sct2e_c, SpiceDouble, SpiceInt};
+
+/// The maximum number of partitions a spacecraft clock may have.
+const MAX_PARTITIONS: usize = 9999;
+
+/// A partition of a spacecraft clock, the clock readings are in ticks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Partition {
+    pub start: SpiceDouble,
+    pub stop: SpiceDouble,
+}
+
+impl Partition {
+    /// The number of ticks in the partition.
+    pub fn len(&self) -> SpiceDouble {
+        self.stop - self.start
+    }
+
+    /// Returns true if the partition contains no ticks.
+    pub fn is_empty(&self) -> bool {
+        self.len() <= 0.0
+    }
+}
+
+/// Get the partitions of a spacecraft clock.
+///
+/// See [scpart_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/scpart_c.html).
+pub fn partitions(spacecraft: SpiceInt) -> Result<Vec<Partition>, Error> {
+    let mut start = vec![0.0; MAX_PARTITIONS];
+    let mut stop = vec![0.0; MAX_PARTITIONS];
+    let mut count: SpiceInt = 0;
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            scpart_c(
+                spacecraft,
+                &mut count,
+                start.as_mut_ptr(),
+                stop.as_mut_ptr(),
+            )
+        };
+        get_last_error()
+    })?;
+    Ok(start
+        .into_iter()
+        .zip(stop)
+        .take(count as usize)
+        .map(|(start, stop)| Partition { start, stop })
+        .collect())
+}
+
+/// A spacecraft clock (SCLK) time.
+///
+/// Internally this is stored as encoded SCLK, i.e. continuous (possibly fractional) ticks since
+/// the start of the first partition, which is the form used by the CK subsystem. Clock readings
+/// restart at each partition boundary so a reading is only meaningful together with its
+/// partition number, see [SclkTime::partition].
+///
+/// See [SCLK required reading](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/req/sclk.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SclkTime {
+    spacecraft: SpiceInt,
+    encoded: SpiceDouble,
+}
+
+impl SclkTime {
+    /// Create from encoded SCLK (continuous ticks).
+    pub fn from_encoded(spacecraft: SpiceInt, encoded: SpiceDouble) -> Self {
+        Self {
+            spacecraft,
+            encoded,
+        }
+    }
+
+    /// Create from a clock reading in ticks within a partition (numbered from 1).
+    pub fn from_partition_ticks(
+        spacecraft: SpiceInt,
+        partition: SpiceInt,
+        ticks: SpiceDouble,
+    ) -> Result<Self, Error> {
+        let partitions = partitions(spacecraft)?;
+        if partition < 1 || partition as usize > partitions.len() {
+            return signal(
+                format!(
+                    "Partition {partition} is not in the range 1 to {}",
+                    partitions.len()
+                ),
+                "SPICE(BADPARTNUMBER)",
+            );
+        }
+        let index = partition as usize - 1;
+        let p = partitions[index];
+        if ticks < p.start || ticks > p.stop {
+            return signal(
+                format!("Ticks {ticks} are not within partition {partition}"),
+                "SPICE(NOTINPART)",
+            );
+        }
+        let offset: SpiceDouble = partitions[..index].iter().map(Partition::len).sum();
+        Ok(Self::from_encoded(spacecraft, offset + ticks - p.start))
+    }
+
+    /// Parse a spacecraft clock string, such as `"1/0488762903.205"`. If the partition is omitted
+    /// the first partition containing the reading is used.
+    ///
+    /// See [scencd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/scencd_c.html).
+    pub fn parse<'s, S: Into<StringParam<'s>>>(spacecraft: SpiceInt, s: S) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut encoded = 0.0;
+            unsafe { scencd_c(spacecraft, s.into().as_mut_ptr()?, &mut encoded) };
+            get_last_error()?;
+            Ok(Self::from_encoded(spacecraft, encoded))
+        })
+    }
+
+    /// Convert from Ephemeris Time, the result may have fractional ticks.
+    ///
+    /// See [sce2c_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sce2c_c.html).
+    pub fn from_et(spacecraft: SpiceInt, et: Et) -> Result<Self, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut encoded = 0.0;
+            unsafe { sce2c_c(spacecraft, et.0, &mut encoded) };
+            get_last_error()?;
+            Ok(Self::from_encoded(spacecraft, encoded))
+        })
+    }
+
+    /// The NAIF ID code of the spacecraft (clock).
+    pub fn spacecraft(&self) -> SpiceInt {
+        self.spacecraft
+    }
+
+    /// The encoded SCLK (continuous ticks).
+    pub fn encoded(&self) -> SpiceDouble {
+        self.encoded
+    }
+
+    /// Get the partition number (from 1) and the clock reading in ticks within that partition.
+    ///
+    /// A time on a boundary between partitions is placed in the later partition.
+    pub fn partition(&self) -> Result<(SpiceInt, SpiceDouble), Error> {
+        let partitions = partitions(self.spacecraft)?;
+        let mut offset = 0.0;
+        for (i, p) in partitions.iter().enumerate() {
+            if self.encoded < offset + p.len() || i == partitions.len() - 1 {
+                return Ok((i as SpiceInt + 1, p.start + self.encoded - offset));
+            }
+            offset += p.len();
+        }
+        signal(
+            format!("Spacecraft clock {} has no partitions", self.spacecraft),
+            "SPICE(NOPARTITIONS)",
+        )
+    }
+
+    /// Format as a spacecraft clock string including the partition number.
+    ///
+    /// See [scdecd_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/scdecd_c.html).
+    pub fn format(&self) -> Result<String, Error> {
+        let mut buffer = vec![0; 128];
+        with_spice_lock_or_panic(|| {
+            unsafe {
+                scdecd_c(
+                    self.spacecraft,
+                    self.encoded,
+                    buffer.len() as SpiceInt,
+                    buffer.as_mut_ptr(),
+                )
+            };
+            get_last_error()
+        })?;
+        Ok(SpiceString::from_buffer(buffer).to_string())
+    }
+
+    /// Convert to Ephemeris Time.
+    ///
+    /// See [sct2e_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/sct2e_c.html).
+    pub fn to_et(&self) -> Result<Et, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut et = 0.0;
+            unsafe { sct2e_c(self.spacecraft, self.encoded, &mut et) };
+            get_last_error()?;
+            Ok(Et(et))
+        })
+    }
+}
+
+fn signal<T>(message: String, short_message: &str) -> Result<T, Error> {
+    with_spice_lock_or_panic(|| {
+        set_error_message(message);
+        signal_error(short_message);
+        get_last_error()?;
+        unreachable!("signalled error was not reported")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::furnish;
+    use std::sync::Once;
+
+    const SPACECRAFT: SpiceInt = -999;
+
+    /// One second per 256 ticks, with a second partition starting at reading 200:000
+    fn load_test_sclk() {
+        static LOAD: Once = Once::new();
+        LOAD.call_once(|| {
+            let path = std::env::temp_dir().join("cspice_rs_sclk_test.tsc");
+            std::fs::write(
+                &path,
+                "\\begindata
+SCLK_KERNEL_ID           = ( @2000-01-01/00:00:00 )
+SCLK_DATA_TYPE_999       = ( 1 )
+SCLK01_TIME_SYSTEM_999   = ( 1 )
+SCLK01_N_FIELDS_999      = ( 2 )
+SCLK01_MODULI_999        = ( 4294967296 256 )
+SCLK01_OFFSETS_999       = ( 0 0 )
+SCLK01_OUTPUT_DELIM_999  = ( 2 )
+SCLK_PARTITION_START_999 = ( 0 51200 )
+SCLK_PARTITION_END_999   = ( 25600 76800 )
+SCLK01_COEFFICIENTS_999  = ( 0 0 1 )
+\\begintext
+",
+            )
+            .unwrap();
+            furnish(path.to_string_lossy()).unwrap();
+        });
+    }
+
+    #[test]
+    fn test_partitions() {
+        load_test_sclk();
+        assert_eq!(
+            partitions(SPACECRAFT).unwrap(),
+            vec![
+                Partition {
+                    start: 0.0,
+                    stop: 25600.0
+                },
+                Partition {
+                    start: 51200.0,
+                    stop: 76800.0
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sclk_time() {
+        load_test_sclk();
+        let time = SclkTime::parse(SPACECRAFT, "2/250:128").unwrap();
+        assert_eq!(time.encoded(), 25600.0 + 64128.0 - 51200.0);
+        assert_eq!(time.partition().unwrap(), (2, 64128.0));
+        assert_eq!(time.to_et().unwrap(), Et(150.5));
+        assert_eq!(SclkTime::from_et(SPACECRAFT, Et(150.5)).unwrap(), time);
+        assert_eq!(
+            SclkTime::from_partition_ticks(SPACECRAFT, 2, 64128.0).unwrap(),
+            time
+        );
+
+        let formatted = time.format().unwrap();
+        assert!(formatted.starts_with("2/"), "{formatted}");
+        assert_eq!(SclkTime::parse(SPACECRAFT, &formatted).unwrap(), time);
+
+        // Fractional ticks are kept
+        let fractional = SclkTime::from_et(SPACECRAFT, Et(0.5 / 256.0)).unwrap();
+        assert_eq!(fractional.partition().unwrap(), (1, 0.5));
+
+        let err = SclkTime::from_partition_ticks(SPACECRAFT, 1, 64128.0).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(NOTINPART)");
+        let err = SclkTime::from_partition_ticks(SPACECRAFT, 3, 0.0).unwrap_err();
+        assert_eq!(err.short_message, "SPICE(BADPARTNUMBER)");
+    }
+}
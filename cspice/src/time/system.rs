@@ -5,6 +5,34 @@ use std::borrow::Cow;
 pub trait System: Default {
     fn system_name() -> &'static str;
     fn meta_marker(&self) -> Cow<'static, str>;
+    fn time_system(&self) -> TimeSystem;
+    fn from_time_system(system: TimeSystem) -> Option<Self>;
+}
+
+/// A time system selected at runtime, see [DynDateTime](super::DynDateTime).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TimeSystem {
+    Tdt,
+    Tdb,
+    Utc(Utc),
+}
+
+impl TimeSystem {
+    pub fn system_name(&self) -> &'static str {
+        match self {
+            TimeSystem::Tdt => Tdt::system_name(),
+            TimeSystem::Tdb => Tdb::system_name(),
+            TimeSystem::Utc(_) => Utc::system_name(),
+        }
+    }
+
+    pub fn meta_marker(&self) -> Cow<'static, str> {
+        match self {
+            TimeSystem::Tdt => Tdt.meta_marker(),
+            TimeSystem::Tdb => Tdb.meta_marker(),
+            TimeSystem::Utc(utc) => utc.meta_marker(),
+        }
+    }
 }
 
 /// Terrestrial Dynamical Time (TDT).
@@ -18,7 +46,7 @@ pub struct Tdt;
 pub struct Tdb;
 
 /// Coordinated Universal Time (UTC).
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Utc {
     pub zone_hours: i8,
     pub zone_minutes: u8,
@@ -32,6 +60,17 @@ impl System for Tdt {
     fn meta_marker(&self) -> Cow<'static, str> {
         "TDT".into()
     }
+
+    fn time_system(&self) -> TimeSystem {
+        TimeSystem::Tdt
+    }
+
+    fn from_time_system(system: TimeSystem) -> Option<Self> {
+        match system {
+            TimeSystem::Tdt => Some(Tdt),
+            _ => None,
+        }
+    }
 }
 
 impl System for Tdb {
@@ -42,6 +81,17 @@ impl System for Tdb {
     fn meta_marker(&self) -> Cow<'static, str> {
         "TDB".into()
     }
+
+    fn time_system(&self) -> TimeSystem {
+        TimeSystem::Tdb
+    }
+
+    fn from_time_system(system: TimeSystem) -> Option<Self> {
+        match system {
+            TimeSystem::Tdb => Some(Tdb),
+            _ => None,
+        }
+    }
 }
 
 impl System for Utc {
@@ -52,6 +102,17 @@ impl System for Utc {
     fn meta_marker(&self) -> Cow<'static, str> {
         format!("UTC{:+}:{}", self.zone_hours, self.zone_minutes).into()
     }
+
+    fn time_system(&self) -> TimeSystem {
+        TimeSystem::Utc(*self)
+    }
+
+    fn from_time_system(system: TimeSystem) -> Option<Self> {
+        match system {
+            TimeSystem::Utc(utc) => Some(utc),
+            _ => None,
+        }
+    }
 }
 
 impl Utc {
@@ -0,0 +1,167 @@
+use crate::common::{CALENDAR, GET, SET, SYSTEM, ZONE};
+use crate::error::get_last_error;
+use crate::string::{SpiceStr, SpiceString, StaticSpiceStr, StringParam};
+use crate::time::calendar::Calendar;
+use crate::{with_spice_lock_or_panic, Error, SpiceLock};
+use cspice_sys::{timdef_c, SpiceChar, SpiceInt};
+
+/// A default used when parsing time strings.
+///
+/// See [timdef_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/timdef_c.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TimDefItem {
+    /// The calendar, `GREGORIAN`, `JULIAN` or `MIXED`.
+    Calendar,
+    /// The time system, `UTC`, `TDB` or `TDT`. Setting the system clears the zone.
+    System,
+    /// The time zone, e.g. `UTC+5:30`. Setting the zone clears the system.
+    Zone,
+}
+
+impl TimDefItem {
+    fn name(&self) -> StaticSpiceStr {
+        match self {
+            TimDefItem::Calendar => CALENDAR,
+            TimDefItem::System => SYSTEM,
+            TimDefItem::Zone => ZONE,
+        }
+    }
+}
+
+/// Get the current value of a time default.
+///
+/// See [timdef_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/timdef_c.html).
+pub fn get_time_default(item: TimDefItem) -> Result<String, Error> {
+    let mut value = [0 as SpiceChar; 32];
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            timdef_c(
+                GET.as_mut_ptr(),
+                item.name().as_mut_ptr(),
+                value.len() as SpiceInt,
+                value.as_mut_ptr(),
+            )
+        };
+        get_last_error()
+    })?;
+    Ok(SpiceStr::from_buffer(&value).as_str().trim().to_string())
+}
+
+fn set_time_default(item: TimDefItem, value: &SpiceString) -> Result<(), Error> {
+    with_spice_lock_or_panic(|| {
+        unsafe {
+            timdef_c(
+                SET.as_mut_ptr(),
+                item.name().as_mut_ptr(),
+                0,
+                value.as_mut_ptr()?,
+            )
+        };
+        get_last_error()
+    })
+}
+
+/// Temporarily changes the time defaults used when parsing time strings, restoring the original
+/// values when dropped.
+///
+/// The time defaults are global to SPICE, so the SPICE lock is held for the lifetime of the
+/// ScopedTimDef; other threads can't observe the temporary values, and will block until it is
+/// dropped.
+///
+/// ```no_run
+/// use cspice::time::calendar::Julian;
+/// use cspice::time::{Et, ScopedTimDef};
+///
+/// let mut timdef = ScopedTimDef::new();
+/// timdef.set_calendar::<Julian>().unwrap();
+/// let et = Et::from_string("1500-01-01").unwrap();
+/// drop(timdef);
+/// ```
+#[derive(Debug)]
+pub struct ScopedTimDef {
+    // Calendar, system and zone, saved before the first change
+    original: Option<(SpiceString, SpiceString, SpiceString)>,
+    _lock: SpiceLock,
+}
+
+impl Default for ScopedTimDef {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScopedTimDef {
+    /// Acquire the SPICE lock, blocking until it is available.
+    pub fn new() -> Self {
+        Self {
+            original: None,
+            _lock: SpiceLock::acquire(),
+        }
+    }
+
+    /// Change a time default until the ScopedTimDef is dropped.
+    pub fn set<'v, V: Into<StringParam<'v>>>(
+        &mut self,
+        item: TimDefItem,
+        value: V,
+    ) -> Result<(), Error> {
+        if self.original.is_none() {
+            let get = |item| get_time_default(item).map(SpiceString::from);
+            self.original = Some((
+                get(TimDefItem::Calendar)?,
+                get(TimDefItem::System)?,
+                get(TimDefItem::Zone)?,
+            ));
+        }
+        set_time_default(item, &value.into())
+    }
+
+    /// Change the default calendar until the ScopedTimDef is dropped.
+    pub fn set_calendar<C: Calendar>(&mut self) -> Result<(), Error> {
+        self.set(TimDefItem::Calendar, C::name())
+    }
+}
+
+impl Drop for ScopedTimDef {
+    fn drop(&mut self) {
+        if let Some((calendar, system, zone)) = self.original.take() {
+            let _ = set_time_default(TimDefItem::Calendar, &calendar);
+            // Only one of the system and zone is set at a time
+            let _ = if zone.0.as_bytes().is_empty() {
+                set_time_default(TimDefItem::System, &system)
+            } else {
+                set_time_default(TimDefItem::Zone, &zone)
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+    use crate::time::calendar::Julian;
+    use crate::time::Et;
+
+    #[test]
+    fn test_scoped_timdef() {
+        load_test_data();
+        let calendar = get_time_default(TimDefItem::Calendar).unwrap();
+        let system = get_time_default(TimDefItem::System).unwrap();
+        let utc = Et::from_string("2000-01-01T12:00:00").unwrap();
+        {
+            let mut timdef = ScopedTimDef::new();
+            timdef.set_calendar::<Julian>().unwrap();
+            timdef.set(TimDefItem::Zone, "UTC+1").unwrap();
+            assert_eq!(get_time_default(TimDefItem::Calendar).unwrap(), "JULIAN");
+            assert_eq!(get_time_default(TimDefItem::Zone).unwrap(), "UTC+1");
+            let zoned = Et::from_string("2000-01-01T12:00:00").unwrap();
+            // 13 days between the calendars, and an hour ahead of UTC
+            assert!((zoned.0 - utc.0 - 13.0 * 86400.0 + 3600.0).abs() < 1e-6);
+            assert!(timdef.set(TimDefItem::Calendar, "NOT A CALENDAR").is_err());
+        }
+        assert_eq!(get_time_default(TimDefItem::Calendar).unwrap(), calendar);
+        assert_eq!(get_time_default(TimDefItem::System).unwrap(), system);
+        assert_eq!(Et::from_string("2000-01-01T12:00:00").unwrap(), utc);
+    }
+}
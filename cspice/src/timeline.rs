@@ -0,0 +1,129 @@
+//! Batch conversion of telemetry sample times to Ephemeris Time.
+//!
+//! Telemetry files typically tag each sample with a UTC or spacecraft clock time. The functions
+//! here convert a whole column of times under a single acquisition of the SPICE lock, and
+//! instead of stopping at the first bad time they report every index that failed.
+use crate::time::{Et, SclkTime};
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::SpiceInt;
+use std::fmt::{Display, Formatter};
+
+/// Error returned when some of the times in a batch could not be converted.
+#[derive(Debug, Clone)]
+pub struct TimelineError {
+    /// The number of times in the batch.
+    pub total: usize,
+    /// The index of each time that failed, in ascending order, and its error.
+    pub failures: Vec<(usize, Error)>,
+}
+
+impl TimelineError {
+    /// The indices of the times that failed.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.failures.iter().map(|(i, _)| *i)
+    }
+}
+
+impl Display for TimelineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} of {} times failed", self.failures.len(), self.total)?;
+        if let Some((index, error)) = self.failures.first() {
+            write!(f, ", the first at index {index}: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TimelineError {}
+
+/// Convert UTC (or any other [str2et_c] compatible) time strings to Ephemeris Time.
+///
+/// A leapseconds kernel must be loaded.
+///
+/// [str2et_c]: https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/str2et_c.html
+pub fn from_utc<S: AsRef<str>>(times: &[S]) -> Result<Vec<Et>, TimelineError> {
+    convert(times, |t| Et::from_string(t.as_ref()))
+}
+
+/// Convert spacecraft clock times to Ephemeris Time.
+///
+/// The SCLK kernel of each clock and a leapseconds kernel must be loaded.
+pub fn from_sclk(times: &[SclkTime]) -> Result<Vec<Et>, TimelineError> {
+    convert(times, SclkTime::to_et)
+}
+
+/// Convert spacecraft clock strings, such as `"1/0488762903.205"`, of the clock of `spacecraft`
+/// to Ephemeris Time.
+///
+/// The SCLK kernel of the clock and a leapseconds kernel must be loaded.
+pub fn from_sclk_strings<S: AsRef<str>>(
+    spacecraft: SpiceInt,
+    times: &[S],
+) -> Result<Vec<Et>, TimelineError> {
+    convert(times, |t| SclkTime::parse(spacecraft, t.as_ref())?.to_et())
+}
+
+fn convert<T, F>(times: &[T], f: F) -> Result<Vec<Et>, TimelineError>
+where
+    F: Fn(&T) -> Result<Et, Error>,
+{
+    with_spice_lock_or_panic(|| {
+        let mut ets = Vec::with_capacity(times.len());
+        let mut failures = Vec::new();
+        for (i, time) in times.iter().enumerate() {
+            match f(time) {
+                Ok(et) => ets.push(et),
+                Err(e) => failures.push((i, e)),
+            }
+        }
+        if failures.is_empty() {
+            Ok(ets)
+        } else {
+            Err(TimelineError {
+                total: times.len(),
+                failures,
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::load_test_data;
+
+    #[test]
+    fn test_from_utc() {
+        load_test_data();
+        let times = ["2000-01-01T12:00:00", "2000-01-01T12:01:00"];
+        let ets = from_utc(&times).unwrap();
+        assert_eq!(ets.len(), 2);
+        assert_eq!(ets[0], Et::from_string(times[0]).unwrap());
+        assert!((ets[1].0 - ets[0].0 - 60.0).abs() < 1e-6);
+
+        let times = [
+            "2000-01-01T12:00:00",
+            "NOT A TIME",
+            "2000-01-01",
+            "NOR THIS",
+        ];
+        let err = from_utc(&times).unwrap_err();
+        assert_eq!(err.total, 4);
+        assert_eq!(err.indices().collect::<Vec<_>>(), vec![1, 3]);
+        assert!(err
+            .to_string()
+            .starts_with("2 of 4 times failed, the first at index 1"));
+        assert_eq!(from_utc::<&str>(&[]).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_from_sclk() {
+        load_test_data();
+        // No clock kernel is loaded for this spacecraft
+        let times = [SclkTime::from_encoded(-999999, 0.0)];
+        let err = from_sclk(&times).unwrap_err();
+        assert_eq!(err.indices().collect::<Vec<_>>(), vec![0]);
+        let err = from_sclk_strings(-999999, &["1/0", "1/1"]).unwrap_err();
+        assert_eq!(err.indices().collect::<Vec<_>>(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Topocentric frames for ground stations.
+//!
+//! Defining a new ground station in SPICE requires a frame kernel (FK) describing the
+//! topocentric frame, and an SPK giving the position of the station. [Station::furnish()]
+//! generates and loads both.
+use crate::coordinates::{Geodetic, Rectangular};
+use crate::data::{furnish, is_loaded, unload, KernelError};
+use crate::spk::{SegmentHeader, SpkWriter, State};
+use crate::time::Et;
+use crate::units::Seconds;
+use crate::vector::Vector3D;
+use crate::Error;
+use cspice_sys::SpiceInt;
+use std::f64::consts::FRAC_PI_2;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Error returned from [Station::furnish()].
+#[derive(Debug, thiserror::Error)]
+pub enum TopocentricError {
+    #[error("Failed to write kernel: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Kernel(#[from] KernelError),
+    #[error(transparent)]
+    Spice(#[from] Error),
+}
+
+/// A ground station fixed to the surface of a body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Station {
+    /// Name of the station, e.g. `"MY_STATION"`. The frame will be named `"{name}_TOPO"`.
+    pub name: String,
+    /// NAIF ID code of the station, e.g. 399100.
+    pub id: SpiceInt,
+    /// Frame ID of the topocentric frame, e.g. 1399100.
+    pub frame_id: SpiceInt,
+    /// NAIF ID code of the body the station is on.
+    pub body: SpiceInt,
+    /// Body-fixed frame of the body, e.g. `"ITRF93"` or `"IAU_EARTH"`.
+    pub body_frame: String,
+    /// Geodetic location of the station relative to the body's reference spheroid.
+    pub location: Geodetic,
+}
+
+/// The kernels generated by [Station::furnish()].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StationKernels {
+    /// Name of the topocentric frame.
+    pub frame: String,
+    /// Path of the frame kernel.
+    pub fk: PathBuf,
+    /// Path of the SPK.
+    pub spk: PathBuf,
+}
+
+impl StationKernels {
+    /// Unload both kernels.
+    pub fn unload(&self) -> Result<(), KernelError> {
+        unload(self.spk.to_string_lossy())?;
+        unload(self.fk.to_string_lossy())
+    }
+}
+
+impl Station {
+    /// The name of the topocentric frame.
+    pub fn frame_name(&self) -> String {
+        format!("{}_TOPO", self.name)
+    }
+
+    /// Generate the frame kernel text.
+    ///
+    /// The topocentric frame has +Z along the geodetic normal (up), +X north and +Y west.
+    pub fn frame_kernel(&self) -> String {
+        let (id, frame_id, frame) = (self.id, self.frame_id, self.frame_name());
+        let lon = self.location.longitude.to_degrees();
+        let colat = (FRAC_PI_2 - self.location.latitude).to_degrees();
+        let mut fk = String::new();
+        writeln!(fk, "\\begindata").unwrap();
+        writeln!(fk, "FRAME_{frame} = {frame_id}").unwrap();
+        writeln!(fk, "FRAME_{frame_id}_NAME = '{frame}'").unwrap();
+        writeln!(fk, "FRAME_{frame_id}_CLASS = 4").unwrap();
+        writeln!(fk, "FRAME_{frame_id}_CLASS_ID = {frame_id}").unwrap();
+        writeln!(fk, "FRAME_{frame_id}_CENTER = {id}").unwrap();
+        writeln!(fk, "OBJECT_{id}_FRAME = '{frame}'").unwrap();
+        writeln!(fk, "TKFRAME_{frame_id}_RELATIVE = '{}'", self.body_frame).unwrap();
+        writeln!(fk, "TKFRAME_{frame_id}_SPEC = 'ANGLES'").unwrap();
+        writeln!(fk, "TKFRAME_{frame_id}_UNITS = 'DEGREES'").unwrap();
+        writeln!(fk, "TKFRAME_{frame_id}_AXES = ( 3, 2, 3 )").unwrap();
+        writeln!(
+            fk,
+            "TKFRAME_{frame_id}_ANGLES = ( {:.15e}, {:.15e}, 180.0 )",
+            -lon, -colat
+        )
+        .unwrap();
+        writeln!(fk, "NAIF_BODY_NAME += '{}'", self.name).unwrap();
+        writeln!(fk, "NAIF_BODY_CODE += {id}").unwrap();
+        writeln!(fk, "\\begintext").unwrap();
+        fk
+    }
+
+    /// Write the station's frame kernel and SPK to `directory`, then load them.
+    ///
+    /// The SPK covers `start` to `end`, and the radii of the body must be available in the
+    /// kernel pool. Any existing kernels for the station in `directory` are unloaded (if they were
+    /// loaded with [furnish()]) and replaced.
+    pub fn furnish<P: AsRef<Path>>(
+        &self,
+        directory: P,
+        start: Et,
+        end: Et,
+    ) -> Result<StationKernels, TopocentricError> {
+        let directory = directory.as_ref();
+        let fk = directory.join(format!("{}.tf", self.name.to_lowercase()));
+        let spk = directory.join(format!("{}.bsp", self.name.to_lowercase()));
+        for kernel in [&spk, &fk] {
+            let kernel = kernel.to_string_lossy();
+            if is_loaded(&kernel) {
+                unload(kernel)?;
+            }
+        }
+        std::fs::write(&fk, self.frame_kernel())?;
+        if spk.exists() {
+            std::fs::remove_file(&spk)?;
+        }
+        let position = self.location.to_rect_of(self.body)?;
+        self.write_spk(&spk, position, start, end)?;
+        furnish(fk.to_string_lossy())?;
+        if let Err(e) = furnish(spk.to_string_lossy()) {
+            unload(fk.to_string_lossy())?;
+            return Err(e.into());
+        }
+        Ok(StationKernels {
+            frame: self.frame_name(),
+            fk,
+            spk,
+        })
+    }
+
+    fn write_spk(
+        &self,
+        path: &Path,
+        position: Rectangular,
+        start: Et,
+        end: Et,
+    ) -> Result<(), Error> {
+        let state = State::new(position, Vector3D::default());
+        let header = SegmentHeader {
+            body: self.id,
+            center: self.body,
+            frame: &self.body_frame,
+            start,
+            end,
+            id: &self.name,
+        };
+        let mut writer = SpkWriter::create(path.to_string_lossy(), "TOPOCENTRIC STATION")?;
+        writer.write_type_08(&header, 1, &[state, state], start, Seconds(end.0 - start.0))?;
+        writer.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::AberrationCorrection;
+    use crate::kernel_pool::put_numbers;
+    use crate::spk::position;
+    use crate::tests::load_test_data;
+
+    /// A spherical body, so that "down" points at the center, with J2000 as its body-fixed frame.
+    const BODY: SpiceInt = -999200;
+
+    #[test]
+    fn test_station() {
+        load_test_data();
+        let r = 6378.0;
+        put_numbers(format!("BODY{BODY}_RADII"), &[r, r, r]).unwrap();
+        let station = Station {
+            name: String::from("CSPICE_RS_TEST_STATION"),
+            id: 399999,
+            frame_id: 1399999,
+            body: BODY,
+            body_frame: String::from("J2000"),
+            location: Geodetic {
+                longitude: 45f64.to_radians(),
+                latitude: 30f64.to_radians(),
+                altitude: 0.5,
+            },
+        };
+        let kernels = station
+            .furnish(std::env::temp_dir(), Et(-1e6), Et(1e6))
+            .unwrap();
+        assert_eq!(kernels.frame, "CSPICE_RS_TEST_STATION_TOPO");
+        // Furnishing again replaces the loaded kernels
+        let kernels = station
+            .furnish(std::env::temp_dir(), Et(-1e6), Et(1e6))
+            .unwrap();
+
+        let (body, _) = position(
+            BODY.to_string(),
+            Et(0.0),
+            "CSPICE_RS_TEST_STATION_TOPO",
+            AberrationCorrection::NONE,
+            "CSPICE_RS_TEST_STATION",
+        )
+        .unwrap();
+        assert!(body.x.abs() < 1e-6);
+        assert!(body.y.abs() < 1e-6);
+        assert!((body.z + r + 0.5).abs() < 1e-6);
+
+        kernels.unload().unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+//! Structured logging of SPICE calls with [tracing], enabled by the `tracing` feature.
+//!
+//! Each safe wrapper runs its calls to SPICE in a `spice_call` span at `DEBUG` level, with the
+//! wrapper's path as the `function` field (e.g. `cspice::time::Et::from_string`), the source
+//! `location`, and `elapsed_us`, the time spent holding the SPICE lock. Calls made from within
+//! another call, such as GF callbacks, are nested spans. The arguments of the calls aren't
+//! recorded.
+//!
+//! Holding the lock with [with_spice_lock()](crate::with_spice_lock) or a
+//! [ScopedTimDef](crate::time::ScopedTimDef) doesn't create a span, only the wrapper calls made
+//! while holding it do, so SPICE functions called directly through `cspice_sys` aren't traced.
+//!
+//! Waiting for the lock in [with_spice_lock()](crate::with_spice_lock) and
+//! [ScopedTimDef::new()](crate::time::ScopedTimDef::new) is reported with a `TRACE` event, and
+//! failing to acquire it in [try_with_spice_lock()](crate::try_with_spice_lock) with a `WARN`
+//! event, to help diagnose lock contention.
+use std::panic::Location;
+use std::time::Instant;
+use tracing::field::Empty;
+
+/// The path of the function that defined the closure `F`.
+pub(crate) fn function_name<F>() -> &'static str {
+    let name = std::any::type_name::<F>();
+    name.split("::{{closure}}").next().unwrap_or(name)
+}
+
+/// Run `f`, a call to SPICE made at `location`, in a `spice_call` span.
+pub(crate) fn in_call_span<R, F: FnOnce() -> R>(location: &'static Location<'static>, f: F) -> R {
+    let span = tracing::debug_span!(
+        "spice_call",
+        function = function_name::<F>(),
+        location = %location,
+        elapsed_us = Empty,
+    );
+    let _enter = span.enter();
+    let start = Instant::now();
+    let result = f();
+    span.record("elapsed_us", start.elapsed().as_micros() as u64);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::Et;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    #[derive(Default)]
+    struct FunctionNames(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for FunctionNames {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "function" {
+                self.0.lock().unwrap().push(format!("{value:?}"));
+            }
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "function" {
+                self.0.lock().unwrap().push(value.to_string());
+            }
+        }
+    }
+
+    impl Subscriber for FunctionNames {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            span.record(&mut FunctionNames(self.0.clone()));
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_call_spans() {
+        let names = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = FunctionNames(names.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            Et::from_string("2000-01-01T12:00:00 TDB").unwrap();
+        });
+        let names = names.lock().unwrap();
+        assert!(
+            names.iter().any(|n| n == "cspice::time::Et::from_string"),
+            "{names:?}"
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Newtypes for the physical units used in SPICE function parameters.
+//!
+//! Many SPICE functions take bare doubles whose unit depends on the function, these types make
+//! the expected unit part of the signature.
+use cspice_sys::SpiceDouble;
+use derive_more::{From, Into};
+use std::time::Duration;
+
+/// A duration in seconds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd, From, Into)]
+pub struct Seconds(pub SpiceDouble);
+
+impl From<Duration> for Seconds {
+    fn from(d: Duration) -> Self {
+        Self(d.as_secs_f64())
+    }
+}
+
+/// An angle in radians.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd, From, Into)]
+pub struct Radians(pub SpiceDouble);
+
+impl Radians {
+    /// Create an angle from a value in degrees.
+    #[inline]
+    pub fn from_degrees(degrees: SpiceDouble) -> Self {
+        Self(degrees.to_radians())
+    }
+
+    /// The angle in degrees.
+    #[inline]
+    pub fn to_degrees(self) -> SpiceDouble {
+        self.0.to_degrees()
+    }
+}
+
+/// A distance in kilometers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd, From, Into)]
+pub struct Kilometers(pub SpiceDouble);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Seconds::from(Duration::from_millis(1500)), Seconds(1.5));
+        assert_eq!(Radians::from_degrees(180.0), Radians(std::f64::consts::PI));
+        assert_eq!(Radians(std::f64::consts::PI).to_degrees(), 180.0);
+        assert_eq!(SpiceDouble::from(Kilometers(2.0)), 2.0);
+    }
+}
@@ -2,15 +2,80 @@
 //!
 //! See [Performing simple operations on 3D vectors](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/info/mostused.html#U)
 use crate::coordinates::Rectangular;
-use crate::with_spice_lock_or_panic;
-use cspice_sys::{vsep_c, SpiceDouble};
+use crate::error::get_last_error;
+use crate::matrix::Axis;
+use crate::units::Radians;
+use crate::{with_spice_lock_or_panic, Error};
+use cspice_sys::{
+    rotvec_c, stelab_c, stlabx_c, vdot_c, vhat_c, vnorm_c, vperp_c, vproj_c, vrotv_c, vsep_c,
+    vsub_c, SpiceDouble, SpiceInt,
+};
 use derive_more::{Deref, DerefMut, From, Into};
+use std::array::TryFromSliceError;
 
 /// A 3D vector
 #[derive(Copy, Clone, Debug, Default, PartialEq, From, Into, Deref, DerefMut)]
 pub struct Vector3D(pub [SpiceDouble; 3]);
 
 impl Vector3D {
+    /// Pack three scalar components into a vector, equivalent to
+    /// [vpack_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vpack_c.html).
+    pub const fn new(x: SpiceDouble, y: SpiceDouble, z: SpiceDouble) -> Self {
+        Self([x, y, z])
+    }
+
+    /// Copy the components into an array.
+    pub const fn to_array(&self) -> [SpiceDouble; 3] {
+        self.0
+    }
+
+    /// Compute the dot product of two vectors.
+    ///
+    /// See [vdot_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vdot_c.html)
+    pub fn dot(&self, other: &Vector3D) -> SpiceDouble {
+        with_spice_lock_or_panic(|| unsafe {
+            vdot_c(
+                self.as_ptr() as *mut SpiceDouble,
+                other.as_ptr() as *mut SpiceDouble,
+            )
+        })
+    }
+
+    /// Compute the magnitude of the vector.
+    ///
+    /// See [vnorm_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vnorm_c.html)
+    pub fn norm(&self) -> SpiceDouble {
+        with_spice_lock_or_panic(|| unsafe { vnorm_c(self.as_ptr() as *mut SpiceDouble) })
+    }
+
+    /// Find the unit vector along the vector, or the zero vector if it is zero.
+    ///
+    /// See [vhat_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vhat_c.html)
+    pub fn unit(&self) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe { vhat_c(self.as_ptr() as *mut SpiceDouble, out.as_mut_ptr()) };
+            out
+        })
+    }
+
+    /// Subtract `other` from this vector.
+    ///
+    /// See [vsub_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vsub_c.html)
+    pub fn subtract(&self, other: &Vector3D) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                vsub_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    other.as_ptr() as *mut SpiceDouble,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
     /// Find the separation angle in radians between two double precision, 3-dimensional vectors.
     /// This angle is defined as zero if either vector is zero.
     ///
@@ -23,6 +88,122 @@ impl Vector3D {
             )
         })
     }
+
+    /// Find the projection of this vector onto `other`.
+    ///
+    /// See [vproj_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vproj_c.html)
+    pub fn project_onto(&self, other: &Vector3D) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                vproj_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    other.as_ptr() as *mut SpiceDouble,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
+    /// Find the component of this vector perpendicular to `other`.
+    ///
+    /// See [vperp_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vperp_c.html)
+    pub fn perpendicular_to(&self, other: &Vector3D) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                vperp_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    other.as_ptr() as *mut SpiceDouble,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
+    /// Rotate this vector about an arbitrary axis by an angle, the rotation is right-handed
+    /// (counterclockwise when looking down the axis). A zero axis leaves the vector unchanged.
+    ///
+    /// See [vrotv_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vrotv_c.html)
+    pub fn rotate_about(&self, axis: &Vector3D, angle: Radians) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                vrotv_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    axis.as_ptr() as *mut SpiceDouble,
+                    angle.0,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
+    /// Express this vector in a coordinate frame rotated by an angle about one of the
+    /// coordinate axes. This is the rotation of the frame, so the vector appears rotated by
+    /// `-angle`.
+    ///
+    /// See [rotvec_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/rotvec_c.html)
+    pub fn rotate_frame(&self, angle: Radians, axis: Axis) -> Vector3D {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                rotvec_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    angle.0,
+                    axis as SpiceInt,
+                    out.as_mut_ptr(),
+                )
+            };
+            out
+        })
+    }
+
+    /// Correct the apparent position of an object for stellar aberration, where this vector is
+    /// the position of the object relative to the observer, and `observer_velocity` is the
+    /// velocity (km/s) of the observer relative to the solar system barycenter.
+    ///
+    /// See [stelab_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/stelab_c.html)
+    pub fn stellar_aberration(&self, observer_velocity: &Vector3D) -> Result<Vector3D, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                stelab_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    observer_velocity.as_ptr() as *mut SpiceDouble,
+                    out.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(out)
+        })
+    }
+
+    /// Correct the position of an object for transmission stellar aberration, i.e. the direction
+    /// in which a photon must be emitted by the observer to reach the object.
+    ///
+    /// See [Vector3D::stellar_aberration()] and
+    /// [stlabx_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/stlabx_c.html)
+    pub fn stellar_aberration_transmission(
+        &self,
+        observer_velocity: &Vector3D,
+    ) -> Result<Vector3D, Error> {
+        with_spice_lock_or_panic(|| {
+            let mut out = Vector3D::default();
+            unsafe {
+                stlabx_c(
+                    self.as_ptr() as *mut SpiceDouble,
+                    observer_velocity.as_ptr() as *mut SpiceDouble,
+                    out.as_mut_ptr(),
+                )
+            };
+            get_last_error()?;
+            Ok(out)
+        })
+    }
 }
 
 impl From<Rectangular> for Vector3D {
@@ -30,3 +211,103 @@ impl From<Rectangular> for Vector3D {
         Self([rect.x, rect.y, rect.z])
     }
 }
+
+impl From<(SpiceDouble, SpiceDouble, SpiceDouble)> for Vector3D {
+    fn from((x, y, z): (SpiceDouble, SpiceDouble, SpiceDouble)) -> Self {
+        Self([x, y, z])
+    }
+}
+
+/// Unpack the vector into its scalar components, equivalent to
+/// [vupack_c](https://naif.jpl.nasa.gov/pub/naif/toolkit_docs/C/cspice/vupack_c.html).
+impl From<Vector3D> for (SpiceDouble, SpiceDouble, SpiceDouble) {
+    fn from(v: Vector3D) -> Self {
+        (v[0], v[1], v[2])
+    }
+}
+
+/// Fails unless the slice has exactly 3 elements.
+impl TryFrom<&[SpiceDouble]> for Vector3D {
+    type Error = TryFromSliceError;
+
+    fn try_from(slice: &[SpiceDouble]) -> Result<Self, Self::Error> {
+        Ok(Self(slice.try_into()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lighttime::SPEED_OF_LIGHT;
+
+    #[test]
+    fn test_conversions() {
+        let v = Vector3D::new(1.0, 2.0, 3.0);
+        assert_eq!(Vector3D::from((1.0, 2.0, 3.0)), v);
+        assert_eq!(<(f64, f64, f64)>::from(v), (1.0, 2.0, 3.0));
+        assert_eq!(v.to_array(), [1.0, 2.0, 3.0]);
+        assert_eq!(Vector3D::try_from(&[1.0, 2.0, 3.0][..]).unwrap(), v);
+        assert!(Vector3D::try_from(&[1.0, 2.0][..]).is_err());
+        assert!(Vector3D::try_from(&[1.0, 2.0, 3.0, 4.0][..]).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let v = Vector3D([3.0, 0.0, 4.0]);
+        assert_eq!(v.dot(&Vector3D([1.0, 2.0, 3.0])), 15.0);
+        assert_eq!(v.norm(), 5.0);
+        assert_eq!(v.unit(), Vector3D([0.6, 0.0, 0.8]));
+        assert_eq!(Vector3D::default().unit(), Vector3D::default());
+        assert_eq!(
+            v.subtract(&Vector3D([1.0, 2.0, 3.0])),
+            Vector3D([2.0, -2.0, 1.0])
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        let v = Vector3D([1.0, 2.0, 3.0]);
+        let axis = Vector3D([0.0, 0.0, 5.0]);
+        assert_eq!(v.project_onto(&axis), Vector3D([0.0, 0.0, 3.0]));
+        assert_eq!(v.perpendicular_to(&axis), Vector3D([1.0, 2.0, 0.0]));
+        assert_eq!(v.project_onto(&Vector3D::default()), Vector3D::default());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let x = Vector3D([1.0, 0.0, 0.0]);
+        let quarter = Radians(std::f64::consts::FRAC_PI_2);
+
+        let about_z = x.rotate_about(&Vector3D([0.0, 0.0, 2.0]), quarter);
+        assert!((about_z[0]).abs() < 1e-15);
+        assert!((about_z[1] - 1.0).abs() < 1e-15);
+
+        let frame = x.rotate_frame(quarter, Axis::Z);
+        assert!((frame[0]).abs() < 1e-15);
+        assert!((frame[1] + 1.0).abs() < 1e-15);
+
+        assert_eq!(x.rotate_about(&Vector3D::default(), quarter), x);
+    }
+
+    #[test]
+    fn test_stellar_aberration() {
+        let position = Vector3D([1e6, 0.0, 0.0]);
+        let velocity = Vector3D([0.0, 30.0, 0.0]);
+
+        // The apparent position is shifted towards the observer's velocity
+        let apparent = position.stellar_aberration(&velocity).unwrap();
+        assert!(apparent[1] > 0.0);
+        let angle = position.separation_angle(&apparent);
+        assert!((angle - (30.0 / SPEED_OF_LIGHT).asin()).abs() < 1e-12);
+
+        // And the transmission direction is shifted away from it
+        let transmit = position.stellar_aberration_transmission(&velocity).unwrap();
+        assert!(transmit[1] < 0.0);
+        assert!((position.separation_angle(&transmit) - angle).abs() < 1e-12);
+
+        let err = position
+            .stellar_aberration(&Vector3D([0.0, 2.0 * SPEED_OF_LIGHT, 0.0]))
+            .unwrap_err();
+        assert_eq!(err.short_message, "SPICE(VALUEOUTOFRANGE)");
+    }
+}